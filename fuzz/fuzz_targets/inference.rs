@@ -0,0 +1,16 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use loopybayesnet::fuzzing::NetWithEvidence;
+
+// the evidence always has a non-zero probability, so the inference must not produce invalid messages
+fuzz_target!(|case: NetWithEvidence| {
+    let NetWithEvidence { mut net, evidence } = case;
+    net.set_evidence(&evidence);
+    for _ in 0..20 {
+        net.try_step().unwrap();
+    }
+    for belief in net.beliefs() {
+        assert!(belief.as_probabilities().iter().all(|p| p.is_finite()));
+    }
+});
@@ -0,0 +1,214 @@
+use crate::{BayesNet, LogProbVector};
+use ndarray::Array1;
+use std::collections::VecDeque;
+
+/// Anderson acceleration of the Loopy Belief Propagation
+///
+/// The Loopy Belief Propagation is a fixed-point iteration on the messages. Anderson acceleration
+/// (or Anderson mixing) extrapolates the next messages from the last `depth` steps, finding the
+/// combination of previous iterates whose residuals best cancel out. This can reach the fixed point in
+/// much fewer steps when the propagation converges slowly, for example on strongly coupled loops.
+///
+/// The extrapolation is done on the log-probabilities of the normalized messages, clamped to the
+/// `log_floor` of the numeric policy of the network to keep the arithmetic finite. The same
+/// accelerator must be used for successive steps of a same inference, and `reset` after changing
+/// the evidence or resetting the network.
+#[derive(Debug, Clone)]
+pub struct AndersonAcceleration {
+    depth: usize,
+    // previous messages and their residuals
+    history: VecDeque<(Vec<f64>, Vec<f64>)>,
+}
+
+impl AndersonAcceleration {
+    /// Create an accelerator extrapolating from the last `depth` steps
+    pub fn new(depth: usize) -> AndersonAcceleration {
+        AndersonAcceleration {
+            depth,
+            history: VecDeque::new(),
+        }
+    }
+
+    /// Forget the previous steps
+    pub fn reset(&mut self) {
+        self.history.clear();
+    }
+
+    /// Run one accelerated step of the propagation on the network
+    ///
+    /// Returns the largest change of a log-probability of a message made by the plain step, which can be
+    /// used as a convergence criterion.
+    pub fn step(&mut self, net: &mut BayesNet) -> f32 {
+        let numerics = *net.numerics();
+        let before = flatten(&net.messages(), numerics.log_floor);
+        net.step();
+        let messages = net.messages();
+        let after = flatten(&messages, numerics.log_floor);
+        let residual: Vec<f64> = after
+            .iter()
+            .zip(before.iter())
+            .map(|(a, b)| a - b)
+            .collect();
+        let change = residual.iter().fold(0.0f64, |m, r| m.max(r.abs())) as f32;
+
+        if self.depth > 0 {
+            let regularization = f64::from(numerics.regularization);
+            if let Some(extrapolated) = self.extrapolate(&after, &residual, regularization) {
+                net.set_messages(&unflatten(&messages, &extrapolated));
+            }
+            self.history.push_back((before, residual));
+            if self.history.len() > self.depth {
+                self.history.pop_front();
+            }
+        }
+        change
+    }
+
+    // Anderson type II update: `g - (dX + dF) gamma`, where gamma minimizes `|f - dF gamma|`
+    fn extrapolate(&self, g: &[f64], f: &[f64], regularization: f64) -> Option<Vec<f64>> {
+        if self.history.is_empty() {
+            return None;
+        }
+        let x: Vec<f64> = g.iter().zip(f.iter()).map(|(g, f)| g - f).collect();
+        let mut iterates: Vec<(&[f64], &[f64])> = self
+            .history
+            .iter()
+            .map(|(x, f)| (x.as_slice(), f.as_slice()))
+            .collect();
+        iterates.push((&x, f));
+        // differences between successive iterates
+        let mut dx: Vec<Vec<f64>> = Vec::new();
+        let mut df: Vec<Vec<f64>> = Vec::new();
+        for window in iterates.windows(2) {
+            let (x0, f0) = window[0];
+            let (x1, f1) = window[1];
+            dx.push(x1.iter().zip(x0.iter()).map(|(a, b)| a - b).collect());
+            df.push(f1.iter().zip(f0.iter()).map(|(a, b)| a - b).collect());
+        }
+        let gamma = least_squares(&df, f, regularization)?;
+        let extrapolated: Vec<f64> = (0..g.len())
+            .map(|i| {
+                g[i] - gamma
+                    .iter()
+                    .enumerate()
+                    .map(|(k, c)| c * (dx[k][i] + df[k][i]))
+                    .sum::<f64>()
+            })
+            .collect();
+        if extrapolated.iter().all(|v| v.is_finite()) {
+            Some(extrapolated)
+        } else {
+            None
+        }
+    }
+}
+
+// solve `min |b - sum_k gamma_k a_k|` with the normal equations, with a small relative regularization
+fn least_squares(a: &[Vec<f64>], b: &[f64], regularization: f64) -> Option<Vec<f64>> {
+    let m = a.len();
+    if m == 0 {
+        return None;
+    }
+    let dot = |u: &[f64], v: &[f64]| u.iter().zip(v.iter()).map(|(x, y)| x * y).sum::<f64>();
+    let mut matrix: Vec<Vec<f64>> = (0..m)
+        .map(|i| {
+            let mut row: Vec<f64> = (0..m).map(|j| dot(&a[i], &a[j])).collect();
+            row.push(dot(&a[i], b));
+            row
+        })
+        .collect();
+    let scale = (0..m).map(|i| matrix[i][i]).fold(0.0, f64::max);
+    if scale == 0.0 {
+        return None;
+    }
+    for (i, row) in matrix.iter_mut().enumerate() {
+        row[i] += regularization * scale;
+    }
+    // Gaussian elimination with partial pivoting
+    for col in 0..m {
+        let pivot = (col..m).max_by(|&i, &j| {
+            matrix[i][col]
+                .abs()
+                .partial_cmp(&matrix[j][col].abs())
+                .unwrap()
+        })?;
+        matrix.swap(col, pivot);
+        if matrix[col][col] == 0.0 {
+            return None;
+        }
+        let (top, bottom) = matrix.split_at_mut(col + 1);
+        let pivot_row = &top[col];
+        for row in bottom {
+            let factor = row[col] / pivot_row[col];
+            for (value, &p) in row[col..].iter_mut().zip(pivot_row[col..].iter()) {
+                *value -= factor * p;
+            }
+        }
+    }
+    let mut gamma = vec![0.0; m];
+    for row in (0..m).rev() {
+        let sum: f64 = (row + 1..m).map(|k| matrix[row][k] * gamma[k]).sum();
+        gamma[row] = (matrix[row][m] - sum) / matrix[row][row];
+    }
+    Some(gamma)
+}
+
+fn flatten(messages: &[LogProbVector], log_floor: f32) -> Vec<f64> {
+    messages
+        .iter()
+        .flat_map(|m| {
+            m.log_probabilities()
+                .iter()
+                .map(|&v| f64::from(v.max(log_floor)))
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+fn unflatten(template: &[LogProbVector], values: &[f64]) -> Vec<LogProbVector> {
+    let mut offset = 0;
+    template
+        .iter()
+        .map(|m| {
+            let original = m.log_probabilities();
+            let len = original.len();
+            // impossible values stay impossible
+            let data: Array1<f32> = original
+                .iter()
+                .zip(values[offset..offset + len].iter())
+                .map(|(&o, &v)| if o == f32::NEG_INFINITY { o } else { v as f32 })
+                .collect();
+            offset += len;
+            let mut msg = LogProbVector::from_log_probabilities(data);
+            msg.renormalize();
+            msg
+        })
+        .collect()
+}
+
+impl BayesNet {
+    // all the messages of the network, normalized, in a fixed order
+    pub(crate) fn messages(&self) -> Vec<LogProbVector> {
+        self.nodes
+            .iter()
+            .flat_map(|node| node.parents.iter().chain(node.children.iter()))
+            .map(|(_, msg)| {
+                let mut msg = msg.clone();
+                msg.renormalize();
+                msg
+            })
+            .collect()
+    }
+
+    // replace all the messages of the network, in the order of `messages`
+    pub(crate) fn set_messages(&mut self, messages: &[LogProbVector]) {
+        let mut messages = messages.iter();
+        for node in &mut self.nodes {
+            for (_, msg) in node.parents.iter_mut().chain(node.children.iter_mut()) {
+                *msg = messages.next().expect("not enough messages").clone();
+            }
+            node.lambda = None;
+            node.pi = None;
+        }
+    }
+}
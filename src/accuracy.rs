@@ -0,0 +1,196 @@
+use crate::BayesNet;
+use std::collections::VecDeque;
+
+/// A qualitative grade of how much the beliefs of a node can be trusted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum AccuracyGrade {
+    /// The belief is likely far from the exact posterior
+    Low,
+    /// The belief is likely close to the exact posterior
+    Medium,
+    /// The belief is likely very close to the exact posterior
+    High,
+    /// The belief is the exact posterior
+    Exact,
+}
+
+/// The expected accuracy of the belief of a node, as reported by `BayesNet::accuracy`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Accuracy {
+    /// Qualitative grade of the belief
+    pub grade: AccuracyGrade,
+    /// Estimate of the largest error on the probability of a value of the node, if one is available
+    ///
+    /// This is `0.0` for exact beliefs. For nodes in loops, this is a heuristic estimate based on the
+    /// strength of the interactions along the loops, and not a guaranteed bound.
+    pub error_estimate: Option<f32>,
+}
+
+// largest total variation distance between the distributions of the node for two values of a parent,
+// the other parents being fixed
+fn edge_strength(net: &BayesNet, node: usize, axis: usize) -> f32 {
+    let table = net.nodes[node].log_probas.dense().mapv(f32::exp);
+    let n_parent = table.shape()[axis + 1];
+    let n_values = table.shape()[0];
+    let mut order: Vec<usize> = vec![axis + 1, 0];
+    order.extend((1..table.ndim()).filter(|&a| a != axis + 1));
+    let table = table.permuted_axes(order);
+    let table = table.as_standard_layout();
+    let rest = table.len() / (n_parent * n_values);
+    let table = table
+        .to_shape((n_parent, n_values, rest))
+        .expect("a standard layout array can be reshaped");
+    let mut strength = 0.0f32;
+    for r in 0..rest {
+        for i in 0..n_parent {
+            for j in i + 1..n_parent {
+                let tv: f32 = (0..n_values)
+                    .map(|x| (table[(i, x, r)] - table[(j, x, r)]).abs())
+                    .sum::<f32>()
+                    / 2.0;
+                strength = strength.max(tv);
+            }
+        }
+    }
+    strength
+}
+
+impl BayesNet {
+    /// Report how much the current beliefs of each node can be trusted
+    ///
+    /// The beliefs are exact for observed nodes, and for nodes whose connected component has no
+    /// loops once enough steps of inference were run (as many as the nodes of the component). The
+    /// beliefs in components without loops but with fewer steps run are graded `Low`.
+    ///
+    /// For components with loops, the error is estimated from the strength of the edges (the largest
+    /// change of distribution of a child caused by a change of one of its parents) and the length
+    /// of the shortest loop of the component: Loopy Belief Propagation is accurate when the loops are
+    /// long and their interactions weak. The error is estimated as `c^g`, where `g` is the length of
+    /// the shortest loop and `c` the largest sum of the strengths of the edges of a node, when `c < 1`.
+    pub fn accuracy(&self) -> Vec<Accuracy> {
+        let n = self.nodes.len();
+        // undirected skeleton, with the strength of each edge
+        let mut neighbours: Vec<Vec<(usize, f32)>> = vec![Vec::new(); n];
+        for (child, node) in self.nodes.iter().enumerate() {
+            for (axis, &(parent, _)) in node.parents.iter().enumerate() {
+                let strength = edge_strength(self, child, axis);
+                neighbours[child].push((parent, strength));
+                neighbours[parent].push((child, strength));
+            }
+        }
+
+        let mut component = vec![usize::MAX; n];
+        let mut reports = vec![
+            Accuracy {
+                grade: AccuracyGrade::Low,
+                error_estimate: None,
+            };
+            n
+        ];
+        for start in 0..n {
+            if component[start] != usize::MAX {
+                continue;
+            }
+            // collect the component
+            let mut members = vec![start];
+            component[start] = start;
+            let mut i = 0;
+            while i < members.len() {
+                for &(other, _) in &neighbours[members[i]] {
+                    if component[other] == usize::MAX {
+                        component[other] = start;
+                        members.push(other);
+                    }
+                }
+                i += 1;
+            }
+            let n_edges: usize = members.iter().map(|&m| neighbours[m].len()).sum::<usize>() / 2;
+
+            let accuracy = if n_edges + 1 == members.len() {
+                if self.iteration >= members.len() {
+                    Accuracy {
+                        grade: AccuracyGrade::Exact,
+                        error_estimate: Some(0.0),
+                    }
+                } else {
+                    Accuracy {
+                        grade: AccuracyGrade::Low,
+                        error_estimate: None,
+                    }
+                }
+            } else {
+                let girth = members
+                    .iter()
+                    .map(|&m| shortest_cycle(&neighbours, m))
+                    .min()
+                    .unwrap_or(usize::MAX);
+                let coupling = members
+                    .iter()
+                    .map(|&m| neighbours[m].iter().map(|&(_, s)| s).sum::<f32>())
+                    .fold(0.0, f32::max);
+                let error_estimate = if coupling < 1.0 {
+                    Some(coupling.powi(girth.min(i32::MAX as usize) as i32))
+                } else {
+                    None
+                };
+                let grade = match error_estimate {
+                    Some(e) if e < 0.01 => AccuracyGrade::High,
+                    Some(e) if e < 0.1 => AccuracyGrade::Medium,
+                    _ => AccuracyGrade::Low,
+                };
+                Accuracy {
+                    grade,
+                    error_estimate,
+                }
+            };
+            for &m in &members {
+                reports[m] = accuracy;
+            }
+        }
+
+        for (id, node) in self.nodes.iter().enumerate() {
+            if node.evidence.is_some() {
+                reports[id] = Accuracy {
+                    grade: AccuracyGrade::Exact,
+                    error_estimate: Some(0.0),
+                };
+            }
+        }
+        reports
+    }
+}
+
+// length of the shortest cycle going through `start` in the undirected skeleton
+fn shortest_cycle(neighbours: &[Vec<(usize, f32)>], start: usize) -> usize {
+    // breadth-first search recording which neighbour of `start` each node was reached through
+    let mut dist = vec![usize::MAX; neighbours.len()];
+    let mut branch = vec![usize::MAX; neighbours.len()];
+    let mut queue = VecDeque::new();
+    dist[start] = 0;
+    for (k, &(other, _)) in neighbours[start].iter().enumerate() {
+        if dist[other] == usize::MAX {
+            dist[other] = 1;
+            branch[other] = k;
+            queue.push_back(other);
+        } else if other != start {
+            // two edges between the same nodes
+            return 2;
+        }
+    }
+    let mut best = usize::MAX;
+    while let Some(node) = queue.pop_front() {
+        for &(other, _) in &neighbours[node] {
+            if other == start {
+                continue;
+            }
+            if dist[other] == usize::MAX {
+                dist[other] = dist[node] + 1;
+                branch[other] = branch[node];
+                queue.push_back(other);
+            } else if branch[other] != branch[node] {
+                best = best.min(dist[node] + dist[other] + 1);
+            }
+        }
+    }
+    best
+}
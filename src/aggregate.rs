@@ -0,0 +1,263 @@
+use crate::semiring::Semiring;
+use crate::table::Table;
+use crate::BayesNet;
+use ndarray::{aview1, Array1, ArrayD, ArrayView1, Dimension, IxDyn};
+use std::sync::Arc;
+
+/// The function of its parents computed by an aggregation node, see `BayesNet::add_aggregate_node`
+///
+/// All the parents must have the same number of values `K`, and their values are read as the
+/// integers `0..K`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Aggregate {
+    /// The number of parents having this value, from `0` to the number of parents
+    Count(usize),
+    /// The sum of the values of the parents, from `0` to `n * (K - 1)` for `n` parents
+    Sum,
+    /// The smallest value of the parents, from `0` to `K - 1`
+    Min,
+    /// The largest value of the parents, from `0` to `K - 1`
+    Max,
+}
+
+impl Aggregate {
+    // number of values of the aggregate of `parents` parents with `size` values each
+    fn num_values(self, parents: usize, size: usize) -> usize {
+        match self {
+            Aggregate::Count(_) => parents + 1,
+            Aggregate::Sum => parents * (size - 1) + 1,
+            Aggregate::Min | Aggregate::Max => size,
+        }
+    }
+
+    // the aggregate of no parent
+    fn initial(self, size: usize) -> usize {
+        match self {
+            Aggregate::Min => size - 1,
+            _ => 0,
+        }
+    }
+
+    // the aggregate after adding a parent with this value
+    fn fold(self, acc: usize, value: usize) -> usize {
+        match self {
+            Aggregate::Count(target) => acc + (value == target) as usize,
+            Aggregate::Sum => acc + value,
+            Aggregate::Min => acc.min(value),
+            Aggregate::Max => acc.max(value),
+        }
+    }
+}
+
+/// An aggregation node, whose value is an aggregate of its parents up to some noise
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct AggregateCpd {
+    aggregate: Aggregate,
+    parent_size: usize,
+    // log P(value | aggregate), indexed by [value, aggregate]
+    log_noise: Vec<Vec<f32>>,
+    // the shape of the table of the node
+    shape: Vec<usize>,
+}
+
+impl AggregateCpd {
+    fn new(aggregate: Aggregate, parents: usize, parent_size: usize, noise: f32) -> AggregateCpd {
+        let n = aggregate.num_values(parents, parent_size);
+        let log_noise = (0..n)
+            .map(|value| {
+                (0..n)
+                    .map(|agg| {
+                        let exact = if value == agg { 1.0 - noise } else { 0.0 };
+                        (exact + noise / n as f32).ln()
+                    })
+                    .collect()
+            })
+            .collect();
+        let mut shape = vec![n];
+        shape.extend(std::iter::repeat_n(parent_size, parents));
+        AggregateCpd {
+            aggregate,
+            parent_size,
+            log_noise,
+            shape,
+        }
+    }
+
+    fn num_values(&self) -> usize {
+        self.log_noise.len()
+    }
+
+    pub(crate) fn shape(&self) -> &[usize] {
+        &self.shape
+    }
+
+    // the entry of the table of the node for a value and values of the parents
+    pub(crate) fn log_probability(&self, value: usize, parent_values: &[usize]) -> f32 {
+        let agg = parent_values
+            .iter()
+            .fold(self.aggregate.initial(self.parent_size), |acc, &v| {
+                self.aggregate.fold(acc, v)
+            });
+        self.log_noise[value][agg]
+    }
+
+    // the dense log-probability table of the node
+    pub(crate) fn to_log_table(&self) -> ArrayD<f32> {
+        ArrayD::from_shape_fn(IxDyn(&self.shape), |index| {
+            self.log_probability(index[0], &index.slice()[1..])
+        })
+    }
+
+    // forward[k][acc]: the messages of the first `k` parents leading to the aggregate `acc`
+    fn forward<S: Semiring>(&self, msgs: &[ArrayView1<f32>]) -> Vec<Array1<f32>> {
+        let n = self.num_values();
+        let mut init = Array1::from_elem(n, f32::NEG_INFINITY);
+        init[self.aggregate.initial(self.parent_size)] = 0.0;
+        let mut forward = vec![init];
+        for msg in msgs {
+            let previous = forward.last().unwrap();
+            let mut next = Array1::from_elem(n, f32::NEG_INFINITY);
+            for (acc, &p) in previous.indexed_iter() {
+                if p == f32::NEG_INFINITY {
+                    continue;
+                }
+                for (value, &m) in msg.indexed_iter() {
+                    let target = self.aggregate.fold(acc, value);
+                    next[target] = add::<S>(next[target], p + m);
+                }
+            }
+            forward.push(next);
+        }
+        forward
+    }
+
+    // backward[k][acc]: the messages of the parents from `k` on and `lambda`, given the aggregate
+    // `acc` of the first `k` parents
+    fn backward<S: Semiring>(
+        &self,
+        msgs: &[ArrayView1<f32>],
+        lambda: Array1<f32>,
+    ) -> Vec<Array1<f32>> {
+        let n = self.num_values();
+        let mut backward = vec![lambda];
+        for msg in msgs.iter().rev() {
+            let next = backward.last().unwrap();
+            let previous = Array1::from_shape_fn(n, |acc| {
+                msg.indexed_iter()
+                    .fold(f32::NEG_INFINITY, |total, (value, &m)| {
+                        // partial aggregates that cannot be reached may overflow
+                        match next.get(self.aggregate.fold(acc, value)) {
+                            Some(&l) => add::<S>(total, m + l),
+                            None => total,
+                        }
+                    })
+            });
+            backward.push(previous);
+        }
+        backward.reverse();
+        backward
+    }
+
+    pub(crate) fn pi<S: Semiring>(&self, msgs: &[ArrayView1<f32>]) -> Array1<f32> {
+        let distribution = self.forward::<S>(msgs).pop().unwrap();
+        Array1::from_shape_fn(self.num_values(), |value| {
+            S::sum((&distribution + &aview1(&self.log_noise[value])).view())
+        })
+    }
+
+    pub(crate) fn lambda_message<S: Semiring>(
+        &self,
+        axis: usize,
+        lambda: ArrayView1<f32>,
+        msgs: &[ArrayView1<f32>],
+    ) -> Array1<f32> {
+        // the likelihood of each value of the aggregate
+        let lambda = Array1::from_shape_fn(self.num_values(), |agg| {
+            S::sum(
+                lambda
+                    .indexed_iter()
+                    .map(|(value, &l)| l + self.log_noise[value][agg])
+                    .collect::<Array1<f32>>()
+                    .view(),
+            )
+        });
+        let forward = self.forward::<S>(&msgs[..axis]);
+        let backward = self.backward::<S>(&msgs[axis + 1..], lambda);
+        let (before, after) = (&forward[axis], &backward[0]);
+        Array1::from_shape_fn(self.parent_size, |value| {
+            before
+                .indexed_iter()
+                .fold(f32::NEG_INFINITY, |total, (acc, &p)| {
+                    if p == f32::NEG_INFINITY {
+                        total
+                    } else {
+                        add::<S>(total, p + after[self.aggregate.fold(acc, value)])
+                    }
+                })
+        })
+    }
+}
+
+// the semiring sum of two log-values
+fn add<S: Semiring>(a: f32, b: f32) -> f32 {
+    if a == f32::NEG_INFINITY {
+        b
+    } else if b == f32::NEG_INFINITY {
+        a
+    } else {
+        S::sum(aview1(&[a, b]))
+    }
+}
+
+impl BayesNet {
+    /// Add a new node whose value is an aggregate of its parents, see `Aggregate`
+    ///
+    /// All the parents must have the same number of values. With probability `1 - noise` the node
+    /// takes the value of the aggregate, otherwise it takes a uniformly random value. The messages of
+    /// the node are computed by dynamic programming over the partial aggregates of the parents, in
+    /// time polynomial in the number of parents instead of exponential.
+    ///
+    /// The dense table of the node is never stored: sampling and the evaluation of joint probabilities
+    /// compute its entries from the aggregate. The algorithms working on whole tables, such as exact
+    /// inference or learning, still build it temporarily. Panics if the parents do not have the same
+    /// number of values, if there are no parents, or if `noise` is not in `[0, 1]`.
+    pub fn add_aggregate_node(
+        &mut self,
+        parents: &[usize],
+        aggregate: Aggregate,
+        noise: f32,
+    ) -> usize {
+        assert!(
+            !parents.is_empty(),
+            "An aggregation node needs at least one parent"
+        );
+        assert!(
+            (0.0..=1.0).contains(&noise),
+            "Aggregation noise {} is not in [0, 1]",
+            noise
+        );
+        let parent_size = self.num_values(parents[0]);
+        for &p in parents {
+            assert!(
+                self.num_values(p) == parent_size,
+                "Parents of an aggregation node must have the same number of values, {} has {} instead of {}",
+                self.node_ref(p),
+                self.num_values(p),
+                parent_size
+            );
+        }
+        let cpd = AggregateCpd::new(aggregate, parents.len(), parent_size, noise);
+        self.push_node(parents, Table::Aggregate(Arc::new(cpd)))
+    }
+
+    /// The aggregate computed by a node, if it is an aggregation node, see `add_aggregate_node`
+    ///
+    /// Returns `None` if the table of the node was replaced since, for example by
+    /// `learning::fit_parameters`.
+    pub fn aggregate(&self, node: usize) -> Option<Aggregate> {
+        match self.nodes[node].log_probas {
+            Table::Aggregate(ref cpd) => Some(cpd.aggregate),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,424 @@
+use crate::json::{parse, quote, Json, Value};
+use crate::table::Table;
+use crate::{BayesNet, BifError, LogProbVector};
+use ndarray::Array1;
+use std::fmt::Write;
+
+// 64-bit FNV-1a, stable across platforms and releases
+struct Fnv(u64);
+
+impl Fnv {
+    fn new() -> Fnv {
+        Fnv(0xcbf2_9ce4_8422_2325)
+    }
+
+    fn bytes(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.0 ^= u64::from(b);
+            self.0 = self.0.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+
+    fn usize(&mut self, value: usize) {
+        self.bytes(&(value as u64).to_le_bytes());
+    }
+
+    fn str(&mut self, text: Option<&str>) {
+        match text {
+            Some(text) => {
+                self.usize(text.len() + 1);
+                self.bytes(text.as_bytes());
+            }
+            None => self.usize(0),
+        }
+    }
+}
+
+impl BayesNet {
+    /// A fingerprint of the model: its structure, probability tables and names
+    ///
+    /// Two networks with the same fingerprint are almost surely the same model. The evidence and the
+    /// inference state are not part of the fingerprint, and it is stable across platforms and releases
+    /// of the crate. It is not a cryptographic hash.
+    pub fn fingerprint(&self) -> u64 {
+        let mut hash = Fnv::new();
+        hash.usize(self.nodes.len());
+        for (node, data) in self.nodes.iter().enumerate() {
+            let parents = self.parents(node);
+            hash.usize(parents.len());
+            for parent in parents {
+                hash.usize(parent);
+            }
+            hash.usize(data.log_probas.ndim());
+            for &size in data.log_probas.shape() {
+                hash.usize(size);
+            }
+            match data.log_probas {
+                Table::Dense(ref table) => {
+                    for value in table.iter() {
+                        hash.bytes(&value.to_bits().to_le_bytes());
+                    }
+                }
+                // structured tables are identified by their CPD rather than by all their entries
+                ref structured => hash.str(Some(&format!("{:?}", structured))),
+            }
+            hash.str(self.node_name(node));
+            match self.state_names(node) {
+                Some(names) => {
+                    hash.usize(names.len());
+                    for name in names {
+                        hash.str(Some(name));
+                    }
+                }
+                None => hash.usize(0),
+            }
+        }
+        hash.0
+    }
+}
+
+/// The inputs and outputs of a query, see `AuditLog`
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuditRecord {
+    /// Position of the record in its log, starting at `0`
+    pub sequence: u64,
+    /// Fingerprint of the model, see `BayesNet::fingerprint`
+    pub model: u64,
+    /// The hard evidence, as `(node, value)`
+    pub evidence: Vec<(usize, usize)>,
+    /// The nodes with soft evidence, and their likelihood
+    pub soft_evidence: Vec<(usize, Array1<f32>)>,
+    /// The engine options of the network, as `(name, value)`
+    pub options: Vec<(String, String)>,
+    /// The number of steps of the propagation since its last reset
+    pub iterations: usize,
+    /// The resulting beliefs, as probabilities
+    pub beliefs: Vec<Array1<f32>>,
+    /// The hash of the previous record of the log, if it is hash-chained (`0` for the first record)
+    pub previous: Option<u64>,
+}
+
+fn number(value: f32) -> String {
+    if value.is_finite() {
+        format!("{}", value)
+    } else {
+        // JSON has no infinite numbers
+        format!("\"{}\"", value)
+    }
+}
+
+fn numbers(values: &Array1<f32>) -> String {
+    let values: Vec<String> = values.iter().map(|&v| number(v)).collect();
+    format!("[{}]", values.join(", "))
+}
+
+// the reverse of `number`
+fn read_number(json: &Json, what: &str) -> Result<f32, BifError> {
+    match json.value {
+        Value::String(ref text) => match text.parse::<f32>() {
+            Ok(value) if !value.is_finite() => Ok(value),
+            _ => json.error(format!("{} must be a number", what)),
+        },
+        _ => json.as_f32(what),
+    }
+}
+
+fn read_numbers(json: &Json, what: &str) -> Result<Array1<f32>, BifError> {
+    json.as_array(what)?
+        .iter()
+        .map(|item| read_number(item, what))
+        .collect()
+}
+
+fn read_hex(json: &Json, what: &str) -> Result<u64, BifError> {
+    match u64::from_str_radix(json.as_str(what)?, 16) {
+        Ok(value) => Ok(value),
+        Err(_) => json.error(format!("{} must be hexadecimal", what)),
+    }
+}
+
+fn read_pair(json: &Json, what: &str) -> Result<(usize, usize), BifError> {
+    match *json.as_array(what)? {
+        [ref node, ref value] => Ok((
+            node.as_integer(what)? as usize,
+            value.as_integer(what)? as usize,
+        )),
+        _ => json.error(format!("{} must be a pair", what)),
+    }
+}
+
+impl AuditRecord {
+    // the JSON object of the record, without its closing brace
+    fn json_content(&self) -> String {
+        let mut json = format!(
+            "{{\"sequence\": {}, \"model\": \"{:016x}\"",
+            self.sequence, self.model
+        );
+        let evidence: Vec<String> = self
+            .evidence
+            .iter()
+            .map(|&(node, value)| format!("[{}, {}]", node, value))
+            .collect();
+        write!(json, ", \"evidence\": [{}]", evidence.join(", ")).unwrap();
+        let soft: Vec<String> = self
+            .soft_evidence
+            .iter()
+            .map(|(node, likelihood)| format!("[{}, {}]", node, numbers(likelihood)))
+            .collect();
+        write!(json, ", \"soft_evidence\": [{}]", soft.join(", ")).unwrap();
+        let options: Vec<String> = self
+            .options
+            .iter()
+            .map(|(name, value)| format!("\"{}\": {}", name, value))
+            .collect();
+        write!(json, ", \"options\": {{{}}}", options.join(", ")).unwrap();
+        write!(json, ", \"iterations\": {}", self.iterations).unwrap();
+        let beliefs: Vec<String> = self.beliefs.iter().map(numbers).collect();
+        write!(json, ", \"beliefs\": [{}]", beliefs.join(", ")).unwrap();
+        if let Some(previous) = self.previous {
+            write!(json, ", \"previous\": \"{:016x}\"", previous).unwrap();
+        }
+        json
+    }
+
+    // the record in a parsed line of JSON
+    fn from_document(document: &Json) -> Result<AuditRecord, BifError> {
+        let field = |key: &str| document.required(key, "the record");
+        let soft_evidence = field("soft_evidence")?
+            .as_array("the soft evidence")?
+            .iter()
+            .map(|item| match *item.as_array("the soft evidence")? {
+                [ref node, ref likelihood] => Ok((
+                    node.as_integer("the soft evidence")? as usize,
+                    read_numbers(likelihood, "a likelihood")?,
+                )),
+                _ => item.error("the soft evidence must be pairs".to_owned()),
+            })
+            .collect::<Result<_, _>>()?;
+        let options = match field("options")?.value {
+            Value::Object(ref options) => options
+                .iter()
+                .map(|(name, value)| {
+                    // options are written as numbers or as strings
+                    let value = match value.value {
+                        Value::String(ref text) => quote(text),
+                        _ => number(value.as_f32("an option")?),
+                    };
+                    Ok((name.clone(), value))
+                })
+                .collect::<Result<_, _>>()?,
+            _ => return field("options")?.error("the options must be an object".to_owned()),
+        };
+        let record = AuditRecord {
+            sequence: field("sequence")?.as_integer("the sequence")?,
+            model: read_hex(field("model")?, "the model")?,
+            evidence: field("evidence")?
+                .as_array("the evidence")?
+                .iter()
+                .map(|item| read_pair(item, "the evidence"))
+                .collect::<Result<_, _>>()?,
+            soft_evidence,
+            options,
+            iterations: field("iterations")?.as_integer("the iterations")? as usize,
+            beliefs: field("beliefs")?
+                .as_array("the beliefs")?
+                .iter()
+                .map(|item| read_numbers(item, "a belief"))
+                .collect::<Result<_, _>>()?,
+            previous: match document.field("previous") {
+                Some(previous) => Some(read_hex(previous, "the previous hash")?),
+                None => None,
+            },
+        };
+        if let Some(hash) = document.field("hash") {
+            if record.previous.is_none() || read_hex(hash, "the hash")? != record.hash() {
+                return hash.error("the hash does not match the record".to_owned());
+            }
+        }
+        Ok(record)
+    }
+
+    /// Read a record written by `to_json`
+    ///
+    /// If the record has a `"hash"` field, it must be the hash of the record.
+    pub fn from_json(json: &str) -> Result<AuditRecord, BifError> {
+        AuditRecord::from_document(&parse(json, 1)?)
+    }
+
+    /// The hash of the record, which the next record of a hash-chained log refers to
+    pub fn hash(&self) -> u64 {
+        let mut hash = Fnv::new();
+        hash.bytes(self.json_content().as_bytes());
+        hash.0
+    }
+
+    /// The record as a single line of JSON, without the line break
+    ///
+    /// The model fingerprint and the hashes are written as 16 hexadecimal digits, and the non-finite
+    /// numbers as strings such as `"-inf"`. The records of a hash-chained log also have a `"hash"`
+    /// field, the hash of the record without it.
+    pub fn to_json(&self) -> String {
+        let mut json = self.json_content();
+        if self.previous.is_some() {
+            write!(json, ", \"hash\": \"{:016x}\"", self.hash()).unwrap();
+        }
+        json.push('}');
+        json
+    }
+}
+
+/// An append-only log of the queries run on networks, for compliance
+///
+/// Each record holds the fingerprint of the model, its evidence, its engine options, the number of
+/// steps run and the resulting beliefs, and can be written as a line of JSON. In a hash-chained log,
+/// each record also holds the hash of the previous one, so that removing or modifying a record breaks
+/// the chain, see `verify_chain`. The hashes are not cryptographic: they detect accidental edits and
+/// truncations, not deliberate forgeries.
+#[derive(Debug, Clone, Default)]
+pub struct AuditLog {
+    records: Vec<AuditRecord>,
+    chained: bool,
+    // the hash of the last record, or of the record before the log when resumed
+    last_hash: u64,
+    // the sequence number of the next record
+    next_sequence: u64,
+}
+
+impl AuditLog {
+    /// Create an empty log
+    pub fn new() -> AuditLog {
+        AuditLog::default()
+    }
+
+    /// Create an empty hash-chained log
+    pub fn chained() -> AuditLog {
+        AuditLog {
+            chained: true,
+            ..AuditLog::default()
+        }
+    }
+
+    /// Create a hash-chained log continuing a previous one
+    ///
+    /// `last` is the last record of the previous log, whose hash and sequence number the new records
+    /// follow.
+    pub fn resume(last: &AuditRecord) -> AuditLog {
+        AuditLog {
+            records: Vec::new(),
+            chained: true,
+            last_hash: last.hash(),
+            next_sequence: last.sequence + 1,
+        }
+    }
+
+    /// The records of the log, in order
+    pub fn records(&self) -> &[AuditRecord] {
+        &self.records
+    }
+
+    /// Record the current inputs and beliefs of a network
+    ///
+    /// This is meant to be called once the inference of a query is done.
+    pub fn record(&mut self, net: &BayesNet) -> &AuditRecord {
+        let policy = net.numerics();
+        let options = vec![
+            ("engine", format!("\"{:?}\"", net.engine_version())),
+            ("damping", number(net.damping())),
+            (
+                "convergence_tolerance",
+                number(policy.convergence_tolerance),
+            ),
+            ("zero_log_probability", number(policy.zero_log_probability)),
+            ("normalization_floor", number(policy.normalization_floor)),
+        ];
+        let record = AuditRecord {
+            sequence: self.next_sequence,
+            model: net.fingerprint(),
+            evidence: (0..net.num_nodes())
+                .filter_map(|node| net.nodes[node].evidence.map(|value| (node, value)))
+                .collect(),
+            soft_evidence: (0..net.num_nodes())
+                .filter_map(|node| {
+                    net.nodes[node]
+                        .soft_likelihood()
+                        .map(|likelihood| (node, likelihood.as_probabilities()))
+                })
+                .collect(),
+            options: options
+                .into_iter()
+                .map(|(name, value)| (name.to_owned(), value))
+                .collect(),
+            iterations: net.iteration(),
+            beliefs: net
+                .beliefs()
+                .iter()
+                .map(LogProbVector::as_probabilities)
+                .collect(),
+            previous: if self.chained {
+                Some(self.last_hash)
+            } else {
+                None
+            },
+        };
+        self.last_hash = record.hash();
+        self.next_sequence += 1;
+        self.records.push(record);
+        self.records.last().unwrap()
+    }
+
+    /// Run a query on a copy of a network, and record it
+    ///
+    /// The propagation is run for `iterations` steps from a reset state with the given evidence, and the
+    /// recorded beliefs are returned. The network itself is not modified.
+    pub fn query(
+        &mut self,
+        net: &BayesNet,
+        evidence: &[(usize, usize)],
+        iterations: usize,
+    ) -> Vec<LogProbVector> {
+        let mut net = net.clone();
+        net.reset_state();
+        net.set_evidence(evidence);
+        for _ in 0..iterations {
+            net.step();
+        }
+        self.record(&net);
+        net.beliefs()
+    }
+
+    /// The records of the log as JSON lines, one record per line, see `AuditRecord::to_json`
+    ///
+    /// The lines of successive logs (see `resume`) can be appended to the same file, and read back with
+    /// `parse_json_lines`.
+    pub fn to_json_lines(&self) -> String {
+        self.records
+            .iter()
+            .map(|record| record.to_json() + "\n")
+            .collect()
+    }
+
+    /// Read the records written by `to_json_lines`, see `AuditRecord::from_json`
+    ///
+    /// Empty lines are skipped. The lines of the errors are the lines of `text`.
+    pub fn parse_json_lines(text: &str) -> Result<Vec<AuditRecord>, BifError> {
+        text.lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(i, line)| AuditRecord::from_document(&parse(line, i + 1)?))
+            .collect()
+    }
+
+    /// Check that the records of a hash-chained log follow each other
+    ///
+    /// Returns the index of the first record which does not refer to the hash of the previous one, or
+    /// whose sequence number does not follow it. The first record is not checked.
+    pub fn verify_chain(records: &[AuditRecord]) -> Result<(), usize> {
+        for (i, pair) in records.windows(2).enumerate() {
+            if pair[1].previous != Some(pair[0].hash()) || pair[1].sequence != pair[0].sequence + 1
+            {
+                return Err(i + 1);
+            }
+        }
+        Ok(())
+    }
+}
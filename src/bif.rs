@@ -0,0 +1,760 @@
+use crate::{BayesNet, BuildError, NodeLayout};
+use ndarray::{ArrayD, ArrayView1, Axis, Dimension, IxDyn};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::{self, Write};
+
+/// Errors reported when reading a network in the BIF, XMLBIF, Hugin NET, UAI or JSON formats, or a
+/// fault tree
+///
+/// See `BayesNet::from_bif`, `BayesNet::from_xmlbif`, `BayesNet::from_hugin_net`,
+/// `BayesNet::from_uai`, `BayesNet::from_json` and `BayesNet::from_fault_tree`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BifError {
+    /// The text is not valid in the format
+    Syntax {
+        /// The line of the error, starting at 1
+        line: usize,
+        /// Description of the error
+        message: String,
+    },
+    /// A probability block refers to a variable that is not declared
+    UnknownVariable {
+        /// The line of the reference
+        line: usize,
+        /// Name of the variable
+        name: String,
+    },
+    /// A row of a probability block refers to a state that the parent does not have
+    UnknownState {
+        /// The line of the row
+        line: usize,
+        /// Name of the parent
+        variable: String,
+        /// The state that was not found
+        state: String,
+    },
+    /// A list of probabilities does not have the expected length
+    TableSize {
+        /// The line of the list
+        line: usize,
+        /// Name of the variable of the probability block
+        variable: String,
+        /// Expected number of probabilities
+        expected: usize,
+        /// Number of probabilities found
+        found: usize,
+    },
+    /// The probabilities of a variable are missing, for some or all of the values of its parents
+    MissingProbabilities(String),
+    /// The parents of this variable form a cycle
+    Cycle(String),
+    /// The probabilities of a variable are rejected by the network, see `BayesNet::set_strict_inputs`
+    Build {
+        /// Name of the variable
+        variable: String,
+        /// The error reported when adding the node
+        error: BuildError,
+    },
+}
+
+impl fmt::Display for BifError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BifError::Syntax { line, message } => write!(f, "line {}: {}", line, message),
+            BifError::UnknownVariable { line, name } => {
+                write!(f, "line {}: unknown variable \"{}\"", line, name)
+            }
+            BifError::UnknownState {
+                line,
+                variable,
+                state,
+            } => write!(
+                f,
+                "line {}: variable \"{}\" has no state \"{}\"",
+                line, variable, state
+            ),
+            BifError::TableSize {
+                line,
+                variable,
+                expected,
+                found,
+            } => write!(
+                f,
+                "line {}: expected {} probabilities for variable \"{}\", found {}",
+                line, expected, variable, found
+            ),
+            BifError::MissingProbabilities(variable) => {
+                write!(f, "missing probabilities for variable \"{}\"", variable)
+            }
+            BifError::Cycle(variable) => {
+                write!(f, "the parents of variable \"{}\" form a cycle", variable)
+            }
+            BifError::Build { variable, error } => {
+                write!(f, "invalid variable \"{}\": {}", variable, error)
+            }
+        }
+    }
+}
+
+impl Error for BifError {}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    Quoted(String),
+    Punct(char),
+}
+
+impl Token {
+    fn text(&self) -> String {
+        match self {
+            Token::Word(word) | Token::Quoted(word) => word.clone(),
+            Token::Punct(c) => c.to_string(),
+        }
+    }
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || "_-+./".contains(c)
+}
+
+// split the text into tokens with their line, skipping comments
+fn tokenize(text: &str) -> Result<Vec<(usize, Token)>, BifError> {
+    let mut tokens = Vec::new();
+    let mut chars = text.chars().peekable();
+    let mut line = 1;
+    while let Some(c) = chars.next() {
+        match c {
+            '\n' => line += 1,
+            c if c.is_whitespace() => {}
+            '/' if chars.peek() == Some(&'/') => while chars.next_if(|&c| c != '\n').is_some() {},
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let start = line;
+                let mut previous = ' ';
+                loop {
+                    match chars.next() {
+                        Some('/') if previous == '*' => break,
+                        Some(c) => {
+                            if c == '\n' {
+                                line += 1;
+                            }
+                            previous = c;
+                        }
+                        None => {
+                            return Err(BifError::Syntax {
+                                line: start,
+                                message: "unterminated comment".to_owned(),
+                            })
+                        }
+                    }
+                }
+            }
+            '"' => {
+                let start = line;
+                let mut word = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some(c) => {
+                            if c == '\n' {
+                                line += 1;
+                            }
+                            word.push(c);
+                        }
+                        None => {
+                            return Err(BifError::Syntax {
+                                line: start,
+                                message: "unterminated string".to_owned(),
+                            })
+                        }
+                    }
+                }
+                tokens.push((start, Token::Quoted(word)));
+            }
+            c if is_word_char(c) => {
+                let mut word = c.to_string();
+                while let Some(&c) = chars.peek() {
+                    // a comment can start right after a word
+                    let comment = c == '/' && {
+                        let mut ahead = chars.clone();
+                        ahead.next();
+                        matches!(ahead.peek(), Some('/') | Some('*'))
+                    };
+                    if !is_word_char(c) || comment {
+                        break;
+                    }
+                    word.push(c);
+                    chars.next();
+                }
+                tokens.push((line, Token::Word(word)));
+            }
+            c if "{}[]()|,;=".contains(c) => tokens.push((line, Token::Punct(c))),
+            c => {
+                return Err(BifError::Syntax {
+                    line,
+                    message: format!("unexpected character '{}'", c),
+                })
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+pub(crate) struct Variable {
+    pub(crate) name: String,
+    pub(crate) line: usize,
+    pub(crate) states: Vec<String>,
+    pub(crate) layout: Option<NodeLayout>,
+}
+
+enum Entry {
+    Table(Vec<f32>),
+    Default(Vec<f32>),
+    Row(Vec<String>, Vec<f32>),
+}
+
+struct Probability {
+    line: usize,
+    variable: String,
+    parents: Vec<String>,
+    entries: Vec<(usize, Entry)>,
+}
+
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    pos: usize,
+}
+
+impl Parser {
+    fn line(&self) -> usize {
+        self.tokens
+            .get(self.pos)
+            .or_else(|| self.tokens.last())
+            .map_or(1, |&(line, _)| line)
+    }
+
+    fn error<T>(&self, message: String) -> Result<T, BifError> {
+        Err(BifError::Syntax {
+            line: self.line(),
+            message,
+        })
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(_, token)| token)
+    }
+
+    fn next(&mut self) -> Result<Token, BifError> {
+        match self.tokens.get(self.pos) {
+            Some((_, token)) => {
+                self.pos += 1;
+                Ok(token.clone())
+            }
+            None => self.error("unexpected end of file".to_owned()),
+        }
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(&Token::Punct(c)) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), BifError> {
+        match self.next()? {
+            Token::Punct(p) if p == c => Ok(()),
+            other => {
+                self.pos -= 1;
+                self.error(format!("expected '{}', found \"{}\"", c, other.text()))
+            }
+        }
+    }
+
+    fn word(&mut self) -> Result<String, BifError> {
+        match self.next()? {
+            Token::Word(word) | Token::Quoted(word) => Ok(word),
+            other => {
+                self.pos -= 1;
+                self.error(format!("expected a name, found \"{}\"", other.text()))
+            }
+        }
+    }
+
+    // a list of names until the closing character, separated by commas or spaces
+    fn names_until(&mut self, close: char) -> Result<Vec<String>, BifError> {
+        let mut names = Vec::new();
+        while !self.eat(close) {
+            if !self.eat(',') {
+                names.push(self.word()?);
+            }
+        }
+        Ok(names)
+    }
+
+    // a list of numbers until the end of the statement
+    fn numbers(&mut self) -> Result<Vec<f32>, BifError> {
+        let mut numbers = Vec::new();
+        while !self.eat(';') {
+            if !self.eat(',') {
+                let word = self.word()?;
+                match word.parse() {
+                    Ok(number) => numbers.push(number),
+                    Err(_) => {
+                        self.pos -= 1;
+                        return self.error(format!("invalid probability \"{}\"", word));
+                    }
+                }
+            }
+        }
+        Ok(numbers)
+    }
+
+    // the content of a property statement, after the `property` keyword
+    fn property(&mut self) -> Result<String, BifError> {
+        let mut words = Vec::new();
+        loop {
+            match self.next()? {
+                Token::Punct(';') => return Ok(words.join(" ")),
+                token => words.push(token.text()),
+            }
+        }
+    }
+
+    fn network(&mut self) -> Result<(), BifError> {
+        if !matches!(self.peek(), Some(Token::Punct('{'))) {
+            self.word()?;
+        }
+        self.expect('{')?;
+        while !self.eat('}') {
+            match self.word()?.as_str() {
+                "property" => {
+                    self.property()?;
+                }
+                other => return self.error(format!("unexpected \"{}\" in network block", other)),
+            }
+        }
+        Ok(())
+    }
+
+    fn variable(&mut self) -> Result<Variable, BifError> {
+        let line = self.line();
+        let name = self.word()?;
+        let mut states = None;
+        let mut position = None;
+        let mut color = None;
+        self.expect('{')?;
+        while !self.eat('}') {
+            match self.word()?.as_str() {
+                "type" => {
+                    if self.word()? != "discrete" {
+                        self.pos -= 1;
+                        return self.error("only discrete variables are supported".to_owned());
+                    }
+                    self.expect('[')?;
+                    let count = self.word()?;
+                    self.expect(']')?;
+                    self.expect('{')?;
+                    let names = self.names_until('}')?;
+                    self.expect(';')?;
+                    if count.parse() != Ok(names.len()) {
+                        return self.error(format!(
+                            "variable \"{}\" declares {} states but lists {}",
+                            name,
+                            count,
+                            names.len()
+                        ));
+                    }
+                    states = Some(names);
+                }
+                "property" => {
+                    let property = self.property()?;
+                    if let Some((key, value)) = property.split_once('=') {
+                        match key.trim() {
+                            "position" => position = parse_position(value),
+                            "color" => color = Some(value.trim().to_owned()),
+                            _ => {}
+                        }
+                    }
+                }
+                other => {
+                    self.pos -= 1;
+                    return self.error(format!("unexpected \"{}\" in variable block", other));
+                }
+            }
+        }
+        let states = match states {
+            Some(states) => states,
+            None => return self.error(format!("variable \"{}\" has no type", name)),
+        };
+        Ok(Variable {
+            name,
+            line,
+            states,
+            layout: position.map(|(x, y)| NodeLayout { x, y, color }),
+        })
+    }
+
+    fn probability(&mut self) -> Result<Probability, BifError> {
+        let line = self.line();
+        self.expect('(')?;
+        let variable = self.word()?;
+        let mut parents = Vec::new();
+        if self.eat('|') {
+            parents = self.names_until(')')?;
+        } else {
+            self.expect(')')?;
+        }
+        let mut entries = Vec::new();
+        self.expect('{')?;
+        while !self.eat('}') {
+            let line = self.line();
+            if self.eat('(') {
+                let states = self.names_until(')')?;
+                entries.push((line, Entry::Row(states, self.numbers()?)));
+                continue;
+            }
+            match self.word()?.as_str() {
+                "table" => entries.push((line, Entry::Table(self.numbers()?))),
+                "default" => entries.push((line, Entry::Default(self.numbers()?))),
+                "property" => {
+                    self.property()?;
+                }
+                other => {
+                    self.pos -= 1;
+                    return self.error(format!("unexpected \"{}\" in probability block", other));
+                }
+            }
+        }
+        Ok(Probability {
+            line,
+            variable,
+            parents,
+            entries,
+        })
+    }
+}
+
+// the coordinates of a `(x, y)` position property
+pub(crate) fn parse_position(value: &str) -> Option<(f32, f32)> {
+    let value = value.trim().trim_start_matches('(').trim_end_matches(')');
+    let mut coords = value.split(',').map(|c| c.trim().parse::<f32>());
+    match (coords.next(), coords.next(), coords.next()) {
+        (Some(Ok(x)), Some(Ok(y)), None) => Some((x, y)),
+        _ => None,
+    }
+}
+
+// a name usable as a BIF identifier
+fn bif_name(name: &str) -> String {
+    name.chars()
+        .map(|c| if is_word_char(c) { c } else { '_' })
+        .collect()
+}
+
+// the probability table of a variable, as `[value, parent values...]`
+fn probability_table(
+    probability: &Probability,
+    variables: &[Variable],
+    parents: &[usize],
+    size: usize,
+) -> Result<ArrayD<f32>, BifError> {
+    let parent_sizes: Vec<usize> = parents.iter().map(|&p| variables[p].states.len()).collect();
+    let mut shape = vec![size];
+    shape.extend_from_slice(&parent_sizes);
+    let mut table = ArrayD::zeros(IxDyn(&shape));
+    let mut filled = ArrayD::from_elem(IxDyn(&parent_sizes), false);
+    let check_size = |line: usize, values: &[f32], expected: usize| {
+        if values.len() == expected {
+            Ok(())
+        } else {
+            Err(BifError::TableSize {
+                line,
+                variable: probability.variable.clone(),
+                expected,
+                found: values.len(),
+            })
+        }
+    };
+    for (line, entry) in &probability.entries {
+        match entry {
+            Entry::Table(values) => {
+                check_size(*line, values, table.len())?;
+                table = ArrayD::from_shape_vec(IxDyn(&shape), values.clone()).unwrap();
+                filled.fill(true);
+            }
+            Entry::Default(values) => {
+                check_size(*line, values, size)?;
+                for (index, done) in filled.indexed_iter_mut() {
+                    if !*done {
+                        for (v, &p) in values.iter().enumerate() {
+                            let mut full = vec![v];
+                            full.extend_from_slice(index.slice());
+                            table[IxDyn(&full)] = p;
+                        }
+                        *done = true;
+                    }
+                }
+            }
+            Entry::Row(states, values) => {
+                if states.len() != parents.len() {
+                    return Err(BifError::Syntax {
+                        line: *line,
+                        message: format!(
+                            "expected {} parent states, found {}",
+                            parents.len(),
+                            states.len()
+                        ),
+                    });
+                }
+                check_size(*line, values, size)?;
+                let mut index = vec![0];
+                for (state, &parent) in states.iter().zip(parents) {
+                    let parent = &variables[parent];
+                    match parent.states.iter().position(|s| s == state) {
+                        Some(value) => index.push(value),
+                        None => {
+                            return Err(BifError::UnknownState {
+                                line: *line,
+                                variable: parent.name.clone(),
+                                state: state.clone(),
+                            })
+                        }
+                    }
+                }
+                filled[IxDyn(&index[1..])] = true;
+                for (v, &p) in values.iter().enumerate() {
+                    index[0] = v;
+                    table[IxDyn(&index)] = p;
+                }
+            }
+        }
+    }
+    if filled.iter().all(|&done| done) {
+        Ok(table)
+    } else {
+        Err(BifError::MissingProbabilities(probability.variable.clone()))
+    }
+}
+
+// the probability table of a node, from values listed with the node varying the fastest
+pub(crate) fn table_with_node_last(
+    values: Vec<f32>,
+    parent_sizes: &[usize],
+    size: usize,
+) -> ArrayD<f32> {
+    let mut shape = parent_sizes.to_vec();
+    shape.push(size);
+    let mut axes = vec![parent_sizes.len()];
+    axes.extend(0..parent_sizes.len());
+    ArrayD::from_shape_vec(IxDyn(&shape), values)
+        .unwrap()
+        .permuted_axes(IxDyn(&axes))
+        .as_standard_layout()
+        .into_owned()
+}
+
+// the network of the variables, with the parents and probability table of each variable
+pub(crate) fn build_network(
+    variables: &[Variable],
+    mut tables: Vec<Option<(Vec<usize>, ArrayD<f32>)>>,
+) -> Result<BayesNet, BifError> {
+    let mut net = BayesNet::new();
+    let mut node_ids = vec![None; variables.len()];
+    while node_ids.iter().any(Option::is_none) {
+        // the first variable whose parents are all in the network
+        let mut next = None;
+        for (variable, id) in node_ids.iter().enumerate() {
+            if id.is_some() {
+                continue;
+            }
+            let (parents, _) = tables[variable]
+                .as_ref()
+                .ok_or_else(|| BifError::MissingProbabilities(variables[variable].name.clone()))?;
+            if parents.iter().all(|&p| node_ids[p].is_some()) {
+                next = Some(variable);
+                break;
+            }
+        }
+        let variable = match next {
+            Some(variable) => variable,
+            None => {
+                let first = node_ids.iter().position(Option::is_none).unwrap();
+                return Err(BifError::Cycle(variables[first].name.clone()));
+            }
+        };
+        let (parents, table) = tables[variable].take().unwrap();
+        let parents: Vec<usize> = parents.iter().map(|&p| node_ids[p].unwrap()).collect();
+        let id = net
+            .try_add_node_from_probabilities(&parents, table)
+            .map_err(|error| BifError::Build {
+                variable: variables[variable].name.clone(),
+                error,
+            })?;
+        net.set_node_name(id, &variables[variable].name);
+        net.set_state_names(id, &variables[variable].states);
+        net.set_layout(id, variables[variable].layout.clone());
+        node_ids[variable] = Some(id);
+    }
+    Ok(net)
+}
+
+impl BayesNet {
+    /// Read a network in the Bayesian Interchange Format (BIF)
+    ///
+    /// This is the text format used by bnlearn, SamIam or pgmpy, among others. Only discrete variables
+    /// are supported. The nodes are named after the variables, with their state names, and are added
+    /// in the order of declaration of the variables, except that the parents of a node are always
+    /// added before it. The tables may be given either as a whole with `table`, the node varying the
+    /// slowest and the last parent the fastest, or row by row for each combination of values of the
+    /// parents, with an optional `default` row.
+    ///
+    /// The `position = (x, y)` and `color = ...` properties of the variables are read as layout hints,
+    /// the other properties are ignored.
+    pub fn from_bif(text: &str) -> Result<BayesNet, BifError> {
+        let mut parser = Parser {
+            tokens: tokenize(text)?,
+            pos: 0,
+        };
+        let mut variables: Vec<Variable> = Vec::new();
+        let mut probabilities = Vec::new();
+        while parser.peek().is_some() {
+            match parser.word()?.as_str() {
+                "network" => parser.network()?,
+                "variable" => {
+                    let variable = parser.variable()?;
+                    if variables.iter().any(|v| v.name == variable.name) {
+                        return Err(BifError::Syntax {
+                            line: variable.line,
+                            message: format!("variable \"{}\" is declared twice", variable.name),
+                        });
+                    }
+                    variables.push(variable);
+                }
+                "probability" => probabilities.push(parser.probability()?),
+                other => {
+                    parser.pos -= 1;
+                    return parser.error(format!("unexpected \"{}\"", other));
+                }
+            }
+        }
+
+        let ids: HashMap<&str, usize> = variables
+            .iter()
+            .enumerate()
+            .map(|(i, v)| (v.name.as_str(), i))
+            .collect();
+        let find = |name: &str, line: usize| {
+            ids.get(name).copied().ok_or(BifError::UnknownVariable {
+                line,
+                name: name.to_owned(),
+            })
+        };
+        let mut tables = vec![None; variables.len()];
+        for probability in &probabilities {
+            let variable = find(&probability.variable, probability.line)?;
+            let parents = probability
+                .parents
+                .iter()
+                .map(|p| find(p, probability.line))
+                .collect::<Result<Vec<usize>, BifError>>()?;
+            if tables[variable].is_some() {
+                return Err(BifError::Syntax {
+                    line: probability.line,
+                    message: format!(
+                        "the probabilities of variable \"{}\" are given twice",
+                        probability.variable
+                    ),
+                });
+            }
+            let size = variables[variable].states.len();
+            let table = probability_table(probability, &variables, &parents, size)?;
+            tables[variable] = Some((parents, table));
+        }
+
+        build_network(&variables, tables)
+    }
+
+    /// Write the network in the Bayesian Interchange Format (BIF), see `from_bif`
+    ///
+    /// Unnamed nodes are called `node{id}` and unnamed states `s{value}`, and the characters of names
+    /// that cannot appear in BIF identifiers are replaced by `_`. The tables are written row by row,
+    /// and the layout hints as `position` and `color` properties.
+    pub fn to_bif(&self) -> String {
+        let name = |node: usize| {
+            self.node_name(node)
+                .map(bif_name)
+                .unwrap_or_else(|| format!("node{}", node))
+        };
+        let states = |node: usize| -> Vec<String> {
+            match self.state_names(node) {
+                Some(names) => names.iter().map(|s| bif_name(s)).collect(),
+                None => (0..self.num_values(node))
+                    .map(|v| format!("s{}", v))
+                    .collect(),
+            }
+        };
+        let mut bif = String::from("network unknown {\n}\n");
+        for node in 0..self.num_nodes() {
+            writeln!(
+                bif,
+                "variable {} {{\n  type discrete [ {} ] {{ {} }};",
+                name(node),
+                self.num_values(node),
+                states(node).join(", ")
+            )
+            .unwrap();
+            if let Some(layout) = self.layout(node) {
+                writeln!(bif, "  property position = ({}, {});", layout.x, layout.y).unwrap();
+                if let Some(ref color) = layout.color {
+                    writeln!(bif, "  property color = \"{}\";", color).unwrap();
+                }
+            }
+            bif.push_str("}\n");
+        }
+        for (node, data) in self.nodes.iter().enumerate() {
+            let parents: Vec<usize> = data.parents.iter().map(|&(p, _)| p).collect();
+            let table = data.log_probas.dense();
+            let write_values = |bif: &mut String, column: ArrayView1<f32>| {
+                let values: Vec<String> = column.iter().map(|l| l.exp().to_string()).collect();
+                writeln!(bif, " {};", values.join(", ")).unwrap();
+            };
+            if parents.is_empty() {
+                writeln!(bif, "probability ( {} ) {{", name(node)).unwrap();
+                bif.push_str("  table");
+                write_values(&mut bif, table.view().into_dimensionality().unwrap());
+            } else {
+                let parent_names: Vec<String> = parents.iter().map(|&p| name(p)).collect();
+                writeln!(
+                    bif,
+                    "probability ( {} | {} ) {{",
+                    name(node),
+                    parent_names.join(", ")
+                )
+                .unwrap();
+                let parent_states: Vec<Vec<String>> = parents.iter().map(|&p| states(p)).collect();
+                let configurations = table.index_axis(Axis(0), 0);
+                for (column, (index, _)) in table
+                    .lanes(Axis(0))
+                    .into_iter()
+                    .zip(configurations.indexed_iter())
+                {
+                    let row: Vec<&str> = index
+                        .slice()
+                        .iter()
+                        .zip(&parent_states)
+                        .map(|(&v, states)| states[v].as_str())
+                        .collect();
+                    write!(bif, "  ({})", row.join(", ")).unwrap();
+                    write_values(&mut bif, column);
+                }
+            }
+            bif.push_str("}\n");
+        }
+        bif
+    }
+}
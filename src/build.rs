@@ -0,0 +1,131 @@
+use crate::{BayesNet, NodeRef};
+use std::error::Error;
+use std::fmt;
+
+/// Errors reported when adding an invalid node to a network
+///
+/// See `BayesNet::try_add_node_from_probabilities` and `BayesNet::try_add_node_from_log_probabilities`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BuildError {
+    /// The array does not have one dimension per parent plus the one of the node
+    DimensionMismatch {
+        /// Id the node would have had
+        node: usize,
+        /// Number of dimensions of the array
+        dimensions: usize,
+        /// Number of parents given
+        parents: usize,
+    },
+    /// A parent is not a node of the network
+    UnknownParent {
+        /// Id the node would have had
+        node: usize,
+        /// The invalid parent
+        parent: usize,
+    },
+    /// The size of a dimension of the array does not match the number of values of its parent
+    ParentSizeMismatch {
+        /// Id the node would have had
+        node: usize,
+        /// The dimension of the array, starting at 1 for the first parent
+        axis: usize,
+        /// Size of the dimension
+        size: usize,
+        /// The parent associated with the dimension
+        parent: NodeRef,
+        /// Number of values of the parent
+        parent_size: usize,
+    },
+    /// A probability is negative or not finite, only reported in strict mode
+    InvalidProbability {
+        /// Id the node would have had
+        node: usize,
+        /// Index of the value in the array, in logical order
+        index: usize,
+        /// The invalid probability
+        value: f32,
+    },
+    /// A log-probability is NaN or `+inf`, only reported in strict mode
+    InvalidLogProbability {
+        /// Id the node would have had
+        node: usize,
+        /// Index of the value in the array, in logical order
+        index: usize,
+        /// The invalid log-probability
+        value: f32,
+    },
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BuildError::DimensionMismatch {
+                node,
+                dimensions,
+                parents,
+            } => write!(
+                f,
+                "Dimensions of log_probas array of node {} does not match number of parents: got {} dimensions for {} parents",
+                node, dimensions, parents
+            ),
+            BuildError::UnknownParent { node, parent } => write!(
+                f,
+                "Parent {} of node {} does not exist, the network only has {} nodes",
+                parent, node, node
+            ),
+            BuildError::ParentSizeMismatch {
+                node,
+                axis,
+                size,
+                parent,
+                parent_size,
+            } => write!(
+                f,
+                "Dimension {} of log_probas array of node {} does not match its associated parent number of element: got {} but {} has {}.",
+                axis, node, size, parent, parent_size
+            ),
+            BuildError::InvalidProbability { node, index, value } => write!(
+                f,
+                "Invalid probability {} at index {} of the probabilities array of node {}",
+                value, index, node
+            ),
+            BuildError::InvalidLogProbability { node, index, value } => write!(
+                f,
+                "Invalid log-probability {} at index {} of the log_probas array of node {}",
+                value, index, node
+            ),
+        }
+    }
+}
+
+impl Error for BuildError {}
+
+impl BayesNet {
+    // check that the shape of the table of a new node matches its parents
+    pub(crate) fn check_table(&self, parents: &[usize], shape: &[usize]) -> Result<(), BuildError> {
+        let node = self.nodes.len();
+        if shape.len() != parents.len() + 1 {
+            return Err(BuildError::DimensionMismatch {
+                node,
+                dimensions: shape.len(),
+                parents: parents.len(),
+            });
+        }
+        if let Some(&parent) = parents.iter().find(|&&p| p >= node) {
+            return Err(BuildError::UnknownParent { node, parent });
+        }
+        for (i, (&size, &parent)) in shape.iter().skip(1).zip(parents.iter()).enumerate() {
+            let parent_size = self.num_values(parent);
+            if parent_size != size {
+                return Err(BuildError::ParentSizeMismatch {
+                    node,
+                    axis: i + 1,
+                    size,
+                    parent: self.node_ref(parent),
+                    parent_size,
+                });
+            }
+        }
+        Ok(())
+    }
+}
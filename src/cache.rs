@@ -0,0 +1,112 @@
+use crate::graph::{requisite_evidence, structure};
+use crate::{BayesNet, LogProbVector};
+use std::collections::HashMap;
+
+/// A cache of posterior distributions, keyed by the evidence relevant to each node
+///
+/// The posterior of a node only depends on the evidence that is not d-separated from it by the rest
+/// of the evidence (see `graph::requisite_evidence`), so cached posteriors are reused across queries
+/// whose evidence only differs on unrelated nodes. Each inference run caches the posteriors of all the
+/// nodes of the network.
+///
+/// Inference is run using only the relevant evidence of the queried node. On networks with loops, where
+/// Loopy Belief Propagation is approximate, the cached answers may thus differ slightly from running
+/// inference with the full evidence.
+#[derive(Debug, Clone)]
+pub struct InferenceCache {
+    net: BayesNet,
+    structure: Vec<Vec<usize>>,
+    iterations: usize,
+    entries: HashMap<(usize, Vec<(usize, usize)>), LogProbVector>,
+    hits: usize,
+    misses: usize,
+}
+
+impl InferenceCache {
+    /// Create an empty cache for a network, running `iterations` steps of inference on each miss
+    pub fn new(net: BayesNet, iterations: usize) -> InferenceCache {
+        InferenceCache {
+            structure: structure(&net),
+            net,
+            iterations,
+            entries: HashMap::new(),
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// The network the posteriors are computed from
+    pub fn net(&self) -> &BayesNet {
+        &self.net
+    }
+
+    /// Replace the network, which clears the cache
+    pub fn set_net(&mut self, net: BayesNet) {
+        self.structure = structure(&net);
+        self.net = net;
+        self.clear();
+    }
+
+    fn key(&self, node: usize, evidence: &[(usize, usize)]) -> Vec<(usize, usize)> {
+        let observed: Vec<usize> = evidence.iter().map(|&(n, _)| n).collect();
+        let requisite = requisite_evidence(&self.structure, node, &observed);
+        evidence
+            .iter()
+            .cloned()
+            .filter(|(n, _)| requisite.binary_search(n).is_ok())
+            .collect()
+    }
+
+    /// The posterior distribution of `node` given the evidence, as a list of `(node_id, node_value)`
+    pub fn posterior(&mut self, evidence: &[(usize, usize)], node: usize) -> LogProbVector {
+        // as in `BayesNet::set_evidence`, the last value given for a node is kept: the stable sort of
+        // the reversed evidence puts it first among the values of the node
+        let mut evidence: Vec<(usize, usize)> = evidence.iter().rev().cloned().collect();
+        evidence.sort_by_key(|&(n, _)| n);
+        evidence.dedup_by_key(|&mut (n, _)| n);
+
+        let key = self.key(node, &evidence);
+        if let Some(posterior) = self.entries.get(&(node, key.clone())) {
+            self.hits += 1;
+            return posterior.clone();
+        }
+        self.misses += 1;
+
+        let mut net = self.net.clone();
+        net.reset_state();
+        net.set_evidence(&key);
+        for _ in 0..self.iterations {
+            net.step();
+        }
+        for (other, belief) in net.beliefs().into_iter().enumerate() {
+            let other_key = self.key(other, &key);
+            self.entries.entry((other, other_key)).or_insert(belief);
+        }
+        self.entries[&(node, key)].clone()
+    }
+
+    /// Number of queries answered from the cache
+    pub fn hits(&self) -> usize {
+        self.hits
+    }
+
+    /// Number of queries that required running inference
+    pub fn misses(&self) -> usize {
+        self.misses
+    }
+
+    /// Number of cached posteriors
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether the cache is empty
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Remove all the cached posteriors
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
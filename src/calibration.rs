@@ -0,0 +1,175 @@
+use crate::{BayesNet, LogProbVector};
+use ndarray::Array1;
+
+/// A correction of the beliefs of a node, learned from validation data
+///
+/// The beliefs computed by Loopy Belief Propagation are often overconfident, as the evidence
+/// reaching a node through several loops is counted several times. A calibration maps them to
+/// probabilities matching the frequencies observed on validation data, see
+/// `BayesNet::set_calibration`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Calibration {
+    /// Divide the log-probabilities by this temperature before normalizing
+    ///
+    /// Temperatures above 1 soften the beliefs, and temperatures below 1 sharpen them.
+    Temperature(f32),
+    /// Map the probability of each value through a non-decreasing piecewise-linear function, then
+    /// normalize
+    ///
+    /// There is one function per value of the node, given as its breakpoints `(raw, calibrated)`
+    /// sorted by raw probability. The functions are constant outside of their breakpoints.
+    Isotonic(Vec<Vec<(f32, f32)>>),
+}
+
+fn check_validation(predictions: &[LogProbVector], outcomes: &[usize]) {
+    assert!(
+        predictions.len() == outcomes.len(),
+        "Calibration needs one outcome per validation case"
+    );
+    assert!(
+        !predictions.is_empty(),
+        "Calibration needs at least one validation case"
+    );
+}
+
+// value of a piecewise-linear function at `x`
+fn interpolate(breakpoints: &[(f32, f32)], x: f32) -> f32 {
+    match breakpoints.iter().position(|&(bx, _)| bx >= x) {
+        None => breakpoints.last().map_or(x, |&(_, y)| y),
+        Some(0) => breakpoints[0].1,
+        Some(i) => {
+            let ((x0, y0), (x1, y1)) = (breakpoints[i - 1], breakpoints[i]);
+            y0 + (y1 - y0) * (x - x0) / (x1 - x0)
+        }
+    }
+}
+
+// the isotonic regression of the points, with the pool adjacent violators algorithm
+fn isotonic_regression(mut points: Vec<(f32, f32)>) -> Vec<(f32, f32)> {
+    points.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+    // blocks of (sum of x, sum of y, number of points)
+    let mut blocks: Vec<(f32, f32, f32)> = Vec::new();
+    for (x, y) in points {
+        blocks.push((x, y, 1.0));
+        while blocks.len() > 1 {
+            let (x1, y1, n1) = blocks[blocks.len() - 1];
+            let (x0, y0, n0) = blocks[blocks.len() - 2];
+            if y0 / n0 < y1 / n1 {
+                break;
+            }
+            blocks.pop();
+            *blocks.last_mut().unwrap() = (x0 + x1, y0 + y1, n0 + n1);
+        }
+    }
+    blocks.iter().map(|&(x, y, n)| (x / n, y / n)).collect()
+}
+
+impl Calibration {
+    /// Fit a temperature minimizing the log-loss of the calibrated predictions
+    ///
+    /// `predictions[k]` is the belief of the node for the `k`-th validation case, and `outcomes[k]` the
+    /// value that was actually observed. The temperature is searched between `0.01` and `100`.
+    pub fn fit_temperature(predictions: &[LogProbVector], outcomes: &[usize]) -> Calibration {
+        check_validation(predictions, outcomes);
+        let log_loss = |log_temperature: f32| -> f32 {
+            let calibration = Calibration::Temperature(log_temperature.exp());
+            predictions
+                .iter()
+                .zip(outcomes)
+                .map(|(p, &y)| -calibration.apply(p).log_probabilities()[y])
+                .sum()
+        };
+        // golden-section search on the logarithm of the temperature
+        let ratio = (5f32.sqrt() - 1.0) / 2.0;
+        let (mut low, mut high) = (0.01f32.ln(), 100f32.ln());
+        for _ in 0..60 {
+            let a = high - ratio * (high - low);
+            let b = low + ratio * (high - low);
+            if log_loss(a) <= log_loss(b) {
+                high = b;
+            } else {
+                low = a;
+            }
+        }
+        Calibration::Temperature(((low + high) / 2.0).exp())
+    }
+
+    /// Fit one isotonic function per value, mapping its raw probability to its observed frequency
+    ///
+    /// See `fit_temperature` for the validation data. This needs more validation cases than a
+    /// temperature, but corrects any monotonic distortion of the probabilities.
+    pub fn fit_isotonic(predictions: &[LogProbVector], outcomes: &[usize]) -> Calibration {
+        check_validation(predictions, outcomes);
+        let n = predictions[0].log_probabilities().len();
+        let probas: Vec<Array1<f32>> = predictions.iter().map(|p| p.as_probabilities()).collect();
+        let functions = (0..n)
+            .map(|value| {
+                let points = probas
+                    .iter()
+                    .zip(outcomes)
+                    .map(|(p, &y)| (p[value], if y == value { 1.0 } else { 0.0 }))
+                    .collect();
+                isotonic_regression(points)
+            })
+            .collect();
+        Calibration::Isotonic(functions)
+    }
+
+    /// Apply the calibration to a belief, the result is normalized
+    pub fn apply(&self, belief: &LogProbVector) -> LogProbVector {
+        let mut calibrated = match self {
+            Calibration::Temperature(temperature) => LogProbVector::from_log_probabilities(
+                belief.log_probabilities().mapv(|l| l / temperature),
+            ),
+            Calibration::Isotonic(functions) => {
+                assert!(
+                    functions.len() == belief.log_probabilities().len(),
+                    "Isotonic calibration has {} functions for a belief over {} values",
+                    functions.len(),
+                    belief.log_probabilities().len()
+                );
+                let probas = belief.as_probabilities();
+                let mapped: Array1<f32> = probas
+                    .iter()
+                    .zip(functions)
+                    .map(|(&p, function)| interpolate(function, p))
+                    .collect();
+                // keep the raw belief if the calibration excludes every value
+                if mapped.sum() > 0.0 {
+                    LogProbVector::from_log_probabilities(mapped.mapv(f32::ln))
+                } else {
+                    belief.clone()
+                }
+            }
+        };
+        calibrated.renormalize();
+        calibrated
+    }
+}
+
+impl BayesNet {
+    /// Set (or remove) the calibration of the beliefs of a node
+    ///
+    /// The calibration is applied by `calibrated_beliefs` and by the queries of a `ModelRegistry`,
+    /// while `beliefs` and the algorithms of the network keep using the raw beliefs.
+    pub fn set_calibration(&mut self, node: usize, calibration: Option<Calibration>) {
+        self.nodes[node].calibration = calibration;
+    }
+
+    /// The calibration of the beliefs of a node, if it has one
+    pub fn calibration(&self, node: usize) -> Option<&Calibration> {
+        self.nodes[node].calibration.as_ref()
+    }
+
+    /// The beliefs of the nodes, calibrated for the nodes having a calibration
+    pub fn calibrated_beliefs(&self) -> Vec<LogProbVector> {
+        self.beliefs()
+            .into_iter()
+            .zip(&self.nodes)
+            .map(|(belief, node)| match node.calibration {
+                Some(ref calibration) => calibration.apply(&belief),
+                None => belief,
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,77 @@
+//! Causal queries on a network, read as a causal model
+//!
+//! An intervention `do(node = value)` sets the node to the value regardless of its parents, which
+//! affects its descendants but, unlike an observation, not its ancestors. It is computed on the
+//! mutilated network, in which the table of the node is replaced by a deterministic one.
+
+use crate::BayesNet;
+use ndarray::{Array1, ArrayD, Axis, IxDyn};
+
+/// The network in which the nodes of `intervention` are set to their value by `do(node = value)`
+///
+/// The table of each node of the intervention puts all the probability on its value, whatever the
+/// values of its parents, so no information flows from the node to its parents, and the evidence on
+/// these nodes is removed. The other tables are shared with `net`, and the inference state is reset.
+/// Panics if a value is out of range.
+pub fn intervene(net: &BayesNet, intervention: &[(usize, usize)]) -> BayesNet {
+    let mut mutilated = net.clone();
+    for &(node, value) in intervention {
+        assert!(
+            value < net.num_values(node),
+            "Intervention sets {} to value {}, but it only has {} values",
+            net.node_ref(node),
+            value,
+            net.num_values(node)
+        );
+        let shape = net.nodes[node].log_probas.shape();
+        let mut table = ArrayD::from_elem(IxDyn(shape), f32::NEG_INFINITY);
+        table.index_axis_mut(Axis(0), value).fill(0.0);
+        mutilated.replace_log_probas(node, table);
+        mutilated.nodes[node].evidence = None;
+    }
+    mutilated.reset_state();
+    mutilated
+}
+
+/// The posterior distribution of `outcome` under an intervention, given some observed covariates
+///
+/// This runs `iterations` steps on the network given by `intervene`, with `covariates` as evidence
+/// instead of the evidence of `net`, and returns the probabilities of the values of `outcome`.
+pub fn interventional_posterior(
+    net: &BayesNet,
+    intervention: &[(usize, usize)],
+    outcome: usize,
+    covariates: &[(usize, usize)],
+    iterations: usize,
+) -> Array1<f32> {
+    let mut mutilated = intervene(net, intervention);
+    mutilated.set_evidence(covariates);
+    for _ in 0..iterations {
+        mutilated.step();
+    }
+    mutilated.beliefs()[outcome].as_probabilities()
+}
+
+/// The average causal effect of a binary treatment on an outcome
+///
+/// Returns, for each value of `outcome`, its probability under `do(treatment = 1)` minus its
+/// probability under `do(treatment = 0)`, both given the observed `covariates` (which replace the
+/// evidence of `net`, and can be empty), see `interventional_posterior`. For a binary outcome, the
+/// second entry is the usual average causal effect. Panics if the treatment has fewer than two
+/// values.
+pub fn ace(
+    net: &BayesNet,
+    treatment: usize,
+    outcome: usize,
+    covariates: &[(usize, usize)],
+    iterations: usize,
+) -> Array1<f32> {
+    assert!(
+        net.num_values(treatment) >= 2,
+        "The treatment {} must have at least two values",
+        net.node_ref(treatment)
+    );
+    let treated = interventional_posterior(net, &[(treatment, 1)], outcome, covariates, iterations);
+    let control = interventional_posterior(net, &[(treatment, 0)], outcome, covariates, iterations);
+    treated - control
+}
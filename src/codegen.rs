@@ -0,0 +1,225 @@
+use crate::BayesNet;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+const KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "do", "dyn",
+    "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl", "in", "let",
+    "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref", "return",
+    "static", "struct", "trait", "true", "try", "type", "typeof", "unsafe", "unsized", "use",
+    "virtual", "where", "while", "yield",
+];
+
+const NODE_MATCHES: &str = "
+}
+
+fn node_matches(
+    net: &::loopybayesnet::BayesNet,
+    id: usize,
+    name: &str,
+    num_values: usize,
+    states: Option<&[&str]>,
+) -> bool {
+    net.node_name(id) == Some(name)
+        && net.num_values(id) == num_values
+        && match (net.state_names(id), states) {
+            (Some(names), Some(states)) => {
+                names.iter().map(String::as_str).eq(states.iter().copied())
+            }
+            (None, None) => true,
+            _ => false,
+        }
+}
+";
+
+// the words of a name, splitting on anything that is not alphanumeric
+fn words(name: &str) -> Vec<String> {
+    name.split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_owned)
+        .collect()
+}
+
+// a snake_case module name
+fn module_ident(name: &str) -> String {
+    let mut ident = words(name).join("_").to_ascii_lowercase();
+    if ident.is_empty() || ident.starts_with(|c: char| c.is_ascii_digit()) {
+        ident.insert(0, '_');
+    }
+    match ident.as_str() {
+        // these cannot be raw identifiers
+        "crate" | "self" | "super" => ident.push('_'),
+        _ if KEYWORDS.contains(&ident.as_str()) => ident.insert_str(0, "r#"),
+        _ => {}
+    }
+    ident
+}
+
+// a CamelCase variant name
+fn variant_ident(name: &str) -> String {
+    let mut ident: String = words(name)
+        .iter()
+        .map(|w| {
+            let mut chars = w.chars();
+            let first = chars.next().unwrap().to_ascii_uppercase();
+            std::iter::once(first).chain(chars).collect::<String>()
+        })
+        .collect();
+    if ident.is_empty() || ident.starts_with(|c: char| c.is_ascii_digit()) {
+        ident.insert(0, 'V');
+    }
+    if ident == "Self" {
+        ident.push('_');
+    }
+    ident
+}
+
+// the nodes of a namespace, by module name
+#[derive(Default)]
+struct Module {
+    node: Option<usize>,
+    submodules: BTreeMap<String, Module>,
+}
+
+impl BayesNet {
+    /// Generate Rust bindings to the named nodes of the network
+    ///
+    /// This is meant to be called from a build script on a model file shipped with the application,
+    /// writing the result to `OUT_DIR` to be included with `include!` in a module, with
+    /// `#[allow(dead_code)]` if only some of the nodes are used. Each named node gets a module,
+    /// nested following its namespaces, with its `ID`, its `NUM_VALUES` and a `State` enum of its
+    /// values, so that the evidence of a node can only be given with the states of this node:
+    ///
+    /// ```text
+    /// net.set_evidence(&[model::weather::rain::State::Yes.evidence()]);
+    /// ```
+    ///
+    /// Names are converted to `snake_case` modules and `CamelCase` variants, and values without a
+    /// state name are called `V0`, `V1`, etc. The generated `matches` function checks that a network
+    /// loaded at runtime has the same nodes as the one the bindings were generated from. Unnamed
+    /// nodes are skipped.
+    ///
+    /// Panics if two nodes, or two states of a node, have names converted to the same identifier.
+    pub fn rust_bindings(&self) -> String {
+        let mut root = Module::default();
+        let mut paths = BTreeMap::new();
+        for node in 0..self.num_nodes() {
+            let name = match self.node_name(node) {
+                Some(name) => name,
+                None => continue,
+            };
+            let path: Vec<String> = name.split('/').map(module_ident).collect();
+            if let Some(other) = paths.insert(path.clone(), node) {
+                panic!(
+                    "Nodes {} and {} are both bound to the module {}",
+                    other,
+                    node,
+                    path.join("::")
+                );
+            }
+            let module = path.into_iter().fold(&mut root, |module, ident| {
+                module.submodules.entry(ident).or_default()
+            });
+            module.node = Some(node);
+        }
+
+        let mut code = String::from("// Generated by loopybayesnet, do not edit\n\n");
+        writeln!(code, "pub const NUM_NODES: usize = {};", self.num_nodes()).unwrap();
+        self.write_submodules(&mut code, &root, 0);
+        code.push_str("\n/// Whether a network has the nodes these bindings were generated from\n");
+        code.push_str("pub fn matches(net: &::loopybayesnet::BayesNet) -> bool {\n");
+        code.push_str("    net.num_nodes() == NUM_NODES");
+        for &node in paths.values() {
+            let states = self
+                .state_names(node)
+                .map(|names| format!("Some(&{:?})", names))
+                .unwrap_or_else(|| "None".to_owned());
+            write!(
+                code,
+                "\n        && node_matches(net, {}, {:?}, {}, {})",
+                node,
+                self.node_name(node).unwrap(),
+                self.num_values(node),
+                states
+            )
+            .unwrap();
+        }
+        code.push_str(NODE_MATCHES);
+        code
+    }
+
+    fn write_submodules(&self, code: &mut String, module: &Module, depth: usize) {
+        let indent = "    ".repeat(depth);
+        for (i, (ident, submodule)) in module.submodules.iter().enumerate() {
+            // no blank line at the start of a namespace without a node
+            if i > 0 || depth == 0 || module.node.is_some() {
+                code.push('\n');
+            }
+            writeln!(code, "{}pub mod {} {{", indent, ident).unwrap();
+            if let Some(node) = submodule.node {
+                self.write_node(code, node, depth + 1);
+            }
+            self.write_submodules(code, submodule, depth + 1);
+            writeln!(code, "{}}}", indent).unwrap();
+        }
+    }
+
+    fn write_node(&self, code: &mut String, node: usize, depth: usize) {
+        let indent = "    ".repeat(depth);
+        let n_values = self.num_values(node);
+        let variants: Vec<String> = match self.state_names(node) {
+            Some(names) => names.iter().map(|name| variant_ident(name)).collect(),
+            None => (0..n_values).map(|v| format!("V{}", v)).collect(),
+        };
+        for (i, variant) in variants.iter().enumerate() {
+            if let Some(j) = variants[..i].iter().position(|other| other == variant) {
+                panic!(
+                    "States {} and {} of {} are both bound to the variant {}",
+                    j,
+                    i,
+                    self.node_ref(node),
+                    variant
+                );
+            }
+        }
+        let all = variants
+            .iter()
+            .map(|v| format!("State::{}", v))
+            .collect::<Vec<_>>()
+            .join(", ");
+        writeln!(
+            code,
+            "{i}/// The node {name:?}\n\
+             {i}pub const ID: usize = {id};\n\
+             {i}pub const NUM_VALUES: usize = {n};\n\n\
+             {i}#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]\n\
+             {i}pub enum State {{",
+            i = indent,
+            name = self.node_name(node).unwrap(),
+            id = node,
+            n = n_values,
+        )
+        .unwrap();
+        for (value, variant) in variants.iter().enumerate() {
+            writeln!(code, "{}    {} = {},", indent, variant, value).unwrap();
+        }
+        writeln!(
+            code,
+            "{i}}}\n\n\
+             {i}impl State {{\n\
+             {i}    pub const ALL: [State; NUM_VALUES] = [{all}];\n\n\
+             {i}    /// The value of the node for this state\n\
+             {i}    pub fn value(self) -> usize {{\n\
+             {i}        self as usize\n\
+             {i}    }}\n\n\
+             {i}    /// This state as evidence, for `BayesNet::set_evidence`\n\
+             {i}    pub fn evidence(self) -> (usize, usize) {{\n\
+             {i}        (ID, self as usize)\n\
+             {i}    }}\n\
+             {i}}}",
+            i = indent,
+            all = all,
+        )
+        .unwrap();
+    }
+}
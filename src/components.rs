@@ -0,0 +1,174 @@
+use crate::network::Node;
+use crate::BayesNet;
+
+/// The outcome of the propagation of one connected component by `BayesNet::propagate_components`
+#[derive(Debug, Clone)]
+pub struct ComponentStatus {
+    /// Nodes of the component, in increasing order
+    pub nodes: Vec<usize>,
+    /// Number of steps run on the component
+    pub iterations: usize,
+    /// Whether the beliefs of the component stabilized before the maximum number of steps
+    pub converged: bool,
+    /// Estimate of the log-probability of the evidence set on the component, see
+    /// `BayesNet::component_log_evidence`
+    pub log_evidence: f32,
+}
+
+impl BayesNet {
+    /// The connected components of the network, ignoring the direction of the edges
+    ///
+    /// Each component is the list of its nodes in increasing order, and the components are sorted by
+    /// their first node. Nodes of different components are independent.
+    pub fn components(&self) -> Vec<Vec<usize>> {
+        let labels = self.component_labels();
+        let mut components: Vec<Vec<usize>> = Vec::new();
+        for (node, &label) in labels.iter().enumerate() {
+            if label == components.len() {
+                components.push(Vec::new());
+            }
+            components[label].push(node);
+        }
+        components
+    }
+
+    /// Index of the component of a node, in the list given by `components`
+    pub fn component_of(&self, node: usize) -> usize {
+        self.component_labels()[node]
+    }
+
+    /// Estimate of `log P(evidence)` for each component, in the order of `components`
+    ///
+    /// The components being independent, the probability of the evidence of the whole network is the
+    /// product of these. They are computed from the current messages using the Bethe free energy (see
+    /// `bethe_free_energy`), and are exact for components without loops once their propagation
+    /// converged.
+    pub fn component_log_evidence(&self) -> Vec<f32> {
+        let labels = self.component_labels();
+        let mut log_evidence = vec![0.0f64; labels.iter().map(|&l| l + 1).max().unwrap_or(0)];
+        for (term, &label) in self.bethe_node_terms().into_iter().zip(labels.iter()) {
+            log_evidence[label] += term;
+        }
+        log_evidence.into_iter().map(|l| l as f32).collect()
+    }
+
+    /// Run the Loopy Belief Propagation on each connected component independently
+    ///
+    /// Each component is stepped until the largest change of the probabilities of its beliefs during a
+    /// step is below `tolerance`, or for at most `max_iterations` steps. Components that converge quickly
+    /// are not stepped anymore while the others go on, and the components are processed in parallel on
+    /// the available threads. The step counter of the network advances by the largest number of steps
+    /// run on a component.
+    pub fn propagate_components(
+        &mut self,
+        max_iterations: usize,
+        tolerance: f32,
+    ) -> Vec<ComponentStatus> {
+        let components = self.components();
+        let mut subnets: Vec<(BayesNet, ComponentStatus)> = components
+            .into_iter()
+            .map(|nodes| {
+                let subnet = self.subnet(&nodes);
+                let status = ComponentStatus {
+                    nodes,
+                    iterations: 0,
+                    converged: false,
+                    log_evidence: 0.0,
+                };
+                (subnet, status)
+            })
+            .collect();
+
+        let workers = std::thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(1)
+            .min(subnets.len())
+            .max(1);
+        let chunk_size = subnets.len().div_ceil(workers);
+        std::thread::scope(|scope| {
+            for chunk in subnets.chunks_mut(chunk_size.max(1)) {
+                scope.spawn(move || {
+                    for (subnet, status) in chunk {
+                        propagate(subnet, status, max_iterations, tolerance);
+                    }
+                });
+            }
+        });
+
+        let mut steps = 0;
+        let mut statuses = Vec::with_capacity(subnets.len());
+        for (subnet, status) in subnets {
+            steps = steps.max(status.iterations);
+            for (node, &id) in subnet.nodes.into_iter().zip(status.nodes.iter()) {
+                self.nodes[id] = remap(node, |local| status.nodes[local]);
+            }
+            statuses.push(status);
+        }
+        self.iteration += steps;
+        statuses
+    }
+
+    // a network made of the given nodes, which must be closed under the edges, with their state
+    fn subnet(&self, nodes: &[usize]) -> BayesNet {
+        let mut local = vec![usize::MAX; self.nodes.len()];
+        for (i, &id) in nodes.iter().enumerate() {
+            local[id] = i;
+        }
+        BayesNet {
+            nodes: nodes
+                .iter()
+                .map(|&id| remap(self.nodes[id].clone(), |other| local[other]))
+                .collect(),
+            iteration: self.iteration,
+            numerics: self.numerics,
+            engine: self.engine,
+            damping: self.damping,
+            ..BayesNet::new()
+        }
+    }
+
+    // the index of the component of each node, components being numbered in the order of their first node
+    pub(crate) fn component_labels(&self) -> Vec<usize> {
+        let n = self.nodes.len();
+        let mut labels = vec![usize::MAX; n];
+        let mut count = 0;
+        for start in 0..n {
+            if labels[start] != usize::MAX {
+                continue;
+            }
+            labels[start] = count;
+            let mut stack = vec![start];
+            while let Some(node) = stack.pop() {
+                let node = &self.nodes[node];
+                for &(other, _) in node.parents.iter().chain(node.children.iter()) {
+                    if labels[other] == usize::MAX {
+                        labels[other] = count;
+                        stack.push(other);
+                    }
+                }
+            }
+            count += 1;
+        }
+        labels
+    }
+}
+
+fn propagate(
+    net: &mut BayesNet,
+    status: &mut ComponentStatus,
+    max_iterations: usize,
+    tolerance: f32,
+) {
+    let (iterations, converged) = net.run_until_convergence(tolerance, max_iterations);
+    status.iterations = iterations;
+    status.converged = converged;
+    status.log_evidence = net.bethe_log_evidence();
+}
+
+// change the ids of the neighbours of a node
+fn remap<F: Fn(usize) -> usize>(mut node: Node, map: F) -> Node {
+    for (other, _) in node.parents.iter_mut().chain(node.children.iter_mut()) {
+        *other = map(*other);
+    }
+    node
+}
@@ -0,0 +1,375 @@
+use crate::semiring::Semiring;
+use crate::table::Table;
+use ndarray::{Array1, ArrayD, ArrayView1, ArrayViewD, Axis, IxDyn};
+use std::sync::Arc;
+
+/// A tree-structured conditional probability table
+///
+/// Inner nodes split on the value of one of the parents (identified by its position in the
+/// parent list of the node), and leaves hold the log-probability vector of the node for all the
+/// parent configurations reaching them. Parents that are not tested along a path have no influence
+/// on the distribution of the node in this context (context-specific independence).
+#[derive(Debug, Clone, PartialEq)]
+pub enum CptTree {
+    /// A distribution shared by all parent configurations reaching this leaf, as log-probabilities
+    Leaf(Array1<f32>),
+    /// A test on the value of a parent
+    Split {
+        /// Position of the tested parent in the parent list of the node
+        parent: usize,
+        /// One subtree for each value of the tested parent
+        children: Vec<CptTree>,
+    },
+}
+
+fn max_abs_diff(a: ArrayView1<f32>, b: ArrayView1<f32>) -> f32 {
+    a.iter()
+        .zip(b.iter())
+        .map(|(&x, &y)| (x.exp() - y.exp()).abs())
+        .fold(0.0, f32::max)
+}
+
+// columns of a (sub)table, i.e. its lanes along the axis of the node values
+fn columns<'a>(table: &'a ArrayViewD<'_, f32>) -> Vec<ArrayView1<'a, f32>> {
+    table.lanes(Axis(0)).into_iter().collect()
+}
+
+fn mean_log_column(table: &ArrayViewD<f32>) -> Array1<f32> {
+    let cols = columns(table);
+    let n = cols.len() as f32;
+    let sum = cols
+        .iter()
+        .fold(Array1::zeros(table.shape()[0]), |acc: Array1<f32>, c| {
+            acc + c.mapv(f32::exp)
+        });
+    (sum / n).mapv(f32::ln)
+}
+
+fn is_homogeneous(table: &ArrayViewD<f32>, tolerance: f32) -> bool {
+    let cols = columns(table);
+    let mean = mean_log_column(table);
+    cols.iter()
+        .all(|c| max_abs_diff(c.view(), mean.view()) <= tolerance)
+}
+
+fn build(table: ArrayViewD<f32>, tolerance: f32, free_axes: &[usize]) -> CptTree {
+    if free_axes.is_empty() || is_homogeneous(&table, tolerance) {
+        return CptTree::Leaf(mean_log_column(&table));
+    }
+    // greedily split on the parent producing the most homogeneous sub-tables
+    let best = *free_axes
+        .iter()
+        .max_by_key(|&&axis| {
+            let homogeneous = table
+                .axis_iter(Axis(axis + 1))
+                .filter(|sub| {
+                    // `axis_iter` removes the axis, keep it with a length of 1 instead
+                    is_homogeneous(&sub.view().insert_axis(Axis(axis + 1)), tolerance)
+                })
+                .count();
+            // prefer the first parents on ties
+            (homogeneous, std::cmp::Reverse(axis))
+        })
+        .unwrap();
+    let remaining: Vec<usize> = free_axes.iter().cloned().filter(|&a| a != best).collect();
+    let children = (0..table.shape()[best + 1])
+        .map(|v| {
+            build(
+                table
+                    .index_axis(Axis(best + 1), v)
+                    .insert_axis(Axis(best + 1)),
+                tolerance,
+                &remaining,
+            )
+        })
+        .collect();
+    CptTree::Split {
+        parent: best,
+        children,
+    }
+}
+
+impl CptTree {
+    /// A leaf from a vector of probabilities, which does not need to be normalized
+    pub fn leaf(probabilities: Array1<f32>) -> CptTree {
+        CptTree::Leaf(probabilities.mapv(f32::ln))
+    }
+
+    /// A test on the parent at position `parent` in the parent list, with one subtree per value
+    pub fn split(parent: usize, children: Vec<CptTree>) -> CptTree {
+        CptTree::Split { parent, children }
+    }
+
+    /// Build a tree from a dense table of log-probabilities, merging near-identical configurations
+    ///
+    /// The table has the shape `(N, N_p1, ... N_pk)`, as for `BayesNet::add_node_from_log_probabilities`,
+    /// and each of its columns must be normalized. Parent configurations whose distributions differ by
+    /// at most `tolerance` (in absolute probability, for every value) are merged into a single leaf
+    /// holding their average distribution. A `tolerance` of `0.0` only merges identical distributions.
+    pub fn from_log_table(table: ArrayViewD<f32>, tolerance: f32) -> CptTree {
+        let free_axes: Vec<usize> = (0..table.ndim() - 1).collect();
+        build(table, tolerance, &free_axes)
+    }
+
+    // check the tree against the number of values of the node and of its parents, and normalize its leaves
+    fn check_and_normalize(
+        &mut self,
+        n_values: usize,
+        parent_sizes: &[usize],
+        tested: &mut Vec<usize>,
+    ) {
+        match self {
+            CptTree::Leaf(v) => {
+                assert!(
+                    v.len() == n_values,
+                    "All leaves of a CPT tree must have the same length: got {} and {}",
+                    v.len(),
+                    n_values
+                );
+                let norm = crate::math::log_sum_exp_vec(v.view());
+                v.mapv_inplace(|x| x - norm);
+            }
+            CptTree::Split { parent, children } => {
+                assert!(
+                    *parent < parent_sizes.len(),
+                    "CPT tree tests parent {} but the node only has {} parents",
+                    parent,
+                    parent_sizes.len()
+                );
+                assert!(
+                    !tested.contains(parent),
+                    "CPT tree tests parent {} twice on the same path",
+                    parent
+                );
+                assert!(
+                    children.len() == parent_sizes[*parent],
+                    "CPT tree test on parent {} has {} branches but the parent has {} values",
+                    parent,
+                    children.len(),
+                    parent_sizes[*parent]
+                );
+                tested.push(*parent);
+                for child in children {
+                    child.check_and_normalize(n_values, parent_sizes, tested);
+                }
+                tested.pop();
+            }
+        }
+    }
+
+    // number of values of the node, as given by the length of its leaves
+    fn num_values(&self) -> usize {
+        match self {
+            CptTree::Leaf(v) => v.len(),
+            CptTree::Split { children, .. } => children
+                .first()
+                .map(CptTree::num_values)
+                .expect("A CPT tree test must have at least one branch"),
+        }
+    }
+
+    /// Number of leaves of the tree
+    pub fn num_leaves(&self) -> usize {
+        match self {
+            CptTree::Leaf(_) => 1,
+            CptTree::Split { children, .. } => children.iter().map(CptTree::num_leaves).sum(),
+        }
+    }
+
+    /// Number of probabilities stored in the leaves of the tree
+    pub fn num_parameters(&self) -> usize {
+        match self {
+            CptTree::Leaf(v) => v.len(),
+            CptTree::Split { children, .. } => children.iter().map(CptTree::num_parameters).sum(),
+        }
+    }
+
+    /// The log-probability vector of the node for a full configuration of its parents
+    pub fn log_probabilities(&self, parent_values: &[usize]) -> ArrayView1<'_, f32> {
+        match self {
+            CptTree::Leaf(v) => v.view(),
+            CptTree::Split { parent, children } => {
+                children[parent_values[*parent]].log_probabilities(parent_values)
+            }
+        }
+    }
+
+    /// Expand the tree into a dense log-probability table of the given shape `(N, N_p1, ... N_pk)`
+    pub fn to_log_table(&self, shape: &[usize]) -> ArrayD<f32> {
+        let mut table = ArrayD::zeros(IxDyn(shape));
+        let n_parents = shape.len() - 1;
+        let mut config = vec![0; n_parents];
+        let n_configs: usize = shape[1..].iter().product();
+        for _ in 0..n_configs {
+            let leaf = self.log_probabilities(&config);
+            let mut index = Vec::with_capacity(shape.len());
+            index.push(0);
+            index.extend_from_slice(&config);
+            for (x, &lp) in leaf.iter().enumerate() {
+                index[0] = x;
+                table[IxDyn(&index)] = lp;
+            }
+            // next configuration, last parent varying fastest
+            for axis in (0..n_parents).rev() {
+                config[axis] += 1;
+                if config[axis] < shape[axis + 1] {
+                    break;
+                }
+                config[axis] = 0;
+            }
+        }
+        table
+    }
+
+    /// Compute the pi vector of the node: `sum_pa P(x | pa) prod_i msgs[i](pa_i)`, in log-space
+    ///
+    /// The messages must be normalized, as parents not tested in a branch are summed out implicitly.
+    pub(crate) fn pi<S: Semiring>(&self, msgs: &[ArrayView1<f32>]) -> Array1<f32> {
+        match self {
+            CptTree::Leaf(v) => v.clone(),
+            CptTree::Split { parent, children } => {
+                let terms: Vec<Array1<f32>> = children
+                    .iter()
+                    .zip(msgs[*parent].iter())
+                    .map(|(child, &m)| child.pi::<S>(msgs) + m)
+                    .collect();
+                sum_arrays::<S>(&terms)
+            }
+        }
+    }
+
+    /// Compute the lambda message to parent `target`, in log-space:
+    /// `sum_x lambda(x) sum_{pa \ pa_target} P(x | pa) prod_{i != target} msgs[i](pa_i)`
+    ///
+    /// The messages must be normalized, as parents not tested in a branch are summed out implicitly.
+    pub(crate) fn lambda_message<S: Semiring>(
+        &self,
+        target: usize,
+        target_size: usize,
+        lambda: ArrayView1<f32>,
+        msgs: &[ArrayView1<f32>],
+    ) -> Array1<f32> {
+        match self {
+            CptTree::Leaf(v) => {
+                let value = S::sum((v + &lambda).view());
+                Array1::from_elem(target_size, value)
+            }
+            CptTree::Split { parent, children } if *parent == target => {
+                Array1::from_shape_fn(target_size, |v| {
+                    children[v].lambda_message::<S>(target, target_size, lambda, msgs)[v]
+                })
+            }
+            CptTree::Split { parent, children } => {
+                let terms: Vec<Array1<f32>> = children
+                    .iter()
+                    .zip(msgs[*parent].iter())
+                    .map(|(child, &m)| {
+                        child.lambda_message::<S>(target, target_size, lambda, msgs) + m
+                    })
+                    .collect();
+                sum_arrays::<S>(&terms)
+            }
+        }
+    }
+}
+
+// element-wise sum of several arrays of the same length
+fn sum_arrays<S: Semiring>(terms: &[Array1<f32>]) -> Array1<f32> {
+    let len = terms[0].len();
+    Array1::from_shape_fn(len, |i| {
+        let column: Array1<f32> = terms.iter().map(|t| t[i]).collect();
+        S::sum(column.view())
+    })
+}
+
+/// The result of the reduction of the probability table of a node by `BayesNet::reduce_cpts`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CptReduction {
+    /// The reduced node
+    pub node: usize,
+    /// Number of probabilities of the dense table
+    pub dense_parameters: usize,
+    /// Number of probabilities in the leaves of the tree
+    pub tree_parameters: usize,
+}
+
+impl crate::BayesNet {
+    /// Replace the probability table of a node by a tree merging near-identical parent configurations
+    ///
+    /// See `CptTree::from_log_table` for the meaning of `tolerance`. Once reduced, the messages of
+    /// the node are computed by traversing the tree, which is faster than contracting the dense table
+    /// when it has few leaves. The tree replaces the dense table of the node, which is not stored
+    /// anymore: the algorithms working on whole tables, such as exact inference or learning, expand it
+    /// temporarily.
+    ///
+    /// Nodes without parents are left untouched.
+    pub fn reduce_cpt(&mut self, node: usize, tolerance: f32) -> CptReduction {
+        let dense_parameters = self.nodes[node].log_probas.len();
+        if self.nodes[node].parents.is_empty() {
+            return CptReduction {
+                node,
+                dense_parameters,
+                tree_parameters: dense_parameters,
+            };
+        }
+        let tree = CptTree::from_log_table(self.nodes[node].log_probas.dense().view(), tolerance);
+        self.set_cpt_tree(node, tree)
+    }
+
+    // replace the table of a node by a tree built from it
+    fn set_cpt_tree(&mut self, node: usize, tree: CptTree) -> CptReduction {
+        let reduction = CptReduction {
+            node,
+            dense_parameters: self.nodes[node].log_probas.len(),
+            tree_parameters: tree.num_parameters(),
+        };
+        let shape = self.nodes[node].log_probas.shape().to_vec();
+        self.set_table(node, Table::Tree(Arc::new(tree), shape));
+        reduction
+    }
+
+    /// Add a new node to the network whose probability table is given by a tree
+    ///
+    /// `parents` are the parents of the node, which are referred to by their position in this list in
+    /// the tests of the tree. Each test must have one branch per value of the tested parent, a parent
+    /// cannot be tested twice on the same path, and all leaves must have the same length, which is
+    /// the number of values of the node. The leaves do not need to be normalized.
+    ///
+    /// The messages of the node are computed by traversing the tree, so their cost grows with the size
+    /// of the tree rather than the size of the full table, and the full table is not stored.
+    pub fn add_node_from_cpt_tree(&mut self, parents: &[usize], mut tree: CptTree) -> usize {
+        let n_values = tree.num_values();
+        let parent_sizes: Vec<usize> = parents.iter().map(|&p| self.num_values(p)).collect();
+        tree.check_and_normalize(n_values, &parent_sizes, &mut Vec::new());
+        let mut shape = vec![n_values];
+        shape.extend_from_slice(&parent_sizes);
+        self.push_node(parents, Table::Tree(Arc::new(tree), shape))
+    }
+
+    /// Reduce the probability tables of all nodes with parents, see `reduce_cpt`
+    ///
+    /// Only the nodes whose table can actually be shrunk are reduced, the others keep their dense
+    /// table. Returns a report for each reduced node.
+    pub fn reduce_cpts(&mut self, tolerance: f32) -> Vec<CptReduction> {
+        let mut reductions = Vec::new();
+        for node in 0..self.num_nodes() {
+            if self.nodes[node].parents.is_empty() {
+                continue;
+            }
+            let tree =
+                CptTree::from_log_table(self.nodes[node].log_probas.dense().view(), tolerance);
+            if tree.num_parameters() < self.nodes[node].log_probas.len() {
+                reductions.push(self.set_cpt_tree(node, tree));
+            }
+        }
+        reductions
+    }
+
+    /// The tree representation of the probability table of a node, if it has one
+    pub fn cpt_tree(&self, node: usize) -> Option<&CptTree> {
+        match self.nodes[node].log_probas {
+            Table::Tree(ref tree, _) => Some(tree),
+            _ => None,
+        }
+    }
+}
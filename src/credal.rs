@@ -0,0 +1,259 @@
+use crate::{BayesNet, NumericsPolicy};
+use ndarray::{Array, Array1, Array2, Dimension, RemoveAxis};
+
+#[derive(Debug, Clone)]
+struct CredalNode {
+    // shape (n_values, n_parent_configurations)
+    lower: Array2<f32>,
+    upper: Array2<f32>,
+    // for each column, the vertices of its credal set
+    vertices: Vec<Vec<Array1<f32>>>,
+    // for each column, the vertex currently used in the precise network
+    current: Array2<f32>,
+}
+
+impl CredalNode {
+    fn write_into(&self, net: &mut BayesNet, id: usize) {
+        let shape = net.nodes[id].log_probas.shape().to_vec();
+        let log_probas = self.current.mapv(f32::ln).into_shape(shape).unwrap();
+        net.replace_log_probas(id, log_probas);
+    }
+}
+
+/// Enumerate the vertices of `{ p | lower <= p <= upper, sum(p) = 1 }`
+///
+/// Each vertex has all its coordinates but one at one of their bounds.
+fn interval_vertices(lower: &[f32], upper: &[f32], eps: f32) -> Vec<Array1<f32>> {
+    let n = lower.len();
+    let mut vertices: Vec<Array1<f32>> = Vec::new();
+    for free in 0..n {
+        for mask in 0..(1usize << (n - 1)) {
+            let mut p = Array1::zeros(n);
+            for (bit, i) in (0..n).filter(|&i| i != free).enumerate() {
+                p[i] = if mask & (1 << bit) != 0 {
+                    upper[i]
+                } else {
+                    lower[i]
+                };
+            }
+            let rest = 1.0 - p.sum();
+            if rest >= lower[free] - eps && rest <= upper[free] + eps {
+                p[free] = rest.max(lower[free]).min(upper[free]);
+                if !vertices
+                    .iter()
+                    .any(|v| v.iter().zip(p.iter()).all(|(a, b)| (a - b).abs() < eps))
+                {
+                    vertices.push(p);
+                }
+            }
+        }
+    }
+    vertices
+}
+
+/// A credal network: a Bayesian Network whose probabilities are only known to lie within intervals
+///
+/// Each column of the conditional probability tables (the distribution of a node for a given
+/// configuration of its parents) is given as a lower and an upper bound on each probability.
+/// Inference then yields lower and upper bounds on the posterior probabilities rather than
+/// precise values.
+#[derive(Debug, Clone)]
+pub struct CredalNet {
+    net: BayesNet,
+    nodes: Vec<CredalNode>,
+}
+
+impl Default for CredalNet {
+    fn default() -> CredalNet {
+        CredalNet::new()
+    }
+}
+
+impl CredalNet {
+    /// Create a new empty credal network
+    pub fn new() -> CredalNet {
+        CredalNet {
+            net: BayesNet::new(),
+            nodes: Vec::new(),
+        }
+    }
+
+    /// Set the numeric policy of the underlying precise network
+    ///
+    /// Its `comparison_epsilon` is the tolerance used to check the interval bounds and to compare the
+    /// posteriors during the optimization.
+    pub fn set_numerics(&mut self, policy: NumericsPolicy) {
+        self.net.set_numerics(policy);
+    }
+
+    /// Add a new node to the network from interval-valued probabilities
+    ///
+    /// The arrays `lower` and `upper` have the same shape as the array given to
+    /// `BayesNet::add_node_from_probabilities`. Unlike there, the bounds must be actual probabilities:
+    /// for every configuration of the parents, the lower bounds must sum to at most `1` and the upper
+    /// bounds to at least `1`, and each lower bound must not exceed its upper bound.
+    ///
+    /// Panics if these conditions are not met.
+    pub fn add_node_from_intervals<D: Dimension + RemoveAxis>(
+        &mut self,
+        parents: &[usize],
+        lower: Array<f32, D>,
+        upper: Array<f32, D>,
+    ) -> usize {
+        assert!(
+            lower.shape() == upper.shape(),
+            "Lower and upper bounds must have the same shape"
+        );
+        let eps = self.net.numerics().comparison_epsilon;
+        let shape = lower.shape().to_vec();
+        let n_values = shape[0];
+        let n_cols = lower.len() / n_values.max(1);
+        let lower = lower
+            .as_standard_layout()
+            .into_owned()
+            .into_shape((n_values, n_cols))
+            .unwrap();
+        let upper = upper
+            .as_standard_layout()
+            .into_owned()
+            .into_shape((n_values, n_cols))
+            .unwrap();
+
+        let mut vertices = Vec::with_capacity(n_cols);
+        let mut current = Array2::zeros((n_values, n_cols));
+        for col in 0..n_cols {
+            let l = lower.column(col).to_vec();
+            let u = upper.column(col).to_vec();
+            assert!(
+                l.iter().zip(u.iter()).all(|(&l, &u)| 0.0 <= l && l <= u),
+                "Invalid interval bounds for configuration {}",
+                col
+            );
+            let (sum_l, sum_u) = (l.iter().sum::<f32>(), u.iter().sum::<f32>());
+            assert!(
+                sum_l <= 1.0 + eps && sum_u >= 1.0 - eps,
+                "Interval bounds for configuration {} do not contain any probability distribution",
+                col
+            );
+            // start from a point in the middle of the credal set
+            let ratio = if sum_u > sum_l {
+                (1.0 - sum_l) / (sum_u - sum_l)
+            } else {
+                0.0
+            };
+            for i in 0..n_values {
+                current[(i, col)] = l[i] + ratio * (u[i] - l[i]);
+            }
+            vertices.push(interval_vertices(&l, &u, eps));
+        }
+
+        let id = self
+            .net
+            .add_node_from_probabilities(parents, current.clone().into_shape(shape).unwrap());
+        self.nodes.push(CredalNode {
+            lower,
+            upper,
+            vertices,
+            current,
+        });
+        id
+    }
+
+    /// Lower bounds of the probabilities of a node, as a `(n_values, n_parent_configurations)` array
+    pub fn lower(&self, node: usize) -> &Array2<f32> {
+        &self.nodes[node].lower
+    }
+
+    /// Upper bounds of the probabilities of a node, as a `(n_values, n_parent_configurations)` array
+    pub fn upper(&self, node: usize) -> &Array2<f32> {
+        &self.nodes[node].upper
+    }
+
+    /// The precise network currently selected inside the credal set
+    ///
+    /// After a call to `posterior_bounds`, this is the network realizing the last computed bound.
+    pub fn precise_net(&self) -> &BayesNet {
+        &self.net
+    }
+
+    fn posterior(
+        &mut self,
+        evidence: &[(usize, usize)],
+        target: usize,
+        state: usize,
+        iterations: usize,
+    ) -> f32 {
+        self.net.reset_state();
+        self.net.set_evidence(evidence);
+        for _ in 0..iterations {
+            self.net.step();
+        }
+        self.net.beliefs()[target].as_probabilities()[state]
+    }
+
+    fn optimize(
+        &mut self,
+        evidence: &[(usize, usize)],
+        target: usize,
+        state: usize,
+        iterations: usize,
+        maximize: bool,
+    ) -> f32 {
+        let eps = self.net.numerics().comparison_epsilon;
+        let sign = if maximize { 1.0 } else { -1.0 };
+        let mut best = sign * self.posterior(evidence, target, state, iterations);
+        // Coordinate-wise search: the posterior is a linear-fractional function of each
+        // column taken separately, so its optimum over a column is attained at a vertex.
+        loop {
+            let mut improved = false;
+            for id in 0..self.nodes.len() {
+                for col in 0..self.nodes[id].vertices.len() {
+                    let mut best_vertex = self.nodes[id].current.column(col).to_owned();
+                    for v in 0..self.nodes[id].vertices[col].len() {
+                        let vertex = self.nodes[id].vertices[col][v].clone();
+                        self.nodes[id].current.column_mut(col).assign(&vertex);
+                        self.nodes[id].write_into(&mut self.net, id);
+                        let value = sign * self.posterior(evidence, target, state, iterations);
+                        if value > best + eps {
+                            best = value;
+                            best_vertex = vertex;
+                            improved = true;
+                        }
+                    }
+                    self.nodes[id].current.column_mut(col).assign(&best_vertex);
+                    self.nodes[id].write_into(&mut self.net, id);
+                }
+            }
+            if !improved {
+                break;
+            }
+        }
+        sign * best
+    }
+
+    /// Compute lower and upper bounds of the posterior probability of each value of `target`
+    ///
+    /// For each bound, the precise network within the credal set is optimized column by column,
+    /// selecting for each column the vertex of its credal set that pushes the posterior furthest,
+    /// until no column can improve it. Each evaluation runs the Loopy Belief Propagation for
+    /// `iterations` steps.
+    ///
+    /// The returned bounds are always attained by a precise network of the credal set, and are
+    /// thus inner approximations of the exact bounds: they are exact when the posterior is monotone
+    /// with respect to each column, which is notably the case for a single uncertain column, but may
+    /// be too narrow on networks where a local optimum is reached.
+    pub fn posterior_bounds(
+        &mut self,
+        evidence: &[(usize, usize)],
+        target: usize,
+        iterations: usize,
+    ) -> Vec<(f32, f32)> {
+        (0..self.net.num_values(target))
+            .map(|state| {
+                let low = self.optimize(evidence, target, state, iterations, false);
+                let high = self.optimize(evidence, target, state, iterations, true);
+                (low, high)
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,75 @@
+use crate::graph::{find_loop_cutset, structure, CutsetHeuristic};
+use crate::{BayesNet, LogProbVector};
+use ndarray::Array1;
+
+impl BayesNet {
+    /// Find a loop cutset of the network
+    ///
+    /// Observing all the nodes of a loop cutset blocks every loop of the network for the Belief
+    /// Propagation, which then becomes exact. This uses `graph::find_loop_cutset` with the
+    /// `CutsetHeuristic::MaxDegree` heuristic.
+    pub fn loop_cutset(&self) -> Vec<usize> {
+        let cardinalities: Vec<usize> = (0..self.num_nodes()).map(|i| self.num_values(i)).collect();
+        find_loop_cutset(&structure(self), &cardinalities, CutsetHeuristic::MaxDegree).nodes
+    }
+
+    /// Compute the beliefs of the nodes given some evidence by cutset conditioning
+    ///
+    /// Each joint assignment of the unobserved nodes of `loop_cutset` is added to the evidence in turn,
+    /// the Belief Propagation is run until it converges on the resulting network (which has no active
+    /// loops left), and the beliefs obtained for each assignment are mixed, weighted by the posterior
+    /// probability of the assignment. The result is exact, but its cost grows exponentially with the
+    /// size of the cutset, so this is only practical for networks with few loops.
+    ///
+    /// The internal state and evidence of the network are left untouched.
+    pub fn cutset_beliefs(&self, evidence: &[(usize, usize)]) -> Vec<LogProbVector> {
+        let cutset: Vec<usize> = self
+            .loop_cutset()
+            .into_iter()
+            .filter(|c| evidence.iter().all(|&(n, _)| n != *c))
+            .collect();
+        let sizes: Vec<usize> = cutset.iter().map(|&c| self.num_values(c)).collect();
+        let total: usize = sizes.iter().product();
+
+        let mut net = self.clone();
+        let mut runs: Vec<(f32, Vec<LogProbVector>)> = Vec::with_capacity(total);
+        let mut assignment = vec![0; cutset.len()];
+        for _ in 0..total {
+            let mut conditioned = evidence.to_vec();
+            conditioned.extend(cutset.iter().cloned().zip(assignment.iter().cloned()));
+            net.reset_state();
+            net.set_evidence(&conditioned);
+            // propagation on a polytree converges in as many steps as its longest path
+            for _ in 0..net.nodes.len().max(1) {
+                net.step();
+            }
+            let log_weight = net.bethe_log_evidence();
+            if log_weight > f32::NEG_INFINITY {
+                runs.push((log_weight, net.beliefs()));
+            }
+            for (value, &size) in assignment.iter_mut().zip(sizes.iter()) {
+                *value += 1;
+                if *value < size {
+                    break;
+                }
+                *value = 0;
+            }
+        }
+
+        let max_weight = runs
+            .iter()
+            .map(|&(w, _)| w)
+            .fold(f32::NEG_INFINITY, f32::max);
+        (0..self.nodes.len())
+            .map(|node| {
+                let mut mixed = Array1::zeros(self.num_values(node));
+                for (weight, beliefs) in &runs {
+                    mixed += &(beliefs[node].as_probabilities() * (weight - max_weight).exp());
+                }
+                let mut belief = LogProbVector::from_log_probabilities(mixed.mapv(f32::ln));
+                belief.renormalize();
+                belief
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,56 @@
+use crate::semiring::{normalize, Semiring, SumProduct};
+use crate::{BayesNet, LogProbVector};
+
+impl BayesNet {
+    /// Set the damping of the messages computed by `step` and `step_in`
+    ///
+    /// With a damping `d`, each new message is the geometric interpolation `new^(1 - d) * old^d` of
+    /// the message computed by the step and the previous one, normalized. This slows the propagation
+    /// down, but is the standard fix for Loopy Belief Propagation oscillating on dense networks
+    /// instead of converging. The fixed points are not changed. The default is `0`, no damping.
+    ///
+    /// Panics if `damping` is not in `[0, 1)`.
+    pub fn set_damping(&mut self, damping: f32) {
+        check_damping(damping);
+        self.damping = damping;
+    }
+
+    /// The damping of the messages, see `set_damping`
+    pub fn damping(&self) -> f32 {
+        self.damping
+    }
+
+    /// Compute one step of the Loopy Belief Propagation with a specific damping
+    ///
+    /// This is the same as `step`, with `damping` instead of the damping of the network for this step.
+    pub fn step_damped(&mut self, damping: f32) {
+        check_damping(damping);
+        self.propagate::<SumProduct>(damping);
+    }
+}
+
+fn check_damping(damping: f32) {
+    assert!(
+        (0.0..1.0).contains(&damping),
+        "Damping must be in [0, 1), got {}",
+        damping
+    );
+}
+
+// interpolate in log-space between the previous and the new message of an edge
+pub(crate) fn damp<S: Semiring>(
+    old: &LogProbVector,
+    new: LogProbVector,
+    damping: f32,
+) -> LogProbVector {
+    if damping == 0.0 {
+        return new;
+    }
+    let mut old = old.clone();
+    normalize::<S>(&mut old);
+    let mixed =
+        (&new.log_probabilities() * (1.0 - damping)) + &(&old.log_probabilities() * damping);
+    let mut mixed = LogProbVector::from_log_probabilities(mixed);
+    normalize::<S>(&mut mixed);
+    mixed
+}
@@ -0,0 +1,147 @@
+use crate::BayesNet;
+use ndarray::Axis;
+use std::fmt;
+
+/// The deterministic columns of the table of a node, see `BayesNet::functional_dependencies`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FunctionalDependency {
+    /// The node of the table
+    pub node: usize,
+    /// The parents of the node, in the order they were given at its creation
+    pub parents: Vec<usize>,
+    /// For each configuration of the values of the parents, the only possible value of the node if
+    /// there is one
+    ///
+    /// The configurations are enumerated in row-major order, the last parent varying the fastest, as
+    /// in the probability tables.
+    pub values: Vec<Option<usize>>,
+}
+
+impl FunctionalDependency {
+    /// Whether the node is a function of its parents, every column of its table being deterministic
+    pub fn is_functional(&self) -> bool {
+        self.values.iter().all(Option::is_some)
+    }
+
+    /// Number of configurations of the parents for which the value of the node is determined
+    pub fn deterministic_columns(&self) -> usize {
+        self.values.iter().filter(|v| v.is_some()).count()
+    }
+}
+
+/// A deterministic node in a configuration known to be difficult for the Loopy Belief Propagation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeterminismWarning {
+    /// A node with deterministic columns lies on a loop of the graph
+    ///
+    /// The zeros of its table are propagated around the loop without being smoothed, so that the
+    /// propagation may oscillate, converge to beliefs far from the true marginals, or give a
+    /// probability of zero to possible values. Such nodes should be handled exactly, for example by
+    /// observing them through a loop cutset (see `BayesNet::cutset_beliefs`).
+    InLoop {
+        /// The deterministic node
+        node: usize,
+        /// Whether the node is a function of its parents
+        functional: bool,
+    },
+}
+
+impl fmt::Display for DeterminismWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            DeterminismWarning::InLoop { node, functional } => write!(
+                f,
+                "node {} is {} deterministic and lies on a loop",
+                node,
+                if functional { "fully" } else { "partly" }
+            ),
+        }
+    }
+}
+
+impl BayesNet {
+    /// Find the nodes whose value is determined by the values of their parents in some configurations
+    ///
+    /// A column of a table is deterministic when exactly one value of the node has a non-zero
+    /// probability. The nodes with at least one deterministic column are returned in increasing order.
+    /// Roots with a deterministic table are constants.
+    pub fn functional_dependencies(&self) -> Vec<FunctionalDependency> {
+        self.nodes
+            .iter()
+            .enumerate()
+            .filter_map(|(id, node)| {
+                let values: Vec<Option<usize>> = node
+                    .log_probas
+                    .dense()
+                    .lanes(Axis(0))
+                    .into_iter()
+                    .map(|column| {
+                        let mut possible = column
+                            .iter()
+                            .enumerate()
+                            .filter(|&(_, &l)| l > f32::NEG_INFINITY);
+                        match (possible.next(), possible.next()) {
+                            (Some((value, _)), None) => Some(value),
+                            _ => None,
+                        }
+                    })
+                    .collect();
+                if values.iter().any(Option::is_some) {
+                    Some(FunctionalDependency {
+                        node: id,
+                        parents: self.parents(id),
+                        values,
+                    })
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// Warn about the deterministic nodes that are likely to disturb the Loopy Belief Propagation
+    ///
+    /// See `DeterminismWarning` for the detected cases. The warnings are sorted by node.
+    pub fn determinism_warnings(&self) -> Vec<DeterminismWarning> {
+        self.functional_dependencies()
+            .into_iter()
+            .filter(|dependency| self.is_on_loop(dependency.node))
+            .map(|dependency| DeterminismWarning::InLoop {
+                node: dependency.node,
+                functional: dependency.is_functional(),
+            })
+            .collect()
+    }
+
+    // whether two neighbours of the node are connected without going through it
+    fn is_on_loop(&self, node: usize) -> bool {
+        let neighbours = |n: usize| {
+            let data = &self.nodes[n];
+            data.parents
+                .iter()
+                .chain(data.children.iter())
+                .map(|&(m, _)| m)
+                .collect::<Vec<_>>()
+        };
+        let mut label = vec![usize::MAX; self.nodes.len()];
+        for (i, start) in neighbours(node).into_iter().enumerate() {
+            if label[start] != usize::MAX {
+                return true;
+            }
+            label[start] = i;
+            let mut stack = vec![start];
+            while let Some(n) = stack.pop() {
+                for m in neighbours(n) {
+                    if m == node {
+                        continue;
+                    }
+                    if label[m] == usize::MAX {
+                        label[m] = i;
+                        stack.push(m);
+                    }
+                }
+            }
+        }
+        false
+    }
+}
@@ -0,0 +1,330 @@
+use crate::semiring::{normalize, Semiring, SumProduct};
+use crate::{BayesNet, LogProbVector};
+use std::error::Error;
+use std::fmt;
+
+// With the `nan-checks` feature, panic as soon as an operation produces NaN, naming the operation
+macro_rules! check_nan {
+    ($values:expr, $($operation:tt)+) => {
+        #[cfg(feature = "nan-checks")]
+        {
+            if $values.iter().any(|v: &f32| v.is_nan()) {
+                panic!("NaN produced by {}", format_args!($($operation)+));
+            }
+        }
+    };
+}
+
+/// A reference to a node in an error, with its name if it has one
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeRef {
+    /// Id of the node
+    pub id: usize,
+    /// Name of the node
+    pub name: Option<String>,
+}
+
+impl fmt::Display for NodeRef {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.name {
+            Some(ref name) => write!(f, "node {} (\"{}\")", self.id, name),
+            None => write!(f, "node {}", self.id),
+        }
+    }
+}
+
+/// Errors detected while running the inference
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InferenceError {
+    /// A message is not a valid probability distribution (it contains NaN)
+    ///
+    /// This is usually caused by evidence having a probability of 0 under the model.
+    InvalidMessage {
+        /// The node sending the message
+        from: NodeRef,
+        /// The node receiving the message
+        to: NodeRef,
+        /// The step of inference that computed the message, starting at 0 after `reset_state`
+        iteration: usize,
+    },
+    /// A belief is not a valid probability distribution (it contains NaN), see `BayesNet::try_beliefs`
+    InvalidBelief {
+        /// The node of the belief
+        node: NodeRef,
+        /// The number of steps run
+        iteration: usize,
+    },
+    /// A message was found corrupted by an audit, see `BayesNet::run_audited`
+    CorruptedMessage {
+        /// The node sending the message
+        from: NodeRef,
+        /// The node receiving the message
+        to: NodeRef,
+        /// The number of steps run when the audit found the message
+        iteration: usize,
+        /// What is wrong with the message
+        issue: MessageIssue,
+    },
+}
+
+/// A problem of a message found by an audit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageIssue {
+    /// The message contains NaN
+    NotANumber,
+    /// The message contains a log-probability of `+inf`
+    Infinite,
+    /// All the probabilities of the message are `0`
+    Vanished,
+    /// The message is no longer normalized, beyond the tolerance of the audit
+    Drift,
+}
+
+impl fmt::Display for MessageIssue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MessageIssue::NotANumber => write!(f, "contains NaN"),
+            MessageIssue::Infinite => write!(f, "contains an infinite log-probability"),
+            MessageIssue::Vanished => write!(f, "has all probabilities at 0"),
+            MessageIssue::Drift => write!(f, "is not normalized"),
+        }
+    }
+}
+
+/// Settings of the periodic audits of the messages made by `BayesNet::run_audited`
+#[derive(Debug, Clone, PartialEq)]
+pub struct MessageAudit {
+    /// Number of steps between two audits
+    pub period: usize,
+    /// Largest deviation of the log-sum of a message from `0` that is not considered a drift (of its
+    /// largest value for max-product messages, see `BayesNet::audit_messages_in`)
+    pub drift_tolerance: f32,
+    /// Whether corrupted messages are repaired, rather than aborting the run
+    pub repair: bool,
+}
+
+impl Default for MessageAudit {
+    fn default() -> MessageAudit {
+        MessageAudit {
+            period: 100,
+            drift_tolerance: 1e-3,
+            repair: true,
+        }
+    }
+}
+
+/// A corrupted message found by an audit
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditFinding {
+    /// The node sending the message
+    pub from: NodeRef,
+    /// The node receiving the message
+    pub to: NodeRef,
+    /// The number of steps run when the audit found the message
+    pub iteration: usize,
+    /// What is wrong with the message
+    pub issue: MessageIssue,
+}
+
+impl fmt::Display for InferenceError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InferenceError::InvalidMessage {
+                from,
+                to,
+                iteration,
+            } => write!(
+                f,
+                "invalid message from {} to {} at step {} (is the evidence impossible?)",
+                from, to, iteration
+            ),
+            InferenceError::InvalidBelief { node, iteration } => write!(
+                f,
+                "invalid belief of {} after {} steps (is the evidence impossible?)",
+                node, iteration
+            ),
+            InferenceError::CorruptedMessage {
+                from,
+                to,
+                iteration,
+                issue,
+            } => write!(
+                f,
+                "message from {} to {} {} after {} steps",
+                from, to, issue, iteration
+            ),
+        }
+    }
+}
+
+impl Error for InferenceError {}
+
+impl BayesNet {
+    /// A reference to a node, for error reporting
+    pub fn node_ref(&self, node: usize) -> NodeRef {
+        NodeRef {
+            id: node,
+            name: self.nodes.get(node).and_then(|n| n.name.clone()),
+        }
+    }
+
+    /// Compute one step of the Loopy Belief Propagation, and check that the new messages are valid
+    ///
+    /// This is the same as `step`, except that the messages are then checked, and the first invalid one
+    /// is reported with the nodes and step involved. The messages are updated even if an error is returned.
+    pub fn try_step(&mut self) -> Result<(), InferenceError> {
+        let iteration = self.iteration;
+        self.step();
+        for (to, node) in self.nodes.iter().enumerate() {
+            let invalid = node
+                .parents
+                .iter()
+                .chain(node.children.iter())
+                .find(|(_, msg)| msg.log_probabilities().iter().any(|v| v.is_nan()));
+            if let Some(&(from, _)) = invalid {
+                return Err(InferenceError::InvalidMessage {
+                    from: self.node_ref(from),
+                    to: self.node_ref(to),
+                    iteration,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Compute the beliefs, and check that they are valid
+    ///
+    /// This is the same as `beliefs`, except that the first belief containing NaN is reported. The check
+    /// is cheap, so this is a good place to catch NaNs that silently spread through the messages; the
+    /// `nan-checks` feature finds the operation that produced them.
+    pub fn try_beliefs(&self) -> Result<Vec<LogProbVector>, InferenceError> {
+        let beliefs = self.beliefs();
+        match beliefs
+            .iter()
+            .position(|b| b.log_probabilities().iter().any(|v| v.is_nan()))
+        {
+            Some(node) => Err(InferenceError::InvalidBelief {
+                node: self.node_ref(node),
+                iteration: self.iteration,
+            }),
+            None => Ok(beliefs),
+        }
+    }
+
+    /// Check all the messages, and repair the corrupted ones if the audit says so
+    ///
+    /// Messages containing NaN are repaired by replacing the NaN with the `log_floor` of the numeric
+    /// policy, infinite log-probabilities by `0`, messages whose probabilities all vanished by uniform
+    /// messages, and all of them are renormalized, as are the drifting ones. Returns the corrupted
+    /// messages found.
+    ///
+    /// The messages are expected to be normalized for the sum-product semiring, see
+    /// `audit_messages_in` for the other semirings.
+    pub fn audit_messages(&mut self, audit: &MessageAudit) -> Vec<AuditFinding> {
+        self.audit_messages_in::<SumProduct>(audit)
+    }
+
+    /// Check all the messages of a propagation over a semiring, see `audit_messages`
+    ///
+    /// The drift is measured, and the messages renormalized, for the semiring: after `step_in::<S>`,
+    /// the messages are audited with `audit_messages_in::<S>`.
+    pub fn audit_messages_in<S: Semiring>(&mut self, audit: &MessageAudit) -> Vec<AuditFinding> {
+        let log_floor = self.numerics.log_floor;
+        let mut found = Vec::new();
+        for to in 0..self.nodes.len() {
+            let node = &mut self.nodes[to];
+            let mut repaired = false;
+            for (from, msg) in node.parents.iter_mut().chain(node.children.iter_mut()) {
+                let values = msg.log_probabilities();
+                let issue = if values.iter().any(|v| v.is_nan()) {
+                    MessageIssue::NotANumber
+                } else if values.iter().any(|&v| v == f32::INFINITY) {
+                    MessageIssue::Infinite
+                } else if values.iter().all(|&v| v == f32::NEG_INFINITY) {
+                    MessageIssue::Vanished
+                } else if S::sum(values).abs() > audit.drift_tolerance {
+                    MessageIssue::Drift
+                } else {
+                    continue;
+                };
+                found.push((*from, to, issue));
+                if audit.repair {
+                    *msg = repair::<S>(msg, issue, log_floor);
+                    repaired = true;
+                }
+            }
+            if repaired {
+                node.lambda = None;
+                node.pi = None;
+            }
+        }
+        found
+            .into_iter()
+            .map(|(from, to, issue)| AuditFinding {
+                from: self.node_ref(from),
+                to: self.node_ref(to),
+                iteration: self.iteration,
+                issue,
+            })
+            .collect()
+    }
+
+    /// Run steps of the propagation with periodic audits of the messages
+    ///
+    /// This is meant for long runs (thousands of steps), where numerical drift or NaN contamination can
+    /// build up unnoticed. The messages are audited every `audit.period` steps and after the last one. If
+    /// the audit repairs the messages, the run goes on and all the repairs made are returned, otherwise
+    /// the run stops at the first corrupted message, which is reported in the error.
+    pub fn run_audited(
+        &mut self,
+        iterations: usize,
+        audit: &MessageAudit,
+    ) -> Result<Vec<AuditFinding>, InferenceError> {
+        self.run_audited_in::<SumProduct>(iterations, audit)
+    }
+
+    /// Run steps of the propagation over a semiring with periodic audits of the messages, see
+    /// `run_audited` and `audit_messages_in`
+    pub fn run_audited_in<S: Semiring>(
+        &mut self,
+        iterations: usize,
+        audit: &MessageAudit,
+    ) -> Result<Vec<AuditFinding>, InferenceError> {
+        let mut repairs = Vec::new();
+        for i in 1..=iterations {
+            self.step_in::<S>();
+            if i % audit.period.max(1) == 0 || i == iterations {
+                let found = self.audit_messages_in::<S>(audit);
+                if audit.repair {
+                    repairs.extend(found);
+                } else if let Some(finding) = found.into_iter().next() {
+                    return Err(InferenceError::CorruptedMessage {
+                        from: finding.from,
+                        to: finding.to,
+                        iteration: finding.iteration,
+                        issue: finding.issue,
+                    });
+                }
+            }
+        }
+        Ok(repairs)
+    }
+}
+
+fn repair<S: Semiring>(msg: &LogProbVector, issue: MessageIssue, log_floor: f32) -> LogProbVector {
+    let mut msg = if issue == MessageIssue::Vanished {
+        LogProbVector::uniform(msg.log_probabilities().len())
+    } else {
+        LogProbVector::from_log_probabilities(msg.log_probabilities().mapv(|v| {
+            if v.is_nan() {
+                log_floor
+            } else if v == f32::INFINITY {
+                0.0
+            } else {
+                v
+            }
+        }))
+    };
+    normalize::<S>(&mut msg);
+    msg
+}
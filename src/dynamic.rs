@@ -0,0 +1,301 @@
+use crate::{BayesNet, LogProbVector};
+use ndarray::{Array, ArrayD, Dimension};
+use std::collections::VecDeque;
+
+/// A parent of a node in the transition model of a `DynamicBayesNet`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SliceParent {
+    /// The given node of the previous time slice
+    Previous(usize),
+    /// The given node of the same time slice, which must have a smaller id
+    Current(usize),
+}
+
+/// A dynamic Bayesian network, defined by a prior slice and a transition model (a 2-TBN)
+///
+/// The prior slice is a network describing the nodes at the first time step. The transition model
+/// gives, for each of these nodes, its table at the next time steps, whose parents can be in the same
+/// slice or in the previous one. The network is unrolled over a number of time steps to run the
+/// inference, node `i` of time step `t` being node `t * n + i` of the unrolled network, where `n` is
+/// the number of nodes of a slice.
+#[derive(Debug, Clone)]
+pub struct DynamicBayesNet {
+    prior: BayesNet,
+    transitions: Vec<Option<(Vec<SliceParent>, ArrayD<f32>)>>,
+}
+
+impl DynamicBayesNet {
+    /// Create a dynamic network with the given prior slice, and no transition model yet
+    ///
+    /// The evidence of the prior network is ignored.
+    pub fn new(prior: BayesNet) -> DynamicBayesNet {
+        let n = prior.num_nodes();
+        DynamicBayesNet {
+            prior,
+            transitions: vec![None; n],
+        }
+    }
+
+    /// Number of nodes of a time slice
+    pub fn slice_size(&self) -> usize {
+        self.prior.num_nodes()
+    }
+
+    /// The prior slice
+    pub fn prior(&self) -> &BayesNet {
+        &self.prior
+    }
+
+    /// Set the table of a node after the first time step
+    ///
+    /// The layout of `probabilities` is the same as for `BayesNet::add_node_from_probabilities`, with
+    /// the number of values of each node taken from the prior slice. Panics if a parent of the
+    /// current slice does not have a smaller id than `node`, or if the shape of the table does not
+    /// match the parents.
+    pub fn set_transition<D: Dimension>(
+        &mut self,
+        node: usize,
+        parents: &[SliceParent],
+        probabilities: Array<f32, D>,
+    ) {
+        let n = self.slice_size();
+        assert!(node < n, "Node {} is not in a slice of {} nodes", node, n);
+        let mut shape = vec![self.prior.num_values(node)];
+        for &parent in parents {
+            let p = match parent {
+                SliceParent::Previous(p) => p,
+                SliceParent::Current(p) => {
+                    assert!(
+                        p < node,
+                        "Parent {} of node {} in the same slice must have a smaller id",
+                        p,
+                        node
+                    );
+                    p
+                }
+            };
+            assert!(p < n, "Parent {} is not in a slice of {} nodes", p, n);
+            shape.push(self.prior.num_values(p));
+        }
+        assert!(
+            probabilities.shape() == shape.as_slice(),
+            "Transition table of node {} has shape {:?} instead of {:?}",
+            node,
+            probabilities.shape(),
+            shape
+        );
+        self.transitions[node] = Some((parents.to_vec(), probabilities.into_dyn()));
+    }
+
+    /// Id of a node of a time step in the unrolled network
+    pub fn node_id(&self, time: usize, node: usize) -> usize {
+        time * self.slice_size() + node
+    }
+
+    /// Unroll the network over `steps` time steps
+    ///
+    /// All the slices after the first one share the tables of the transition model. A node named
+    /// `x` in the prior slice is named `x_t` at time step `t`, and keeps its state names. Panics if
+    /// `steps` is zero, or if a node has no transition table and `steps` is larger than one.
+    pub fn unroll(&self, steps: usize) -> BayesNet {
+        assert!(
+            steps > 0,
+            "A dynamic network must be unrolled over at least one step"
+        );
+        self.unroll_window(None, 0, steps)
+    }
+
+    // unroll the slices of the time steps `start..start + steps`, the slice before the first one
+    // being summarized by independent marginals if `frontier` is given
+    fn unroll_window(
+        &self,
+        frontier: Option<&[LogProbVector]>,
+        start: usize,
+        steps: usize,
+    ) -> BayesNet {
+        let n = self.slice_size();
+        let mut net = BayesNet::new();
+        let mut slices = Vec::with_capacity(steps + 1);
+        match frontier {
+            Some(marginals) => {
+                for marginal in marginals {
+                    let table = marginal.log_probabilities().to_owned();
+                    net.add_node_from_log_probabilities(&[], table);
+                }
+                slices.push(start - 1);
+            }
+            None => {
+                for node in 0..n {
+                    let table = self.prior.nodes[node].log_probas.dense().into_owned();
+                    net.add_node_from_log_probabilities(&self.prior.parents(node), table);
+                }
+            }
+        }
+        slices.extend(start..start + steps);
+        for slice in (slices.len() - steps).max(1)..slices.len() {
+            for node in 0..n {
+                let (parents, table) = self.transitions[node].as_ref().unwrap_or_else(|| {
+                    panic!("Node {} has no transition table", node);
+                });
+                let parents: Vec<usize> = parents
+                    .iter()
+                    .map(|&parent| match parent {
+                        SliceParent::Previous(p) => self.node_id(slice - 1, p),
+                        SliceParent::Current(p) => self.node_id(slice, p),
+                    })
+                    .collect();
+                net.add_node_from_probabilities(&parents, table.clone());
+            }
+        }
+        for (slice, &time) in slices.iter().enumerate() {
+            for node in 0..n {
+                let id = self.node_id(slice, node);
+                if let Some(name) = self.prior.node_name(node) {
+                    net.set_node_name(id, &format!("{}_{}", name, time));
+                }
+                net.nodes[id].state_names = self.prior.nodes[node].state_names.clone();
+            }
+        }
+        net
+    }
+
+    /// The beliefs of the nodes of each time step, given evidence at any time step
+    ///
+    /// The network is unrolled over `steps` time steps, the evidence is given as
+    /// `(time, node, value)` triplets, and `iterations` steps of the Loopy Belief Propagation are
+    /// run. The result holds the beliefs of the nodes of each time step, so that the beliefs of the
+    /// last time step are the filtered ones, and the others are smoothed by the later evidence.
+    pub fn beliefs(
+        &self,
+        steps: usize,
+        evidence: &[(usize, usize, usize)],
+        iterations: usize,
+    ) -> Vec<Vec<LogProbVector>> {
+        let mut net = self.unroll(steps);
+        let evidence: Vec<(usize, usize)> = evidence
+            .iter()
+            .map(|&(time, node, value)| {
+                assert!(
+                    time < steps && node < self.slice_size(),
+                    "Evidence on node {} at time {} is out of the unrolled network",
+                    node,
+                    time
+                );
+                (self.node_id(time, node), value)
+            })
+            .collect();
+        net.set_evidence(&evidence);
+        for _ in 0..iterations {
+            net.step();
+        }
+        net.beliefs()
+            .chunks(self.slice_size())
+            .map(|slice| slice.to_vec())
+            .collect()
+    }
+}
+
+/// Fixed-lag smoothing of a `DynamicBayesNet` over a stream of evidence
+///
+/// Only the last `lag + 1` time steps are kept live in an unrolled window, the earlier ones being
+/// summarized by the filtered marginals of the nodes of the last time step that left the window (the
+/// frontier). The cost of each new time step is thus bounded, whatever the length of the stream.
+///
+/// The frontier is factored: the nodes of its time step are considered independent, which is an
+/// approximation when they are correlated (the Boyen-Koller approximation). The beliefs are otherwise
+/// those of the Loopy Belief Propagation on the window.
+#[derive(Debug, Clone)]
+pub struct FixedLagSmoother {
+    dbn: DynamicBayesNet,
+    lag: usize,
+    iterations: usize,
+    // the time step of the first live slice
+    start: usize,
+    frontier: Option<Vec<LogProbVector>>,
+    evidence: VecDeque<Vec<(usize, usize)>>,
+    beliefs: Vec<Vec<LogProbVector>>,
+}
+
+impl FixedLagSmoother {
+    /// Create a smoother keeping `lag` time steps before the last one, running `iterations` steps of
+    /// the propagation on the window after each new time step
+    pub fn new(dbn: DynamicBayesNet, lag: usize, iterations: usize) -> FixedLagSmoother {
+        FixedLagSmoother {
+            dbn,
+            lag,
+            iterations,
+            start: 0,
+            frontier: None,
+            evidence: VecDeque::new(),
+            beliefs: Vec::new(),
+        }
+    }
+
+    // run the propagation on the live slices after the frontier, and return their beliefs
+    fn propagate(
+        &self,
+        frontier: Option<&[LogProbVector]>,
+        evidence: &[Vec<(usize, usize)>],
+    ) -> Vec<Vec<LogProbVector>> {
+        let mut net = self.dbn.unroll_window(frontier, self.start, evidence.len());
+        let offset = usize::from(frontier.is_some());
+        let evidence: Vec<(usize, usize)> = evidence
+            .iter()
+            .enumerate()
+            .flat_map(|(slice, slice_evidence)| {
+                slice_evidence
+                    .iter()
+                    .map(move |&(node, value)| (self.dbn.node_id(slice + offset, node), value))
+            })
+            .collect();
+        net.set_evidence(&evidence);
+        for _ in 0..self.iterations {
+            net.step();
+        }
+        net.beliefs()
+            .chunks(self.dbn.slice_size())
+            .skip(offset)
+            .map(|slice| slice.to_vec())
+            .collect()
+    }
+
+    /// Add the evidence of a new time step, as `(node, value)` pairs on the nodes of a slice
+    ///
+    /// If the window was full, its first time step leaves it, and its beliefs, smoothed by the evidence
+    /// of the `lag` next time steps, are returned.
+    pub fn push(&mut self, evidence: &[(usize, usize)]) -> Option<Vec<LogProbVector>> {
+        assert!(
+            evidence
+                .iter()
+                .all(|&(node, _)| node < self.dbn.slice_size()),
+            "Evidence {:?} is not on the nodes of a slice of {} nodes",
+            evidence,
+            self.dbn.slice_size()
+        );
+        let mut smoothed = None;
+        if self.evidence.len() == self.lag + 1 {
+            smoothed = Some(self.beliefs.remove(0));
+            // the filtered marginals of the leaving time step, given the previous frontier
+            let oldest = self.evidence.pop_front().unwrap();
+            let frontier = self.propagate(self.frontier.as_deref(), &[oldest]);
+            self.frontier = frontier.into_iter().next();
+            self.start += 1;
+        }
+        self.evidence.push_back(evidence.to_vec());
+        let evidence: Vec<Vec<(usize, usize)>> = self.evidence.iter().cloned().collect();
+        self.beliefs = self.propagate(self.frontier.as_deref(), &evidence);
+        smoothed
+    }
+
+    /// The time step of the first live slice
+    pub fn first_time(&self) -> usize {
+        self.start
+    }
+
+    /// The beliefs of the nodes of the live time steps, from the first one to the last one
+    ///
+    /// The beliefs of the last time step are the filtered ones.
+    pub fn beliefs(&self) -> &[Vec<LogProbVector>] {
+        &self.beliefs
+    }
+}
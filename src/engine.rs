@@ -0,0 +1,41 @@
+use crate::BayesNet;
+
+/// Version of the propagation algorithm run by a network
+///
+/// Improvements of the scheduling or of the numerics of the propagation can change the computed
+/// beliefs, even slightly. Such changes are made under a new engine version, while the previous
+/// versions keep computing exactly the same results in the later releases of the crate, so that
+/// pipelines requiring bit-stable outputs can pin the version of each of their models and migrate at
+/// their own pace. New networks use the latest version.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub enum EngineVersion {
+    /// Flooding schedule: each step computes all the messages from the previous ones, which are
+    /// normalized according to the `NumericsPolicy` of the network
+    V1,
+}
+
+impl EngineVersion {
+    /// The latest version of the engine
+    pub fn latest() -> EngineVersion {
+        EngineVersion::V1
+    }
+}
+
+impl Default for EngineVersion {
+    fn default() -> EngineVersion {
+        EngineVersion::latest()
+    }
+}
+
+impl BayesNet {
+    /// Pin the version of the propagation algorithm run by the network, see `EngineVersion`
+    pub fn set_engine_version(&mut self, version: EngineVersion) {
+        self.engine = version;
+    }
+
+    /// The version of the propagation algorithm run by the network
+    pub fn engine_version(&self) -> EngineVersion {
+        self.engine
+    }
+}
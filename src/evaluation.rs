@@ -0,0 +1,218 @@
+//! Side-by-side evaluation of several models on the same data
+//!
+//! This is meant for champion/challenger rollouts: the models predict the values of some target
+//! nodes from the other observed values of each record, and their predictions are scored and
+//! compared record by record. Records follow the conventions of partially observed data in
+//! `learning`, as a `Vec<Option<usize>>` indexed by node id, so all the models must use the same
+//! node ids for the observed and target nodes.
+
+use crate::{BayesNet, LogProbVector};
+use std::fmt;
+
+/// Scores of the predictions of a model
+#[derive(Debug, Clone, PartialEq)]
+pub struct Metrics {
+    /// Number of predictions scored
+    pub count: usize,
+    /// Fraction of the predictions whose most probable value is the observed one
+    pub accuracy: f32,
+    /// Mean of `-ln p(observed value)`, lower is better
+    pub log_loss: f32,
+    /// Mean of the squared distance between the predicted distribution and the observed value, lower
+    /// is better
+    pub brier: f32,
+}
+
+impl Metrics {
+    fn new(scores: &[(bool, f32, f32)]) -> Metrics {
+        let count = scores.len();
+        let mean = |f: &dyn Fn(&(bool, f32, f32)) -> f32| {
+            if count == 0 {
+                f32::NAN
+            } else {
+                scores.iter().map(f).sum::<f32>() / count as f32
+            }
+        };
+        Metrics {
+            count,
+            accuracy: mean(&|s| if s.0 { 1.0 } else { 0.0 }),
+            log_loss: mean(&|s| s.1),
+            brier: mean(&|s| s.2),
+        }
+    }
+}
+
+/// The scores of a model, overall and for each target
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModelMetrics {
+    /// Scores of all the predictions of the model
+    pub overall: Metrics,
+    /// Scores of the predictions of each target, in the order of the targets
+    pub per_target: Vec<Metrics>,
+}
+
+/// A prediction on which the models do not agree
+#[derive(Debug, Clone, PartialEq)]
+pub struct Disagreement {
+    /// Index of the record in the data
+    pub record: usize,
+    /// The target node
+    pub target: usize,
+    /// The observed value of the target
+    pub observed: usize,
+    /// The most probable value of the target for each model
+    pub predictions: Vec<usize>,
+    /// The probability of the observed value for each model
+    pub probabilities: Vec<f32>,
+}
+
+/// The result of `compare`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Comparison {
+    /// The scores of each model, in the order of the models
+    pub models: Vec<ModelMetrics>,
+    /// The predictions whose most probable value differs between models, by record then target
+    pub disagreements: Vec<Disagreement>,
+}
+
+impl Comparison {
+    /// The index of the model with the lowest overall log-loss, the first one in case of ties
+    pub fn best_by_log_loss(&self) -> Option<usize> {
+        (0..self.models.len()).min_by(|&a, &b| {
+            let (a, b) = (
+                self.models[a].overall.log_loss,
+                self.models[b].overall.log_loss,
+            );
+            a.partial_cmp(&b).unwrap_or(std::cmp::Ordering::Equal)
+        })
+    }
+}
+
+impl fmt::Display for Comparison {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "model  accuracy  log-loss     brier")?;
+        for (i, model) in self.models.iter().enumerate() {
+            writeln!(
+                f,
+                "{:>5}  {:>8.4}  {:>8.4}  {:>8.4}",
+                i, model.overall.accuracy, model.overall.log_loss, model.overall.brier
+            )?;
+        }
+        write!(f, "{} disagreements", self.disagreements.len())
+    }
+}
+
+// the value with the highest probability, the smallest one in case of ties
+fn most_probable(belief: &LogProbVector) -> usize {
+    let probas = belief.as_probabilities();
+    (0..probas.len()).fold(0, |best, v| if probas[v] > probas[best] { v } else { best })
+}
+
+/// Compare the predictions of several models on the targets of each record
+///
+/// For each record, each model is given the observed values of the nodes that are not targets as
+/// evidence, and predicts the targets with `iterations` steps of Loopy Belief Propagation from a
+/// reset state. Only the targets observed in the record are scored. The models are not modified.
+///
+/// Panics if a record does not have one entry per node of a model, or if the models do not have
+/// the same number of values for a target.
+pub fn compare(
+    models: &[&BayesNet],
+    data: &[Vec<Option<usize>>],
+    targets: &[usize],
+    iterations: usize,
+) -> Comparison {
+    for &target in targets {
+        let sizes: Vec<usize> = models.iter().map(|net| net.num_values(target)).collect();
+        assert!(
+            sizes.windows(2).all(|w| w[0] == w[1]),
+            "Models have different numbers of values for target {}: {:?}",
+            target,
+            sizes
+        );
+    }
+
+    // predictions[model][record][target]
+    let predictions: Vec<Vec<Vec<LogProbVector>>> = models
+        .iter()
+        .map(|&model| {
+            let mut net = model.clone();
+            data.iter()
+                .enumerate()
+                .map(|(index, record)| {
+                    assert!(
+                        record.len() == net.num_nodes(),
+                        "Record {} has {} values but the network has {} nodes",
+                        index,
+                        record.len(),
+                        net.num_nodes()
+                    );
+                    let evidence: Vec<(usize, usize)> = record
+                        .iter()
+                        .enumerate()
+                        .filter(|(node, _)| !targets.contains(node))
+                        .filter_map(|(node, &value)| value.map(|value| (node, value)))
+                        .collect();
+                    net.reset_state();
+                    net.set_evidence(&evidence);
+                    for _ in 0..iterations {
+                        net.step();
+                    }
+                    let beliefs = net.beliefs();
+                    targets.iter().map(|&t| beliefs[t].clone()).collect()
+                })
+                .collect()
+        })
+        .collect();
+
+    // scores[model][target]: (correct, log-loss, brier) of each prediction
+    let mut scores = vec![vec![Vec::new(); targets.len()]; models.len()];
+    let mut disagreements = Vec::new();
+    for (r, record) in data.iter().enumerate() {
+        for (t, &target) in targets.iter().enumerate() {
+            let observed = match record[target] {
+                Some(value) => value,
+                None => continue,
+            };
+            let mut most_probables = Vec::new();
+            let mut probabilities = Vec::new();
+            for (m, model_predictions) in predictions.iter().enumerate() {
+                let belief = &model_predictions[r][t];
+                let probas = belief.as_probabilities();
+                let predicted = most_probable(belief);
+                let brier = probas
+                    .iter()
+                    .enumerate()
+                    .map(|(v, &p)| {
+                        let truth = if v == observed { 1.0 } else { 0.0 };
+                        (p - truth) * (p - truth)
+                    })
+                    .sum();
+                scores[m][t].push((predicted == observed, -probas[observed].ln(), brier));
+                most_probables.push(predicted);
+                probabilities.push(probas[observed]);
+            }
+            if most_probables.windows(2).any(|w| w[0] != w[1]) {
+                disagreements.push(Disagreement {
+                    record: r,
+                    target,
+                    observed,
+                    predictions: most_probables,
+                    probabilities,
+                });
+            }
+        }
+    }
+
+    let models = scores
+        .iter()
+        .map(|per_target| ModelMetrics {
+            overall: Metrics::new(&per_target.concat()),
+            per_target: per_target.iter().map(|s| Metrics::new(s)).collect(),
+        })
+        .collect();
+    Comparison {
+        models,
+        disagreements,
+    }
+}
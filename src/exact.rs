@@ -0,0 +1,85 @@
+use crate::factor::{multiply, Factor};
+use crate::{BayesNet, LogProbVector};
+use ndarray::ArrayD;
+
+impl BayesNet {
+    /// Compute the exact beliefs of the nodes given the current evidence, by variable elimination
+    ///
+    /// This is the exact counterpart of `beliefs`, taking into account the hard and soft evidence of
+    /// the network, so that both can be compared directly. The nodes of each connected component are
+    /// eliminated greedily, always picking the node creating the smallest table. The cost grows
+    /// exponentially with the treewidth of the network, so this is only practical for small or sparse
+    /// networks. Two-layer noisy-OR networks are handled by `quickscore_beliefs` instead, whose cost
+    /// does not depend on the treewidth. The internal state of the network is not used nor modified.
+    pub fn exact_beliefs(&self) -> Vec<LogProbVector> {
+        if let Some(beliefs) = self.quickscore_beliefs() {
+            return beliefs;
+        }
+        let sizes: Vec<usize> = (0..self.nodes.len()).map(|n| self.num_values(n)).collect();
+        let labels = self.component_labels();
+        (0..self.nodes.len())
+            .map(|query| {
+                let factors = (0..self.nodes.len())
+                    .filter(|&id| labels[id] == labels[query])
+                    .flat_map(|id| Factor::of_node(self, id))
+                    .collect();
+                let marginal = eliminate_all_but(factors, Some(query), &sizes);
+                let mut belief = LogProbVector::from_log_probabilities(
+                    marginal.into_shape(sizes[query]).unwrap(),
+                );
+                belief.renormalize();
+                belief
+            })
+            .collect()
+    }
+
+    /// Compute exactly `log P(evidence)`, by variable elimination
+    ///
+    /// This is the exact counterpart of `log_evidence`, with the same cost as `exact_beliefs`. The
+    /// internal state of the network is not used nor modified.
+    pub fn exact_log_evidence(&self) -> f32 {
+        let sizes: Vec<usize> = (0..self.nodes.len()).map(|n| self.num_values(n)).collect();
+        let factors = (0..self.nodes.len())
+            .flat_map(|id| Factor::of_node(self, id))
+            .collect();
+        eliminate_all_but(factors, None, &sizes).sum()
+    }
+}
+
+// the product of the factors, with all the nodes but the query summed out
+fn eliminate_all_but(
+    mut factors: Vec<Factor>,
+    query: Option<usize>,
+    sizes: &[usize],
+) -> ArrayD<f32> {
+    loop {
+        let mut candidates: Vec<usize> = factors
+            .iter()
+            .flat_map(|f| f.nodes.iter().cloned())
+            .filter(|&n| Some(n) != query)
+            .collect();
+        candidates.sort_unstable();
+        candidates.dedup();
+        // the node whose elimination creates the smallest table
+        let cost = |node: usize| -> usize {
+            let mut involved: Vec<usize> = factors
+                .iter()
+                .filter(|f| f.nodes.contains(&node))
+                .flat_map(|f| f.nodes.iter().cloned())
+                .collect();
+            involved.sort_unstable();
+            involved.dedup();
+            involved.iter().map(|&n| sizes[n]).product()
+        };
+        let node = match candidates.into_iter().min_by_key(|&n| cost(n)) {
+            Some(node) => node,
+            None => break,
+        };
+        let (involved, mut rest): (Vec<Factor>, Vec<Factor>) =
+            factors.into_iter().partition(|f| f.nodes.contains(&node));
+        let product = multiply(&involved, sizes).unwrap();
+        rest.push(product.sum_out(node));
+        factors = rest;
+    }
+    multiply(&factors, sizes).unwrap_or_else(Factor::unit).table
+}
@@ -0,0 +1,81 @@
+use crate::BayesNet;
+
+impl BayesNet {
+    /// Find the minimal subsets of the current evidence that make a conclusion likely enough
+    ///
+    /// Returns every subset of the evidence of the network under which the posterior probability of
+    /// `value` for `target` is at least `threshold`, and such that no smaller subset of it is enough. The
+    /// subsets are given as lists of `(node, value)` like for `set_evidence`, sorted by size. These are
+    /// concise justifications of a conclusion: each of them alone supports it, and every piece of evidence
+    /// in it is needed.
+    ///
+    /// Subsets are tried by increasing size, and each posterior is computed with `iterations` steps of the
+    /// Loopy Belief Propagation from a reset state. The search can try every subset of the evidence, so
+    /// this is meant for a few tens of observed nodes at most. Soft evidence is kept in all the runs.
+    pub fn minimal_explanations(
+        &self,
+        target: usize,
+        value: usize,
+        threshold: f32,
+        iterations: usize,
+    ) -> Vec<Vec<(usize, usize)>> {
+        let evidence: Vec<(usize, usize)> = self
+            .nodes
+            .iter()
+            .enumerate()
+            .filter_map(|(node, n)| n.evidence.map(|v| (node, v)))
+            .collect();
+        let mut net = self.clone();
+        let mut explanations: Vec<Vec<usize>> = Vec::new();
+        for size in 0..=evidence.len() {
+            for subset in subsets(evidence.len(), size) {
+                if explanations
+                    .iter()
+                    .any(|found| found.iter().all(|i| subset.contains(i)))
+                {
+                    continue;
+                }
+                let chosen: Vec<(usize, usize)> = subset.iter().map(|&i| evidence[i]).collect();
+                net.set_evidence(&chosen);
+                net.reset_state();
+                for _ in 0..iterations {
+                    net.step();
+                }
+                if net.beliefs()[target].as_probabilities()[value] >= threshold {
+                    explanations.push(subset);
+                }
+            }
+        }
+        explanations
+            .into_iter()
+            .map(|subset| subset.into_iter().map(|i| evidence[i]).collect())
+            .collect()
+    }
+}
+
+// all the subsets of `size` elements of `0..n`, in lexicographic order
+fn subsets(n: usize, size: usize) -> Vec<Vec<usize>> {
+    if size == 0 {
+        return vec![Vec::new()];
+    }
+    if size > n {
+        return Vec::new();
+    }
+    let mut result = Vec::new();
+    let mut current: Vec<usize> = (0..size).collect();
+    loop {
+        result.push(current.clone());
+        // advance to the next combination
+        let mut i = size;
+        while i > 0 && current[i - 1] == n - size + i - 1 {
+            i -= 1;
+        }
+        if i == 0 {
+            return result;
+        }
+        current[i - 1] += 1;
+        for j in i..size {
+            current[j] = current[j - 1] + 1;
+        }
+    }
+}
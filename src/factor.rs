@@ -0,0 +1,87 @@
+use crate::math::log_sum_exp_vec;
+use crate::BayesNet;
+use ndarray::{ArrayD, Axis, Dimension, IxDyn};
+
+// a function of some nodes in log-space, axis `i` of the table being the values of `nodes[i]`
+#[derive(Clone)]
+pub(crate) struct Factor {
+    pub(crate) nodes: Vec<usize>,
+    pub(crate) table: ArrayD<f32>,
+}
+
+impl Factor {
+    // the factor of no nodes, neutral for the product
+    pub(crate) fn unit() -> Factor {
+        Factor {
+            nodes: Vec::new(),
+            table: ArrayD::zeros(IxDyn(&[])),
+        }
+    }
+
+    // the factors of a node: its table, and the likelihood of its evidence
+    pub(crate) fn of_node(net: &BayesNet, id: usize) -> [Factor; 2] {
+        let node = &net.nodes[id];
+        let mut nodes = vec![id];
+        nodes.extend(node.parents.iter().map(|&(p, _)| p));
+        [
+            Factor {
+                nodes,
+                table: node.log_probas.dense().into_owned(),
+            },
+            Factor {
+                nodes: vec![id],
+                table: node
+                    .evidence_vec()
+                    .log_probabilities()
+                    .to_owned()
+                    .into_dyn(),
+            },
+        ]
+    }
+
+    pub(crate) fn product(&self, other: &Factor, sizes: &[usize]) -> Factor {
+        let mut nodes = self.nodes.clone();
+        nodes.extend(other.nodes.iter().filter(|n| !self.nodes.contains(n)));
+        let positions: Vec<usize> = other
+            .nodes
+            .iter()
+            .map(|n| nodes.iter().position(|m| m == n).unwrap())
+            .collect();
+        let shape: Vec<usize> = nodes.iter().map(|&n| sizes[n]).collect();
+        let table = ArrayD::from_shape_fn(IxDyn(&shape), |index| {
+            let own = &index.slice()[..self.nodes.len()];
+            let theirs: Vec<usize> = positions.iter().map(|&p| index[p]).collect();
+            self.table[own] + other.table[theirs.as_slice()]
+        });
+        Factor { nodes, table }
+    }
+
+    pub(crate) fn sum_out(&self, node: usize) -> Factor {
+        let axis = self.nodes.iter().position(|&n| n == node).unwrap();
+        let mut nodes = self.nodes.clone();
+        nodes.remove(axis);
+        Factor {
+            nodes,
+            table: self.table.map_axis(Axis(axis), log_sum_exp_vec),
+        }
+    }
+
+    // sum out all the nodes except the given ones
+    pub(crate) fn marginal(&self, keep: &[usize]) -> Factor {
+        self.nodes
+            .iter()
+            .filter(|n| !keep.contains(n))
+            .fold(self.clone(), |factor, &node| factor.sum_out(node))
+    }
+}
+
+pub(crate) fn multiply(factors: &[Factor], sizes: &[usize]) -> Option<Factor> {
+    let (first, others) = factors.split_first()?;
+    Some(others.iter().fold(
+        Factor {
+            nodes: first.nodes.clone(),
+            table: first.table.clone(),
+        },
+        |acc, f| acc.product(f, sizes),
+    ))
+}
@@ -0,0 +1,186 @@
+use crate::bif::{build_network, table_with_node_last, Variable};
+use crate::{BayesNet, BifError};
+use std::collections::HashMap;
+
+// the states of every node of a fault tree
+const STATES: [&str; 2] = ["working", "failed"];
+
+enum Gate {
+    And,
+    Or,
+    // fails when at least this number of inputs failed
+    Vote(usize),
+    // the probability that the failure of each input makes the gate fail
+    NoisyOr(Vec<f32>),
+}
+
+impl Gate {
+    fn failure_probability(&self, failed: &[bool]) -> f32 {
+        let certain = |fails: bool| if fails { 1.0 } else { 0.0 };
+        match *self {
+            Gate::And => certain(failed.iter().all(|&f| f)),
+            Gate::Or => certain(failed.iter().any(|&f| f)),
+            Gate::Vote(k) => certain(failed.iter().filter(|&&f| f).count() >= k),
+            Gate::NoisyOr(ref strengths) => {
+                let working: f32 = failed
+                    .iter()
+                    .zip(strengths)
+                    .filter(|&(&f, _)| f)
+                    .map(|(_, &p)| 1.0 - p)
+                    .product();
+                1.0 - working
+            }
+        }
+    }
+}
+
+enum Event {
+    // probability of failure
+    Basic(f32),
+    // the gate, and its inputs with the line of their reference
+    Gate(Gate, Vec<(usize, String)>),
+}
+
+fn probability(line: usize, word: &str) -> Result<f32, BifError> {
+    match word.parse::<f32>() {
+        Ok(p) if (0.0..=1.0).contains(&p) => Ok(p),
+        _ => Err(BifError::Syntax {
+            line,
+            message: format!("invalid probability \"{}\"", word),
+        }),
+    }
+}
+
+// the gate and its inputs, from the words following `=`
+fn parse_gate<'a>(
+    line: usize,
+    kind: &str,
+    rest: &[&'a str],
+) -> Result<(Gate, Vec<&'a str>), BifError> {
+    let syntax = |message: String| BifError::Syntax { line, message };
+    match kind {
+        "and" => Ok((Gate::And, rest.to_vec())),
+        "or" => Ok((Gate::Or, rest.to_vec())),
+        "vote" => match rest.first().map(|w| w.parse()) {
+            Some(Ok(k)) if k > 0 && k < rest.len() => Ok((Gate::Vote(k), rest[1..].to_vec())),
+            _ => Err(syntax(
+                "a vote gate needs a threshold between 1 and its number of inputs".to_owned(),
+            )),
+        },
+        "noisy-or" => {
+            let mut inputs = Vec::with_capacity(rest.len());
+            let mut strengths = Vec::with_capacity(rest.len());
+            for word in rest {
+                let (input, p) = word.split_once(':').ok_or_else(|| {
+                    syntax(format!(
+                        "noisy-or input \"{}\" must be given as name:probability",
+                        word
+                    ))
+                })?;
+                inputs.push(input);
+                strengths.push(probability(line, p)?);
+            }
+            Ok((Gate::NoisyOr(strengths), inputs))
+        }
+        other => Err(syntax(format!("unknown gate \"{}\"", other))),
+    }
+}
+
+impl BayesNet {
+    /// Read a fault tree, as a network of its basic events and gates
+    ///
+    /// The fault tree is given one event per line, `#` starting a comment:
+    ///
+    /// ```text
+    /// basic pump 0.01
+    /// basic valve 0.02
+    /// cooling = or pump valve
+    /// top = and cooling backup
+    /// ```
+    ///
+    /// Basic events are given with their probability of failure. Gates are `and`, `or`, `vote k` (at
+    /// least `k` of the inputs failed) and `noisy-or`, whose inputs are given as `name:p`, `p` being the
+    /// probability that the failure of the input makes the gate fail. Events can be used before they
+    /// are defined.
+    ///
+    /// Every node is named after its event, with the states `working` and `failed`. Basic events are
+    /// root nodes, and gates have their inputs as parents, in order, with deterministic tables (or
+    /// noisy-OR ones). The nodes are added in the order of the events, except that the inputs of a gate
+    /// are always added before it.
+    pub fn from_fault_tree(text: &str) -> Result<BayesNet, BifError> {
+        let mut variables: Vec<Variable> = Vec::new();
+        let mut events = Vec::new();
+        let mut names: HashMap<String, usize> = HashMap::new();
+        for (i, line) in text.lines().enumerate() {
+            let line_number = i + 1;
+            let syntax = |message: String| BifError::Syntax {
+                line: line_number,
+                message,
+            };
+            let content = line.split('#').next().unwrap();
+            let words: Vec<&str> = content.split_whitespace().collect();
+            let (name, event) = match words.as_slice() {
+                [] => continue,
+                ["basic", name, p] => (*name, Event::Basic(probability(line_number, p)?)),
+                ["basic", ..] => {
+                    return Err(syntax(
+                        "a basic event needs a name and a probability".to_owned(),
+                    ))
+                }
+                [name, "=", kind, rest @ ..] => {
+                    let (gate, inputs) = parse_gate(line_number, kind, rest)?;
+                    if inputs.is_empty() {
+                        return Err(syntax(format!("gate \"{}\" has no inputs", name)));
+                    }
+                    let inputs = inputs
+                        .iter()
+                        .map(|&input| (line_number, input.to_owned()))
+                        .collect();
+                    (*name, Event::Gate(gate, inputs))
+                }
+                _ => return Err(syntax(format!("cannot parse \"{}\"", content.trim()))),
+            };
+            if names.insert(name.to_owned(), variables.len()).is_some() {
+                return Err(syntax(format!("event \"{}\" is defined twice", name)));
+            }
+            variables.push(Variable {
+                name: name.to_owned(),
+                line: line_number,
+                states: STATES.iter().map(|&s| s.to_owned()).collect(),
+                layout: None,
+            });
+            events.push(event);
+        }
+
+        let mut tables = Vec::with_capacity(events.len());
+        for event in events {
+            let table = match event {
+                Event::Basic(p) => (Vec::new(), table_with_node_last(vec![1.0 - p, p], &[], 2)),
+                Event::Gate(gate, inputs) => {
+                    let parents = inputs
+                        .into_iter()
+                        .map(|(line, name)| {
+                            names
+                                .get(&name)
+                                .copied()
+                                .ok_or(BifError::UnknownVariable { line, name })
+                        })
+                        .collect::<Result<Vec<usize>, BifError>>()?;
+                    let n = parents.len();
+                    let mut values = Vec::with_capacity(2 << n);
+                    // the configurations of the inputs, the last one varying the fastest
+                    for config in 0..1usize << n {
+                        let failed: Vec<bool> =
+                            (0..n).map(|i| (config >> (n - 1 - i)) & 1 == 1).collect();
+                        let p = gate.failure_probability(&failed);
+                        values.push(1.0 - p);
+                        values.push(p);
+                    }
+                    (parents, table_with_node_last(values, &vec![2; n], 2))
+                }
+            };
+            tables.push(Some(table));
+        }
+        build_network(&variables, tables)
+    }
+}
@@ -0,0 +1,95 @@
+//! Selection of the variables relevant to a target in a dataset
+//!
+//! The Markov blanket of a target is the smallest set of variables making it independent of all the
+//! others: no other variable brings information about the target once these are known. In a Bayesian
+//! network, it is made of the parents, the children and the other parents of the children of the
+//! target, so it is both a feature set for predicting the target and a local structure to start from.
+//!
+//! Each record is a `Vec<usize>` holding the value of every variable, as in `learning`, but no
+//! network is needed: the number of values of each variable is deduced from the largest value observed
+//! for it.
+
+use crate::stats::{test_independence, IndependenceTest};
+
+/// Estimate the Markov blanket of `target` from the data
+///
+/// This uses `markov_blanket_with` with the G² test at significance level `0.05`.
+pub fn markov_blanket(data: &[Vec<usize>], target: usize) -> Vec<usize> {
+    markov_blanket_with(data, target, IndependenceTest::GSquared, 0.05)
+}
+
+/// Estimate the Markov blanket of `target` from the data, with the Incremental Association Markov
+/// Blanket algorithm (IAMB)
+///
+/// The blanket is grown by adding, at each step, the variable with the highest mutual information with
+/// the target given the current blanket, as long as their independence is rejected by `test` at
+/// significance level `alpha`. Then the variables that are independent of the target given the rest of
+/// the blanket are removed. The result is sorted.
+///
+/// The tests condition on the whole blanket, so they need enough records for each configuration of its
+/// values. Panics if the records do not all have the same number of variables, or if the target is
+/// not one of them.
+pub fn markov_blanket_with(
+    data: &[Vec<usize>],
+    target: usize,
+    test: IndependenceTest,
+    alpha: f32,
+) -> Vec<usize> {
+    let n_variables = data.first().map_or(0, Vec::len);
+    for (index, record) in data.iter().enumerate() {
+        assert!(
+            record.len() == n_variables,
+            "Record {} has {} values but the first record has {}",
+            index,
+            record.len(),
+            n_variables
+        );
+    }
+    assert!(
+        target < n_variables || data.is_empty(),
+        "Target {} is not one of the {} variables of the data",
+        target,
+        n_variables
+    );
+    let mut cardinalities = vec![0; n_variables];
+    for record in data {
+        for (cardinality, &value) in cardinalities.iter_mut().zip(record) {
+            *cardinality = (*cardinality).max(value + 1);
+        }
+    }
+
+    // growing phase
+    let mut blanket: Vec<usize> = Vec::new();
+    loop {
+        let best = (0..n_variables)
+            .filter(|&v| v != target && !blanket.contains(&v))
+            .map(|v| {
+                (
+                    v,
+                    test_independence(data, &cardinalities, v, target, &blanket, test),
+                )
+            })
+            .max_by(|(_, a), (_, b)| a.mutual_information.total_cmp(&b.mutual_information));
+        match best {
+            Some((variable, result)) if result.rejects_independence(alpha) => {
+                blanket.push(variable)
+            }
+            _ => break,
+        }
+    }
+
+    // shrinking phase
+    let mut i = 0;
+    while i < blanket.len() {
+        let variable = blanket[i];
+        let others: Vec<usize> = blanket.iter().copied().filter(|&v| v != variable).collect();
+        let result = test_independence(data, &cardinalities, variable, target, &others, test);
+        if result.rejects_independence(alpha) {
+            i += 1;
+        } else {
+            blanket.remove(i);
+        }
+    }
+    blanket.sort_unstable();
+    blanket
+}
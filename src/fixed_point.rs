@@ -0,0 +1,363 @@
+//! Experimental deterministic fixed-point backend
+//!
+//! This module provides `FixedPointNet`, which runs the same message passing as `BayesNet` but
+//! using Q16.16 fixed-point log-probabilities and integer-only arithmetic. It is meant for contexts
+//! where floating point nondeterminism is not acceptable.
+//!
+//! # Accuracy
+//!
+//! - Converting a log-probability to `Fixed` has an absolute error of at most `2^-17` (~`7.6e-6`).
+//! - Each `Fixed::log_add` has an absolute error of at most `1.3e-4` nats, coming mostly from the
+//!   linear interpolation of the `ln(1 + exp(-x))` table.
+//! - A sum over `n` terms thus has an error of at most `(n - 1) * 1.3e-4` nats, and each message
+//!   of a node with `k` parents of `m` values involves sums over up to `m^k` terms.
+//! - Log-probabilities below `-32768` are treated as `-inf` and products that overflow saturate.
+//!
+//! In practice, the resulting beliefs are within `1e-3` of the floating point ones for small networks.
+use crate::{BayesNet, LogProbVector};
+use ndarray::{Array1, ArrayD, ArrayView1, ArrayViewD, Axis};
+use std::ops::{Add, Sub};
+
+// `LOG1P_EXP_NEG[k]` is `ln(1 + exp(-k / 16))` in Q16.16, for `k` in `0..=256`.
+// Past `x = 16`, `ln(1 + exp(-x))` is smaller than the resolution of the format.
+const LOG1P_EXP_NEG: [i32; 257] = [
+    45426, 43410, 41458, 39570, 37745, 35983, 34283, 32646, 31069, 29553, 28095, 26696, 25354,
+    24068, 22836, 21657, 20530, 19453, 18425, 17445, 16510, 15620, 14773, 13966, 13200, 12471,
+    11780, 11123, 10500, 9910, 9350, 8820, 8318, 7843, 7394, 6969, 6567, 6187, 5829, 5490, 5170,
+    4868, 4583, 4315, 4061, 3822, 3597, 3384, 3184, 2996, 2818, 2651, 2493, 2345, 2205, 2074, 1950,
+    1833, 1724, 1620, 1523, 1432, 1346, 1265, 1189, 1118, 1051, 988, 928, 872, 820, 770, 724, 680,
+    639, 601, 565, 530, 498, 468, 440, 414, 389, 365, 343, 322, 303, 284, 267, 251, 236, 222, 208,
+    196, 184, 173, 162, 152, 143, 135, 126, 119, 112, 105, 98, 92, 87, 82, 77, 72, 68, 64, 60, 56,
+    53, 50, 47, 44, 41, 39, 36, 34, 32, 30, 28, 27, 25, 23, 22, 21, 19, 18, 17, 16, 15, 14, 13, 13,
+    12, 11, 10, 10, 9, 9, 8, 8, 7, 7, 6, 6, 6, 5, 5, 5, 4, 4, 4, 4, 3, 3, 3, 3, 3, 2, 2, 2, 2, 2,
+    2, 2, 2, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+];
+const TABLE_STEP_BITS: u32 = 12;
+
+/// A log-probability represented as a Q16.16 fixed-point number
+///
+/// All arithmetic on this type is done with integers, and is thus bit-for-bit reproducible
+/// on every platform. The representable range is roughly `[-32768, 32768)` with a resolution
+/// of `2^-16`. The smallest value of the underlying integer is reserved to represent `-inf`,
+/// i.e. a probability of `0`. Values which overflow are saturated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Fixed(i32);
+
+impl Fixed {
+    /// Number of fractional bits of the representation
+    pub const FRAC_BITS: u32 = 16;
+    /// The log-probability `0`, i.e. a probability of `1`
+    pub const ZERO: Fixed = Fixed(0);
+    /// The log-probability `-inf`, i.e. a probability of `0`
+    pub const NEG_INFINITY: Fixed = Fixed(i32::MIN);
+    const MIN_FINITE: i32 = i32::MIN + 1;
+
+    /// Create a fixed-point value from its raw Q16.16 representation
+    pub fn from_bits(bits: i32) -> Fixed {
+        Fixed(bits)
+    }
+
+    /// The raw Q16.16 representation of this value
+    pub fn to_bits(self) -> i32 {
+        self.0
+    }
+
+    /// Convert a floating point log-probability, rounding to the nearest representable value
+    ///
+    /// `-inf` and values too small to be represented are mapped to `Fixed::NEG_INFINITY`, and `NaN`
+    /// is mapped to `Fixed::NEG_INFINITY` as well.
+    pub fn from_f32(v: f32) -> Fixed {
+        let scaled = (f64::from(v) * f64::from(1u32 << Self::FRAC_BITS)).round();
+        if scaled.is_nan() || scaled < f64::from(Self::MIN_FINITE) {
+            Fixed::NEG_INFINITY
+        } else if scaled > f64::from(i32::MAX) {
+            Fixed(i32::MAX)
+        } else {
+            Fixed(scaled as i32)
+        }
+    }
+
+    /// Convert back to a floating point log-probability
+    pub fn to_f32(self) -> f32 {
+        if self.is_neg_infinity() {
+            f32::NEG_INFINITY
+        } else {
+            self.0 as f32 / (1u32 << Self::FRAC_BITS) as f32
+        }
+    }
+
+    /// Whether this value represents `-inf`
+    pub fn is_neg_infinity(self) -> bool {
+        self.0 == i32::MIN
+    }
+
+    fn saturate(v: i64) -> Fixed {
+        Fixed(v.clamp(i64::from(Self::MIN_FINITE), i64::from(i32::MAX)) as i32)
+    }
+
+    /// Compute `ln(exp(self) + exp(other))`, i.e. the sum in probability space
+    ///
+    /// The correction term `ln(1 + exp(-|a - b|))` is read by linear interpolation in a table
+    /// with a step of `1/16`. The absolute error of the result is below `1.3e-4` (see the
+    /// module documentation).
+    pub fn log_add(self, other: Fixed) -> Fixed {
+        if self.is_neg_infinity() {
+            return other;
+        }
+        if other.is_neg_infinity() {
+            return self;
+        }
+        let (max, min) = if self >= other {
+            (self, other)
+        } else {
+            (other, self)
+        };
+        let diff = i64::from(max.0) - i64::from(min.0);
+        let idx = (diff >> TABLE_STEP_BITS) as usize;
+        if idx >= LOG1P_EXP_NEG.len() - 1 {
+            return max;
+        }
+        let frac = diff & ((1 << TABLE_STEP_BITS) - 1);
+        let low = i64::from(LOG1P_EXP_NEG[idx]);
+        let high = i64::from(LOG1P_EXP_NEG[idx + 1]);
+        let correction = low + (((high - low) * frac) >> TABLE_STEP_BITS);
+        Fixed::saturate(i64::from(max.0) + correction)
+    }
+}
+
+/// Product in probability space: log-probabilities are summed, and `-inf` is absorbing.
+impl Add for Fixed {
+    type Output = Fixed;
+    fn add(self, other: Fixed) -> Fixed {
+        if self.is_neg_infinity() || other.is_neg_infinity() {
+            Fixed::NEG_INFINITY
+        } else {
+            Fixed::saturate(i64::from(self.0) + i64::from(other.0))
+        }
+    }
+}
+
+/// Division in probability space: `other` must be finite, and `-inf` is preserved.
+impl Sub for Fixed {
+    type Output = Fixed;
+    fn sub(self, other: Fixed) -> Fixed {
+        if self.is_neg_infinity() {
+            Fixed::NEG_INFINITY
+        } else {
+            Fixed::saturate(i64::from(self.0) - i64::from(other.0))
+        }
+    }
+}
+
+fn log_sum(v: ArrayView1<Fixed>) -> Fixed {
+    v.fold(Fixed::NEG_INFINITY, |acc, &x| acc.log_add(x))
+}
+
+fn log_contract(tensor: ArrayViewD<Fixed>, vector: ArrayView1<Fixed>, axis: Axis) -> ArrayD<Fixed> {
+    tensor.map_axis(axis, |v| {
+        v.iter()
+            .zip(vector.iter())
+            .fold(Fixed::NEG_INFINITY, |acc, (&a, &b)| acc.log_add(a + b))
+    })
+}
+
+fn renormalize(v: &mut Array1<Fixed>) {
+    let sum = log_sum(v.view());
+    if !sum.is_neg_infinity() {
+        v.mapv_inplace(|x| x - sum);
+    }
+}
+
+fn prod(a: &mut Array1<Fixed>, b: &Array1<Fixed>) {
+    a.zip_mut_with(b, |x, &y| *x = *x + y);
+}
+
+fn into_vector(a: ArrayD<Fixed>) -> Array1<Fixed> {
+    assert!(a.ndim() == 1);
+    let len = a.len();
+    a.into_shape((len,)).unwrap()
+}
+
+#[derive(Debug, Clone)]
+struct FixedNode {
+    parents: Vec<(usize, Array1<Fixed>)>,
+    children: Vec<(usize, Array1<Fixed>)>,
+    log_probas: ArrayD<Fixed>,
+    evidence: Option<usize>,
+}
+
+impl FixedNode {
+    fn n_values(&self) -> usize {
+        self.log_probas.shape()[0]
+    }
+
+    fn evidence_vec(&self) -> Array1<Fixed> {
+        match self.evidence {
+            Some(value) => Array1::from_shape_fn(self.n_values(), |i| {
+                if i == value {
+                    Fixed::ZERO
+                } else {
+                    Fixed::NEG_INFINITY
+                }
+            }),
+            None => Array1::from_elem(self.n_values(), Fixed::ZERO),
+        }
+    }
+
+    fn compute_lambda(&self) -> Array1<Fixed> {
+        let mut lambda = self.evidence_vec();
+        for (_, msg) in &self.children {
+            prod(&mut lambda, msg);
+        }
+        lambda
+    }
+
+    fn compute_pi(&self) -> Array1<Fixed> {
+        let mut pi = self.log_probas.clone();
+        for (_, msg) in self.parents.iter().rev() {
+            pi = log_contract(pi.view(), msg.view(), Axis(pi.ndim() - 1));
+        }
+        into_vector(pi)
+    }
+}
+
+/// A copy of a `BayesNet` running the Loopy Belief Propagation in fixed-point arithmetic
+///
+/// This mirrors the `BayesNet` inference API (`set_evidence`, `reset_state`, `step`, `beliefs`),
+/// but all message computations are done on `Fixed` values, making the results independent of
+/// the platform floating point behavior.
+#[derive(Debug, Clone)]
+pub struct FixedPointNet {
+    nodes: Vec<FixedNode>,
+}
+
+impl FixedPointNet {
+    /// Convert a network to fixed-point
+    ///
+    /// The (normalized) conditional probability tables and the current evidence of `net` are copied,
+    /// and the messages are initialized to uniform.
+    pub fn from_net(net: &BayesNet) -> FixedPointNet {
+        let nodes = net
+            .nodes
+            .iter()
+            .map(|node| FixedNode {
+                parents: node
+                    .parents
+                    .iter()
+                    .map(|&(p, _)| (p, Array1::from_elem(net.num_values(p), Fixed::ZERO)))
+                    .collect(),
+                children: node
+                    .children
+                    .iter()
+                    .map(|&(c, _)| {
+                        (
+                            c,
+                            Array1::from_elem(node.log_probas.shape()[0], Fixed::ZERO),
+                        )
+                    })
+                    .collect(),
+                log_probas: node.log_probas.dense().mapv(Fixed::from_f32),
+                evidence: node.evidence,
+            })
+            .collect();
+        FixedPointNet { nodes }
+    }
+
+    /// Sets the evidence for the network
+    ///
+    /// See `BayesNet::set_evidence`.
+    pub fn set_evidence(&mut self, evidence: &[(usize, usize)]) {
+        for node in &mut self.nodes {
+            node.evidence = None;
+        }
+        for &(node, value) in evidence {
+            self.nodes[node].evidence = Some(value);
+        }
+    }
+
+    /// Resets the messages to uniform, to begin a new inference
+    pub fn reset_state(&mut self) {
+        for node in &mut self.nodes {
+            for (_, msg) in node.children.iter_mut().chain(node.parents.iter_mut()) {
+                msg.fill(Fixed::ZERO);
+            }
+        }
+    }
+
+    /// Compute one step of the Loopy Belief Propagation Algorithm
+    ///
+    /// See `BayesNet::step`.
+    pub fn step(&mut self) {
+        let mut pi_msgs = Vec::new();
+        let mut lambda_msgs = Vec::new();
+
+        for (id, node) in self.nodes.iter().enumerate() {
+            let mut pi = node.compute_pi();
+            prod(&mut pi, &node.evidence_vec());
+            for &(child_id, _) in &node.children {
+                let mut msg = pi.clone();
+                for (_, lambda) in node.children.iter().filter(|&&(c, _)| c != child_id) {
+                    prod(&mut msg, lambda);
+                }
+                renormalize(&mut msg);
+                pi_msgs.push((id, child_id, msg));
+            }
+
+            let lambda = node.compute_lambda();
+            for &(parent_id, _) in &node.parents {
+                let acc = node
+                    .parents
+                    .iter()
+                    .enumerate()
+                    .rev()
+                    .filter(|&(_, &(pid, _))| pid != parent_id)
+                    .fold(node.log_probas.clone(), |acc, (axid, (_, v))| {
+                        log_contract(acc.view(), v.view(), Axis(axid + 1))
+                    });
+                let mut msg = into_vector(log_contract(acc.view(), lambda.view(), Axis(0)));
+                renormalize(&mut msg);
+                lambda_msgs.push((id, parent_id, msg));
+            }
+        }
+
+        for (from, to, msg) in pi_msgs {
+            let place = self.nodes[to]
+                .parents
+                .iter_mut()
+                .find(|&&mut (parent_id, _)| parent_id == from)
+                .expect("Message sent to a node who doesn't recognize its parent?!");
+            place.1 = msg;
+        }
+        for (from, to, msg) in lambda_msgs {
+            let place = self.nodes[to]
+                .children
+                .iter_mut()
+                .find(|&&mut (child_id, _)| child_id == from)
+                .expect("Message sent to a node who doesn't recognize its child?!");
+            place.1 = msg;
+        }
+    }
+
+    /// Compute the current normalized log-beliefs of each node, in fixed-point
+    pub fn beliefs_fixed(&self) -> Vec<Array1<Fixed>> {
+        self.nodes
+            .iter()
+            .map(|node| {
+                let mut belief = node.compute_lambda();
+                prod(&mut belief, &node.compute_pi());
+                renormalize(&mut belief);
+                belief
+            })
+            .collect()
+    }
+
+    /// Compute the current beliefs of each node, converted back to floating point
+    pub fn beliefs(&self) -> Vec<LogProbVector> {
+        self.beliefs_fixed()
+            .into_iter()
+            .map(|b| LogProbVector::from_log_probabilities(b.mapv(Fixed::to_f32)))
+            .collect()
+    }
+}
@@ -0,0 +1,76 @@
+//! Generation of random networks and evidence for fuzzing, with the `arbitrary` crate
+//!
+//! The generated networks are valid (consistent shapes, every column of every table has a non-zero
+//! probability), but may contain zero probabilities, loops, and nodes with a single value, which are
+//! the interesting cases for the inference algorithm.
+
+use crate::BayesNet;
+use arbitrary::{Arbitrary, Result, Unstructured};
+use ndarray::{ArrayD, IxDyn};
+
+const MAX_NODES: usize = 8;
+const MAX_VALUES: usize = 4;
+const MAX_PARENTS: usize = 3;
+
+impl<'a> Arbitrary<'a> for BayesNet {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<BayesNet> {
+        let mut net = BayesNet::new();
+        let n_nodes = u.int_in_range(1..=MAX_NODES)?;
+        for id in 0..n_nodes {
+            let n_values = u.int_in_range(1..=MAX_VALUES)?;
+            let mut parents = Vec::new();
+            for candidate in 0..id {
+                if parents.len() < MAX_PARENTS && u.ratio(1, 3)? {
+                    parents.push(candidate);
+                }
+            }
+            let mut shape = vec![n_values];
+            shape.extend(parents.iter().map(|&p| net.num_values(p)));
+            let mut probabilities = ArrayD::zeros(IxDyn(&shape));
+            for p in probabilities.iter_mut() {
+                *p = f32::from(u.arbitrary::<u8>()?) / 255.0;
+            }
+            for mut column in probabilities.lanes_mut(ndarray::Axis(0)) {
+                if column.sum() == 0.0 {
+                    column.fill(1.0);
+                }
+            }
+            net.add_node_from_probabilities(&parents, probabilities);
+        }
+        Ok(net)
+    }
+}
+
+/// A random network with random evidence of non-zero probability
+#[derive(Debug, Clone)]
+pub struct NetWithEvidence {
+    /// The network
+    pub net: BayesNet,
+    /// The evidence, as a list of `(node_id, node_value)`
+    pub evidence: Vec<(usize, usize)>,
+}
+
+impl<'a> Arbitrary<'a> for NetWithEvidence {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<NetWithEvidence> {
+        let net = BayesNet::arbitrary(u)?;
+        // sample a full configuration of the network, and observe some of its values
+        let mut values: Vec<usize> = Vec::with_capacity(net.num_nodes());
+        let mut evidence = Vec::new();
+        for id in 0..net.num_nodes() {
+            let mut index = vec![0];
+            index.extend(net.parents(id).iter().map(|&p| values[p]));
+            let possible: Vec<usize> = (0..net.num_values(id))
+                .filter(|&v| {
+                    index[0] = v;
+                    net.nodes[id].log_probas.get(&index) > f32::NEG_INFINITY
+                })
+                .collect();
+            let value = *u.choose(&possible)?;
+            values.push(value);
+            if u.arbitrary()? {
+                evidence.push((id, value));
+            }
+        }
+        Ok(NetWithEvidence { net, evidence })
+    }
+}
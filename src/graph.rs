@@ -0,0 +1,269 @@
+//! Algorithms on the directed acyclic graph underlying a network
+//!
+//! Graph structures are described by their parent lists: `parents[i]` contains the parents of
+//! node `i`, as returned by `BayesNet::parents`.
+
+use crate::BayesNet;
+use std::collections::HashSet;
+
+/// Whether the orientation of an edge is determined by its Markov equivalence class
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EdgeOrientation {
+    /// Every DAG of the equivalence class orients this edge the same way
+    Compelled,
+    /// Some DAGs of the equivalence class orient this edge the other way
+    Reversible,
+}
+
+/// Parent lists of all the nodes of a network
+pub fn structure(net: &BayesNet) -> Vec<Vec<usize>> {
+    (0..net.num_nodes()).map(|i| net.parents(i)).collect()
+}
+
+/// Compute the completed partially directed acyclic graph (CPDAG) of a DAG
+///
+/// Returns all the edges `(from, to)` of the DAG with their orientation status. Reversible edges
+/// can be reversed without changing the set of independencies encoded by the graph (and thus
+/// without changing how well it can fit any data), so their direction should not be interpreted.
+///
+/// The v-structures of the graph are compelled, and the other compelled edges are found by
+/// applying Meek's orientation rules until no edge can be oriented anymore.
+pub fn cpdag(parents: &[Vec<usize>]) -> Vec<(usize, usize, EdgeOrientation)> {
+    let n = parents.len();
+    let edges: Vec<(usize, usize)> = (0..n)
+        .flat_map(|child| parents[child].iter().map(move |&p| (p, child)))
+        .collect();
+    let adjacent = |a: usize, b: usize| parents[a].contains(&b) || parents[b].contains(&a);
+
+    // edges oriented so far
+    let mut oriented: HashSet<(usize, usize)> = HashSet::new();
+    for (child, child_parents) in parents.iter().enumerate() {
+        for (i, &a) in child_parents.iter().enumerate() {
+            for &b in &child_parents[i + 1..] {
+                if !adjacent(a, b) {
+                    oriented.insert((a, child));
+                    oriented.insert((b, child));
+                }
+            }
+        }
+    }
+
+    let undirected = |oriented: &HashSet<(usize, usize)>, a: usize, b: usize| {
+        adjacent(a, b) && !oriented.contains(&(a, b)) && !oriented.contains(&(b, a))
+    };
+
+    loop {
+        let mut changed = false;
+        for &(from, to) in &edges {
+            if !undirected(&oriented, from, to) {
+                continue;
+            }
+            // In a DAG, Meek's rules can only orient an edge along its actual direction,
+            // so we only need to check whether `from -> to` is implied.
+            let (b, c) = (from, to);
+            // R1: a -> b, b - c, a and c not adjacent
+            let r1 = (0..n).any(|a| oriented.contains(&(a, b)) && a != c && !adjacent(a, c));
+            // R2: b -> a -> c with b - c
+            let r2 = (0..n).any(|a| oriented.contains(&(b, a)) && oriented.contains(&(a, c)));
+            // R3: b - x, b - y, x -> c, y -> c, x and y not adjacent
+            let r3 = (0..n).any(|x| {
+                undirected(&oriented, b, x)
+                    && oriented.contains(&(x, c))
+                    && (x + 1..n).any(|y| {
+                        undirected(&oriented, b, y) && oriented.contains(&(y, c)) && !adjacent(x, y)
+                    })
+            });
+            if r1 || r2 || r3 {
+                oriented.insert((b, c));
+                changed = true;
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+
+    edges
+        .into_iter()
+        .map(|(from, to)| {
+            let kind = if oriented.contains(&(from, to)) {
+                EdgeOrientation::Compelled
+            } else {
+                EdgeOrientation::Reversible
+            };
+            (from, to, kind)
+        })
+        .collect()
+}
+
+/// Find the evidence that can influence the posterior distribution of a node
+///
+/// Returns, in increasing order, the nodes of `observed` that are not d-separated from `target` by
+/// the other observed nodes, using the Bayes-Ball algorithm. The posterior of `target` given all the
+/// observations is the same as its posterior given only these ones.
+pub fn requisite_evidence(parents: &[Vec<usize>], target: usize, observed: &[usize]) -> Vec<usize> {
+    let n = parents.len();
+    let mut children = vec![Vec::new(); n];
+    for (child, child_parents) in parents.iter().enumerate() {
+        for &p in child_parents {
+            children[p].push(child);
+        }
+    }
+    let is_observed: Vec<bool> = (0..n).map(|i| observed.contains(&i)).collect();
+    let mut visited = vec![false; n];
+    // the ball has been sent to the parents (top) or the children (bottom) of the node
+    let mut top = vec![false; n];
+    let mut bottom = vec![false; n];
+    // (node, whether the ball comes from one of its children)
+    let mut schedule = vec![(target, true)];
+    while let Some((node, from_child)) = schedule.pop() {
+        visited[node] = true;
+        let pass_up = if is_observed[node] {
+            !from_child
+        } else {
+            from_child
+        };
+        let pass_down = !is_observed[node];
+        if pass_up && !top[node] {
+            top[node] = true;
+            schedule.extend(parents[node].iter().map(|&p| (p, true)));
+        }
+        if pass_down && !bottom[node] {
+            bottom[node] = true;
+            schedule.extend(children[node].iter().map(|&c| (c, false)));
+        }
+    }
+    (0..n).filter(|&i| is_observed[i] && visited[i]).collect()
+}
+
+/// Heuristics used by `find_loop_cutset` to choose the next node of the cutset
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CutsetHeuristic {
+    /// The node with the most neighbours, preferring the nodes with fewer values (Suermondt and Cooper)
+    MaxDegree,
+    /// The node with the smallest ratio between the logarithm of its number of values and its number of
+    /// neighbours, which tends to minimize the number of joint assignments of the cutset rather than its size
+    MinWeight,
+}
+
+/// A loop cutset found by `find_loop_cutset`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoopCutset {
+    /// The nodes of the cutset, in increasing order
+    pub nodes: Vec<usize>,
+    /// Number of joint assignments of the nodes of the cutset, `None` if it overflows a `usize`
+    pub assignments: Option<usize>,
+    /// Number of independent loops of the graph (its cyclomatic number), `0` for polytrees
+    pub independent_loops: usize,
+}
+
+/// Find a loop cutset of a graph
+///
+/// Observing all the nodes of a loop cutset blocks every loop of the graph for the Belief Propagation,
+/// which then becomes exact (see `BayesNet::cutset_beliefs`). `cardinalities[i]` is the number of values
+/// of node `i`.
+///
+/// This uses the greedy algorithm of Suermondt and Cooper: nodes that cannot be part of a loop (with at
+/// most one neighbour) are pruned, then a remaining node with at most one parent is chosen by the heuristic
+/// and added to the cutset, until no loop remains. The cutset is not guaranteed to be minimal. The size of
+/// the cutset and its number of assignments measure how costly exact inference by conditioning is.
+pub fn find_loop_cutset(
+    parents: &[Vec<usize>],
+    cardinalities: &[usize],
+    heuristic: CutsetHeuristic,
+) -> LoopCutset {
+    let n = parents.len();
+    let mut parents: Vec<Vec<usize>> = parents.to_vec();
+    let mut children: Vec<Vec<usize>> = vec![Vec::new(); n];
+    for (child, child_parents) in parents.iter().enumerate() {
+        for &p in child_parents {
+            children[p].push(child);
+        }
+    }
+    let n_edges: usize = parents.iter().map(Vec::len).sum();
+    let independent_loops = n_edges + connected_components(&parents, &children) - n;
+
+    let mut removed = vec![false; n];
+    let mut cutset = Vec::new();
+    let remove = |node: usize,
+                  parents: &mut Vec<Vec<usize>>,
+                  children: &mut Vec<Vec<usize>>,
+                  removed: &mut Vec<bool>| {
+        removed[node] = true;
+        for p in std::mem::take(&mut parents[node]) {
+            children[p].retain(|&c| c != node);
+        }
+        for c in std::mem::take(&mut children[node]) {
+            parents[c].retain(|&p| p != node);
+        }
+    };
+
+    loop {
+        // prune the nodes that cannot be part of a loop
+        let mut pruned = true;
+        while pruned {
+            pruned = false;
+            for node in 0..n {
+                if !removed[node] && parents[node].len() + children[node].len() <= 1 {
+                    remove(node, &mut parents, &mut children, &mut removed);
+                    pruned = true;
+                }
+            }
+        }
+        let degree = |i: usize| parents[i].len() + children[i].len();
+        // ordered score of a node, the highest is chosen, ties are broken in favor of the first nodes
+        let score = |i: usize| -> (f64, f64, std::cmp::Reverse<usize>) {
+            let log_values = (cardinalities[i] as f64).ln();
+            let (primary, secondary) = match heuristic {
+                CutsetHeuristic::MaxDegree => (degree(i) as f64, -log_values),
+                CutsetHeuristic::MinWeight => (-log_values / degree(i) as f64, 0.0),
+            };
+            (primary, secondary, std::cmp::Reverse(i))
+        };
+        let pick = |candidates: &mut dyn Iterator<Item = usize>| {
+            candidates.max_by(|&a, &b| score(a).partial_cmp(&score(b)).unwrap())
+        };
+        let best = pick(&mut (0..n).filter(|&i| !removed[i] && parents[i].len() <= 1))
+            // every remaining node has several parents, fall back to any of them
+            .or_else(|| pick(&mut (0..n).filter(|&i| !removed[i])));
+        match best {
+            Some(node) => {
+                cutset.push(node);
+                remove(node, &mut parents, &mut children, &mut removed);
+            }
+            None => break,
+        }
+    }
+    cutset.sort_unstable();
+    let assignments = cutset
+        .iter()
+        .try_fold(1usize, |acc, &c| acc.checked_mul(cardinalities[c]));
+    LoopCutset {
+        nodes: cutset,
+        assignments,
+        independent_loops,
+    }
+}
+
+fn connected_components(parents: &[Vec<usize>], children: &[Vec<usize>]) -> usize {
+    let n = parents.len();
+    let mut seen = vec![false; n];
+    let mut components = 0;
+    for start in 0..n {
+        if seen[start] {
+            continue;
+        }
+        components += 1;
+        seen[start] = true;
+        let mut stack = vec![start];
+        while let Some(node) = stack.pop() {
+            for &other in parents[node].iter().chain(children[node].iter()) {
+                if !seen[other] {
+                    seen[other] = true;
+                    stack.push(other);
+                }
+            }
+        }
+    }
+    components
+}
@@ -0,0 +1,92 @@
+use crate::BayesNet;
+use ndarray::ArrayD;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Weak};
+
+// the tables of the nodes of a network, shared between the nodes having identical tables
+#[derive(Debug, Clone, Default)]
+pub(crate) struct TablePool {
+    tables: HashMap<u64, Vec<Weak<ArrayD<f32>>>>,
+    // number of buckets above which the dead entries of all the buckets are removed
+    sweep_at: usize,
+}
+
+// the pool is swept when it has doubled since the last sweep, and not below this size
+const MIN_SWEEP: usize = 64;
+
+fn content_hash(table: &ArrayD<f32>) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    table.shape().hash(&mut hasher);
+    table.iter().for_each(|v| v.to_bits().hash(&mut hasher));
+    hasher.finish()
+}
+
+// identical shapes and bit patterns, so that tables differing by the sign of a zero or by a NaN
+// payload are not merged
+fn identical(a: &ArrayD<f32>, b: &ArrayD<f32>) -> bool {
+    a.shape() == b.shape()
+        && a.iter()
+            .zip(b.iter())
+            .all(|(x, y)| x.to_bits() == y.to_bits())
+}
+
+impl TablePool {
+    // the shared copy of the table, added to the pool if it is not there yet
+    pub(crate) fn intern(&mut self, table: ArrayD<f32>) -> Arc<ArrayD<f32>> {
+        let candidates = self.tables.entry(content_hash(&table)).or_default();
+        candidates.retain(|weak| weak.strong_count() > 0);
+        if let Some(shared) = candidates
+            .iter()
+            .filter_map(Weak::upgrade)
+            .find(|shared| identical(shared, &table))
+        {
+            return shared;
+        }
+        let shared = Arc::new(table);
+        candidates.push(Arc::downgrade(&shared));
+        if self.tables.len() >= self.sweep_at {
+            self.sweep();
+        }
+        shared
+    }
+
+    // remove the tables which are not used anymore, so that replacing tables again and again does not
+    // grow the pool; sweeping when the pool doubled keeps the cost amortized
+    fn sweep(&mut self) {
+        self.tables.retain(|_, candidates| {
+            candidates.retain(|weak| weak.strong_count() > 0);
+            !candidates.is_empty()
+        });
+        self.sweep_at = (2 * self.tables.len()).max(MIN_SWEEP);
+    }
+
+    // number of tables in the pool, including the unused ones not swept yet
+    pub(crate) fn len(&self) -> usize {
+        self.tables.values().map(Vec::len).sum()
+    }
+}
+
+impl BayesNet {
+    /// Number of distinct probability tables stored by the network
+    ///
+    /// Nodes whose tables are identical, as is common in networks generated from templates, share a
+    /// single copy of their table, so this can be much lower than the number of nodes.
+    pub fn num_distinct_tables(&self) -> usize {
+        self.nodes
+            .iter()
+            .map(|node| node.log_probas.as_ptr())
+            .collect::<HashSet<_>>()
+            .len()
+    }
+
+    /// Number of tables held by the pool used to share identical tables, for memory diagnostics
+    ///
+    /// Tables that are not used by any node anymore, for example after `set_probabilities` or the
+    /// learning of the parameters, are removed from the pool from time to time, so this stays
+    /// proportional to `num_distinct_tables`.
+    pub fn num_pooled_tables(&self) -> usize {
+        self.tables.len()
+    }
+}
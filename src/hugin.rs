@@ -0,0 +1,405 @@
+use crate::bif::{build_network, table_with_node_last, Variable};
+use crate::{BayesNet, BifError, NodeLayout};
+use ndarray::{ArrayViewD, Axis};
+use std::collections::HashMap;
+use std::fmt::Write;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    Quoted(String),
+    Punct(char),
+}
+
+// split the text into tokens with their line, skipping `%` comments
+fn tokenize(text: &str) -> Result<Vec<(usize, Token)>, BifError> {
+    let mut tokens = Vec::new();
+    let mut chars = text.chars().peekable();
+    let mut line = 1;
+    while let Some(c) = chars.next() {
+        match c {
+            '\n' => line += 1,
+            c if c.is_whitespace() => {}
+            '%' => while chars.next_if(|&c| c != '\n').is_some() {},
+            '"' => {
+                let start = line;
+                let mut word = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => word.extend(chars.next()),
+                        Some(c) => {
+                            if c == '\n' {
+                                line += 1;
+                            }
+                            word.push(c);
+                        }
+                        None => {
+                            return Err(BifError::Syntax {
+                                line: start,
+                                message: "unterminated string".to_owned(),
+                            })
+                        }
+                    }
+                }
+                tokens.push((start, Token::Quoted(word)));
+            }
+            c if "{}()|=;".contains(c) => tokens.push((line, Token::Punct(c))),
+            c => {
+                let mut word = c.to_string();
+                word.extend(std::iter::from_fn(|| {
+                    chars.next_if(|&c| !c.is_whitespace() && !"{}()|=;%\"".contains(c))
+                }));
+                tokens.push((line, Token::Word(word)));
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+// the value of an attribute
+enum Value {
+    Text(String),
+    List(Vec<Value>),
+}
+
+impl Value {
+    fn flatten(&self, values: &mut Vec<String>) {
+        match self {
+            Value::Text(text) => values.push(text.clone()),
+            Value::List(list) => list.iter().for_each(|v| v.flatten(values)),
+        }
+    }
+}
+
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    pos: usize,
+}
+
+impl Parser {
+    fn line(&self) -> usize {
+        self.tokens
+            .get(self.pos)
+            .or_else(|| self.tokens.last())
+            .map_or(1, |&(line, _)| line)
+    }
+
+    fn error<T>(&self, message: String) -> Result<T, BifError> {
+        Err(BifError::Syntax {
+            line: self.line(),
+            message,
+        })
+    }
+
+    fn next(&mut self) -> Result<Token, BifError> {
+        match self.tokens.get(self.pos) {
+            Some((_, token)) => {
+                self.pos += 1;
+                Ok(token.clone())
+            }
+            None => self.error("unexpected end of file".to_owned()),
+        }
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.tokens.get(self.pos).map(|(_, t)| t) == Some(&Token::Punct(c)) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), BifError> {
+        if self.eat(c) {
+            Ok(())
+        } else {
+            self.error(format!("expected '{}'", c))
+        }
+    }
+
+    fn word(&mut self) -> Result<String, BifError> {
+        match self.next()? {
+            Token::Word(word) => Ok(word),
+            _ => {
+                self.pos -= 1;
+                self.error("expected a name".to_owned())
+            }
+        }
+    }
+
+    fn value(&mut self) -> Result<Value, BifError> {
+        match self.next()? {
+            Token::Word(word) | Token::Quoted(word) => Ok(Value::Text(word)),
+            Token::Punct('(') => {
+                let mut list = Vec::new();
+                while !self.eat(')') {
+                    list.push(self.value()?);
+                }
+                Ok(Value::List(list))
+            }
+            _ => {
+                self.pos -= 1;
+                self.error("expected a value".to_owned())
+            }
+        }
+    }
+
+    // the `name = value;` attributes of a block, up to its closing brace
+    fn attributes(&mut self) -> Result<Vec<(usize, String, Value)>, BifError> {
+        let mut attributes = Vec::new();
+        self.expect('{')?;
+        while !self.eat('}') {
+            let line = self.line();
+            let name = self.word()?;
+            self.expect('=')?;
+            let value = self.value()?;
+            self.expect(';')?;
+            attributes.push((line, name, value));
+        }
+        Ok(attributes)
+    }
+}
+
+// a name usable as a Hugin identifier
+fn hugin_name(name: &str) -> String {
+    let mut ident: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    if !ident.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+        ident.insert(0, '_');
+    }
+    ident
+}
+
+fn quote(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+// the nested lists of the probabilities of a table, the node varying the fastest
+fn write_data(data: &mut String, table: ArrayViewD<f32>) {
+    data.push('(');
+    if table.ndim() == 1 {
+        for l in table.iter() {
+            write!(data, " {}", l.exp()).unwrap();
+        }
+        data.push(' ');
+    } else {
+        for sub in table.axis_iter(Axis(0)) {
+            write_data(data, sub);
+        }
+    }
+    data.push(')');
+}
+
+impl BayesNet {
+    /// Read a network in the NET format of Hugin, also used by GeNIe
+    ///
+    /// The nodes are named after the identifiers of the Hugin nodes, with their state names, and are
+    /// added in the order of declaration, except that the parents of a node are always added before
+    /// it. Their labels are kept in the `"label"` tag (see `BayesNet::tag`) and their positions as
+    /// layout hints. Only discrete chance nodes are supported, and the other attributes are ignored.
+    pub fn from_hugin_net(text: &str) -> Result<BayesNet, BifError> {
+        let mut parser = Parser {
+            tokens: tokenize(text)?,
+            pos: 0,
+        };
+        let mut variables: Vec<Variable> = Vec::new();
+        let mut labels = Vec::new();
+        let mut potentials = Vec::new();
+        while parser.pos < parser.tokens.len() {
+            let line = parser.line();
+            match parser.word()?.as_str() {
+                "net" => {
+                    parser.attributes()?;
+                }
+                keyword @ "node" | keyword @ "discrete" => {
+                    if keyword == "discrete" && parser.word()? != "node" {
+                        parser.pos -= 1;
+                        return parser.error("only chance nodes are supported".to_owned());
+                    }
+                    let name = parser.word()?;
+                    if variables.iter().any(|v| v.name == name) {
+                        return parser.error(format!("node \"{}\" is declared twice", name));
+                    }
+                    let mut states = None;
+                    let mut layout = None;
+                    let mut label = None;
+                    for (_, attribute, value) in parser.attributes()? {
+                        let mut values = Vec::new();
+                        value.flatten(&mut values);
+                        match attribute.as_str() {
+                            "states" => states = Some(values),
+                            "label" => label = values.pop(),
+                            "position" => {
+                                if let [x, y] = &values[..] {
+                                    if let (Ok(x), Ok(y)) = (x.parse(), y.parse()) {
+                                        layout = Some(NodeLayout { x, y, color: None });
+                                    }
+                                }
+                            }
+                            _ => {}
+                        }
+                    }
+                    let states = match states {
+                        Some(states) if !states.is_empty() => states,
+                        _ => {
+                            return Err(BifError::Syntax {
+                                line,
+                                message: format!("node \"{}\" has no states", name),
+                            })
+                        }
+                    };
+                    variables.push(Variable {
+                        name,
+                        line,
+                        states,
+                        layout,
+                    });
+                    labels.push(label);
+                }
+                "potential" => {
+                    parser.expect('(')?;
+                    let name = parser.word()?;
+                    let mut parents = Vec::new();
+                    if parser.eat('|') {
+                        while !parser.eat(')') {
+                            parents.push(parser.word()?);
+                        }
+                    } else {
+                        parser.expect(')')?;
+                    }
+                    let data = parser
+                        .attributes()?
+                        .into_iter()
+                        .find(|(_, attribute, _)| attribute == "data");
+                    potentials.push((line, name, parents, data));
+                }
+                other => {
+                    parser.pos -= 1;
+                    return parser.error(format!("unexpected \"{}\"", other));
+                }
+            }
+        }
+
+        let ids: HashMap<&str, usize> = variables
+            .iter()
+            .enumerate()
+            .map(|(i, v)| (v.name.as_str(), i))
+            .collect();
+        let find = |name: &str, line: usize| {
+            ids.get(name).copied().ok_or(BifError::UnknownVariable {
+                line,
+                name: name.to_owned(),
+            })
+        };
+        let mut tables = vec![None; variables.len()];
+        for (line, name, parents, data) in potentials {
+            let variable = find(&name, line)?;
+            let parents = parents
+                .iter()
+                .map(|p| find(p, line))
+                .collect::<Result<Vec<usize>, BifError>>()?;
+            if tables[variable].is_some() {
+                return Err(BifError::Syntax {
+                    line,
+                    message: format!("the potential of node \"{}\" is given twice", name),
+                });
+            }
+            let (data_line, data) = match data {
+                Some((data_line, _, data)) => (data_line, data),
+                None => return Err(BifError::MissingProbabilities(name)),
+            };
+            let mut words = Vec::new();
+            data.flatten(&mut words);
+            let values = words
+                .iter()
+                .map(|w| {
+                    w.parse::<f32>().map_err(|_| BifError::Syntax {
+                        line: data_line,
+                        message: format!("invalid probability \"{}\"", w),
+                    })
+                })
+                .collect::<Result<Vec<f32>, BifError>>()?;
+            let parent_sizes: Vec<usize> =
+                parents.iter().map(|&p| variables[p].states.len()).collect();
+            let size = variables[variable].states.len();
+            let expected = size * parent_sizes.iter().product::<usize>();
+            if values.len() != expected {
+                return Err(BifError::TableSize {
+                    line: data_line,
+                    variable: name,
+                    expected,
+                    found: values.len(),
+                });
+            }
+            let table = table_with_node_last(values, &parent_sizes, size);
+            tables[variable] = Some((parents, table));
+        }
+
+        let mut net = build_network(&variables, tables)?;
+        for (variable, label) in variables.iter().zip(labels) {
+            if let Some(label) = label {
+                let node = net.find_node(&variable.name).unwrap();
+                net.set_tag(node, "label", &label);
+            }
+        }
+        Ok(net)
+    }
+
+    /// Write the network in the NET format of Hugin, see `from_hugin_net`
+    ///
+    /// Unnamed nodes are called `node{id}` and unnamed states `s{value}`, and the characters of node
+    /// names that cannot appear in Hugin identifiers are replaced by `_`. The `"label"` tags and the
+    /// positions of the layout hints are written as the attributes of the nodes.
+    pub fn to_hugin_net(&self) -> String {
+        let name = |node: usize| {
+            self.node_name(node)
+                .map(hugin_name)
+                .unwrap_or_else(|| format!("node{}", node))
+        };
+        let mut net = String::from("net\n{\n}\n");
+        for node in 0..self.num_nodes() {
+            write!(net, "\nnode {}\n{{\n", name(node)).unwrap();
+            if let Some(label) = self.tag(node, "label") {
+                writeln!(net, "    label = {};", quote(label)).unwrap();
+            }
+            if let Some(layout) = self.layout(node) {
+                writeln!(net, "    position = ({} {});", layout.x, layout.y).unwrap();
+            }
+            let states: Vec<String> = match self.state_names(node) {
+                Some(names) => names.iter().map(|s| quote(s)).collect(),
+                None => (0..self.num_values(node))
+                    .map(|v| quote(&format!("s{}", v)))
+                    .collect(),
+            };
+            writeln!(net, "    states = ({});\n}}", states.join(" ")).unwrap();
+        }
+        for (node, data) in self.nodes.iter().enumerate() {
+            let parents: Vec<String> = data.parents.iter().map(|&(p, _)| name(p)).collect();
+            if parents.is_empty() {
+                write!(net, "\npotential ( {} )\n{{\n", name(node)).unwrap();
+            } else {
+                write!(
+                    net,
+                    "\npotential ( {} | {} )\n{{\n",
+                    name(node),
+                    parents.join(" ")
+                )
+                .unwrap();
+            }
+            // move the axis of the node last, so that it varies the fastest
+            let log_probas = data.log_probas.dense();
+            let mut table = log_probas.view();
+            for axis in 0..parents.len() {
+                table.swap_axes(axis, axis + 1);
+            }
+            let mut values = String::new();
+            write_data(&mut values, table);
+            writeln!(net, "    data = {};\n}}", values).unwrap();
+        }
+        net
+    }
+}
@@ -0,0 +1,110 @@
+use crate::{BayesNet, LogProbVector};
+use ndarray::Array1;
+use rand::Rng;
+
+/// The result of `BayesNet::importance_refinement`
+#[derive(Debug, Clone)]
+pub struct ImportanceEstimate {
+    /// The importance sampling estimate of the posterior marginal of each node
+    pub marginals: Vec<LogProbVector>,
+    /// The effective sample size of the weighted samples, between `0` and the number of samples
+    ///
+    /// It is close to the number of samples when the beliefs are close to the true posterior, and
+    /// small when a few samples carry most of the weight, in which case the marginals are unreliable.
+    pub effective_sample_size: f32,
+    /// Configurations of all the nodes resampled according to their weights, approximately drawn from
+    /// the posterior
+    pub samples: Vec<Vec<usize>>,
+}
+
+// draw a value from normalized probabilities
+fn draw<R: Rng + ?Sized>(rng: &mut R, probas: &[f64]) -> usize {
+    let u: f64 = rng.gen();
+    let mut acc = 0.0;
+    probas
+        .iter()
+        .position(|&p| {
+            acc += p;
+            u < acc
+        })
+        .unwrap_or_else(|| probas.iter().rposition(|&p| p > 0.0).unwrap_or(0))
+}
+
+impl BayesNet {
+    /// Correct the current beliefs by importance sampling of the posterior
+    ///
+    /// The `n_samples` configurations are drawn with each node independent from the others, following
+    /// its current belief, and weighted by the ratio between their probability under the model and
+    /// the evidence, and their probability under the beliefs. This is run after the Loopy Belief
+    /// Propagation has converged, and corrects its bias when the beliefs are close to the posterior.
+    ///
+    /// Values to which a belief gives a probability of zero are never drawn, so their probability
+    /// cannot be corrected. If no sample is compatible with the evidence, the marginals are the
+    /// current beliefs, the effective sample size is `0` and there are no resampled configurations.
+    pub fn importance_refinement<R: Rng + ?Sized>(
+        &self,
+        rng: &mut R,
+        n_samples: usize,
+    ) -> ImportanceEstimate {
+        assert!(
+            n_samples > 0,
+            "Importance sampling needs at least one sample"
+        );
+        let proposals: Vec<Vec<f64>> = self
+            .beliefs()
+            .iter()
+            .map(|b| b.as_probabilities().iter().map(|&p| f64::from(p)).collect())
+            .collect();
+        let evidence: Vec<LogProbVector> = self.nodes.iter().map(|n| n.evidence_vec()).collect();
+
+        let mut samples = Vec::with_capacity(n_samples);
+        let mut log_weights = Vec::with_capacity(n_samples);
+        for _ in 0..n_samples {
+            let sample: Vec<usize> = proposals.iter().map(|q| draw(rng, q)).collect();
+            let log_proposal: f64 = sample.iter().zip(&proposals).map(|(&v, q)| q[v].ln()).sum();
+            let log_evidence: f64 = sample
+                .iter()
+                .zip(&evidence)
+                .map(|(&v, e)| f64::from(e.log_probabilities()[v]))
+                .sum();
+            let log_joint = f64::from(self.log_joint_probability(&sample));
+            log_weights.push(log_joint + log_evidence - log_proposal);
+            samples.push(sample);
+        }
+
+        let max = log_weights
+            .iter()
+            .copied()
+            .fold(f64::NEG_INFINITY, f64::max);
+        if !max.is_finite() {
+            return ImportanceEstimate {
+                marginals: self.beliefs(),
+                effective_sample_size: 0.0,
+                samples: Vec::new(),
+            };
+        }
+        let weights: Vec<f64> = log_weights.iter().map(|&l| (l - max).exp()).collect();
+        let total: f64 = weights.iter().sum();
+        let weights: Vec<f64> = weights.iter().map(|w| w / total).collect();
+        let effective_sample_size = 1.0 / weights.iter().map(|w| w * w).sum::<f64>();
+
+        let mut sums: Vec<Array1<f64>> = proposals.iter().map(|q| Array1::zeros(q.len())).collect();
+        for (sample, &w) in samples.iter().zip(&weights) {
+            for (sum, &v) in sums.iter_mut().zip(sample) {
+                sum[v] += w;
+            }
+        }
+        let marginals = sums
+            .iter()
+            .map(|sum| LogProbVector::from_log_probabilities(sum.mapv(|p| p.ln() as f32)))
+            .collect();
+        let resampled = (0..n_samples)
+            .map(|_| samples[draw(rng, &weights)].clone())
+            .collect();
+        ImportanceEstimate {
+            marginals,
+            effective_sample_size: effective_sample_size as f32,
+            samples: resampled,
+        }
+    }
+}
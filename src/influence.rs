@@ -0,0 +1,209 @@
+use crate::learning::Query;
+use crate::table::Table;
+use crate::BayesNet;
+use ndarray::{Array3, ArrayView1, Axis, Ix3};
+
+/// How strongly a parent influences a child, see `BayesNet::influence_strength`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InfluenceStrength {
+    /// Largest Kullback-Leibler divergence between the distributions of the child for two values of
+    /// the parent, the other parents being fixed, in nats
+    pub max_kl: f32,
+    /// Average of these divergences over all the pairs of values of the parent and all the
+    /// configurations of the other parents
+    pub mean_kl: f32,
+}
+
+/// The result of `BayesNet::simplify`
+#[derive(Debug, Clone)]
+pub struct Simplification {
+    /// The simplified network
+    pub net: BayesNet,
+    /// The removed edges, as `(parent, child)`
+    pub removed_edges: Vec<(usize, usize)>,
+    /// For each validation query, the total variation distance between the posteriors of its target
+    /// in the original and in the simplified network
+    pub query_shifts: Vec<f32>,
+}
+
+impl Simplification {
+    /// The largest shift of the posterior of a validation query
+    pub fn max_shift(&self) -> f32 {
+        self.query_shifts.iter().cloned().fold(0.0, f32::max)
+    }
+}
+
+fn kl_divergence(log_p: ArrayView1<f32>, log_q: ArrayView1<f32>) -> f32 {
+    log_p
+        .iter()
+        .zip(log_q.iter())
+        .filter(|&(&p, _)| p > f32::NEG_INFINITY)
+        .map(|(&p, &q)| p.exp() * (p - q))
+        .sum::<f32>()
+        .max(0.0)
+}
+
+impl BayesNet {
+    /// Measure how strongly a parent influences its child, from the probability table of the child
+    ///
+    /// For each configuration of the other parents, the distributions of the child given each value of
+    /// `parent` are compared with the Kullback-Leibler divergence. A divergence of `0` everywhere means the
+    /// edge is useless, and small values mean the model could be simplified by removing the edge. The
+    /// divergences are infinite if a value of the child is possible for a value of the parent and
+    /// impossible for another.
+    ///
+    /// Panics if `parent` is not a parent of `child`.
+    pub fn influence_strength(&self, parent: usize, child: usize) -> InfluenceStrength {
+        let table = self.table_by_parent(parent, child);
+        let n_parent = table.shape()[1];
+
+        let mut max_kl = 0.0f32;
+        let mut sum = 0.0f32;
+        let mut count = 0usize;
+        for config in table.axis_iter(Axis(2)) {
+            for a in 0..n_parent {
+                for b in (0..n_parent).filter(|&b| b != a) {
+                    let kl = kl_divergence(config.column(a), config.column(b));
+                    max_kl = max_kl.max(kl);
+                    sum += kl;
+                    count += 1;
+                }
+            }
+        }
+        InfluenceStrength {
+            max_kl,
+            mean_kl: if count > 0 { sum / count as f32 } else { 0.0 },
+        }
+    }
+
+    // the log-probability table of `child` arranged as (child values, parent values, configurations of
+    // the other parents), the other parents being in order with the last one varying the fastest
+    pub(crate) fn table_by_parent(&self, parent: usize, child: usize) -> Array3<f32> {
+        let node = &self.nodes[child];
+        let position = node
+            .parents
+            .iter()
+            .position(|&(p, _)| p == parent)
+            .unwrap_or_else(|| {
+                panic!(
+                    "{} is not a parent of {}",
+                    self.node_ref(parent),
+                    self.node_ref(child)
+                )
+            });
+        let shape = node.log_probas.shape();
+        let (n_values, n_parent) = (shape[0], shape[position + 1]);
+        let mut order: Vec<usize> = vec![0, position + 1];
+        order.extend((1..shape.len()).filter(|&axis| axis != position + 1));
+        let log_probas = node.log_probas.dense();
+        let table = log_probas.view().permuted_axes(order);
+        let table = table.as_standard_layout();
+        let n_configs = table.len() / (n_values * n_parent).max(1);
+        table
+            .into_shape((n_values, n_parent, n_configs))
+            .unwrap()
+            .into_dimensionality::<Ix3>()
+            .unwrap()
+            .to_owned()
+    }
+
+    /// The strength of influence of every edge, as `(parent, child, strength)`
+    ///
+    /// Edges are listed by child, and for each child in the order of its parents.
+    pub fn influence_strengths(&self) -> Vec<(usize, usize, InfluenceStrength)> {
+        (0..self.nodes.len())
+            .flat_map(|child| {
+                self.parents(child)
+                    .into_iter()
+                    .map(move |parent| (parent, child, self.influence_strength(parent, child)))
+            })
+            .collect()
+    }
+
+    /// Export the graph of the network in the DOT format, with edge thickness showing influence strength
+    ///
+    /// This is the same as `to_dot`, except that each edge gets a `penwidth` from `1` (no influence) to
+    /// `5` (deterministic influence), growing with the `max_kl` of its `influence_strength`.
+    pub fn to_dot_with_influence(&self) -> String {
+        self.dot_with_edge_attributes(|parent, child| {
+            let strength = self.influence_strength(parent, child);
+            let width = 1.0 + 4.0 * (1.0 - (-strength.max_kl).exp());
+            Some(format!("penwidth={:.2}", width))
+        })
+    }
+
+    /// Simplify the network by removing the edges with a weak influence
+    ///
+    /// Every edge whose `influence_strength` has a `max_kl` below `threshold` is removed, and the parent is
+    /// summed out of the probability table of the child, weighted by its prior marginal (see
+    /// `prior_marginals`). Node ids are kept, as well as names and evidence, but the CPT trees, softmax
+    /// weights, aggregates and Dirichlet concentrations of the modified nodes are dropped.
+    ///
+    /// To estimate the impact of the simplification, each validation query is evaluated on both networks
+    /// with `iterations` steps of the Loopy Belief Propagation, and the shift of its posterior is reported.
+    pub fn simplify(
+        &self,
+        threshold: f32,
+        validation: &[Query],
+        iterations: usize,
+    ) -> Simplification {
+        let removed_edges: Vec<(usize, usize)> = self
+            .influence_strengths()
+            .into_iter()
+            .filter(|&(_, _, strength)| strength.max_kl < threshold)
+            .map(|(parent, child, _)| (parent, child))
+            .collect();
+        let priors = self.prior_marginals().to_vec();
+        let mut net = self.clone();
+        for &(parent, child) in &removed_edges {
+            net.remove_edge(parent, child, priors[parent].log_probabilities());
+        }
+        net.reset_state();
+
+        let query_shifts = validation
+            .iter()
+            .map(|query| {
+                let posterior = |net: &BayesNet| {
+                    let mut net = net.clone();
+                    net.set_evidence(&query.evidence);
+                    net.reset_state();
+                    for _ in 0..iterations {
+                        net.step();
+                    }
+                    net.beliefs()[query.target].as_probabilities()
+                };
+                let (before, after) = (posterior(self), posterior(&net));
+                0.5 * before
+                    .iter()
+                    .zip(after.iter())
+                    .map(|(a, b)| (a - b).abs())
+                    .sum::<f32>()
+            })
+            .collect();
+
+        Simplification {
+            net,
+            removed_edges,
+            query_shifts,
+        }
+    }
+
+    // remove an edge, summing the parent out of the table of the child with the given weights
+    fn remove_edge(&mut self, parent: usize, child: usize, log_weights: ArrayView1<f32>) {
+        let position = self.nodes[child]
+            .parents
+            .iter()
+            .position(|&(p, _)| p == parent)
+            .expect("the edge to remove does not exist");
+        let table = crate::math::log_contract(
+            self.nodes[child].log_probas.dense().view(),
+            log_weights,
+            Axis(position + 1),
+        );
+        self.nodes[child].parents.remove(position);
+        self.nodes[child].dirichlet = None;
+        let table = Table::Dense(self.tables.intern(table));
+        self.set_table(child, table);
+        self.nodes[parent].children.retain(|&(c, _)| c != child);
+    }
+}
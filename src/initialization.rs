@@ -0,0 +1,107 @@
+use crate::{BayesNet, LogProbVector};
+use ndarray::Axis;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+/// How the messages are initialized by `BayesNet::reset_state_with`
+#[derive(Debug, Clone)]
+pub enum MessageInit {
+    /// All messages are uniform, as done by `reset_state`
+    Uniform,
+    /// The messages from parents to children are the prior marginals of the parents, computed by a
+    /// single ancestral pass that ignores the loops and the evidence, the other messages are uniform
+    PriorMarginals,
+    /// The messages from parents to children are the given distributions of the parents (one per node
+    /// of the network, typically the beliefs of a previous inference), the other messages are uniform
+    Beliefs(Vec<LogProbVector>),
+    /// Every message is random, its log-probabilities being drawn uniformly in `[-noise, noise]`
+    Random {
+        /// Seed of the random number generator
+        seed: u64,
+        /// Amplitude of the random log-probabilities
+        noise: f32,
+    },
+}
+
+impl BayesNet {
+    /// Resets the internal state of the inference algorithm, initializing the messages as specified
+    ///
+    /// Loopy Belief Propagation can reach different fixed points depending on the initial messages. The
+    /// evidence is kept, and the step counter is reset as by `reset_state`.
+    pub fn reset_state_with(&mut self, init: MessageInit) {
+        self.reset_state();
+        match init {
+            MessageInit::Uniform => {}
+            MessageInit::PriorMarginals => {
+                let marginals = self.prior_marginals().to_vec();
+                self.set_pi_messages(&marginals);
+            }
+            MessageInit::Beliefs(beliefs) => {
+                assert!(
+                    beliefs.len() == self.nodes.len(),
+                    "Got {} beliefs for {} nodes",
+                    beliefs.len(),
+                    self.nodes.len()
+                );
+                for (id, belief) in beliefs.iter().enumerate() {
+                    assert!(
+                        belief.log_probabilities().len() == self.num_values(id),
+                        "Belief of {} has {} values instead of {}",
+                        self.node_ref(id),
+                        belief.log_probabilities().len(),
+                        self.num_values(id)
+                    );
+                }
+                self.set_pi_messages(&beliefs);
+            }
+            MessageInit::Random { seed, noise } => {
+                let mut rng = StdRng::seed_from_u64(seed);
+                self.randomize_messages(&mut rng, noise);
+            }
+        }
+    }
+
+    /// Prior marginals of all the nodes, without any evidence
+    ///
+    /// They are computed by a single ancestral pass, which assumes the parents of each node to be
+    /// independent: they are exact for networks without loops, and otherwise an approximation that is a
+    /// good starting point for the propagation (see `MessageInit::PriorMarginals`). The result is cached
+    /// on the network until one of its probability tables changes.
+    pub fn prior_marginals(&self) -> &[LogProbVector] {
+        self.priors.get_or_init(|| self.forward_pass())
+    }
+
+    // prior marginals, assuming the parents of each node independent
+    fn forward_pass(&self) -> Vec<LogProbVector> {
+        let mut marginals: Vec<LogProbVector> = Vec::with_capacity(self.nodes.len());
+        // parents always have smaller ids than their children
+        for node in &self.nodes {
+            let marginal = node.parents.iter().enumerate().rev().fold(
+                node.log_probas.dense().into_owned(),
+                |acc, (axid, &(p, _))| {
+                    crate::math::log_contract(
+                        acc.view(),
+                        marginals[p].log_probabilities(),
+                        Axis(axid + 1),
+                    )
+                },
+            );
+            let len = marginal.len();
+            let mut marginal =
+                LogProbVector::from_log_probabilities(marginal.into_shape(len).unwrap());
+            marginal.renormalize();
+            marginals.push(marginal);
+        }
+        marginals
+    }
+
+    fn set_pi_messages(&mut self, distributions: &[LogProbVector]) {
+        for node in &mut self.nodes {
+            for (parent, msg) in &mut node.parents {
+                *msg = distributions[*parent].clone();
+            }
+            node.lambda = None;
+            node.pi = None;
+        }
+    }
+}
@@ -0,0 +1,547 @@
+use crate::bif::{build_network, table_with_node_last, Variable};
+use crate::{BayesNet, BifError, NodeLayout};
+use ndarray::{ArrayViewD, Axis};
+use std::collections::HashMap;
+use std::fmt::Write;
+
+// the version of the schema written by `to_json`
+const VERSION: f64 = 1.0;
+
+// a JSON value, with the line where it starts
+pub(crate) struct Json {
+    pub(crate) line: usize,
+    pub(crate) value: Value,
+}
+
+pub(crate) enum Value {
+    // `true`, `false` or `null`, which the schema does not use
+    Literal,
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    pub(crate) fn error<T>(&self, message: String) -> Result<T, BifError> {
+        Err(BifError::Syntax {
+            line: self.line,
+            message,
+        })
+    }
+
+    pub(crate) fn field(&self, key: &str) -> Option<&Json> {
+        match self.value {
+            Value::Object(ref fields) => fields.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn required(&self, key: &str, what: &str) -> Result<&Json, BifError> {
+        match self.field(key) {
+            Some(value) => Ok(value),
+            None => self.error(format!("{} has no \"{}\"", what, key)),
+        }
+    }
+
+    pub(crate) fn as_str(&self, what: &str) -> Result<&str, BifError> {
+        match self.value {
+            Value::String(ref s) => Ok(s),
+            _ => self.error(format!("{} must be a string", what)),
+        }
+    }
+
+    pub(crate) fn as_f32(&self, what: &str) -> Result<f32, BifError> {
+        match self.value {
+            Value::Number(n) => Ok(n as f32),
+            _ => self.error(format!("{} must be a number", what)),
+        }
+    }
+
+    // a non-negative integer, exact up to 2^53
+    pub(crate) fn as_integer(&self, what: &str) -> Result<u64, BifError> {
+        match self.value {
+            Value::Number(n) if n >= 0.0 && n.fract() == 0.0 && n <= (1u64 << 53) as f64 => {
+                Ok(n as u64)
+            }
+            _ => self.error(format!("{} must be a non-negative integer", what)),
+        }
+    }
+
+    pub(crate) fn as_array(&self, what: &str) -> Result<&[Json], BifError> {
+        match self.value {
+            Value::Array(ref items) => Ok(items),
+            _ => self.error(format!("{} must be an array", what)),
+        }
+    }
+
+    fn strings(&self, what: &str) -> Result<Vec<String>, BifError> {
+        self.as_array(what)?
+            .iter()
+            .map(|item| item.as_str(what).map(str::to_owned))
+            .collect()
+    }
+
+    // the numbers of nested arrays, in order
+    fn flatten(&self, values: &mut Vec<f32>) -> Result<(), BifError> {
+        match self.value {
+            Value::Array(ref items) => items.iter().try_for_each(|item| item.flatten(values)),
+            _ => {
+                values.push(self.as_f32("a probability")?);
+                Ok(())
+            }
+        }
+    }
+}
+
+struct Reader {
+    chars: Vec<char>,
+    pos: usize,
+    line: usize,
+}
+
+impl Reader {
+    fn error<T>(&self, message: &str) -> Result<T, BifError> {
+        Err(BifError::Syntax {
+            line: self.line,
+            message: message.to_owned(),
+        })
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(&c) = self.chars.get(self.pos) {
+            if !c.is_whitespace() {
+                break;
+            }
+            if c == '\n' {
+                self.line += 1;
+            }
+            self.pos += 1;
+        }
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.skip_whitespace();
+        self.chars.get(self.pos).copied()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), BifError> {
+        if self.eat(c) {
+            Ok(())
+        } else {
+            self.error(&format!("expected '{}'", c))
+        }
+    }
+
+    fn keyword(&mut self, word: &str) -> Result<Value, BifError> {
+        let end = self.pos + word.len();
+        if end <= self.chars.len() && self.chars[self.pos..end].iter().copied().eq(word.chars()) {
+            self.pos = end;
+            Ok(Value::Literal)
+        } else {
+            self.error("invalid value")
+        }
+    }
+
+    fn string(&mut self) -> Result<String, BifError> {
+        self.expect('"')?;
+        let mut string = String::new();
+        loop {
+            let c = match self.chars.get(self.pos) {
+                Some(&c) => c,
+                None => return self.error("unterminated string"),
+            };
+            self.pos += 1;
+            match c {
+                '"' => return Ok(string),
+                '\n' => return self.error("unterminated string"),
+                '\\' => {
+                    let escaped = self.chars.get(self.pos).copied();
+                    self.pos += 1;
+                    string.push(match escaped {
+                        Some('n') => '\n',
+                        Some('t') => '\t',
+                        Some('r') => '\r',
+                        Some('b') => '\u{8}',
+                        Some('f') => '\u{c}',
+                        Some('u') => {
+                            let hex: String = self.chars.iter().skip(self.pos).take(4).collect();
+                            self.pos += 4;
+                            match u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32) {
+                                Some(c) => c,
+                                None => return self.error("invalid unicode escape"),
+                            }
+                        }
+                        Some(c @ '"') | Some(c @ '\\') | Some(c @ '/') => c,
+                        _ => return self.error("invalid escape"),
+                    });
+                }
+                c => string.push(c),
+            }
+        }
+    }
+
+    fn number(&mut self) -> Result<Value, BifError> {
+        let start = self.pos;
+        while self
+            .chars
+            .get(self.pos)
+            .is_some_and(|&c| c.is_ascii_digit() || "+-.eE".contains(c))
+        {
+            self.pos += 1;
+        }
+        let text: String = self.chars[start..self.pos].iter().collect();
+        match text.parse() {
+            Ok(n) => Ok(Value::Number(n)),
+            Err(_) => self.error(&format!("invalid number \"{}\"", text)),
+        }
+    }
+
+    fn value(&mut self) -> Result<Json, BifError> {
+        let c = match self.peek() {
+            Some(c) => c,
+            None => return self.error("unexpected end of file"),
+        };
+        let line = self.line;
+        let value = match c {
+            '{' => {
+                self.pos += 1;
+                let mut fields = Vec::new();
+                if !self.eat('}') {
+                    loop {
+                        if self.peek() != Some('"') {
+                            return self.error("expected a key");
+                        }
+                        let key = self.string()?;
+                        self.expect(':')?;
+                        fields.push((key, self.value()?));
+                        if self.eat('}') {
+                            break;
+                        }
+                        self.expect(',')?;
+                    }
+                }
+                Value::Object(fields)
+            }
+            '[' => {
+                self.pos += 1;
+                let mut items = Vec::new();
+                if !self.eat(']') {
+                    loop {
+                        items.push(self.value()?);
+                        if self.eat(']') {
+                            break;
+                        }
+                        self.expect(',')?;
+                    }
+                }
+                Value::Array(items)
+            }
+            '"' => Value::String(self.string()?),
+            't' => self.keyword("true")?,
+            'f' => self.keyword("false")?,
+            'n' => self.keyword("null")?,
+            _ => self.number()?,
+        };
+        Ok(Json { line, value })
+    }
+}
+
+// parse a whole document, whose first line is `line`
+pub(crate) fn parse(text: &str, line: usize) -> Result<Json, BifError> {
+    let mut reader = Reader {
+        chars: text.chars().collect(),
+        pos: 0,
+        line,
+    };
+    let document = reader.value()?;
+    if reader.peek().is_some() {
+        return reader.error("unexpected data after the document");
+    }
+    Ok(document)
+}
+
+pub(crate) fn quote(text: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            '\r' => quoted.push_str("\\r"),
+            c if (c as u32) < 0x20 => write!(quoted, "\\u{:04x}", c as u32).unwrap(),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+// the nested arrays of the probabilities of a table whose last axis is the node, one row per line
+fn write_probabilities(json: &mut String, table: ArrayViewD<f32>, indent: usize) {
+    if table.ndim() == 1 {
+        let values: Vec<String> = table.iter().map(|l| l.exp().to_string()).collect();
+        write!(json, "[{}]", values.join(", ")).unwrap();
+        return;
+    }
+    json.push_str("[\n");
+    let count = table.len_of(Axis(0));
+    for (i, sub) in table.axis_iter(Axis(0)).enumerate() {
+        json.push_str(&" ".repeat(indent + 2));
+        write_probabilities(json, sub, indent + 2);
+        json.push_str(if i + 1 < count { ",\n" } else { "\n" });
+    }
+    json.push_str(&" ".repeat(indent));
+    json.push(']');
+}
+
+impl BayesNet {
+    /// Read a network in the JSON schema of this crate, see `to_json`
+    ///
+    /// The document is an object with a `"nodes"` array, and an optional `"version"` which must be `1`.
+    /// Each node is an object with:
+    ///
+    /// - `"name"`: the name of the node, unique in the network,
+    /// - `"states"`: the names of its values,
+    /// - `"parents"`: the names of its parents, which may be omitted for nodes without parents,
+    /// - `"probabilities"`: its table, as nested arrays indexed by the values of the parents in order,
+    ///   then by the value of the node, so that each innermost array is the distribution of the node
+    ///   for a combination of values of the parents (the rows do not need to be normalized),
+    /// - `"layout"` (optional): an object with `"x"`, `"y"` and an optional `"color"`, see `NodeLayout`,
+    /// - `"tags"` (optional): an object of string tags, see `BayesNet::set_tag`,
+    /// - `"labels"` (optional): an object of display names by locale, each an object with an optional
+    ///   `"name"` and optional `"states"`, see `BayesNet::set_localized_name`.
+    ///
+    /// The nodes are added in the order of the array, except that the parents of a node are always
+    /// added before it. Other fields are ignored.
+    pub fn from_json(text: &str) -> Result<BayesNet, BifError> {
+        let document = parse(text, 1)?;
+        if let Some(version) = document.field("version") {
+            if version.as_f32("the version")? != VERSION as f32 {
+                return version.error("unsupported version".to_owned());
+            }
+        }
+        let nodes = document
+            .required("nodes", "the document")?
+            .as_array("the nodes")?;
+
+        let mut variables: Vec<Variable> = Vec::new();
+        let mut tags = Vec::new();
+        let mut labels = Vec::new();
+        for node in nodes {
+            let name = node.required("name", "a node")?.as_str("the name")?;
+            if variables.iter().any(|v| v.name == name) {
+                return node.error(format!("node \"{}\" is declared twice", name));
+            }
+            let what = format!("node \"{}\"", name);
+            let states = node.required("states", &what)?.strings("the states")?;
+            if states.is_empty() {
+                return node.error(format!("node \"{}\" has no states", name));
+            }
+            let layout = match node.field("layout") {
+                Some(layout) => Some(NodeLayout {
+                    x: layout.required("x", "the layout")?.as_f32("x")?,
+                    y: layout.required("y", "the layout")?.as_f32("y")?,
+                    color: match layout.field("color") {
+                        Some(color) => Some(color.as_str("the color")?.to_owned()),
+                        None => None,
+                    },
+                }),
+                None => None,
+            };
+            let node_tags = match node.field("tags").map(|t| &t.value) {
+                Some(Value::Object(fields)) => fields
+                    .iter()
+                    .map(|(k, v)| Ok((k.clone(), v.as_str("a tag")?.to_owned())))
+                    .collect::<Result<Vec<_>, BifError>>()?,
+                Some(_) => return node.error("the tags must be an object".to_owned()),
+                None => Vec::new(),
+            };
+            let node_labels = match node.field("labels").map(|l| &l.value) {
+                Some(Value::Object(locales)) => locales
+                    .iter()
+                    .map(|(locale, label)| {
+                        let label_name = match label.field("name") {
+                            Some(name) => Some(name.as_str("a label")?.to_owned()),
+                            None => None,
+                        };
+                        let label_states = match label.field("states") {
+                            Some(names) => {
+                                let names = names.strings("the state labels")?;
+                                if names.len() != states.len() {
+                                    return label.error(format!(
+                                        "node \"{}\" has {} states but {} labels in locale \"{}\"",
+                                        name,
+                                        states.len(),
+                                        names.len(),
+                                        locale
+                                    ));
+                                }
+                                Some(names)
+                            }
+                            None => None,
+                        };
+                        Ok((locale.clone(), label_name, label_states))
+                    })
+                    .collect::<Result<Vec<_>, BifError>>()?,
+                Some(_) => return node.error("the labels must be an object".to_owned()),
+                None => Vec::new(),
+            };
+            variables.push(Variable {
+                name: name.to_owned(),
+                line: node.line,
+                states,
+                layout,
+            });
+            tags.push(node_tags);
+            labels.push(node_labels);
+        }
+
+        let ids: HashMap<&str, usize> = variables
+            .iter()
+            .enumerate()
+            .map(|(i, v)| (v.name.as_str(), i))
+            .collect();
+        let mut tables = Vec::with_capacity(nodes.len());
+        for (node, variable) in nodes.iter().zip(&variables) {
+            let parents = match node.field("parents") {
+                Some(parents) => parents
+                    .as_array("the parents")?
+                    .iter()
+                    .map(|p| {
+                        let name = p.as_str("a parent")?;
+                        ids.get(name).copied().ok_or(BifError::UnknownVariable {
+                            line: p.line,
+                            name: name.to_owned(),
+                        })
+                    })
+                    .collect::<Result<Vec<usize>, BifError>>()?,
+                None => Vec::new(),
+            };
+            let what = format!("node \"{}\"", variable.name);
+            let probabilities = node.required("probabilities", &what)?;
+            let mut values = Vec::new();
+            probabilities.flatten(&mut values)?;
+            let parent_sizes: Vec<usize> =
+                parents.iter().map(|&p| variables[p].states.len()).collect();
+            let size = variable.states.len();
+            let expected = size * parent_sizes.iter().product::<usize>();
+            if values.len() != expected {
+                return Err(BifError::TableSize {
+                    line: probabilities.line,
+                    variable: variable.name.clone(),
+                    expected,
+                    found: values.len(),
+                });
+            }
+            let table = table_with_node_last(values, &parent_sizes, size);
+            tables.push(Some((parents, table)));
+        }
+
+        let mut net = build_network(&variables, tables)?;
+        for ((variable, tags), labels) in variables.iter().zip(tags).zip(labels) {
+            let id = net.find_node(&variable.name).unwrap();
+            for (key, value) in tags {
+                net.set_tag(id, &key, &value);
+            }
+            for (locale, name, states) in labels {
+                if let Some(name) = name {
+                    net.set_localized_name(id, &locale, &name);
+                }
+                if let Some(states) = states {
+                    net.set_localized_state_names(id, &locale, &states);
+                }
+            }
+        }
+        Ok(net)
+    }
+
+    /// Write the network in the JSON schema of this crate, see `from_json`
+    ///
+    /// Unnamed nodes are called `node{id}` and unnamed states `s{value}`. The layout hints, the tags
+    /// and the localized display names of the nodes are written when they have some.
+    pub fn to_json(&self) -> String {
+        let name = |node: usize| {
+            self.node_name(node)
+                .map(str::to_owned)
+                .unwrap_or_else(|| format!("node{}", node))
+        };
+        let mut json = format!("{{\n  \"version\": {},\n  \"nodes\": [", VERSION);
+        for (node, data) in self.nodes.iter().enumerate() {
+            json.push_str(if node == 0 { "\n" } else { ",\n" });
+            write!(json, "    {{\n      \"name\": {},\n", quote(&name(node))).unwrap();
+            let states: Vec<String> = match self.state_names(node) {
+                Some(names) => names.iter().map(|s| quote(s)).collect(),
+                None => (0..self.num_values(node))
+                    .map(|v| quote(&format!("s{}", v)))
+                    .collect(),
+            };
+            writeln!(json, "      \"states\": [{}],", states.join(", ")).unwrap();
+            let parents: Vec<String> = data.parents.iter().map(|&(p, _)| quote(&name(p))).collect();
+            writeln!(json, "      \"parents\": [{}],", parents.join(", ")).unwrap();
+            // move the axis of the node last, so that the innermost arrays are its distributions
+            let log_probas = data.log_probas.dense();
+            let mut table = log_probas.view();
+            for axis in 0..parents.len() {
+                table.swap_axes(axis, axis + 1);
+            }
+            json.push_str("      \"probabilities\": ");
+            write_probabilities(&mut json, table, 6);
+            if let Some(layout) = self.layout(node) {
+                write!(
+                    json,
+                    ",\n      \"layout\": {{\"x\": {}, \"y\": {}",
+                    layout.x, layout.y
+                )
+                .unwrap();
+                if let Some(ref color) = layout.color {
+                    write!(json, ", \"color\": {}", quote(color)).unwrap();
+                }
+                json.push('}');
+            }
+            if !data.tags.is_empty() {
+                let tags: Vec<String> = data
+                    .tags
+                    .iter()
+                    .map(|(k, v)| format!("{}: {}", quote(k), quote(v)))
+                    .collect();
+                write!(json, ",\n      \"tags\": {{{}}}", tags.join(", ")).unwrap();
+            }
+            if !data.localized.is_empty() {
+                let labels: Vec<String> = data
+                    .localized
+                    .iter()
+                    .map(|(locale, names)| {
+                        let mut fields = Vec::new();
+                        if let Some(ref name) = names.name {
+                            fields.push(format!("\"name\": {}", quote(name)));
+                        }
+                        if let Some(ref states) = names.states {
+                            let states: Vec<String> = states.iter().map(|s| quote(s)).collect();
+                            fields.push(format!("\"states\": [{}]", states.join(", ")));
+                        }
+                        format!("{}: {{{}}}", quote(locale), fields.join(", "))
+                    })
+                    .collect();
+                write!(json, ",\n      \"labels\": {{{}}}", labels.join(", ")).unwrap();
+            }
+            json.push_str("\n    }");
+        }
+        json.push_str(if self.nodes.is_empty() {
+            "]\n}\n"
+        } else {
+            "\n  ]\n}\n"
+        });
+        json
+    }
+}
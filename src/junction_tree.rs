@@ -0,0 +1,257 @@
+use crate::factor::{multiply, Factor};
+use crate::graph::structure;
+use crate::{BayesNet, LogProbVector};
+use std::collections::HashMap;
+
+/// A junction tree (or clique tree) of a network, for exact inference
+///
+/// The tree is built from the structure of the network: the moral graph is triangulated by
+/// eliminating greedily the node creating the smallest clique, and the maximal cliques are connected
+/// by a maximum spanning tree on the size of their intersections, which has the running intersection
+/// property. Its size only depends on the structure and the number of values of the nodes, so it can
+/// be reused for any evidence, and any tables of the same shape.
+#[derive(Debug, Clone)]
+pub struct JunctionTree {
+    cliques: Vec<Vec<usize>>,
+    edges: Vec<(usize, usize)>,
+    // clique holding the factors of each node
+    homes: Vec<usize>,
+    sizes: Vec<usize>,
+}
+
+impl JunctionTree {
+    /// Build a junction tree of a network
+    pub fn new(net: &BayesNet) -> JunctionTree {
+        let parents = structure(net);
+        let sizes: Vec<usize> = (0..net.num_nodes()).map(|n| net.num_values(n)).collect();
+        let n = parents.len();
+
+        // moral graph
+        let mut neighbours = vec![vec![false; n]; n];
+        for (child, ps) in parents.iter().enumerate() {
+            for (i, &p) in ps.iter().enumerate() {
+                neighbours[child][p] = true;
+                neighbours[p][child] = true;
+                for &q in &ps[i + 1..] {
+                    neighbours[p][q] = true;
+                    neighbours[q][p] = true;
+                }
+            }
+        }
+
+        // triangulation by elimination, collecting the cliques
+        let mut eliminated = vec![false; n];
+        let mut cliques: Vec<Vec<usize>> = Vec::new();
+        for _ in 0..n {
+            let clique_of = |v: usize| -> Vec<usize> {
+                (0..n)
+                    .filter(|&u| u == v || (neighbours[v][u] && !eliminated[u]))
+                    .collect()
+            };
+            let weight = |clique: &[usize]| -> usize { clique.iter().map(|&u| sizes[u]).product() };
+            let node = (0..n)
+                .filter(|&v| !eliminated[v])
+                .min_by_key(|&v| weight(&clique_of(v)))
+                .unwrap();
+            let clique = clique_of(node);
+            for &u in &clique {
+                for &w in &clique {
+                    if u != w {
+                        neighbours[u][w] = true;
+                    }
+                }
+            }
+            eliminated[node] = true;
+            if !cliques
+                .iter()
+                .any(|other| clique.iter().all(|u| other.contains(u)))
+            {
+                cliques.retain(|other| !other.iter().all(|u| clique.contains(u)));
+                cliques.push(clique);
+            }
+        }
+
+        // maximum spanning tree on the size of the separators, by Kruskal's algorithm
+        let mut candidates: Vec<(usize, usize, usize)> = Vec::new();
+        for i in 0..cliques.len() {
+            for j in i + 1..cliques.len() {
+                let shared = cliques[i].iter().filter(|u| cliques[j].contains(u)).count();
+                if shared > 0 {
+                    candidates.push((shared, i, j));
+                }
+            }
+        }
+        candidates.sort_by_key(|&(shared, _, _)| std::cmp::Reverse(shared));
+        let mut tree_of: Vec<usize> = (0..cliques.len()).collect();
+        let mut edges = Vec::new();
+        for (_, i, j) in candidates {
+            let (ti, tj) = (tree_of[i], tree_of[j]);
+            if ti != tj {
+                tree_of
+                    .iter_mut()
+                    .filter(|t| **t == tj)
+                    .for_each(|t| *t = ti);
+                edges.push((i, j));
+            }
+        }
+
+        let homes = (0..n)
+            .map(|node| {
+                (0..cliques.len())
+                    .filter(|&c| {
+                        cliques[c].contains(&node)
+                            && parents[node].iter().all(|p| cliques[c].contains(p))
+                    })
+                    .min_by_key(|&c| cliques[c].len())
+                    .unwrap()
+            })
+            .collect();
+
+        JunctionTree {
+            cliques,
+            edges,
+            homes,
+            sizes,
+        }
+    }
+
+    /// The cliques of the tree, each being a sorted list of nodes
+    pub fn cliques(&self) -> &[Vec<usize>] {
+        &self.cliques
+    }
+
+    /// The edges of the tree, as pairs of indices in `cliques`
+    ///
+    /// Networks with several connected components give a forest, with one tree per component.
+    pub fn edges(&self) -> &[(usize, usize)] {
+        &self.edges
+    }
+
+    /// The width of the tree, the size of its largest clique minus one
+    pub fn width(&self) -> usize {
+        self.cliques.iter().map(|c| c.len()).max().unwrap_or(1) - 1
+    }
+
+    /// Number of entries of the largest clique table, which drives the cost of the inference
+    pub fn max_clique_states(&self) -> usize {
+        self.cliques
+            .iter()
+            .map(|c| c.iter().map(|&u| self.sizes[u]).product())
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Compute the exact beliefs of the nodes of a network, given its current evidence
+    ///
+    /// The tree must have been built from this network, or from a network with the same structure and
+    /// numbers of values. The potentials of the cliques are calibrated with the two passes of the
+    /// Shafer-Shenoy message passing, and the belief of each node is taken from the smallest clique
+    /// containing it. The internal state of the network is not used nor modified.
+    pub fn beliefs(&self, net: &BayesNet) -> Vec<LogProbVector> {
+        assert!(
+            net.num_nodes() == self.sizes.len()
+                && (0..net.num_nodes()).all(|n| net.num_values(n) == self.sizes[n]),
+            "The junction tree was not built for this network"
+        );
+        let sizes = &self.sizes;
+        let mut potentials: Vec<Factor> = vec![Factor::unit(); self.cliques.len()];
+        for node in 0..net.num_nodes() {
+            let home = self.homes[node];
+            for factor in &Factor::of_node(net, node) {
+                potentials[home] = potentials[home].product(factor, sizes);
+            }
+        }
+
+        let mut adjacent: Vec<Vec<usize>> = vec![Vec::new(); self.cliques.len()];
+        for &(i, j) in &self.edges {
+            adjacent[i].push(j);
+            adjacent[j].push(i);
+        }
+        // order the cliques from the roots, each after its parent in the tree
+        let mut order = Vec::with_capacity(self.cliques.len());
+        let mut parent = vec![usize::MAX; self.cliques.len()];
+        let mut visited = vec![false; self.cliques.len()];
+        for root in 0..self.cliques.len() {
+            if visited[root] {
+                continue;
+            }
+            visited[root] = true;
+            let mut stack = vec![root];
+            while let Some(c) = stack.pop() {
+                order.push(c);
+                for &d in &adjacent[c] {
+                    if !visited[d] {
+                        visited[d] = true;
+                        parent[d] = c;
+                        stack.push(d);
+                    }
+                }
+            }
+        }
+
+        // messages[(from, to)], from the leaves to the roots then back
+        let mut messages: HashMap<(usize, usize), Factor> = HashMap::new();
+        let send = |from: usize, to: usize, messages: &HashMap<(usize, usize), Factor>| {
+            let mut incoming = vec![potentials[from].clone()];
+            incoming.extend(
+                adjacent[from]
+                    .iter()
+                    .filter(|&&k| k != to)
+                    .map(|&k| messages[&(k, from)].clone()),
+            );
+            let separator: Vec<usize> = self.cliques[from]
+                .iter()
+                .cloned()
+                .filter(|u| self.cliques[to].contains(u))
+                .collect();
+            multiply(&incoming, sizes).unwrap().marginal(&separator)
+        };
+        for &c in order.iter().rev() {
+            if parent[c] != usize::MAX {
+                let message = send(c, parent[c], &messages);
+                messages.insert((c, parent[c]), message);
+            }
+        }
+        for &c in &order {
+            for &d in &adjacent[c] {
+                if d != parent[c] {
+                    let message = send(c, d, &messages);
+                    messages.insert((c, d), message);
+                }
+            }
+        }
+
+        (0..net.num_nodes())
+            .map(|node| {
+                let clique = (0..self.cliques.len())
+                    .filter(|&c| self.cliques[c].contains(&node))
+                    .min_by_key(|&c| self.cliques[c].len())
+                    .unwrap();
+                let mut incoming = vec![potentials[clique].clone()];
+                incoming.extend(
+                    adjacent[clique]
+                        .iter()
+                        .map(|&k| messages[&(k, clique)].clone()),
+                );
+                let marginal = multiply(&incoming, sizes).unwrap().marginal(&[node]);
+                let mut belief = LogProbVector::from_log_probabilities(
+                    marginal.table.into_shape(sizes[node]).unwrap(),
+                );
+                belief.renormalize();
+                belief
+            })
+            .collect()
+    }
+}
+
+impl BayesNet {
+    /// Compute the exact beliefs of the nodes given the current evidence, with a junction tree
+    ///
+    /// This builds a `JunctionTree` of the network and runs the inference on it. The result is the
+    /// same as `exact_beliefs`, but the tree is much faster when many beliefs are needed; build the
+    /// tree once with `JunctionTree::new` to reuse it across queries. The cost grows exponentially
+    /// with the width of the tree.
+    pub fn junction_tree_beliefs(&self) -> Vec<LogProbVector> {
+        JunctionTree::new(self).beliefs(self)
+    }
+}
@@ -0,0 +1,148 @@
+use crate::BayesNet;
+use std::fmt::Write;
+
+/// Hints about how to draw a node in a diagram of the network
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodeLayout {
+    /// Horizontal position of the node
+    pub x: f32,
+    /// Vertical position of the node
+    pub y: f32,
+    /// Color of the node, in any format understood by the drawing tool (such as `"red"` or `"#ff0000"`)
+    pub color: Option<String>,
+}
+
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+// parse the attribute list of a DOT statement, such as `label="a", pos="1,2!"`
+fn parse_attributes(list: &str) -> Vec<(String, String)> {
+    let mut attributes = Vec::new();
+    let mut chars = list.chars().peekable();
+    loop {
+        while let Some(&c) = chars.peek() {
+            if c.is_whitespace() || c == ',' || c == ';' {
+                chars.next();
+            } else {
+                break;
+            }
+        }
+        let key: String =
+            std::iter::from_fn(|| chars.next_if(|&c| c != '=' && !c.is_whitespace())).collect();
+        if key.is_empty() {
+            break;
+        }
+        while chars.next_if(|&c| c.is_whitespace() || c == '=').is_some() {}
+        let mut value = String::new();
+        if chars.next_if_eq(&'"').is_some() {
+            while let Some(c) = chars.next() {
+                match c {
+                    '\\' => value.extend(chars.next()),
+                    '"' => break,
+                    c => value.push(c),
+                }
+            }
+        } else {
+            value.extend(std::iter::from_fn(|| {
+                chars.next_if(|&c| c != ',' && c != ';' && !c.is_whitespace())
+            }));
+        }
+        attributes.push((key, value));
+    }
+    attributes
+}
+
+impl BayesNet {
+    /// Set (or remove) the layout hints of a node
+    pub fn set_layout(&mut self, node: usize, layout: Option<NodeLayout>) {
+        self.nodes[node].layout = layout;
+    }
+
+    /// The layout hints of a node, if it has any
+    pub fn layout(&self, node: usize) -> Option<&NodeLayout> {
+        self.nodes[node].layout.as_ref()
+    }
+
+    /// Export the graph of the network in the DOT format of Graphviz
+    ///
+    /// Node `i` is written as `n{i}`, labeled with its name if it has one. The layout hints are written
+    /// as the `pos` (pinned, in points) and `color` attributes of the nodes.
+    pub fn to_dot(&self) -> String {
+        self.dot_with_edge_attributes(|_, _| None)
+    }
+
+    // the DOT export, with optional attributes for the edges `parent -> child`
+    pub(crate) fn dot_with_edge_attributes<F>(&self, edge_attributes: F) -> String
+    where
+        F: Fn(usize, usize) -> Option<String>,
+    {
+        let mut dot = String::from("digraph {\n");
+        for (i, node) in self.nodes.iter().enumerate() {
+            let label = node.name.clone().unwrap_or_else(|| i.to_string());
+            write!(dot, "    n{} [label=\"{}\"", i, escape(&label)).unwrap();
+            if let Some(ref layout) = node.layout {
+                write!(dot, ", pos=\"{},{}!\"", layout.x, layout.y).unwrap();
+                if let Some(ref color) = layout.color {
+                    write!(dot, ", color=\"{}\"", escape(color)).unwrap();
+                }
+            }
+            dot.push_str("];\n");
+        }
+        for (i, node) in self.nodes.iter().enumerate() {
+            for &(parent, _) in &node.parents {
+                match edge_attributes(parent, i) {
+                    Some(attributes) => {
+                        writeln!(dot, "    n{} -> n{} [{}];", parent, i, attributes).unwrap()
+                    }
+                    None => writeln!(dot, "    n{} -> n{};", parent, i).unwrap(),
+                }
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Import layout hints from a DOT graph, such as one exported by `to_dot` and edited by a drawing tool
+    ///
+    /// Only the node statements of the form `n{i} [...]` with a `pos` attribute are considered, other
+    /// statements are ignored. Returns the ids of the nodes whose layout was updated.
+    pub fn import_dot_layout(&mut self, dot: &str) -> Vec<usize> {
+        let mut updated = Vec::new();
+        for line in dot.lines() {
+            let line = line.trim();
+            let (id, rest) = match line.strip_prefix('n').and_then(|l| l.split_once('[')) {
+                Some(split) => split,
+                None => continue,
+            };
+            let id = match id.trim().parse::<usize>() {
+                Ok(id) if id < self.nodes.len() => id,
+                _ => continue,
+            };
+            let attributes =
+                parse_attributes(rest.trim_end_matches(';').trim_end().trim_end_matches(']'));
+            let find = |key: &str| {
+                attributes
+                    .iter()
+                    .find(|(k, _)| k == key)
+                    .map(|(_, v)| v.clone())
+            };
+            let pos = find("pos").and_then(|pos| {
+                let mut coords = pos.trim_end_matches('!').split(',').map(str::parse::<f32>);
+                match (coords.next(), coords.next()) {
+                    (Some(Ok(x)), Some(Ok(y))) => Some((x, y)),
+                    _ => None,
+                }
+            });
+            if let Some((x, y)) = pos {
+                self.nodes[id].layout = Some(NodeLayout {
+                    x,
+                    y,
+                    color: find("color"),
+                });
+                updated.push(id);
+            }
+        }
+        updated
+    }
+}
@@ -0,0 +1,325 @@
+//! Learning the parameters of a network from data
+//!
+//! Complete datasets are given as a slice of records, each record being a `Vec<usize>` containing the
+//! observed value of every node of the network, indexed by node id. Partially observed datasets use
+//! `Vec<Option<usize>>` records instead, `None` marking unobserved values.
+
+use crate::BayesNet;
+use ndarray::{Array1, ArrayD, Axis, IxDyn};
+use rand::Rng;
+
+mod constraints;
+mod em;
+mod mixture;
+mod score;
+mod search;
+mod softmax;
+
+pub use self::constraints::{ConstraintViolation, StructureConstraints};
+pub use self::em::{em, em_with_restarts, log_likelihood, EmOptions, EmResult};
+pub use self::mixture::MixtureModel;
+pub use self::score::ScoreCriterion;
+pub use self::search::{hill_climb, HillClimbOptions, LearnedStructure};
+pub use self::softmax::fit_softmax;
+
+pub(crate) fn check_record(net: &BayesNet, record: &[usize], index: usize) {
+    assert!(
+        record.len() == net.num_nodes(),
+        "Record {} has {} values but the network has {} nodes",
+        index,
+        record.len(),
+        net.num_nodes()
+    );
+    for (node, &value) in record.iter().enumerate() {
+        assert!(
+            value < net.num_values(node),
+            "Record {} has value {} for node {}, which only has {} values",
+            index,
+            value,
+            node,
+            net.num_values(node)
+        );
+    }
+}
+
+/// Count the occurrences of each configuration of a node and its parents in the data
+///
+/// The returned array has the same shape as the probability table of the node.
+pub(crate) fn family_counts(net: &BayesNet, node: usize, data: &[Vec<usize>]) -> ArrayD<f32> {
+    let shape = net.nodes[node].log_probas.shape().to_vec();
+    let parents = net.parents(node);
+    let mut counts = ArrayD::zeros(IxDyn(&shape));
+    let mut index = vec![0; parents.len() + 1];
+    for record in data {
+        index[0] = record[node];
+        for (slot, &p) in index[1..].iter_mut().zip(parents.iter()) {
+            *slot = record[p];
+        }
+        counts[IxDyn(&index)] += 1.0;
+    }
+    counts
+}
+
+/// The log-probability table estimated from counts, uniform for the configurations of the parents
+/// without any count
+pub(crate) fn log_frequencies(counts: &ArrayD<f32>) -> ArrayD<f32> {
+    let mut counts = counts.clone();
+    for mut lane in counts.lanes_mut(Axis(0)) {
+        if lane.sum() <= 0.0 {
+            lane.fill(1.0);
+        }
+    }
+    counts.mapv(f32::ln)
+}
+
+/// Estimate the probability tables of a network from complete data
+///
+/// The structure of `net` (its nodes, their number of values and their parents) is kept, and the
+/// probability tables are replaced by the frequencies observed in `data`, after adding `pseudo_count`
+/// to every count (`0.0` gives the maximum-likelihood estimate, `1.0` is Laplace smoothing).
+/// Configurations of the parents that never occur in the data without smoothing get a uniform
+/// distribution.
+///
+/// If `pseudo_count` is strictly positive, the smoothed counts are also attached to each node as
+/// Dirichlet concentrations (see `BayesNet::belief_uncertainty`). Names and evidence are preserved,
+/// and the inference state is reset.
+///
+/// Panics if a record does not have exactly one valid value per node.
+pub fn fit_parameters(net: &BayesNet, data: &[Vec<usize>], pseudo_count: f32) -> BayesNet {
+    for (i, record) in data.iter().enumerate() {
+        check_record(net, record, i);
+    }
+    let mut fitted = net.clone();
+    for node in 0..net.num_nodes() {
+        let counts = family_counts(net, node, data) + pseudo_count;
+        fitted.replace_log_probas(node, log_frequencies(&counts));
+        fitted.nodes[node].dirichlet = if pseudo_count > 0.0 {
+            Some(counts)
+        } else {
+            None
+        };
+    }
+    fitted.reset_state();
+    fitted
+}
+
+/// Build a network of the given structure with probability tables estimated from complete data
+///
+/// `parents[i]` contains the parents of node `i` (see the `graph` module) and `cardinalities[i]` its
+/// number of values, node ids being the indices of the values in the records of `data`. The tables
+/// are estimated as by `fit_parameters`, with the same `pseudo_count`.
+///
+/// Panics if a node has a parent with a larger or equal id, as nodes are added in the order of their
+/// ids, or if a record does not have exactly one valid value per node.
+pub fn fit_structure(
+    parents: &[Vec<usize>],
+    cardinalities: &[usize],
+    data: &[Vec<usize>],
+    pseudo_count: f32,
+) -> BayesNet {
+    assert!(
+        parents.len() == cardinalities.len(),
+        "The structure has {} nodes but {} cardinalities are given",
+        parents.len(),
+        cardinalities.len()
+    );
+    let mut net = BayesNet::new();
+    for (node, node_parents) in parents.iter().enumerate() {
+        assert!(
+            node_parents.iter().all(|&p| p < node),
+            "Node {} has a parent with a larger id, the structure is not topologically ordered",
+            node
+        );
+        let shape: Vec<usize> = std::iter::once(cardinalities[node])
+            .chain(node_parents.iter().map(|&p| cardinalities[p]))
+            .collect();
+        net.add_node_from_log_probabilities(node_parents, ArrayD::zeros(IxDyn(&shape)));
+    }
+    fit_parameters(&net, data, pseudo_count)
+}
+
+/// A posterior query evaluated during a bootstrap
+#[derive(Debug, Clone)]
+pub struct Query {
+    /// Evidence to set on the network, as for `BayesNet::set_evidence`
+    pub evidence: Vec<(usize, usize)>,
+    /// The node whose posterior is computed
+    pub target: usize,
+}
+
+/// The posteriors obtained for a query over all the resamples of a bootstrap
+#[derive(Debug, Clone)]
+pub struct BootstrapResult {
+    /// Posterior probabilities of the target, one vector per resample
+    pub samples: Vec<Array1<f32>>,
+}
+
+impl BootstrapResult {
+    /// Mean posterior probability of each value of the target
+    pub fn mean(&self) -> Array1<f32> {
+        let n = self.samples.len().max(1) as f32;
+        self.samples
+            .iter()
+            .fold(Array1::zeros(self.n_values()), |acc, s| acc + s)
+            / n
+    }
+
+    /// Standard deviation of the posterior probability of each value of the target
+    pub fn std_dev(&self) -> Array1<f32> {
+        let mean = self.mean();
+        let n = self.samples.len().max(1) as f32;
+        let var = self
+            .samples
+            .iter()
+            .fold(Array1::zeros(self.n_values()), |acc, s| {
+                acc + (s - &mean).mapv(|d| d * d)
+            })
+            / n;
+        var.mapv(f32::sqrt)
+    }
+
+    /// Percentile interval of the posterior probability of each value of the target
+    ///
+    /// For example `interval(0.95)` gives, for each value, the 2.5% and 97.5% percentiles of its
+    /// posterior probability across resamples.
+    pub fn interval(&self, level: f32) -> Vec<(f32, f32)> {
+        let tail = (1.0 - level) / 2.0;
+        (0..self.n_values())
+            .map(|v| {
+                let mut values: Vec<f32> = self.samples.iter().map(|s| s[v]).collect();
+                values.sort_by(|a, b| a.partial_cmp(b).unwrap());
+                let pick = |q: f32| {
+                    let idx = (q * (values.len() - 1) as f32).round() as usize;
+                    values[idx]
+                };
+                (pick(tail), pick(1.0 - tail))
+            })
+            .collect()
+    }
+
+    fn n_values(&self) -> usize {
+        self.samples.first().map(|s| s.len()).unwrap_or(0)
+    }
+}
+
+/// Measure the variability of posteriors due to the finite size of the training data
+///
+/// For each of the `n_resamples` resamples of `data` (drawn with replacement, with the same size as
+/// `data`), the parameters of `net` are re-estimated with `fit_parameters`, and the given queries are
+/// evaluated by running the Loopy Belief Propagation for `iterations` steps.
+///
+/// Returns one `BootstrapResult` per query, in the same order.
+pub fn bootstrap<R: Rng + ?Sized>(
+    rng: &mut R,
+    net: &BayesNet,
+    data: &[Vec<usize>],
+    n_resamples: usize,
+    pseudo_count: f32,
+    queries: &[Query],
+    iterations: usize,
+) -> Vec<BootstrapResult> {
+    let mut results = vec![
+        BootstrapResult {
+            samples: Vec::with_capacity(n_resamples)
+        };
+        queries.len()
+    ];
+    if data.is_empty() {
+        return results;
+    }
+    for _ in 0..n_resamples {
+        let resample: Vec<Vec<usize>> = (0..data.len())
+            .map(|_| data[rng.gen_range(0..data.len())].clone())
+            .collect();
+        let mut fitted = fit_parameters(net, &resample, pseudo_count);
+        for (query, result) in queries.iter().zip(results.iter_mut()) {
+            fitted.reset_state();
+            fitted.set_evidence(&query.evidence);
+            for _ in 0..iterations {
+                fitted.step();
+            }
+            result
+                .samples
+                .push(fitted.beliefs()[query.target].as_probabilities());
+        }
+    }
+    results
+}
+
+/// How often an edge was found across the resamples of `bootstrap_structure`
+#[derive(Debug, Clone, PartialEq)]
+pub struct EdgeConfidence {
+    /// Source of the edge
+    pub from: usize,
+    /// Target of the edge
+    pub to: usize,
+    /// Fraction of the resamples in which the edge `from -> to` was learned
+    pub directed: f32,
+    /// Fraction of the resamples in which `from` and `to` were adjacent, in either direction
+    pub adjacent: f32,
+    /// Fraction of the resamples in which the edge `from -> to` was learned and was compelled
+    ///
+    /// See `graph::cpdag`: only compelled orientations are supported by the data, so an edge with a
+    /// high `directed` but low `compelled` confidence has an arbitrary orientation.
+    pub compelled: f32,
+}
+
+/// Measure the confidence in the edges produced by a structure learning algorithm
+///
+/// For each of the `n_resamples` resamples of `data` (drawn with replacement), `learn` is called to
+/// learn a structure, which it returns as parent lists (see the `graph` module). The edges found in
+/// at least one resample are returned, sorted by decreasing `directed` confidence.
+pub fn bootstrap_structure<R, F>(
+    rng: &mut R,
+    data: &[Vec<usize>],
+    n_resamples: usize,
+    mut learn: F,
+) -> Vec<EdgeConfidence>
+where
+    R: Rng + ?Sized,
+    F: FnMut(&[Vec<usize>]) -> Vec<Vec<usize>>,
+{
+    use crate::graph::{cpdag, EdgeOrientation};
+    use std::collections::HashMap;
+
+    // (directed count, compelled count) for each oriented edge
+    let mut counts: HashMap<(usize, usize), (usize, usize)> = HashMap::new();
+    if data.is_empty() || n_resamples == 0 {
+        return Vec::new();
+    }
+    for _ in 0..n_resamples {
+        let resample: Vec<Vec<usize>> = (0..data.len())
+            .map(|_| data[rng.gen_range(0..data.len())].clone())
+            .collect();
+        let structure = learn(&resample);
+        for (from, to, kind) in cpdag(&structure) {
+            let entry = counts.entry((from, to)).or_insert((0, 0));
+            entry.0 += 1;
+            if kind == EdgeOrientation::Compelled {
+                entry.1 += 1;
+            }
+        }
+    }
+
+    let n = n_resamples as f32;
+    let mut edges: Vec<EdgeConfidence> = counts
+        .iter()
+        .map(|(&(from, to), &(directed, compelled))| {
+            let reverse = counts.get(&(to, from)).map(|c| c.0).unwrap_or(0);
+            EdgeConfidence {
+                from,
+                to,
+                directed: directed as f32 / n,
+                adjacent: (directed + reverse) as f32 / n,
+                compelled: compelled as f32 / n,
+            }
+        })
+        .collect();
+    edges.sort_by(|a, b| {
+        b.directed
+            .partial_cmp(&a.directed)
+            .unwrap()
+            .then((a.from, a.to).cmp(&(b.from, b.to)))
+    });
+    edges
+}
@@ -0,0 +1,196 @@
+use std::collections::{BTreeSet, HashMap};
+use std::error::Error;
+use std::fmt;
+
+// whether there is a directed path from `from` to `to` in a structure given as parent lists
+fn reaches(parents: &[Vec<usize>], from: usize, to: usize) -> bool {
+    let mut visited = vec![false; parents.len()];
+    let mut stack = vec![to];
+    while let Some(node) = stack.pop() {
+        if node == from {
+            return true;
+        }
+        for &p in &parents[node] {
+            if !visited[p] {
+                visited[p] = true;
+                stack.push(p);
+            }
+        }
+    }
+    false
+}
+
+/// A way in which a structure does not satisfy some `StructureConstraints`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConstraintViolation {
+    /// The structure contains the forbidden edge `(from, to)`
+    ForbiddenEdge(usize, usize),
+    /// The structure lacks the required edge `(from, to)`
+    MissingRequiredEdge(usize, usize),
+    /// The edge `(from, to)` goes from a later tier to an earlier one
+    TierOrder(usize, usize),
+    /// The required edge `(from, to)` is part of a cycle of required edges
+    RequiredCycle(usize, usize),
+    /// A constraint refers to this node, which is not one of the variables
+    UnknownNode(usize),
+}
+
+impl fmt::Display for ConstraintViolation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ConstraintViolation::ForbiddenEdge(from, to) => {
+                write!(f, "edge {} -> {} is forbidden", from, to)
+            }
+            ConstraintViolation::MissingRequiredEdge(from, to) => {
+                write!(f, "edge {} -> {} is required but missing", from, to)
+            }
+            ConstraintViolation::TierOrder(from, to) => write!(
+                f,
+                "edge {} -> {} goes from a later tier to an earlier one",
+                from, to
+            ),
+            ConstraintViolation::RequiredCycle(from, to) => write!(
+                f,
+                "required edge {} -> {} is part of a cycle of required edges",
+                from, to
+            ),
+            ConstraintViolation::UnknownNode(node) => {
+                write!(f, "node {} is not one of the variables", node)
+            }
+        }
+    }
+}
+
+impl Error for ConstraintViolation {}
+
+/// Domain knowledge that a learned structure must respect
+///
+/// Constraints are expressed on node ids:
+///
+/// - required edges must be present in the learned structure,
+/// - forbidden edges must be absent from it,
+/// - tiers encode a temporal or causal ordering: a node can never be the parent of a node from an
+///   earlier tier. Nodes without a tier are unconstrained.
+#[derive(Debug, Clone, Default)]
+pub struct StructureConstraints {
+    required: BTreeSet<(usize, usize)>,
+    forbidden: BTreeSet<(usize, usize)>,
+    tiers: HashMap<usize, usize>,
+}
+
+impl StructureConstraints {
+    /// Create an empty set of constraints
+    pub fn new() -> StructureConstraints {
+        StructureConstraints::default()
+    }
+
+    /// Require the edge `from -> to` to be part of the structure
+    pub fn require_edge(&mut self, from: usize, to: usize) {
+        self.required.insert((from, to));
+    }
+
+    /// Forbid the edge `from -> to` (the reverse edge is still allowed)
+    pub fn forbid_edge(&mut self, from: usize, to: usize) {
+        self.forbidden.insert((from, to));
+    }
+
+    /// Assign a node to a tier: edges from a higher tier to a lower tier are forbidden
+    pub fn set_tier(&mut self, node: usize, tier: usize) {
+        self.tiers.insert(node, tier);
+    }
+
+    /// Assign a list of nodes to successive tiers, starting from tier `0`
+    pub fn set_tiers(&mut self, tiers: &[&[usize]]) {
+        for (tier, nodes) in tiers.iter().enumerate() {
+            for &node in nodes.iter() {
+                self.set_tier(node, tier);
+            }
+        }
+    }
+
+    /// The required edges, sorted
+    pub fn required_edges(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.required.iter().cloned()
+    }
+
+    /// Whether the edge `from -> to` is required
+    pub fn is_required(&self, from: usize, to: usize) -> bool {
+        self.required.contains(&(from, to))
+    }
+
+    /// Whether the edge `from -> to` may be part of the structure
+    pub fn allows_edge(&self, from: usize, to: usize) -> bool {
+        if from == to || self.forbidden.contains(&(from, to)) {
+            return false;
+        }
+        match (self.tiers.get(&from), self.tiers.get(&to)) {
+            (Some(tf), Some(tt)) => tf <= tt,
+            _ => true,
+        }
+    }
+
+    /// Whether the edge `from -> to` may be removed from the structure
+    pub fn allows_removal(&self, from: usize, to: usize) -> bool {
+        !self.is_required(from, to)
+    }
+
+    /// Check that the constraints are not contradictory, for a structure of `n_nodes` variables
+    ///
+    /// Returns the first node which is not a variable, or the first required edge which is also
+    /// forbidden, goes against the tier ordering, or is part of a cycle of required edges.
+    pub fn validate(&self, n_nodes: usize) -> Result<(), ConstraintViolation> {
+        let nodes = self
+            .required
+            .iter()
+            .chain(&self.forbidden)
+            .flat_map(|&(from, to)| [from, to])
+            .chain(self.tiers.keys().copied());
+        if let Some(node) = nodes.filter(|&node| node >= n_nodes).min() {
+            return Err(ConstraintViolation::UnknownNode(node));
+        }
+        let parents = self.initial_structure(n_nodes);
+        for &(from, to) in &self.required {
+            if self.forbidden.contains(&(from, to)) || from == to {
+                return Err(ConstraintViolation::ForbiddenEdge(from, to));
+            }
+            if !self.allows_edge(from, to) {
+                return Err(ConstraintViolation::TierOrder(from, to));
+            }
+            if reaches(&parents, to, from) {
+                return Err(ConstraintViolation::RequiredCycle(from, to));
+            }
+        }
+        Ok(())
+    }
+
+    /// Check that a structure, given as parent lists (see the `graph` module), satisfies the constraints
+    pub fn check(&self, parents: &[Vec<usize>]) -> Result<(), ConstraintViolation> {
+        for (to, node_parents) in parents.iter().enumerate() {
+            for &from in node_parents {
+                if self.forbidden.contains(&(from, to)) {
+                    return Err(ConstraintViolation::ForbiddenEdge(from, to));
+                }
+                if !self.allows_edge(from, to) {
+                    return Err(ConstraintViolation::TierOrder(from, to));
+                }
+            }
+        }
+        for &(from, to) in &self.required {
+            if !parents.get(to).map(|p| p.contains(&from)).unwrap_or(false) {
+                return Err(ConstraintViolation::MissingRequiredEdge(from, to));
+            }
+        }
+        Ok(())
+    }
+
+    /// The minimal structure over `n_nodes` nodes satisfying the constraints: only the required edges
+    ///
+    /// This is the natural starting point of a structure search.
+    pub fn initial_structure(&self, n_nodes: usize) -> Vec<Vec<usize>> {
+        let mut parents = vec![Vec::new(); n_nodes];
+        for &(from, to) in &self.required {
+            parents[to].push(from);
+        }
+        parents
+    }
+}
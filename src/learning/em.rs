@@ -0,0 +1,272 @@
+use super::log_frequencies;
+use super::softmax::ascend_softmax;
+use crate::BayesNet;
+use ndarray::{ArrayD, IxDyn};
+use rand::Rng;
+use rand_distr::{Distribution, Exp1, StandardNormal};
+
+impl BayesNet {
+    /// Declare a node as latent (or not)
+    ///
+    /// Latent nodes are never observed: datasets must not provide values for them, and their
+    /// probability tables are learned purely by `learning::em`. Their tables and the tables of their
+    /// children are randomly re-initialized by `learning::em_with_restarts`.
+    pub fn set_latent(&mut self, node: usize, latent: bool) {
+        self.nodes[node].latent = latent;
+    }
+
+    /// Whether a node was declared latent
+    pub fn is_latent(&self, node: usize) -> bool {
+        self.nodes[node].latent
+    }
+}
+
+/// Options of the expectation-maximization algorithm
+#[derive(Debug, Clone)]
+pub struct EmOptions {
+    /// Maximum number of EM iterations
+    pub max_iterations: usize,
+    /// EM stops once the log-likelihood per record improves by less than this value
+    pub tolerance: f32,
+    /// Number of Loopy Belief Propagation steps used to process each record
+    pub bp_iterations: usize,
+    /// Pseudo-count added to the expected counts, as in `fit_parameters`
+    pub pseudo_count: f32,
+    /// Number of gradient steps on the weights of the softmax nodes at each M-step, see
+    /// `fit_softmax`
+    pub softmax_steps: usize,
+    /// Learning rate of the gradient steps on the weights of the softmax nodes
+    pub softmax_learning_rate: f32,
+    /// L2 penalty on the weights of the softmax nodes
+    pub softmax_l2: f32,
+}
+
+impl Default for EmOptions {
+    fn default() -> EmOptions {
+        EmOptions {
+            max_iterations: 100,
+            tolerance: 1e-4,
+            bp_iterations: 10,
+            pseudo_count: 1.0,
+            softmax_steps: 20,
+            softmax_learning_rate: 1.0,
+            softmax_l2: 0.0,
+        }
+    }
+}
+
+/// The result of a run of the expectation-maximization algorithm
+#[derive(Debug, Clone)]
+pub struct EmResult {
+    /// The network with the learned probability tables
+    pub net: BayesNet,
+    /// Log-likelihood of the training data under the learned network
+    pub log_likelihood: f32,
+    /// Log-likelihood of the training data before each M-step and at the end, which never decreases
+    /// for networks without loops
+    pub log_likelihoods: Vec<f32>,
+    /// Log-likelihood of the held-out data under the learned network, if any was provided
+    pub held_out_log_likelihood: Option<f32>,
+    /// Number of EM iterations that were run
+    pub iterations: usize,
+    /// Whether EM stopped because the log-likelihood stabilized
+    pub converged: bool,
+}
+
+fn record_evidence(net: &BayesNet, record: &[Option<usize>], index: usize) -> Vec<(usize, usize)> {
+    assert!(
+        record.len() == net.num_nodes(),
+        "Record {} has {} values but the network has {} nodes",
+        index,
+        record.len(),
+        net.num_nodes()
+    );
+    record
+        .iter()
+        .enumerate()
+        .filter_map(|(node, &value)| {
+            value.map(|value| {
+                assert!(
+                    !net.is_latent(node),
+                    "Record {} has a value for latent node {}",
+                    index,
+                    node
+                );
+                assert!(
+                    value < net.num_values(node),
+                    "Record {} has value {} for node {}, which only has {} values",
+                    index,
+                    value,
+                    node,
+                    net.num_values(node)
+                );
+                (node, value)
+            })
+        })
+        .collect()
+}
+
+fn propagate(net: &mut BayesNet, evidence: &[(usize, usize)], bp_iterations: usize) {
+    net.reset_state();
+    net.set_evidence(evidence);
+    for _ in 0..bp_iterations {
+        net.step();
+    }
+}
+
+/// Estimate the log-likelihood of partially observed data under a network
+///
+/// Each record gives the observed value of every node (`None` for unobserved ones). The likelihood
+/// of each record is estimated after `bp_iterations` steps of Loopy Belief Propagation, and is exact
+/// for networks without loops.
+pub fn log_likelihood(net: &BayesNet, data: &[Vec<Option<usize>>], bp_iterations: usize) -> f32 {
+    let mut net = net.clone();
+    data.iter()
+        .enumerate()
+        .map(|(i, record)| {
+            let evidence = record_evidence(&net, record, i);
+            propagate(&mut net, &evidence, bp_iterations);
+            net.bethe_log_evidence()
+        })
+        .sum()
+}
+
+/// Learn the probability tables of a network from partially observed data
+///
+/// Each record gives the observed value of every node (`None` for unobserved ones, which must be
+/// the case for latent nodes). Starting from the current probability tables of `net`, this alternates
+/// between computing the expected counts of each node and its parents given each record using the
+/// Loopy Belief Propagation (E-step), and re-estimating the tables from these counts (M-step), until the
+/// log-likelihood stabilizes.
+///
+/// The weights of softmax nodes (see `BayesNet::add_softmax_node`) are re-estimated by a few steps of
+/// gradient ascent on the expected counts instead, starting from the previous weights, as configured
+/// by the `softmax_*` options. The pseudo-count does not apply to them.
+pub fn em(net: &BayesNet, data: &[Vec<Option<usize>>], options: &EmOptions) -> EmResult {
+    let evidences: Vec<_> = data
+        .iter()
+        .enumerate()
+        .map(|(i, record)| record_evidence(net, record, i))
+        .collect();
+    let mut net = net.clone();
+    let mut previous = f32::NEG_INFINITY;
+    let mut iterations = 0;
+    let mut converged = false;
+    let mut log_likelihood = f32::NEG_INFINITY;
+    let mut log_likelihoods = Vec::new();
+
+    while iterations < options.max_iterations {
+        // E-step
+        let mut counts: Vec<ArrayD<f32>> = net
+            .nodes
+            .iter()
+            .map(|node| ArrayD::zeros(IxDyn(node.log_probas.shape())))
+            .collect();
+        log_likelihood = 0.0;
+        for evidence in &evidences {
+            propagate(&mut net, evidence, options.bp_iterations);
+            log_likelihood += net.bethe_log_evidence();
+            for (id, count) in counts.iter_mut().enumerate() {
+                *count += &net.family_log_beliefs(id).mapv(f32::exp);
+            }
+        }
+        log_likelihoods.push(log_likelihood);
+        if (log_likelihood - previous).abs() < options.tolerance * evidences.len().max(1) as f32 {
+            converged = true;
+            break;
+        }
+        previous = log_likelihood;
+
+        // M-step
+        for (id, count) in counts.into_iter().enumerate() {
+            if net.softmax_weights(id).is_some() {
+                let frequencies = count / evidences.len().max(1) as f32;
+                ascend_softmax(
+                    &mut net,
+                    id,
+                    &frequencies,
+                    options.softmax_learning_rate,
+                    options.softmax_l2,
+                    options.softmax_steps,
+                );
+            } else {
+                net.replace_log_probas(id, log_frequencies(&(count + options.pseudo_count)));
+            }
+        }
+        iterations += 1;
+    }
+    if !converged {
+        // the last M-step changed the tables, so the likelihood of the returned network is not known yet
+        log_likelihood = evidences
+            .iter()
+            .map(|evidence| {
+                propagate(&mut net, evidence, options.bp_iterations);
+                net.bethe_log_evidence()
+            })
+            .sum();
+        log_likelihoods.push(log_likelihood);
+    }
+
+    net.set_evidence(&[]);
+    net.reset_state();
+    EmResult {
+        net,
+        log_likelihood,
+        log_likelihoods,
+        held_out_log_likelihood: None,
+        iterations,
+        converged,
+    }
+}
+
+/// Run `em` from several random initializations and keep the best run
+///
+/// Before each of the `restarts` runs, the probability tables of the latent nodes and of their
+/// children are re-drawn uniformly at random (from a flat Dirichlet distribution), which breaks the
+/// symmetry between the states of the latent nodes. Softmax nodes keep their form, with weights drawn
+/// from a standard normal distribution instead. The other tables start from their value in `net`.
+///
+/// The best run is the one with the highest log-likelihood on `held_out`, or on `train` if `held_out`
+/// is empty.
+pub fn em_with_restarts<R: Rng + ?Sized>(
+    rng: &mut R,
+    net: &BayesNet,
+    train: &[Vec<Option<usize>>],
+    held_out: &[Vec<Option<usize>>],
+    restarts: usize,
+    options: &EmOptions,
+) -> EmResult {
+    let randomized: Vec<usize> = (0..net.num_nodes())
+        .filter(|&id| net.is_latent(id) || net.parents(id).iter().any(|&p| net.is_latent(p)))
+        .collect();
+
+    let mut best: Option<(f32, EmResult)> = None;
+    for _ in 0..restarts.max(1) {
+        let mut init = net.clone();
+        for &id in &randomized {
+            if let Some(weights) = init.softmax_weights(id) {
+                let draw = weights.mapv(|_| StandardNormal.sample(rng));
+                init.set_softmax_weights(id, draw);
+                continue;
+            }
+            let draw = init.nodes[id]
+                .log_probas
+                .dense()
+                .mapv(|_| Exp1.sample(rng))
+                .mapv(|x: f32| x.ln());
+            init.replace_log_probas(id, draw);
+        }
+        let mut result = em(&init, train, options);
+        let score = if held_out.is_empty() {
+            result.log_likelihood
+        } else {
+            let held_out_ll = log_likelihood(&result.net, held_out, options.bp_iterations);
+            result.held_out_log_likelihood = Some(held_out_ll);
+            held_out_ll
+        };
+        if best.as_ref().map(|&(s, _)| score > s).unwrap_or(true) {
+            best = Some((score, result));
+        }
+    }
+    best.unwrap().1
+}
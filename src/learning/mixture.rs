@@ -0,0 +1,114 @@
+use super::{em_with_restarts, EmOptions};
+use crate::BayesNet;
+use ndarray::{Array1, Array2};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+/// A latent class model: a hidden class variable with conditionally independent observed variables
+///
+/// The underlying network has the latent class as node `0`, and observed variable `i` of the
+/// records as node `i + 1`, with the class as its only parent.
+#[derive(Debug, Clone)]
+pub struct MixtureModel {
+    net: BayesNet,
+    posteriors: Vec<Array1<f32>>,
+    log_likelihood: f32,
+}
+
+impl MixtureModel {
+    /// Fit a mixture with `k` classes to the data
+    ///
+    /// Each record contains the observed value of every variable (`None` for missing values). The
+    /// number of values of each variable is deduced from the largest value observed for it. This uses
+    /// `fit_with` with a fixed seed, 5 restarts and the default EM options.
+    pub fn fit(data: &[Vec<Option<usize>>], k: usize) -> MixtureModel {
+        let mut rng = StdRng::seed_from_u64(0);
+        MixtureModel::fit_with(&mut rng, data, k, 5, &EmOptions::default())
+    }
+
+    /// Fit a mixture with `k` classes to the data, with explicit randomness and EM options
+    ///
+    /// The EM algorithm is run `restarts` times from random initializations, and the run with the
+    /// highest likelihood is kept.
+    pub fn fit_with<R: Rng + ?Sized>(
+        rng: &mut R,
+        data: &[Vec<Option<usize>>],
+        k: usize,
+        restarts: usize,
+        options: &EmOptions,
+    ) -> MixtureModel {
+        assert!(k > 0, "A mixture needs at least one class");
+        let n_vars = data.first().map(|r| r.len()).unwrap_or(0);
+        let mut cardinalities = vec![1; n_vars];
+        for record in data {
+            assert!(
+                record.len() == n_vars,
+                "All records must have the same number of variables"
+            );
+            for (card, value) in cardinalities.iter_mut().zip(record.iter()) {
+                if let Some(v) = *value {
+                    *card = (*card).max(v + 1);
+                }
+            }
+        }
+
+        let mut net = BayesNet::new();
+        let class = net.add_node_from_probabilities(&[], Array1::from_elem(k, 1.0));
+        net.set_latent(class, true);
+        for &card in &cardinalities {
+            net.add_node_from_probabilities(&[class], Array2::from_elem((card, k), 1.0));
+        }
+
+        let records: Vec<Vec<Option<usize>>> = data.iter().map(|r| with_class(r)).collect();
+        let result = em_with_restarts(rng, &net, &records, &[], restarts, options);
+        let mut model = MixtureModel {
+            net: result.net,
+            posteriors: Vec::new(),
+            log_likelihood: result.log_likelihood,
+        };
+        model.posteriors = data.iter().map(|r| model.predict(r)).collect();
+        model
+    }
+
+    /// Number of classes of the mixture
+    pub fn k(&self) -> usize {
+        self.net.num_values(0)
+    }
+
+    /// The underlying network, with the class as node `0`
+    pub fn net(&self) -> &BayesNet {
+        &self.net
+    }
+
+    /// Log-likelihood of the training data under the fitted model
+    pub fn log_likelihood(&self) -> f32 {
+        self.log_likelihood
+    }
+
+    /// Posterior probabilities of the classes for each training record, in order
+    pub fn class_posteriors(&self) -> &[Array1<f32>] {
+        &self.posteriors
+    }
+
+    /// Posterior probabilities of the classes given a new record
+    pub fn predict(&self, record: &[Option<usize>]) -> Array1<f32> {
+        let mut net = self.net.clone();
+        let evidence: Vec<(usize, usize)> = record
+            .iter()
+            .enumerate()
+            .filter_map(|(i, v)| v.map(|v| (i + 1, v)))
+            .collect();
+        net.reset_state();
+        net.set_evidence(&evidence);
+        // the network is a tree of depth 1, two steps reach the fixed point
+        net.step();
+        net.step();
+        net.beliefs()[0].as_probabilities()
+    }
+}
+
+fn with_class(record: &[Option<usize>]) -> Vec<Option<usize>> {
+    std::iter::once(None)
+        .chain(record.iter().cloned())
+        .collect()
+}
@@ -0,0 +1,147 @@
+use super::check_record;
+use crate::math::ln_gamma;
+use crate::BayesNet;
+use ndarray::Array2;
+
+/// A criterion to score how well a network explains a dataset
+///
+/// All criteria are "higher is better", and can be used to compare different structures on the
+/// same data.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScoreCriterion {
+    /// The log-likelihood of the data
+    LogLikelihood,
+    /// Akaike information criterion: `log-likelihood - k`, with `k` the number of free parameters
+    Aic,
+    /// Bayesian information criterion: `log-likelihood - k * ln(N) / 2`, with `N` the number of records
+    Bic,
+    /// Log marginal likelihood of the structure, with a BDeu prior of the given equivalent sample size
+    ///
+    /// This integrates over all the possible parameters of the structure, and thus does not depend
+    /// on the probability tables of the network.
+    Bdeu {
+        /// Total weight of the uniform prior over parameters, typically `1.0`
+        equivalent_sample_size: f32,
+    },
+}
+
+/// Counts of a family as a `(n_values, n_parent_configurations)` matrix
+///
+/// Parent configurations are enumerated in row-major order, the last parent varying fastest,
+/// matching the layout of probability tables.
+pub(crate) fn family_count_matrix(
+    data: &[Vec<usize>],
+    node: usize,
+    parents: &[usize],
+    cardinalities: &[usize],
+) -> Array2<f64> {
+    let n_configs: usize = parents.iter().map(|&p| cardinalities[p]).product();
+    let mut counts = Array2::zeros((cardinalities[node], n_configs));
+    for record in data {
+        let config = parents
+            .iter()
+            .fold(0, |acc, &p| acc * cardinalities[p] + record[p]);
+        counts[(record[node], config)] += 1.0;
+    }
+    counts
+}
+
+/// Number of free parameters of a family
+pub(crate) fn family_dimension(counts: &Array2<f64>) -> f64 {
+    ((counts.nrows() - 1) * counts.ncols()) as f64
+}
+
+/// BDeu log marginal likelihood of a family
+pub(crate) fn family_bdeu(counts: &Array2<f64>, equivalent_sample_size: f64) -> f64 {
+    let alpha_config = equivalent_sample_size / counts.ncols() as f64;
+    let alpha_cell = alpha_config / counts.nrows() as f64;
+    counts
+        .columns()
+        .into_iter()
+        .map(|column| {
+            ln_gamma(alpha_config) - ln_gamma(alpha_config + column.sum())
+                + column
+                    .iter()
+                    .map(|&n| ln_gamma(alpha_cell + n) - ln_gamma(alpha_cell))
+                    .sum::<f64>()
+        })
+        .sum()
+}
+
+/// Score of a family with maximum-likelihood parameters, or BDeu score, under a criterion
+///
+/// The score of a structure is the sum of the scores of its families.
+pub(crate) fn family_score(
+    counts: &Array2<f64>,
+    criterion: ScoreCriterion,
+    n_records: usize,
+) -> f64 {
+    let log_likelihood = || {
+        counts
+            .columns()
+            .into_iter()
+            .map(|column| {
+                let total = column.sum();
+                column
+                    .iter()
+                    .filter(|&&n| n > 0.0)
+                    .map(|&n| n * (n / total).ln())
+                    .sum::<f64>()
+            })
+            .sum::<f64>()
+    };
+    match criterion {
+        ScoreCriterion::LogLikelihood => log_likelihood(),
+        ScoreCriterion::Aic => log_likelihood() - family_dimension(counts),
+        ScoreCriterion::Bic => {
+            log_likelihood() - family_dimension(counts) * (n_records as f64).ln() / 2.0
+        }
+        ScoreCriterion::Bdeu {
+            equivalent_sample_size,
+        } => family_bdeu(counts, f64::from(equivalent_sample_size)),
+    }
+}
+
+impl BayesNet {
+    /// Score the network on a complete dataset
+    ///
+    /// Records are given as for `learning::fit_parameters`. The log-likelihood based criteria use the
+    /// current probability tables of the network: to compare structures rather than parametrized
+    /// models, fit the parameters of each candidate on the data first. `ScoreCriterion::Bdeu` only
+    /// depends on the structure.
+    pub fn score(&self, data: &[Vec<usize>], criterion: ScoreCriterion) -> f32 {
+        for (i, record) in data.iter().enumerate() {
+            check_record(self, record, i);
+        }
+        let cardinalities: Vec<usize> = (0..self.num_nodes()).map(|i| self.num_values(i)).collect();
+        let mut log_likelihood = 0.0;
+        let mut dimension = 0.0;
+        let mut bdeu = 0.0;
+        for node in 0..self.num_nodes() {
+            let counts = family_count_matrix(data, node, &self.parents(node), &cardinalities);
+            dimension += family_dimension(&counts);
+            match criterion {
+                ScoreCriterion::Bdeu {
+                    equivalent_sample_size,
+                } => bdeu += family_bdeu(&counts, f64::from(equivalent_sample_size)),
+                _ => {
+                    let log_probas = self.nodes[node].log_probas.dense();
+                    let log_probas = log_probas.to_shape(counts.raw_dim()).unwrap();
+                    log_likelihood += counts
+                        .iter()
+                        .zip(log_probas.iter())
+                        .filter(|&(&n, _)| n > 0.0)
+                        .map(|(&n, &lp)| n * f64::from(lp))
+                        .sum::<f64>();
+                }
+            }
+        }
+        let score = match criterion {
+            ScoreCriterion::LogLikelihood => log_likelihood,
+            ScoreCriterion::Aic => log_likelihood - dimension,
+            ScoreCriterion::Bic => log_likelihood - dimension * (data.len() as f64).ln() / 2.0,
+            ScoreCriterion::Bdeu { .. } => bdeu,
+        };
+        score as f32
+    }
+}
@@ -0,0 +1,200 @@
+use super::score::{family_count_matrix, family_score};
+use super::{fit_structure, ConstraintViolation, ScoreCriterion, StructureConstraints};
+use crate::BayesNet;
+
+/// Options of the hill-climbing structure search, see `hill_climb`
+#[derive(Debug, Clone)]
+pub struct HillClimbOptions {
+    /// The score maximized by the search
+    pub criterion: ScoreCriterion,
+    /// Maximum number of parents of a node
+    pub max_parents: usize,
+    /// Maximum number of edge modifications
+    pub max_iterations: usize,
+    /// Pseudo-count used to fit the probability tables of the learned structure, as in
+    /// `fit_parameters`
+    pub pseudo_count: f32,
+    /// Domain knowledge that the learned structure must respect
+    pub constraints: StructureConstraints,
+}
+
+impl Default for HillClimbOptions {
+    fn default() -> HillClimbOptions {
+        HillClimbOptions {
+            criterion: ScoreCriterion::Bic,
+            max_parents: 4,
+            max_iterations: 1000,
+            pseudo_count: 1.0,
+            constraints: StructureConstraints::new(),
+        }
+    }
+}
+
+/// A structure learned by `hill_climb`, with its fitted probability tables
+#[derive(Debug, Clone)]
+pub struct LearnedStructure {
+    /// The parents of each variable, indexed as the values of the records
+    pub parents: Vec<Vec<usize>>,
+    /// The score of the structure on the data
+    pub score: f32,
+    /// Number of edge modifications applied by the search
+    pub iterations: usize,
+    /// The variable of each node of `net`: node `i` of the network models the value at index
+    /// `order[i]` of the records
+    ///
+    /// The nodes of a network are added after their parents, so the variables are reordered when an
+    /// edge goes from a variable to one with a smaller index. `order` is the identity otherwise.
+    pub order: Vec<usize>,
+    /// The network of the learned structure, with probability tables fitted on the data
+    pub net: BayesNet,
+}
+
+// the new parents of the nodes modified by a move
+type Changes = Vec<(usize, Vec<usize>)>;
+
+// whether there is a directed path from `from` to `to`, ignoring the edge `skip`
+fn has_path(parents: &[Vec<usize>], from: usize, to: usize, skip: Option<(usize, usize)>) -> bool {
+    let mut visited = vec![false; parents.len()];
+    let mut stack = vec![to];
+    while let Some(n) = stack.pop() {
+        if n == from {
+            return true;
+        }
+        for &p in &parents[n] {
+            if Some((p, n)) != skip && !visited[p] {
+                visited[p] = true;
+                stack.push(p);
+            }
+        }
+    }
+    false
+}
+
+/// Learn a structure from complete data by greedy hill climbing
+///
+/// Records are given as for `fit_parameters`, `cardinalities[i]` being the number of values of the
+/// `i`-th variable. Starting from the required edges of the constraints, the search repeatedly applies
+/// the addition, removal or reversal of a single edge that improves the score the most, among those
+/// keeping the graph acyclic, respecting the constraints and the maximum number of parents. It stops
+/// at a local maximum of the score, or after `max_iterations` modifications.
+///
+/// The log-likelihood is computed with the maximum-likelihood parameters of each structure, so it
+/// never decreases when adding an edge: `ScoreCriterion::LogLikelihood` only makes sense with a small
+/// `max_parents`. The probability tables of the learned network are then fitted with the pseudo-count
+/// of the options.
+///
+/// Returns an error if the constraints are contradictory, see `StructureConstraints::validate`.
+/// Panics if a record does not have exactly one valid value per variable.
+pub fn hill_climb(
+    data: &[Vec<usize>],
+    cardinalities: &[usize],
+    options: &HillClimbOptions,
+) -> Result<LearnedStructure, ConstraintViolation> {
+    let n = cardinalities.len();
+    for (i, record) in data.iter().enumerate() {
+        assert!(
+            record.len() == n && record.iter().zip(cardinalities).all(|(&v, &c)| v < c),
+            "Record {} does not have one valid value for each of the {} variables",
+            i,
+            n
+        );
+    }
+    options.constraints.validate(n)?;
+    let constraints = &options.constraints;
+    let family = |node: usize, parents: &[usize]| {
+        let counts = family_count_matrix(data, node, parents, cardinalities);
+        family_score(&counts, options.criterion, data.len())
+    };
+
+    let mut parents = constraints.initial_structure(n);
+    let mut scores: Vec<f64> = (0..n).map(|i| family(i, &parents[i])).collect();
+    let mut iterations = 0;
+    while iterations < options.max_iterations {
+        // the improvement of the best move, and its changes
+        let mut best: Option<(f64, Changes)> = None;
+        let mut consider = |delta: f64, changes: Changes| {
+            if delta > 1e-9 && best.as_ref().is_none_or(|b| delta > b.0) {
+                best = Some((delta, changes));
+            }
+        };
+        for to in 0..n {
+            for from in 0..n {
+                if parents[to].contains(&from) {
+                    let without: Vec<usize> =
+                        parents[to].iter().copied().filter(|&p| p != from).collect();
+                    if !constraints.allows_removal(from, to) {
+                        continue;
+                    }
+                    let removed = family(to, &without);
+                    consider(removed - scores[to], vec![(to, without.clone())]);
+                    if constraints.allows_edge(to, from)
+                        && parents[from].len() < options.max_parents
+                        && !has_path(&parents, from, to, Some((from, to)))
+                    {
+                        let mut reversed = parents[from].clone();
+                        reversed.push(to);
+                        let delta = removed + family(from, &reversed) - scores[to] - scores[from];
+                        consider(delta, vec![(to, without), (from, reversed)]);
+                    }
+                } else if constraints.allows_edge(from, to)
+                    && parents[to].len() < options.max_parents
+                    && !has_path(&parents, to, from, None)
+                {
+                    let mut with = parents[to].clone();
+                    with.push(from);
+                    consider(family(to, &with) - scores[to], vec![(to, with)]);
+                }
+            }
+        }
+        let changes = match best {
+            Some((_, changes)) => changes,
+            None => break,
+        };
+        for (node, node_parents) in changes {
+            scores[node] = family(node, &node_parents);
+            parents[node] = node_parents;
+        }
+        iterations += 1;
+    }
+    for node_parents in &mut parents {
+        node_parents.sort_unstable();
+    }
+
+    // add the variables in a topological order, preferring the smallest indices
+    let mut order = Vec::with_capacity(n);
+    let mut placed = vec![false; n];
+    while order.len() < n {
+        let next = (0..n)
+            .find(|&i| !placed[i] && parents[i].iter().all(|&p| placed[p]))
+            .expect("the search keeps the structure acyclic");
+        placed[next] = true;
+        order.push(next);
+    }
+    let mut position = vec![0; n];
+    for (i, &variable) in order.iter().enumerate() {
+        position[variable] = i;
+    }
+    let net_parents: Vec<Vec<usize>> = order
+        .iter()
+        .map(|&v| parents[v].iter().map(|&p| position[p]).collect())
+        .collect();
+    let net_cardinalities: Vec<usize> = order.iter().map(|&v| cardinalities[v]).collect();
+    let net_data: Vec<Vec<usize>> = data
+        .iter()
+        .map(|record| order.iter().map(|&v| record[v]).collect())
+        .collect();
+    let net = fit_structure(
+        &net_parents,
+        &net_cardinalities,
+        &net_data,
+        options.pseudo_count,
+    );
+
+    Ok(LearnedStructure {
+        score: scores.iter().sum::<f64>() as f32,
+        parents,
+        iterations,
+        order,
+        net,
+    })
+}
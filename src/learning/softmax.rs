@@ -0,0 +1,70 @@
+use super::{check_record, family_counts};
+use crate::softmax::one_hot_offsets;
+use crate::BayesNet;
+use ndarray::{Array2, ArrayD, Axis, Dimension, IxDyn};
+
+/// Learn the weights of a softmax node from complete data, by gradient ascent
+///
+/// Starting from the current weights of `node` (or zero weights if it is not a softmax node), runs
+/// `iterations` steps of gradient ascent of the average log-likelihood of `data`, penalized by
+/// `l2 / 2` times the squared norm of the weights, with the given `learning_rate`. Returns a copy of
+/// the network in which `node` is a softmax node with the learned weights, see
+/// `BayesNet::add_softmax_node`. The inference state is reset.
+///
+/// Panics if a record does not have exactly one valid value per node.
+pub fn fit_softmax(
+    net: &BayesNet,
+    node: usize,
+    data: &[Vec<usize>],
+    learning_rate: f32,
+    l2: f32,
+    iterations: usize,
+) -> BayesNet {
+    for (i, record) in data.iter().enumerate() {
+        check_record(net, record, i);
+    }
+    let counts = family_counts(net, node, data) / data.len().max(1) as f32;
+    let mut fitted = net.clone();
+    ascend_softmax(&mut fitted, node, &counts, learning_rate, l2, iterations);
+    fitted.reset_state();
+    fitted
+}
+
+// gradient ascent of the weights of a node from the frequencies of its family configurations,
+// starting from its current weights if it is a softmax node
+pub(crate) fn ascend_softmax(
+    net: &mut BayesNet,
+    node: usize,
+    frequencies: &ArrayD<f32>,
+    learning_rate: f32,
+    l2: f32,
+    iterations: usize,
+) {
+    let parents = net.parents(node);
+    let sizes: Vec<usize> = parents.iter().map(|&p| net.num_values(p)).collect();
+    let offsets = one_hot_offsets(&sizes);
+    let n_values = net.num_values(node);
+    let mut weights = net
+        .softmax_weights(node)
+        .cloned()
+        .unwrap_or_else(|| Array2::zeros((n_values, 1 + sizes.iter().sum::<usize>())));
+
+    let totals = frequencies.sum_axis(Axis(0));
+    for _ in 0..iterations {
+        net.set_softmax_weights(node, weights.clone());
+        let probabilities = net.nodes[node].log_probas.dense().mapv(f32::exp);
+        // gradient with respect to each entry of the table, then to the weights
+        let mut gradient = Array2::<f32>::zeros(weights.dim());
+        for (index, &frequency) in frequencies.indexed_iter() {
+            let index = index.slice();
+            let g = frequency - totals[IxDyn(&index[1..])] * probabilities[IxDyn(index)];
+            let value = index[0];
+            gradient[(value, 0)] += g;
+            for (i, &offset) in offsets.iter().enumerate() {
+                gradient[(value, offset + index[i + 1])] += g;
+            }
+        }
+        weights = &weights + &((gradient - &weights * l2) * learning_rate);
+    }
+    net.set_softmax_weights(node, weights);
+}
@@ -1,6 +1,118 @@
+mod acceleration;
+mod accuracy;
+mod aggregate;
+mod audit;
+mod bif;
+mod build;
+mod cache;
+mod calibration;
+pub mod causal;
+mod codegen;
+mod components;
+mod consistency;
+mod cpt_tree;
+mod credal;
+mod cutset;
+mod damping;
+mod determinism;
+#[macro_use]
+mod diagnostics;
+mod dynamic;
+mod engine;
+pub mod evaluation;
+mod exact;
+mod explanation;
+mod factor;
+mod fault_tree;
+pub mod feature_selection;
+#[cfg(feature = "fixed-point")]
+pub mod fixed_point;
+#[cfg(feature = "arbitrary")]
+pub mod fuzzing;
+pub mod graph;
+mod hashcons;
+mod hugin;
+mod importance;
+mod influence;
+mod initialization;
+mod json;
+mod junction_tree;
+mod layout;
+pub mod learning;
+mod localization;
+mod loop_correction;
 mod math;
+mod measurement;
+mod messages;
+mod metadata;
+mod migration;
+mod monotonicity;
 mod network;
+mod numerics;
+pub mod pooling;
 mod prob_vector;
+mod quickscore;
+mod ranking;
+mod registry;
+pub mod reliability;
+pub mod report;
+mod restarts;
+mod rules;
+pub mod sampling;
+mod scenario;
+mod schema;
+pub mod semiring;
+mod sensitivity;
+mod session;
+mod snapshot;
+mod softmax;
+mod sources;
+mod sparse;
+pub mod stats;
+mod strict;
+pub mod sweep;
+mod table;
+mod temporal;
+pub mod testing;
+mod uai;
+mod uncertainty;
+mod what_if;
+mod xmlbif;
 
+pub use acceleration::AndersonAcceleration;
+pub use accuracy::{Accuracy, AccuracyGrade};
+pub use aggregate::Aggregate;
+pub use audit::{AuditLog, AuditRecord};
+pub use bif::BifError;
+pub use build::BuildError;
+pub use cache::InferenceCache;
+pub use calibration::Calibration;
+pub use components::ComponentStatus;
+pub use cpt_tree::{CptReduction, CptTree};
+pub use credal::CredalNet;
+pub use determinism::{DeterminismWarning, FunctionalDependency};
+pub use diagnostics::{AuditFinding, InferenceError, MessageAudit, MessageIssue, NodeRef};
+pub use dynamic::{DynamicBayesNet, FixedLagSmoother, SliceParent};
+pub use engine::EngineVersion;
+pub use importance::ImportanceEstimate;
+pub use influence::{InfluenceStrength, Simplification};
+pub use initialization::MessageInit;
+pub use junction_tree::JunctionTree;
+pub use layout::NodeLayout;
+pub use measurement::Measurement;
+pub use migration::{Migration, MigrationChain};
+pub use monotonicity::{Monotonicity, MonotonicityViolation, ViolationContext};
 pub use network::BayesNet;
+pub use numerics::NumericsPolicy;
 pub use prob_vector::LogProbVector;
+pub use ranking::{RankingNet, INFINITE_RANK};
+pub use registry::{ModelHandle, ModelRegistry, RegistryError};
+pub use rules::CptRules;
+pub use schema::SchemaError;
+pub use sensitivity::{FindingSensitivity, ObservationValue, PlannedObservation};
+pub use session::{Session, SessionEvent, SessionParseError, SessionRecorder};
+pub use snapshot::BeliefSnapshot;
+pub use sources::{Report, SourceReliabilities};
+pub use strict::InputWarning;
+pub use temporal::Staleness;
+pub use uncertainty::BeliefStats;
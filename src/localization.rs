@@ -0,0 +1,125 @@
+use crate::{BayesNet, LogProbVector};
+use std::collections::BTreeSet;
+use std::fmt::Write;
+
+/// The display names of a node in a locale
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct LocalizedNames {
+    pub(crate) name: Option<String>,
+    pub(crate) states: Option<Vec<String>>,
+}
+
+// the locale and its parent locales, from the most specific: `fr-CA`, then `fr`
+fn fallbacks(locale: &str) -> impl Iterator<Item = &str> {
+    std::iter::successors(Some(locale), |l| l.rfind(['-', '_']).map(|i| &l[..i]))
+}
+
+impl BayesNet {
+    /// Set the display name of a node in a locale, such as `"fr"` or `"pt-BR"`
+    ///
+    /// Unlike the name of the node (see `set_node_name`), display names are not used to refer to the
+    /// node, and do not need to be unique.
+    pub fn set_localized_name(&mut self, node: usize, locale: &str, name: &str) {
+        self.nodes[node]
+            .localized
+            .entry(locale.to_owned())
+            .or_default()
+            .name = Some(name.to_owned());
+    }
+
+    /// Set the display names of the values of a node in a locale
+    ///
+    /// This function panics if the number of names does not match the number of values of the node.
+    pub fn set_localized_state_names<S: AsRef<str>>(
+        &mut self,
+        node: usize,
+        locale: &str,
+        names: &[S],
+    ) {
+        let n_values = self.num_values(node);
+        assert!(
+            names.len() == n_values,
+            "{} state names given in locale {} for {}, which has {} values",
+            names.len(),
+            locale,
+            self.node_ref(node),
+            n_values
+        );
+        self.nodes[node]
+            .localized
+            .entry(locale.to_owned())
+            .or_default()
+            .states = Some(names.iter().map(|s| s.as_ref().to_owned()).collect());
+    }
+
+    /// The display name of a node in a locale, if it was set for this locale or one of its parents
+    ///
+    /// The parents of a locale are found by removing its last `-` or `_` component: the names set for
+    /// `"fr"` are used for `"fr-CA"` if none were set for `"fr-CA"`.
+    pub fn localized_name(&self, node: usize, locale: &str) -> Option<&str> {
+        let localized = &self.nodes[node].localized;
+        fallbacks(locale).find_map(|l| localized.get(l).and_then(|names| names.name.as_deref()))
+    }
+
+    /// The display names of the values of a node in a locale, see `localized_name`
+    pub fn localized_state_names(&self, node: usize, locale: &str) -> Option<&[String]> {
+        let localized = &self.nodes[node].localized;
+        fallbacks(locale).find_map(|l| localized.get(l).and_then(|names| names.states.as_deref()))
+    }
+
+    /// All the locales in which some display names are set, sorted
+    pub fn locales(&self) -> Vec<String> {
+        let locales: BTreeSet<&String> = self
+            .nodes
+            .iter()
+            .flat_map(|node| node.localized.keys())
+            .collect();
+        locales.into_iter().cloned().collect()
+    }
+
+    /// The name of a node to display in a locale
+    ///
+    /// This is its localized name, or its name if it has none in this locale, or `node {id}` for
+    /// unnamed nodes.
+    pub fn display_name(&self, node: usize, locale: &str) -> String {
+        self.localized_name(node, locale)
+            .or_else(|| self.node_name(node))
+            .map(str::to_owned)
+            .unwrap_or_else(|| format!("node {}", node))
+    }
+
+    /// The name of a value of a node to display in a locale
+    ///
+    /// This is its localized name, or its state name if it has none in this locale, or the index of
+    /// the value.
+    pub fn display_state(&self, node: usize, value: usize, locale: &str) -> String {
+        self.localized_state_names(node, locale)
+            .or_else(|| self.state_names(node))
+            .map(|names| names[value].clone())
+            .unwrap_or_else(|| value.to_string())
+    }
+
+    /// A human-readable report of beliefs in a locale, one line per node
+    ///
+    /// Each line gives the display name of the node followed by the probability of each of its values,
+    /// such as `Pluie: oui 0.300, non 0.700`. `beliefs` are typically the result of `beliefs`.
+    pub fn belief_report(&self, beliefs: &[LogProbVector], locale: &str) -> String {
+        let mut report = String::new();
+        for (node, belief) in beliefs.iter().enumerate() {
+            let values: Vec<String> = belief
+                .as_probabilities()
+                .iter()
+                .enumerate()
+                .map(|(value, p)| format!("{} {:.3}", self.display_state(node, value, locale), p))
+                .collect();
+            writeln!(
+                report,
+                "{}: {}",
+                self.display_name(node, locale),
+                values.join(", ")
+            )
+            .unwrap();
+        }
+        report
+    }
+}
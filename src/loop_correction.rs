@@ -0,0 +1,211 @@
+use crate::{BayesNet, LogProbVector};
+use ndarray::{Array1, ArrayD, Axis, IxDyn};
+
+// biconnected components of the undirected skeleton, as lists of nodes, computed with Tarjan's algorithm
+struct Blocks<'a> {
+    neighbours: &'a [Vec<usize>],
+    index: Vec<usize>,
+    low: Vec<usize>,
+    counter: usize,
+    edges: Vec<(usize, usize)>,
+    blocks: Vec<Vec<usize>>,
+}
+
+impl<'a> Blocks<'a> {
+    fn visit(&mut self, node: usize, parent: Option<usize>) {
+        self.counter += 1;
+        self.index[node] = self.counter;
+        self.low[node] = self.counter;
+        let mut skipped_parent = false;
+        for &other in &self.neighbours[node] {
+            // skip the edge we came from (once, parallel edges form a block)
+            if Some(other) == parent && !skipped_parent {
+                skipped_parent = true;
+                continue;
+            }
+            if self.index[other] == 0 {
+                self.edges.push((node, other));
+                self.visit(other, Some(node));
+                self.low[node] = self.low[node].min(self.low[other]);
+                if self.low[other] >= self.index[node] {
+                    // node is an articulation point, pop the block
+                    let mut block = Vec::new();
+                    while let Some((a, b)) = self.edges.pop() {
+                        block.push(a);
+                        block.push(b);
+                        if (a, b) == (node, other) {
+                            break;
+                        }
+                    }
+                    block.sort_unstable();
+                    block.dedup();
+                    self.blocks.push(block);
+                }
+            } else if self.index[other] < self.index[node] {
+                self.edges.push((node, other));
+                self.low[node] = self.low[node].min(self.index[other]);
+            }
+        }
+    }
+}
+
+impl BayesNet {
+    /// Compute the beliefs of the nodes, with a correction of the errors caused by short loops
+    ///
+    /// This should be used once the Loopy Belief Propagation has converged. The nodes of the graph are
+    /// grouped into clusters, which are the sets of nodes forming loops (the biconnected components of the
+    /// graph, ignoring the direction of the edges). For each cluster with at most `max_cluster_states`
+    /// joint configurations, the joint distribution of its nodes is computed exactly from their probability
+    /// tables, their evidence, and the messages they receive from outside of the cluster, and the beliefs of
+    /// its nodes are read from it. This is the region-based approximation of the cluster variation method
+    /// with the clusters as regions, which is exact when the clusters are only connected to each other
+    /// through single nodes.
+    ///
+    /// The beliefs of nodes outside of the corrected clusters are the same as returned by `beliefs`.
+    pub fn loop_corrected_beliefs(&self, max_cluster_states: usize) -> Vec<LogProbVector> {
+        let n = self.nodes.len();
+        let mut neighbours = vec![Vec::new(); n];
+        for (child, node) in self.nodes.iter().enumerate() {
+            for &(parent, _) in &node.parents {
+                neighbours[child].push(parent);
+                neighbours[parent].push(child);
+            }
+        }
+        let mut blocks = Blocks {
+            neighbours: &neighbours,
+            index: vec![0; n],
+            low: vec![0; n],
+            counter: 0,
+            edges: Vec::new(),
+            blocks: Vec::new(),
+        };
+        for start in 0..n {
+            if blocks.index[start] == 0 {
+                blocks.visit(start, None);
+            }
+        }
+
+        let mut beliefs = self.beliefs();
+        for cluster in blocks.blocks {
+            // a single edge is not a loop
+            if cluster.len() < 3 && !self.has_parallel_edges(&cluster) {
+                continue;
+            }
+            let states = cluster
+                .iter()
+                .try_fold(1usize, |acc, &m| acc.checked_mul(self.num_values(m)));
+            if states.map(|s| s <= max_cluster_states) != Some(true) {
+                continue;
+            }
+            for (member, belief) in cluster.iter().zip(self.cluster_marginals(&cluster)) {
+                beliefs[*member] = belief;
+            }
+        }
+        beliefs
+    }
+
+    fn has_parallel_edges(&self, cluster: &[usize]) -> bool {
+        cluster.iter().any(|&m| {
+            let parents = self.parents(m);
+            parents
+                .iter()
+                .enumerate()
+                .any(|(i, p)| parents[i + 1..].contains(p))
+        })
+    }
+
+    // exact marginals of the nodes of a cluster, given the messages from outside of it
+    fn cluster_marginals(&self, cluster: &[usize]) -> Vec<LogProbVector> {
+        let position = |node: usize| cluster.iter().position(|&m| m == node);
+        // for each member: its local factor, and the positions in the cluster of its inner parents
+        let factors: Vec<(ArrayD<f32>, Vec<usize>)> = cluster
+            .iter()
+            .map(|&m| {
+                let node = &self.nodes[m];
+                // sum out the outer parents, weighted by their messages
+                let factor = node
+                    .parents
+                    .iter()
+                    .enumerate()
+                    .rev()
+                    .filter(|&(_, &(p, _))| position(p).is_none())
+                    .fold(
+                        node.log_probas.dense().into_owned(),
+                        |acc, (axid, (_, msg))| {
+                            let mut msg = msg.clone();
+                            msg.renormalize();
+                            crate::math::log_contract(
+                                acc.view(),
+                                msg.log_probabilities(),
+                                Axis(axid + 1),
+                            )
+                        },
+                    );
+                // the evidence and the messages from the outer children only depend on the node
+                let mut local = node.evidence_vec();
+                for &(c, ref msg) in &node.children {
+                    if position(c).is_none() {
+                        local.prod(msg);
+                    }
+                }
+                let mut factor = factor;
+                for mut lane in factor.lanes_mut(Axis(0)) {
+                    lane += &local.log_probabilities();
+                }
+                let inner: Vec<usize> = node
+                    .parents
+                    .iter()
+                    .filter_map(|&(p, _)| position(p))
+                    .collect();
+                (factor, inner)
+            })
+            .collect();
+
+        let sizes: Vec<usize> = cluster.iter().map(|&m| self.num_values(m)).collect();
+        let mut marginals: Vec<Vec<f32>> =
+            sizes.iter().map(|&s| vec![f32::NEG_INFINITY; s]).collect();
+        let mut config = vec![0; cluster.len()];
+        let total: usize = sizes.iter().product();
+        let mut index = Vec::new();
+        for _ in 0..total {
+            let log_p: f32 = factors
+                .iter()
+                .enumerate()
+                .map(|(i, (factor, inner))| {
+                    index.clear();
+                    index.push(config[i]);
+                    index.extend(inner.iter().map(|&p| config[p]));
+                    factor[IxDyn(&index)]
+                })
+                .sum();
+            for (marginal, &value) in marginals.iter_mut().zip(config.iter()) {
+                marginal[value] = log_add(marginal[value], log_p);
+            }
+            // next configuration
+            for (value, &size) in config.iter_mut().zip(sizes.iter()) {
+                *value += 1;
+                if *value < size {
+                    break;
+                }
+                *value = 0;
+            }
+        }
+        marginals
+            .into_iter()
+            .map(|m| {
+                let mut v = LogProbVector::from_log_probabilities(Array1::from(m));
+                v.renormalize();
+                v
+            })
+            .collect()
+    }
+}
+
+fn log_add(a: f32, b: f32) -> f32 {
+    let max = a.max(b);
+    if max == f32::NEG_INFINITY {
+        max
+    } else {
+        max + ((a - max).exp() + (b - max).exp()).ln()
+    }
+}
@@ -1,7 +1,8 @@
+use crate::semiring::{Semiring, SumProduct};
 use ndarray::{Array, ArrayView, ArrayView1, ArrayViewMut, Axis, Dimension, RemoveAxis};
 
 pub fn log_sum_exp_vec(x: ArrayView1<f32>) -> f32 {
-    let max_log = x.fold(std::f32::NEG_INFINITY, |old_max, &v| f32::max(old_max, v));
+    let max_log = x.fold(f32::NEG_INFINITY, |old_max, &v| f32::max(old_max, v));
     if !max_log.is_finite() {
         // if max_log is +inf, result will be +inf anyway
         // if max_log is -inf, then all log values are -inf, and the result of the log_sum_exp is too
@@ -11,21 +12,16 @@ pub fn log_sum_exp_vec(x: ArrayView1<f32>) -> f32 {
     }
 }
 
-pub fn log_sum_exp<D: Dimension + RemoveAxis>(
-    x: ArrayView<f32, D>,
+pub fn log_contract<D: Dimension + RemoveAxis>(
+    tensor: ArrayView<f32, D>,
+    vector: ArrayView1<f32>,
     axis: Axis,
 ) -> Array<f32, D::Smaller> {
-    x.map_axis(axis, log_sum_exp_vec)
-}
-
-pub fn log_sum_exp_keepdim<D: Dimension + RemoveAxis>(
-    x: ArrayView<f32, D>,
-    axis: Axis,
-) -> Array<f32, <D::Smaller as Dimension>::Larger> {
-    log_sum_exp(x, axis).insert_axis(axis)
+    contract::<SumProduct, D>(tensor, vector, axis)
 }
 
-pub fn log_contract<D: Dimension + RemoveAxis>(
+/// Combine a vector into a tensor along an axis, and sum this axis out over the semiring
+pub fn contract<S: Semiring, D: Dimension + RemoveAxis>(
     tensor: ArrayView<f32, D>,
     vector: ArrayView1<f32>,
     axis: Axis,
@@ -33,11 +29,94 @@ pub fn log_contract<D: Dimension + RemoveAxis>(
     tensor.map_axis(axis, |v| {
         let mut v = v.into_owned();
         v += &vector;
-        log_sum_exp_vec(v.view())
+        S::sum(v.view())
     })
 }
 
-pub fn normalize_log_probas<D: Dimension + RemoveAxis>(mut x: ArrayViewMut<f32, D>) {
-    let lsm = log_sum_exp_keepdim(x.view(), Axis(0));
-    x -= &lsm;
+pub fn normalize_log_probas<D: Dimension + RemoveAxis>(x: ArrayViewMut<f32, D>) {
+    normalize_table::<SumProduct, D>(x)
+}
+
+/// Normalize each distribution of a table (the lanes along its first axis) over the semiring
+pub fn normalize_table<S: Semiring, D: Dimension + RemoveAxis>(mut table: ArrayViewMut<f32, D>) {
+    for lane in table.lanes_mut(Axis(0)) {
+        S::normalize(lane);
+    }
+}
+
+/// Natural logarithm of the gamma function, for `x > 0`
+///
+/// Uses the Lanczos approximation (g = 7, n = 9), accurate to about 15 significant digits.
+pub fn ln_gamma(x: f64) -> f64 {
+    const COEFS: [f64; 9] = [
+        0.999_999_999_999_809_9,
+        676.520_368_121_885_1,
+        -1_259.139_216_722_402_8,
+        771.323_428_777_653_1,
+        -176.615_029_162_140_6,
+        12.507_343_278_686_905,
+        -0.138_571_095_265_720_12,
+        9.984_369_578_019_572e-6,
+        1.505_632_735_149_311_6e-7,
+    ];
+    if x < 0.5 {
+        // reflection formula
+        std::f64::consts::PI.ln() - (std::f64::consts::PI * x).sin().ln() - ln_gamma(1.0 - x)
+    } else {
+        let x = x - 1.0;
+        let t = x + 7.5;
+        let sum = COEFS[1..]
+            .iter()
+            .enumerate()
+            .fold(COEFS[0], |acc, (i, &c)| acc + c / (x + i as f64 + 1.0));
+        0.5 * (2.0 * std::f64::consts::PI).ln() + (x + 0.5) * t.ln() - t + sum.ln()
+    }
+}
+
+/// Regularized upper incomplete gamma function `Q(a, x)`, for `a > 0` and `x >= 0`
+///
+/// Uses the series expansion of `P(a, x)` for `x < a + 1` and a continued fraction otherwise.
+pub fn gamma_q(a: f64, x: f64) -> f64 {
+    if x <= 0.0 {
+        return 1.0;
+    }
+    let log_prefactor = a * x.ln() - x - ln_gamma(a);
+    if x < a + 1.0 {
+        let (mut term, mut sum, mut n) = (1.0 / a, 1.0 / a, a);
+        for _ in 0..1000 {
+            n += 1.0;
+            term *= x / n;
+            sum += term;
+            if term.abs() < sum.abs() * 1e-15 {
+                break;
+            }
+        }
+        1.0 - sum * log_prefactor.exp()
+    } else {
+        // modified Lentz's method
+        let tiny = 1e-300;
+        let mut b = x + 1.0 - a;
+        let mut c = 1.0 / tiny;
+        let mut d = 1.0 / b;
+        let mut h = d;
+        for i in 1..1000 {
+            let an = -(i as f64) * (i as f64 - a);
+            b += 2.0;
+            d = an * d + b;
+            if d.abs() < tiny {
+                d = tiny;
+            }
+            c = b + an / c;
+            if c.abs() < tiny {
+                c = tiny;
+            }
+            d = 1.0 / d;
+            let delta = d * c;
+            h *= delta;
+            if (delta - 1.0).abs() < 1e-15 {
+                break;
+            }
+        }
+        log_prefactor.exp() * h
+    }
 }
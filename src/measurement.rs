@@ -0,0 +1,172 @@
+use crate::table::Table;
+use crate::BayesNet;
+use ndarray::{Array1, Array2, ArrayD};
+
+/// A parametric model of a continuous observation of a discrete node, see
+/// `BayesNet::add_measurement_node`
+///
+/// Each model gives one set of parameters per value of the observed node.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Measurement {
+    /// A count of events, following a Poisson distribution of the given rate for each value
+    Poisson {
+        /// Mean count for each value of the observed node
+        rates: Vec<f64>,
+    },
+    /// A duration, following an exponential distribution of the given rate for each value
+    Exponential {
+        /// Rate (inverse of the mean duration) for each value of the observed node
+        rates: Vec<f64>,
+    },
+    /// A measurement, following a normal distribution for each value
+    Gaussian {
+        /// Mean for each value of the observed node
+        means: Vec<f64>,
+        /// Standard deviation for each value of the observed node
+        std_devs: Vec<f64>,
+    },
+}
+
+impl Measurement {
+    fn num_values(&self) -> usize {
+        match self {
+            Measurement::Poisson { rates } | Measurement::Exponential { rates } => rates.len(),
+            Measurement::Gaussian { means, std_devs } => {
+                assert!(
+                    means.len() == std_devs.len(),
+                    "Gaussian measurement has {} means and {} standard deviations",
+                    means.len(),
+                    std_devs.len()
+                );
+                means.len()
+            }
+        }
+    }
+
+    /// The log-likelihood of an observation for each value of the observed node
+    ///
+    /// Counts of a Poisson measurement are rounded to the nearest integer. Observations outside of the
+    /// support of the distribution (negative counts or durations) have a log-likelihood of `-inf`.
+    pub fn log_likelihood(&self, x: f64) -> Array1<f32> {
+        let values: Vec<f64> = match self {
+            Measurement::Poisson { rates } => {
+                let k = x.round();
+                let log_factorial = crate::math::ln_gamma(k.max(0.0) + 1.0);
+                rates
+                    .iter()
+                    .map(|&rate| {
+                        if k < 0.0 {
+                            f64::NEG_INFINITY
+                        } else if rate == 0.0 {
+                            if k == 0.0 {
+                                0.0
+                            } else {
+                                f64::NEG_INFINITY
+                            }
+                        } else {
+                            k * rate.ln() - rate - log_factorial
+                        }
+                    })
+                    .collect()
+            }
+            Measurement::Exponential { rates } => rates
+                .iter()
+                .map(|&rate| {
+                    if x < 0.0 {
+                        f64::NEG_INFINITY
+                    } else {
+                        rate.ln() - rate * x
+                    }
+                })
+                .collect(),
+            Measurement::Gaussian { means, std_devs } => means
+                .iter()
+                .zip(std_devs.iter())
+                .map(|(&mean, &std_dev)| {
+                    let z = (x - mean) / std_dev;
+                    -0.5 * z * z - std_dev.ln() - 0.5 * (2.0 * std::f64::consts::PI).ln()
+                })
+                .collect(),
+        };
+        values.into_iter().map(|v| v as f32).collect()
+    }
+}
+
+impl BayesNet {
+    /// Add a node representing a continuous observation of a discrete node
+    ///
+    /// The new node is a child of `parent` with a single value, whose table is the likelihood of the
+    /// observed data for each value of the parent: the message it sends to its parent is thus the
+    /// likelihood of the observation, and raw sensor values can be fed to the network with
+    /// `observe_measurement` without binning them. Until a value is observed, the node carries no
+    /// information. Several measurement nodes can observe the same node.
+    ///
+    /// The table of a measurement node is not a probability distribution, it should not be learned or
+    /// replaced. Panics if the model does not have one set of parameters per value of `parent`, if a
+    /// rate is negative, or if a standard deviation is not positive.
+    pub fn add_measurement_node(&mut self, parent: usize, model: Measurement) -> usize {
+        assert!(
+            model.num_values() == self.num_values(parent),
+            "Measurement model has parameters for {} values, but {} has {}",
+            model.num_values(),
+            self.node_ref(parent),
+            self.num_values(parent)
+        );
+        match model {
+            Measurement::Poisson { ref rates } | Measurement::Exponential { ref rates } => {
+                if let Some(&rate) = rates.iter().find(|r| r.is_nan() || **r < 0.0) {
+                    panic!("Measurement rates must be non-negative, got {}", rate);
+                }
+            }
+            Measurement::Gaussian { ref std_devs, .. } => {
+                if let Some(&std_dev) = std_devs.iter().find(|s| s.is_nan() || **s <= 0.0) {
+                    panic!(
+                        "Measurement standard deviations must be positive, got {}",
+                        std_dev
+                    );
+                }
+            }
+        }
+        let id =
+            self.add_node_from_log_probabilities(&[parent], Array2::zeros((1, model.num_values())));
+        self.nodes[id].measurement = Some(model);
+        id
+    }
+
+    /// Observe the continuous value measured by a measurement node
+    ///
+    /// This replaces the previous observation of the node. Panics if the node is not a measurement
+    /// node, see `add_measurement_node`.
+    pub fn observe_measurement(&mut self, node: usize, x: f64) {
+        let likelihood = self
+            .measurement(node)
+            .log_likelihood(x)
+            .insert_axis(ndarray::Axis(0));
+        self.set_measurement_table(node, likelihood.into_dyn());
+    }
+
+    /// Forget the observation of a measurement node, which then carries no information
+    ///
+    /// Panics if the node is not a measurement node, see `add_measurement_node`.
+    pub fn clear_measurement(&mut self, node: usize) {
+        let n_values = self.measurement(node).num_values();
+        self.set_measurement_table(node, ArrayD::zeros(vec![1, n_values]));
+    }
+
+    fn measurement(&self, node: usize) -> &Measurement {
+        self.nodes[node]
+            .measurement
+            .as_ref()
+            .unwrap_or_else(|| panic!("{} is not a measurement node", self.node_ref(node)))
+    }
+
+    // the table of a measurement node is a likelihood, which must not be normalized
+    fn set_measurement_table(&mut self, node: usize, table: ArrayD<f32>) {
+        let table = Table::Dense(self.tables.intern(table));
+        let node = &mut self.nodes[node];
+        node.log_probas = table;
+        node.lambda = None;
+        node.pi = None;
+        self.priors.take();
+    }
+}
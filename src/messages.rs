@@ -0,0 +1,43 @@
+use crate::BayesNet;
+use ndarray::ArrayView1;
+
+impl BayesNet {
+    /// The current message sent by a node to one of its parents or children
+    ///
+    /// The message is given as log-probabilities over the values of the node that is the parent of
+    /// the edge, normalized for the semiring of the last step. The view borrows the network, so it stays
+    /// valid until the network is modified, typically by the next call to `step`: the messages are only
+    /// replaced at the end of a step, all at once.
+    ///
+    /// Panics if the nodes are not connected by an edge.
+    pub fn message_view(&self, from: usize, to: usize) -> ArrayView1<'_, f32> {
+        let receiver = &self.nodes[to];
+        receiver
+            .parents
+            .iter()
+            .chain(receiver.children.iter())
+            .find(|&&(id, _)| id == from)
+            .map(|(_, msg)| msg.log_probabilities())
+            .unwrap_or_else(|| {
+                panic!(
+                    "{} and {} are not connected",
+                    self.node_ref(from),
+                    self.node_ref(to)
+                )
+            })
+    }
+
+    /// All the current messages of the network, as `(from, to, log-probabilities)`
+    ///
+    /// The messages are given in a stable order: by receiving node, the messages from its parents in
+    /// the order of the parents, then the messages from its children in the order they were added. See
+    /// `message_view` for the meaning and lifetime of the views.
+    pub fn message_views(&self) -> impl Iterator<Item = (usize, usize, ArrayView1<'_, f32>)> {
+        self.nodes.iter().enumerate().flat_map(|(to, node)| {
+            node.parents
+                .iter()
+                .chain(node.children.iter())
+                .map(move |(from, msg)| (*from, to, msg.log_probabilities()))
+        })
+    }
+}
@@ -0,0 +1,97 @@
+use crate::{BayesNet, LogProbVector};
+use std::collections::{BTreeMap, BTreeSet};
+
+// the namespace prefix of a node name, with its trailing separator
+fn prefix(namespace: &str) -> String {
+    let namespace = namespace.trim_end_matches('/');
+    if namespace.is_empty() {
+        String::new()
+    } else {
+        format!("{}/", namespace)
+    }
+}
+
+impl BayesNet {
+    /// Attach a key/value tag to a node, replacing the previous value for this key
+    pub fn set_tag(&mut self, node: usize, key: &str, value: &str) {
+        self.nodes[node]
+            .tags
+            .insert(key.to_owned(), value.to_owned());
+    }
+
+    /// Remove a tag from a node, returning its value if it was set
+    pub fn remove_tag(&mut self, node: usize, key: &str) -> Option<String> {
+        self.nodes[node].tags.remove(key)
+    }
+
+    /// Get the value of a tag of a node
+    pub fn tag(&self, node: usize, key: &str) -> Option<&str> {
+        self.nodes[node].tags.get(key).map(String::as_str)
+    }
+
+    /// All the tags of a node
+    pub fn tags(&self, node: usize) -> &BTreeMap<String, String> {
+        &self.nodes[node].tags
+    }
+
+    /// Find the nodes having a tag, with the given value if `value` is not `None`
+    pub fn nodes_with_tag(&self, key: &str, value: Option<&str>) -> Vec<usize> {
+        (0..self.nodes.len())
+            .filter(|&i| match (self.tag(i, key), value) {
+                (Some(v), Some(expected)) => v == expected,
+                (Some(_), None) => true,
+                (None, _) => false,
+            })
+            .collect()
+    }
+
+    /// The namespace of a node, which is the part of its name before the last `/`
+    ///
+    /// Node names are organized in hierarchical namespaces with `/` as separator: the node named
+    /// `"sensors/temperature/indoor"` is in the namespace `"sensors/temperature"`, itself in the
+    /// namespace `"sensors"`. Returns `None` for unnamed nodes, and an empty string for nodes at the root.
+    pub fn namespace(&self, node: usize) -> Option<&str> {
+        self.node_name(node)
+            .map(|name| name.rfind('/').map(|i| &name[..i]).unwrap_or(""))
+    }
+
+    /// Find the named nodes in a namespace or in any of its sub-namespaces
+    ///
+    /// The empty namespace contains all the named nodes.
+    pub fn nodes_in_namespace(&self, namespace: &str) -> Vec<usize> {
+        let prefix = prefix(namespace);
+        (0..self.nodes.len())
+            .filter(|&i| {
+                self.node_name(i)
+                    .map(|name| name.starts_with(&prefix))
+                    .unwrap_or(false)
+            })
+            .collect()
+    }
+
+    /// The direct sub-namespaces of a namespace, in alphabetical order
+    pub fn sub_namespaces(&self, namespace: &str) -> Vec<String> {
+        let prefix = prefix(namespace);
+        let subs: BTreeSet<String> = self
+            .nodes
+            .iter()
+            .filter_map(|n| n.name.as_deref())
+            .filter_map(|name| name.strip_prefix(prefix.as_str()))
+            .filter_map(|rest| rest.find('/').map(|i| format!("{}{}", prefix, &rest[..i])))
+            .collect();
+        subs.into_iter().collect()
+    }
+
+    /// The current beliefs of the nodes in a namespace, see `nodes_in_namespace` and `beliefs`
+    pub fn beliefs_in_namespace(&self, namespace: &str) -> Vec<(usize, LogProbVector)> {
+        let nodes = self.nodes_in_namespace(namespace);
+        let mut beliefs = self.beliefs();
+        nodes
+            .into_iter()
+            .map(|i| {
+                let belief = std::mem::replace(&mut beliefs[i], LogProbVector::uniform(0));
+                (i, belief)
+            })
+            .collect()
+    }
+}
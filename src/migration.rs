@@ -0,0 +1,139 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// A declarative description of the changes between two versions of a model
+///
+/// It is used to upgrade persisted string-keyed evidence (as accepted by
+/// `BayesNet::validate_evidence`) and lists of queried node names, so that they remain
+/// valid for the new version of the model.
+///
+/// States added to a node in the new version don't need to be declared, as the existing
+/// state names remain valid.
+#[derive(Debug, Clone, Default)]
+pub struct Migration {
+    node_renames: HashMap<String, String>,
+    state_renames: HashMap<(String, String), String>,
+    removed_nodes: HashSet<String>,
+}
+
+impl Migration {
+    /// Create a new migration that changes nothing
+    pub fn new() -> Migration {
+        Migration::default()
+    }
+
+    /// Declare that node `from` is now named `to`
+    pub fn rename_node(&mut self, from: &str, to: &str) {
+        self.node_renames.insert(from.to_owned(), to.to_owned());
+    }
+
+    /// Declare that the state `from` of `node` is now named `to`
+    ///
+    /// `node` is the name of the node in the *old* version of the model. Several old states
+    /// can be renamed to the same new state, to represent a merge of states.
+    pub fn rename_state(&mut self, node: &str, from: &str, to: &str) {
+        self.state_renames
+            .insert((node.to_owned(), from.to_owned()), to.to_owned());
+    }
+
+    /// Declare that `node` does not exist anymore in the new version
+    ///
+    /// Evidence about this node is dropped during migration.
+    pub fn remove_node(&mut self, node: &str) {
+        self.removed_nodes.insert(node.to_owned());
+    }
+
+    /// Get the name of a node in the new version, or `None` if it was removed
+    pub fn migrate_node(&self, node: &str) -> Option<String> {
+        if self.removed_nodes.contains(node) {
+            None
+        } else {
+            Some(
+                self.node_renames
+                    .get(node)
+                    .cloned()
+                    .unwrap_or_else(|| node.to_owned()),
+            )
+        }
+    }
+
+    /// Get the name of a state of a node in the new version
+    ///
+    /// `node` is the name of the node in the old version.
+    pub fn migrate_state(&self, node: &str, state: &str) -> String {
+        self.state_renames
+            .get(&(node.to_owned(), state.to_owned()))
+            .cloned()
+            .unwrap_or_else(|| state.to_owned())
+    }
+
+    /// Upgrade string-keyed evidence to the new version
+    pub fn migrate_evidence(&self, evidence: &HashMap<String, String>) -> HashMap<String, String> {
+        evidence
+            .iter()
+            .filter_map(|(node, state)| {
+                self.migrate_node(node)
+                    .map(|new_node| (new_node, self.migrate_state(node, state)))
+            })
+            .collect()
+    }
+
+    /// Upgrade a list of queried nodes to the new version
+    ///
+    /// Removed nodes are dropped from the list, the order of the others is preserved.
+    pub fn migrate_nodes<S: AsRef<str>>(&self, nodes: &[S]) -> Vec<String> {
+        nodes
+            .iter()
+            .filter_map(|n| self.migrate_node(n.as_ref()))
+            .collect()
+    }
+}
+
+/// A sequence of migrations between successive versions of a model
+///
+/// The migration registered for version `v` upgrades data from version `v` to version `v + 1`,
+/// matching the versions assigned by a `ModelRegistry`.
+#[derive(Debug, Clone, Default)]
+pub struct MigrationChain {
+    steps: BTreeMap<u64, Migration>,
+}
+
+impl MigrationChain {
+    /// Create an empty migration chain
+    pub fn new() -> MigrationChain {
+        MigrationChain::default()
+    }
+
+    /// Register the migration from version `from_version` to version `from_version + 1`
+    pub fn add_step(&mut self, from_version: u64, migration: Migration) {
+        self.steps.insert(from_version, migration);
+    }
+
+    fn path(&self, from: u64, to: u64) -> impl Iterator<Item = &Migration> {
+        assert!(
+            from <= to,
+            "Cannot migrate backwards from version {} to version {}",
+            from,
+            to
+        );
+        // missing steps are versions with no relevant changes
+        self.steps.range(from..to).map(|(_, m)| m)
+    }
+
+    /// Upgrade string-keyed evidence from version `from` to version `to`
+    pub fn migrate_evidence(
+        &self,
+        from: u64,
+        to: u64,
+        evidence: &HashMap<String, String>,
+    ) -> HashMap<String, String> {
+        self.path(from, to)
+            .fold(evidence.clone(), |ev, m| m.migrate_evidence(&ev))
+    }
+
+    /// Upgrade a list of queried nodes from version `from` to version `to`
+    pub fn migrate_nodes<S: AsRef<str>>(&self, from: u64, to: u64, nodes: &[S]) -> Vec<String> {
+        let nodes: Vec<String> = nodes.iter().map(|n| n.as_ref().to_owned()).collect();
+        self.path(from, to)
+            .fold(nodes, |nodes, m| m.migrate_nodes(&nodes))
+    }
+}
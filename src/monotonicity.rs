@@ -0,0 +1,214 @@
+use crate::BayesNet;
+use ndarray::{Array1, ArrayView1, Axis};
+use rand::Rng;
+use std::fmt;
+
+/// A monotonic relationship between a parent and its child
+///
+/// The values of both nodes are considered ordered. The relationship is increasing if a higher value
+/// of the parent never makes high values of the child less likely: for every value `k` of the child,
+/// `P(child >= k)` does not decrease when the parent increases (first-order stochastic dominance). It is
+/// decreasing if `P(child >= k)` never increases.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Monotonicity {
+    /// The parent node
+    pub parent: usize,
+    /// The child node
+    pub child: usize,
+    /// Whether the relationship is increasing, rather than decreasing
+    pub increasing: bool,
+}
+
+/// Where a monotonicity violation was found
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ViolationContext {
+    /// In the probability table of the child, for this configuration of its other parents
+    Table {
+        /// Values of the other parents of the child, as `(node, value)`
+        other_parents: Vec<(usize, usize)>,
+    },
+    /// In the posteriors computed with this evidence, in addition to the value of the parent
+    Inference {
+        /// The sampled evidence, as for `BayesNet::set_evidence`
+        evidence: Vec<(usize, usize)>,
+    },
+}
+
+/// A counterexample to a declared monotonic relationship, see `BayesNet::verify_monotonicity`
+#[derive(Debug, Clone, PartialEq)]
+pub struct MonotonicityViolation {
+    /// The violated relationship
+    pub relation: Monotonicity,
+    /// Where the violation was found
+    pub context: ViolationContext,
+    /// The violation is between this value of the parent and the next one
+    pub parent_value: usize,
+    /// The value `k` of the child for which `P(child >= k)` is not monotonic
+    pub child_value: usize,
+    /// `P(child >= k)` for `parent_value`
+    pub before: f32,
+    /// `P(child >= k)` for the next value of the parent
+    pub after: f32,
+}
+
+impl fmt::Display for MonotonicityViolation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "P(node {} >= {}) goes from {} to {} when node {} goes from {} to {}",
+            self.relation.child,
+            self.child_value,
+            self.before,
+            self.after,
+            self.relation.parent,
+            self.parent_value,
+            self.parent_value + 1
+        )?;
+        match self.context {
+            ViolationContext::Table { ref other_parents } => {
+                write!(f, " in the table, with other parents {:?}", other_parents)
+            }
+            ViolationContext::Inference { ref evidence } => {
+                write!(f, " in the posteriors, with evidence {:?}", evidence)
+            }
+        }
+    }
+}
+
+// P(x >= k) for k in 1..n
+fn tails(probabilities: ArrayView1<f32>) -> Vec<f32> {
+    let mut tails: Vec<f32> = probabilities
+        .iter()
+        .rev()
+        .scan(0.0, |acc, &p| {
+            *acc += p;
+            Some(*acc)
+        })
+        .collect();
+    tails.pop();
+    tails.reverse();
+    tails
+}
+
+impl BayesNet {
+    /// Verify declared monotonic relationships, returning the counterexamples found
+    ///
+    /// Each relationship is checked in two ways:
+    ///
+    /// - in the probability table of the child, for every configuration of its other parents,
+    /// - in the posteriors of the child, for `n_samples` random evidence sets: each is drawn by sampling
+    ///   a configuration of the whole network and observing each node (other than the parent and the
+    ///   child) with probability `1/2`, and the posterior of the child is computed with `iterations`
+    ///   steps for each value of the parent added as evidence.
+    ///
+    /// Monotonic tables do not always give monotonic posteriors, as other paths between the parent and
+    /// the child can reverse the effect. Differences below the `comparison_epsilon` of the numeric policy
+    /// are ignored. The current evidence of the network is not used.
+    ///
+    /// Panics if a relationship is not between a parent and its child.
+    pub fn verify_monotonicity<R: Rng + ?Sized>(
+        &self,
+        relations: &[Monotonicity],
+        rng: &mut R,
+        n_samples: usize,
+        iterations: usize,
+    ) -> Vec<MonotonicityViolation> {
+        let eps = self.numerics.comparison_epsilon;
+        let mut violations = Vec::new();
+        let mut check =
+            |relation: &Monotonicity, context: &ViolationContext, distributions: &[Array1<f32>]| {
+                for (v, pair) in distributions.windows(2).enumerate() {
+                    let (before, after) = (tails(pair[0].view()), tails(pair[1].view()));
+                    for (k, (&b, &a)) in before.iter().zip(after.iter()).enumerate() {
+                        let decreased = if relation.increasing {
+                            a < b - eps
+                        } else {
+                            a > b + eps
+                        };
+                        if decreased {
+                            violations.push(MonotonicityViolation {
+                                relation: *relation,
+                                context: context.clone(),
+                                parent_value: v,
+                                child_value: k + 1,
+                                before: b,
+                                after: a,
+                            });
+                        }
+                    }
+                }
+            };
+
+        for relation in relations {
+            let table = self.table_by_parent(relation.parent, relation.child);
+            let others: Vec<usize> = self
+                .parents(relation.child)
+                .into_iter()
+                .filter(|&p| p != relation.parent)
+                .collect();
+            for (config, columns) in table.axis_iter(Axis(2)).enumerate() {
+                // decode the configuration, the last parent varying the fastest
+                let mut rest = config;
+                let mut other_parents: Vec<(usize, usize)> = others
+                    .iter()
+                    .rev()
+                    .map(|&p| {
+                        let n = self.num_values(p);
+                        let value = rest % n;
+                        rest /= n;
+                        (p, value)
+                    })
+                    .collect();
+                other_parents.reverse();
+                let distributions: Vec<Array1<f32>> = columns
+                    .axis_iter(Axis(1))
+                    .map(|column| column.mapv(f32::exp))
+                    .collect();
+                check(
+                    relation,
+                    &ViolationContext::Table { other_parents },
+                    &distributions,
+                );
+            }
+        }
+
+        let mut net = self.clone();
+        for _ in 0..n_samples {
+            let sample = self.forward_sample(rng);
+            let observed: Vec<bool> = (0..self.nodes.len()).map(|_| rng.gen_bool(0.5)).collect();
+            for relation in relations {
+                let evidence: Vec<(usize, usize)> = sample
+                    .iter()
+                    .enumerate()
+                    .filter(|&(node, _)| {
+                        observed[node] && node != relation.parent && node != relation.child
+                    })
+                    .map(|(node, &value)| (node, value))
+                    .collect();
+                let mut distributions = Vec::new();
+                for value in 0..self.num_values(relation.parent) {
+                    let mut full = evidence.clone();
+                    full.push((relation.parent, value));
+                    net.set_evidence(&full);
+                    net.reset_state();
+                    for _ in 0..iterations {
+                        net.step();
+                    }
+                    let posterior = net.beliefs()[relation.child].as_probabilities();
+                    if posterior.iter().any(|p| p.is_nan()) {
+                        // this value of the parent is impossible given the evidence
+                        distributions.clear();
+                        break;
+                    }
+                    distributions.push(posterior);
+                }
+                check(
+                    relation,
+                    &ViolationContext::Inference { evidence },
+                    &distributions,
+                );
+            }
+        }
+        violations
+    }
+}
@@ -1,29 +1,71 @@
-use crate::LogProbVector;
-use ndarray::{Array, ArrayD, Axis, Dimension, RemoveAxis};
-
-#[derive(Debug)]
-struct Node {
-    parents: Vec<(usize, LogProbVector)>,
-    children: Vec<(usize, LogProbVector)>,
-    log_probas: ArrayD<f32>,
-    evidence: Option<usize>,
-    lambda: Option<LogProbVector>,
-    pi: Option<LogProbVector>,
+use crate::damping::damp;
+use crate::hashcons::TablePool;
+use crate::localization::LocalizedNames;
+use crate::math::contract;
+use crate::semiring::{normalize, Semiring, SumProduct};
+use crate::table::Table;
+use crate::temporal::TimedEvidence;
+use crate::{
+    BuildError, Calibration, EngineVersion, InputWarning, LogProbVector, Measurement, NodeLayout,
+    NumericsPolicy, Staleness,
+};
+use ndarray::{Array, Array1, Array2, ArrayD, Axis, Dimension, RemoveAxis, Zip};
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+
+#[derive(Debug, Clone)]
+pub(crate) struct Node {
+    pub(crate) parents: Vec<(usize, LogProbVector)>,
+    pub(crate) children: Vec<(usize, LogProbVector)>,
+    pub(crate) log_probas: Table,
+    pub(crate) evidence: Option<usize>,
+    pub(crate) soft_evidence: Option<LogProbVector>,
+    pub(crate) lambda: Option<LogProbVector>,
+    pub(crate) pi: Option<LogProbVector>,
+    pub(crate) name: Option<String>,
+    pub(crate) state_names: Option<Vec<String>>,
+    pub(crate) tags: BTreeMap<String, String>,
+    pub(crate) localized: BTreeMap<String, LocalizedNames>,
+    pub(crate) layout: Option<NodeLayout>,
+    pub(crate) dirichlet: Option<ArrayD<f32>>,
+    pub(crate) latent: bool,
+    pub(crate) timed_evidence: Option<TimedEvidence>,
+    pub(crate) staleness: Staleness,
+    pub(crate) measurement: Option<Measurement>,
+    pub(crate) softmax: Option<Array2<f32>>,
+    pub(crate) calibration: Option<Calibration>,
 }
 
 impl Node {
-    fn evidence_vec(&self) -> LogProbVector {
-        if let Some(id) = self.evidence {
-            LogProbVector::deterministic(self.log_probas.shape()[0], id)
+    pub(crate) fn evidence_vec(&self) -> LogProbVector {
+        let mut evidence = if let Some(id) = self.evidence {
+            LogProbVector::deterministic(self.log_probas.num_values(), id)
         } else {
-            LogProbVector::uniform(self.log_probas.shape()[0])
+            LogProbVector::uniform(self.log_probas.num_values())
+        };
+        if let Some(soft) = self.soft_likelihood() {
+            evidence.prod(&soft);
+        }
+        evidence
+    }
+
+    // the likelihood of the soft and timestamped evidence, if any
+    pub(crate) fn soft_likelihood(&self) -> Option<LogProbVector> {
+        let timed = self.timed_evidence.as_ref().map(|timed| &timed.likelihood);
+        match (self.soft_evidence.as_ref(), timed) {
+            (Some(soft), Some(timed)) => {
+                let mut likelihood = soft.clone();
+                likelihood.prod(timed);
+                Some(likelihood)
+            }
+            (soft, timed) => soft.or(timed).cloned(),
         }
     }
 
     fn compute_lambda(&self) -> LogProbVector {
         self.children
             .iter()
-            .fold(self.evidence_vec(), |mut curr_ev, &(_, ref lambda)| {
+            .fold(self.evidence_vec(), |mut curr_ev, (_, lambda)| {
                 curr_ev.prod(lambda);
                 curr_ev
             })
@@ -41,28 +83,94 @@ impl Node {
         self.lambda.clone().unwrap()
     }
 
-    fn compute_pi(&self) -> LogProbVector {
-        let mut pi = self.log_probas.clone();
-        for (_, ref pi_msg) in self.parents.iter().rev() {
-            pi = crate::math::log_contract(
-                pi.view(),
-                pi_msg.log_probabilities(),
-                Axis(pi.ndim() - 1),
+    // the messages from the parents, normalized as required by the CPT trees
+    fn normalized_parent_msgs<S: Semiring>(&self) -> Vec<Array1<f32>> {
+        self.parents
+            .iter()
+            .map(|(_, msg)| {
+                let norm = S::sum(msg.log_probabilities());
+                if norm.is_finite() {
+                    msg.log_probabilities().mapv(|v| v - norm)
+                } else {
+                    msg.log_probabilities().to_owned()
+                }
+            })
+            .collect()
+    }
+
+    fn compute_lambda_msg<S: Semiring>(
+        &self,
+        axis: usize,
+        lambda: &LogProbVector,
+    ) -> LogProbVector {
+        if let Table::Tree(ref tree, ref shape) = self.log_probas {
+            let msgs = self.normalized_parent_msgs::<S>();
+            let views: Vec<_> = msgs.iter().map(|m| m.view()).collect();
+            let parent_size = shape[axis + 1];
+            return LogProbVector::from_log_probabilities(tree.lambda_message::<S>(
+                axis,
+                parent_size,
+                lambda.log_probabilities(),
+                &views,
+            ));
+        }
+        if let Table::Aggregate(ref cpd) = self.log_probas {
+            let msgs = self.normalized_parent_msgs::<S>();
+            let views: Vec<_> = msgs.iter().map(|m| m.view()).collect();
+            return LogProbVector::from_log_probabilities(cpd.lambda_message::<S>(
+                axis,
+                lambda.log_probabilities(),
+                &views,
+            ));
+        }
+        let acc = self
+            .parents
+            .iter()
+            .enumerate()
+            .rev()
+            .filter(|&(axid, _)| axid != axis)
+            .fold(
+                self.log_probas.dense().into_owned(),
+                |acc, (axid, (_, v))| {
+                    contract::<S, _>(acc.view(), v.log_probabilities(), Axis(axid + 1))
+                },
             );
+        let acc = contract::<S, _>(acc.view(), lambda.log_probabilities(), Axis(0));
+        assert!(acc.ndim() == 1);
+        let shape = (acc.len(),);
+        LogProbVector::from_log_probabilities(acc.into_shape(shape).unwrap())
+    }
+
+    fn compute_pi<S: Semiring>(&self) -> LogProbVector {
+        if let Table::Tree(ref tree, _) = self.log_probas {
+            let msgs = self.normalized_parent_msgs::<S>();
+            let views: Vec<_> = msgs.iter().map(|m| m.view()).collect();
+            return LogProbVector::from_log_probabilities(tree.pi::<S>(&views));
+        }
+        if let Table::Aggregate(ref cpd) = self.log_probas {
+            let msgs = self.normalized_parent_msgs::<S>();
+            let views: Vec<_> = msgs.iter().map(|m| m.view()).collect();
+            return LogProbVector::from_log_probabilities(cpd.pi::<S>(&views));
+        }
+        let mut pi = self.log_probas.dense().into_owned();
+        for (_, ref pi_msg) in self.parents.iter().rev() {
+            pi = contract::<S, _>(pi.view(), pi_msg.log_probabilities(), Axis(pi.ndim() - 1));
         }
         // sanity check
         assert!(pi.ndim() == 1);
-        LogProbVector::from_log_probabilities(pi.into_shape((self.log_probas.shape()[0],)).unwrap())
+        LogProbVector::from_log_probabilities(
+            pi.into_shape((self.log_probas.num_values(),)).unwrap(),
+        )
     }
 
-    fn compute_and_cache_pi(&mut self) {
-        let pi = self.compute_pi();
+    fn compute_and_cache_pi<S: Semiring>(&mut self) {
+        let pi = self.compute_pi::<S>();
         self.pi = Some(pi.clone());
     }
 
-    fn get_or_compute_pi(&mut self) -> LogProbVector {
+    fn get_or_compute_pi<S: Semiring>(&mut self) -> LogProbVector {
         if self.pi.is_none() {
-            self.compute_and_cache_pi();
+            self.compute_and_cache_pi::<S>();
         }
         self.pi.clone().unwrap()
     }
@@ -72,14 +180,45 @@ impl Node {
 ///
 /// Once built by adding the nodes one by one, you can use it for inference
 /// computation on the graph given some evidence.
+#[derive(Debug, Clone)]
 pub struct BayesNet {
-    nodes: Vec<Node>,
+    pub(crate) nodes: Vec<Node>,
+    pub(crate) iteration: usize,
+    // nodes whose messages were set to their prior state by the sparse propagation, if enabled
+    pub(crate) sparse: Option<Vec<bool>>,
+    // prior marginals of the nodes, computed on demand and cleared whenever a table changes
+    pub(crate) priors: OnceLock<Vec<LogProbVector>>,
+    pub(crate) numerics: NumericsPolicy,
+    // whether the tables of new nodes are validated, and the warnings they raised
+    pub(crate) strict: bool,
+    pub(crate) warnings: Vec<InputWarning>,
+    pub(crate) engine: EngineVersion,
+    pub(crate) damping: f32,
+    // the tables of the nodes, shared between identical tables
+    pub(crate) tables: TablePool,
+}
+
+impl Default for BayesNet {
+    fn default() -> BayesNet {
+        BayesNet::new()
+    }
 }
 
 impl BayesNet {
     /// Create a new empty Bayesian Network
     pub fn new() -> BayesNet {
-        BayesNet { nodes: Vec::new() }
+        BayesNet {
+            nodes: Vec::new(),
+            iteration: 0,
+            sparse: None,
+            priors: OnceLock::new(),
+            numerics: NumericsPolicy::default(),
+            strict: false,
+            warnings: Vec::new(),
+            engine: EngineVersion::default(),
+            damping: 0.0,
+            tables: TablePool::default(),
+        }
     }
 
     /// Add a new node to the network
@@ -92,13 +231,33 @@ impl BayesNet {
     /// If the node has no parents, the propabilities must be single-dimenstionnal and represents a prior.
     ///
     /// All values of probabilities should be finite, but the probabilities array does not need to be normalized,
-    /// as it will be during the construction process.
+    /// as it will be during the construction process. See `set_strict_inputs` to have them validated.
+    ///
+    /// Panics if the array does not match the parents, see `try_add_node_from_probabilities`.
     pub fn add_node_from_probabilities<D: Dimension + RemoveAxis>(
         &mut self,
         parents: &[usize],
         probabilities: Array<f32, D>,
     ) -> usize {
-        self.add_node_from_log_probabilities(parents, probabilities.mapv(f32::ln))
+        self.try_add_node_from_probabilities(parents, probabilities)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Add a new node to the network, reporting invalid inputs as errors
+    ///
+    /// Same as `add_node_from_probabilities`, but an error is returned rather than panicking if the
+    /// array does not match the parents, or if the probabilities are invalid in strict mode. The network
+    /// is left unchanged in that case.
+    pub fn try_add_node_from_probabilities<D: Dimension + RemoveAxis>(
+        &mut self,
+        parents: &[usize],
+        probabilities: Array<f32, D>,
+    ) -> Result<usize, BuildError> {
+        self.check_table(parents, probabilities.shape())?;
+        if self.strict {
+            self.validate_probabilities(probabilities.view())?;
+        }
+        self.try_add_node_from_log_probabilities(parents, probabilities.mapv(f32::ln))
     }
 
     /// Add a new node to the network from log-probabilities
@@ -110,57 +269,220 @@ impl BayesNet {
     /// process. For example, the log-vector `[0.0, -inf]` will represent a vector of probabilities of `[1.0, 0.0]`.
     ///
     /// Log-probabilities are intepreted as computed with the natural logarithm (base e).
+    ///
+    /// Panics if the array does not match the parents, see `try_add_node_from_log_probabilities`.
     pub fn add_node_from_log_probabilities<D: Dimension + RemoveAxis>(
         &mut self,
         parents: &[usize],
-        mut log_probabilities: Array<f32, D>,
+        log_probabilities: Array<f32, D>,
     ) -> usize {
-        let id = self.nodes.len();
-        // sanity checks
-        let shape = log_probabilities.shape();
-        assert!(
-            shape.len() == parents.len() + 1,
-            "Dimensions of log_probas array does not match number of parents"
-        );
-        for (i, (&val, &parent)) in shape.iter().skip(1).zip(parents.iter()).enumerate() {
-            let parent_n_val = self.nodes[parent].log_probas.shape()[0];
-            if parent_n_val != val {
-                panic!("Dimension {} of log_probas array does not match its associated parent number of element: got {} but parent {} has {}.", i+1, val, parent, parent_n_val);
-            }
+        self.try_add_node_from_log_probabilities(parents, log_probabilities)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Add a new node to the network from log-probabilities, reporting invalid inputs as errors
+    ///
+    /// Same as `add_node_from_log_probabilities`, but an error is returned rather than panicking if the
+    /// array does not match the parents, or if the log-probabilities are invalid in strict mode. The
+    /// network is left unchanged in that case.
+    pub fn try_add_node_from_log_probabilities<D: Dimension + RemoveAxis>(
+        &mut self,
+        parents: &[usize],
+        mut log_probabilities: Array<f32, D>,
+    ) -> Result<usize, BuildError> {
+        self.check_table(parents, log_probabilities.shape())?;
+        if self.strict {
+            self.validate_log_probabilities(log_probabilities.view())?;
         }
 
+        check_nan!(
+            log_probabilities,
+            "the log_probas array of node {}",
+            self.nodes.len()
+        );
+        crate::math::normalize_log_probas(log_probabilities.view_mut());
+        check_nan!(
+            log_probabilities,
+            "the normalization of the log_probas array of node {}",
+            self.nodes.len()
+        );
+
         // the shapes match, proceed to insert the node
+        let log_probas = Table::Dense(self.tables.intern(log_probabilities.into_dyn()));
+        Ok(self.push_node(parents, log_probas))
+    }
+
+    // insert a node whose table was checked against its parents
+    pub(crate) fn push_node(&mut self, parents: &[usize], log_probas: Table) -> usize {
+        let id = self.nodes.len();
+        self.priors.take();
         for &p in parents {
-            let size = self.nodes[p].log_probas.shape()[0];
+            let size = self.num_values(p);
             self.nodes[p]
                 .children
                 .push((id, LogProbVector::uniform(size)));
         }
-
-        crate::math::normalize_log_probas(log_probabilities.view_mut());
-
         let parents = parents
             .iter()
-            .map(|&p| {
-                (
-                    p,
-                    LogProbVector::uniform(self.nodes[p].log_probas.shape()[0]),
-                )
-            })
+            .map(|&p| (p, LogProbVector::uniform(self.num_values(p))))
             .collect();
-
         self.nodes.push(Node {
             parents,
             children: Vec::new(),
-            log_probas: log_probabilities.into_dyn(),
+            log_probas,
             evidence: None,
+            soft_evidence: None,
             lambda: None,
             pi: None,
+            name: None,
+            state_names: None,
+            tags: BTreeMap::new(),
+            localized: BTreeMap::new(),
+            layout: None,
+            dirichlet: None,
+            latent: false,
+            timed_evidence: None,
+            staleness: Staleness::default(),
+            measurement: None,
+            softmax: None,
+            calibration: None,
         });
-
         id
     }
 
+    /// Number of nodes in the network
+    pub fn num_nodes(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Number of possible values of a node
+    pub fn num_values(&self, node: usize) -> usize {
+        self.nodes[node].log_probas.num_values()
+    }
+
+    /// Parents of a node, in the order they were given at its creation
+    pub fn parents(&self, node: usize) -> Vec<usize> {
+        self.nodes[node].parents.iter().map(|&(p, _)| p).collect()
+    }
+
+    /// Children of a node, in the order they were added to the network
+    pub fn children(&self, node: usize) -> Vec<usize> {
+        self.nodes[node].children.iter().map(|&(c, _)| c).collect()
+    }
+
+    /// Ancestors of a node (its parents, their parents, and so on), in increasing order
+    pub fn ancestors(&self, node: usize) -> Vec<usize> {
+        let mut found = vec![false; self.nodes.len()];
+        let mut stack = vec![node];
+        while let Some(n) = stack.pop() {
+            for &(p, _) in &self.nodes[n].parents {
+                if !found[p] {
+                    found[p] = true;
+                    stack.push(p);
+                }
+            }
+        }
+        (0..self.nodes.len()).filter(|&i| found[i]).collect()
+    }
+
+    /// Descendants of a node (its children, their children, and so on), in increasing order
+    pub fn descendants(&self, node: usize) -> Vec<usize> {
+        let mut found = vec![false; self.nodes.len()];
+        let mut stack = vec![node];
+        while let Some(n) = stack.pop() {
+            for &(c, _) in &self.nodes[n].children {
+                if !found[c] {
+                    found[c] = true;
+                    stack.push(c);
+                }
+            }
+        }
+        (0..self.nodes.len()).filter(|&i| found[i]).collect()
+    }
+
+    /// Whether there is a directed path from `a` to `b`, a node not being its own ancestor
+    pub fn is_ancestor(&self, a: usize, b: usize) -> bool {
+        assert!(
+            a < self.nodes.len() && b < self.nodes.len(),
+            "Nodes {} and {} are not both in a network of {} nodes",
+            a,
+            b,
+            self.nodes.len()
+        );
+        // the nodes are topologically ordered, so the ancestors of `b` all have smaller ids
+        a < b && self.ancestors(b).binary_search(&a).is_ok()
+    }
+
+    /// All the directed paths from `a` to `b`, each given as the list of its nodes from `a` to `b`
+    ///
+    /// The paths are sorted in lexicographic order. The only path from a node to itself is the one
+    /// containing only this node. The number of paths can grow exponentially with the size of the
+    /// network.
+    pub fn all_directed_paths(&self, a: usize, b: usize) -> Vec<Vec<usize>> {
+        if a != b && !self.is_ancestor(a, b) {
+            return Vec::new();
+        }
+        // only explore the nodes from which `b` can be reached
+        let mut leads_to_b = vec![false; self.nodes.len()];
+        leads_to_b[b] = true;
+        for n in self.ancestors(b) {
+            leads_to_b[n] = true;
+        }
+        let mut paths = Vec::new();
+        let mut path = vec![a];
+        // for each node of the path, the index of the next child to explore
+        let mut next_child = vec![0];
+        while let Some(&n) = path.last() {
+            if n == b {
+                paths.push(path.clone());
+                path.pop();
+                next_child.pop();
+                continue;
+            }
+            let i = next_child.last_mut().unwrap();
+            match self.nodes[n].children.get(*i) {
+                Some(&(c, _)) => {
+                    *i += 1;
+                    if leads_to_b[c] {
+                        path.push(c);
+                        next_child.push(0);
+                    }
+                }
+                None => {
+                    path.pop();
+                    next_child.pop();
+                }
+            }
+        }
+        paths.sort();
+        paths
+    }
+
+    /// Replace the log-probability table of a node, which must have the same shape as the previous one
+    pub(crate) fn replace_log_probas(&mut self, node: usize, mut log_probas: ArrayD<f32>) {
+        assert!(
+            log_probas.shape() == self.nodes[node].log_probas.shape(),
+            "New log_probas array of {} has shape {:?} instead of {:?}",
+            self.node_ref(node),
+            log_probas.shape(),
+            self.nodes[node].log_probas.shape()
+        );
+        crate::math::normalize_log_probas(log_probas.view_mut());
+        let log_probas = Table::Dense(self.tables.intern(log_probas));
+        self.set_table(node, log_probas);
+    }
+
+    // replace the table of a node, dropping its softmax weights and the cached computations depending
+    // on it
+    pub(crate) fn set_table(&mut self, node: usize, log_probas: Table) {
+        let node = &mut self.nodes[node];
+        node.log_probas = log_probas;
+        node.softmax = None;
+        self.priors.take();
+        node.lambda = None;
+        node.pi = None;
+    }
+
     /// Sets the evidence for the network
     ///
     /// Input is interpreted as a list of `(node_id, node_value)`. Out-of-range evidence is not checked, but
@@ -187,28 +509,131 @@ impl BayesNet {
             node.lambda = None;
             node.pi = None;
         }
+        if let Some(ref mut settled) = self.sparse {
+            settled.iter_mut().for_each(|s| *s = false);
+        }
+        self.iteration = 0;
+    }
+
+    /// Number of steps of inference run since the last call to `reset_state`
+    pub fn iteration(&self) -> usize {
+        self.iteration
     }
 
     /// Compute the current state belief of each node according to the current internal messages
     pub fn beliefs(&self) -> Vec<LogProbVector> {
+        self.beliefs_in::<SumProduct>()
+    }
+
+    /// Compute the beliefs over a semiring, from the current messages
+    ///
+    /// The beliefs are normalized for the semiring, see the `semiring` module.
+    pub fn beliefs_in<S: Semiring>(&self) -> Vec<LogProbVector> {
         self.nodes
             .iter()
             .map(|node| {
                 let mut lambda = node.lambda.clone().unwrap_or_else(|| node.compute_lambda());
-                let pi = node.pi.clone().unwrap_or_else(|| node.compute_pi());
+                let pi = node.pi.clone().unwrap_or_else(|| node.compute_pi::<S>());
                 lambda.prod(&pi);
-                lambda.renormalize();
+                normalize::<S>(&mut lambda);
                 lambda
             })
             .collect()
     }
 
+    /// Compute the normalized joint log-belief of a node and its parents according to the current messages
+    ///
+    /// The returned array has the same shape as the probability table of the node.
+    pub(crate) fn family_log_beliefs(&self, id: usize) -> ArrayD<f32> {
+        let node = &self.nodes[id];
+        let mut family = node.log_probas.dense().into_owned();
+        let lambda = node.lambda.clone().unwrap_or_else(|| node.compute_lambda());
+        for mut lane in family.lanes_mut(Axis(0)) {
+            lane += &lambda.log_probabilities();
+        }
+        for (axis, (_, msg)) in node.parents.iter().enumerate() {
+            for mut lane in family.lanes_mut(Axis(axis + 1)) {
+                lane += &msg.log_probabilities();
+            }
+        }
+        let norm =
+            crate::math::log_sum_exp_vec(family.iter().cloned().collect::<Array1<f32>>().view());
+        if norm.is_finite() {
+            family -= norm;
+        }
+        family
+    }
+
+    /// Estimate of `log P(evidence)`, the log-probability of the hard and soft evidence under the model
+    ///
+    /// It is computed from the current messages using the Bethe free energy (see
+    /// `bethe_free_energy`), so it should be queried once the propagation has converged, and is then
+    /// exact for networks without loops. The difference of log-evidence between two models, or
+    /// between two sets of evidence, scores competing hypotheses. See `exact_log_evidence` for an exact
+    /// value on small networks.
+    pub fn log_evidence(&self) -> f32 {
+        self.bethe_log_evidence()
+    }
+
+    /// Estimate `log P(evidence)` from the current messages using the Bethe free energy
+    ///
+    /// This is exact once the algorithm has converged on a network without loops.
+    pub(crate) fn bethe_log_evidence(&self) -> f32 {
+        self.bethe_node_terms().iter().sum::<f64>() as f32
+    }
+
+    // contribution of each node to `bethe_log_evidence`
+    pub(crate) fn bethe_node_terms(&self) -> Vec<f64> {
+        let beliefs = self.beliefs();
+        let mut terms = Vec::with_capacity(self.nodes.len());
+        for (id, node) in self.nodes.iter().enumerate() {
+            let mut log_z = 0.0f64;
+            let family = self.family_log_beliefs(id);
+            let evidence = node.evidence_vec();
+            let evidence = evidence.log_probabilities();
+            let table = node.log_probas.dense();
+            for (b_lane, p_lane) in family.lanes(Axis(0)).into_iter().zip(table.lanes(Axis(0))) {
+                Zip::from(&b_lane)
+                    .and(&p_lane)
+                    .and(&evidence)
+                    .for_each(|&b, &p, &e| {
+                        if b > f32::NEG_INFINITY {
+                            log_z += f64::from(b.exp()) * f64::from(p + e - b);
+                        }
+                    });
+            }
+            let extra_degree = node.children.len() as f64;
+            if extra_degree > 0.0 {
+                for &b in beliefs[id].log_probabilities() {
+                    if b > f32::NEG_INFINITY {
+                        log_z += extra_degree * f64::from(b.exp()) * f64::from(b);
+                    }
+                }
+            }
+            terms.push(log_z);
+        }
+        terms
+    }
+
     /// Compute one step of the Loopy Belief Propagation Algorithm
     ///
     /// The algorithm can be run for any number of steps. it is up to you to decide when to stop.
     ///
     /// A classic stopping criterion is when the yielded beliefs stop significantly changing.
     pub fn step(&mut self) {
+        self.step_in::<SumProduct>()
+    }
+
+    /// Compute one step of the message passing over a semiring
+    ///
+    /// `step` is the same as `step_in::<SumProduct>`. Messages of different semirings should not be
+    /// mixed: call `reset_state` before switching to another semiring.
+    pub fn step_in<S: Semiring>(&mut self) {
+        self.propagate::<S>(self.damping)
+    }
+
+    // one step of the message passing, the new messages being damped towards the previous ones
+    pub(crate) fn propagate<S: Semiring>(&mut self, damping: f32) {
         // At the start of the algorithm, we assume all present cached values for lambda and pi are valid for
         // the currently stored messages. We will then compute the new messages and invalidate the caches.
 
@@ -221,9 +646,21 @@ impl BayesNet {
         let mut lambda_msgs: Vec<(usize, usize, LogProbVector)> =
             Vec::with_capacity(self.nodes.iter().map(|n| n.parents.len()).sum());
 
+        let skipped = self.settle_idle_components();
+        let numerics = self.numerics;
+
         for (id, node) in self.nodes.iter_mut().enumerate() {
+            if skipped[id] {
+                continue;
+            }
             // compute the pi messages:
-            let mut pi = node.get_or_compute_pi();
+            let mut pi = node.get_or_compute_pi::<S>();
+            check_nan!(
+                pi.log_probabilities(),
+                "the pi vector of node {} at step {}",
+                id,
+                self.iteration
+            );
             pi.prod(&node.evidence_vec());
             for &(child_id, _) in &node.children {
                 let mut msg = node
@@ -234,28 +671,35 @@ impl BayesNet {
                         acc.prod(v);
                         acc
                     });
-                msg.renormalize();
+                numerics.normalize_message::<S>(&mut msg);
+                check_nan!(
+                    msg.log_probabilities(),
+                    "the message from node {} to its child {} at step {}",
+                    id,
+                    child_id,
+                    self.iteration
+                );
                 pi_msgs.push((id, child_id, msg));
             }
 
             // compute the lambda messages:
             let lambda = node.get_or_compute_lambda();
-            for &(parent_id, _) in &node.parents {
-                let acc = node
-                    .parents
-                    .iter()
-                    .enumerate()
-                    .rev()
-                    .filter(|&(_, &(pid, _))| pid != parent_id)
-                    .fold(node.log_probas.clone(), |acc, (axid, &(_, ref v))| {
-                        crate::math::log_contract(acc.view(), v.log_probabilities(), Axis(axid + 1))
-                    });
-                let acc =
-                    crate::math::log_contract(acc.view(), lambda.log_probabilities(), Axis(0));
-                assert!(acc.ndim() == 1);
-                let shape = (acc.len(),);
-                let mut msg = LogProbVector::from_log_probabilities(acc.into_shape(shape).unwrap());
-                msg.renormalize();
+            check_nan!(
+                lambda.log_probabilities(),
+                "the lambda vector of node {} at step {}",
+                id,
+                self.iteration
+            );
+            for (axid, &(parent_id, _)) in node.parents.iter().enumerate() {
+                let mut msg = node.compute_lambda_msg::<S>(axid, &lambda);
+                numerics.normalize_message::<S>(&mut msg);
+                check_nan!(
+                    msg.log_probabilities(),
+                    "the message from node {} to its parent {} at step {}",
+                    id,
+                    parent_id,
+                    self.iteration
+                );
                 lambda_msgs.push((id, parent_id, msg));
             }
 
@@ -271,11 +715,13 @@ impl BayesNet {
                 .iter_mut()
                 .find(|&&mut (parent_id, _)| parent_id == from)
             {
-                *place = msg;
+                *place = damp::<S>(place, msg, damping);
             } else {
                 panic!(
-                    "Message from {} to {} who doesn't recognize its parent?!",
-                    from, to
+                    "Message from {} to {} who doesn't recognize its parent?! (at step {})",
+                    self.node_ref(from),
+                    self.node_ref(to),
+                    self.iteration
                 );
             }
         }
@@ -285,13 +731,16 @@ impl BayesNet {
                 .iter_mut()
                 .find(|&&mut (child_id, _)| child_id == from)
             {
-                *place = msg;
+                *place = damp::<S>(place, msg, damping);
             } else {
                 panic!(
-                    "Message from {} to {} who doesn't recognize its child?!",
-                    from, to
+                    "Message from {} to {} who doesn't recognize its child?! (at step {})",
+                    self.node_ref(from),
+                    self.node_ref(to),
+                    self.iteration
                 );
             }
         }
+        self.iteration += 1;
     }
 }
@@ -0,0 +1,164 @@
+use crate::semiring::{normalize, Semiring};
+use crate::{BayesNet, LogProbVector};
+
+/// The tolerances and thresholds used by the numeric computations of a network
+///
+/// The default policy keeps every computation exact up to the float precision. `high_precision` and
+/// `speed_first` give coherent sets of values for the corresponding regimes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NumericsPolicy {
+    /// Largest change of a belief probability between two steps for which the propagation is
+    /// considered converged by `BayesNet::step_until_converged`
+    pub convergence_tolerance: f32,
+    /// Tolerance used when comparing probabilities, for example by the credal networks to check that
+    /// interval bounds are consistent
+    pub comparison_epsilon: f32,
+    /// Log-probabilities of messages at or below this value are flushed to `-inf` (probability `0`)
+    /// after each step, which makes messages sparser
+    pub zero_log_probability: f32,
+    /// Messages whose log-sum is below this value after a step carry no usable information and are
+    /// reset to uniform, rather than being normalized into NaNs
+    pub normalization_floor: f32,
+    /// Finite value to which log-probabilities are clamped when an algorithm needs finite values, such
+    /// as the extrapolation of `AndersonAcceleration`
+    pub log_floor: f32,
+    /// Relative regularization of the least-squares problems solved by `AndersonAcceleration`
+    pub regularization: f32,
+}
+
+impl Default for NumericsPolicy {
+    fn default() -> NumericsPolicy {
+        NumericsPolicy {
+            convergence_tolerance: 1e-5,
+            comparison_epsilon: 1e-6,
+            zero_log_probability: f32::NEG_INFINITY,
+            normalization_floor: f32::NEG_INFINITY,
+            log_floor: -50.0,
+            regularization: 1e-10,
+        }
+    }
+}
+
+impl NumericsPolicy {
+    /// A policy for high-precision regimes: tight tolerances and no shortcuts
+    pub fn high_precision() -> NumericsPolicy {
+        NumericsPolicy {
+            convergence_tolerance: 1e-7,
+            comparison_epsilon: 1e-7,
+            log_floor: -80.0,
+            regularization: 1e-12,
+            ..NumericsPolicy::default()
+        }
+    }
+
+    /// A policy for speed-first regimes: loose tolerances, and negligible probabilities are dropped
+    pub fn speed_first() -> NumericsPolicy {
+        NumericsPolicy {
+            convergence_tolerance: 1e-3,
+            comparison_epsilon: 1e-4,
+            zero_log_probability: -30.0,
+            normalization_floor: -80.0,
+            log_floor: -30.0,
+            regularization: 1e-8,
+        }
+    }
+
+    // normalize a message computed by a step
+    pub(crate) fn normalize_message<S: Semiring>(&self, msg: &mut LogProbVector) {
+        if S::sum(msg.log_probabilities()) < self.normalization_floor {
+            msg.reset();
+            normalize::<S>(msg);
+            return;
+        }
+        normalize::<S>(msg);
+        if self.zero_log_probability > f32::NEG_INFINITY {
+            let threshold = self.zero_log_probability;
+            let flushed =
+                msg.log_probabilities().mapv(
+                    |v| {
+                        if v <= threshold {
+                            f32::NEG_INFINITY
+                        } else {
+                            v
+                        }
+                    },
+                );
+            *msg = LogProbVector::from_log_probabilities(flushed);
+        }
+    }
+}
+
+// the largest difference of probability of a value between two lists of distributions, NaN if any
+// difference is NaN
+pub(crate) fn max_change(before: &[LogProbVector], after: &[LogProbVector]) -> f32 {
+    let mut change = 0.0f32;
+    for (a, b) in before.iter().zip(after) {
+        for d in (a.as_probabilities() - b.as_probabilities()).iter() {
+            if d.is_nan() {
+                return f32::NAN;
+            }
+            change = change.max(d.abs());
+        }
+    }
+    change
+}
+
+impl BayesNet {
+    /// Set the numeric policy of the network
+    pub fn set_numerics(&mut self, policy: NumericsPolicy) {
+        self.numerics = policy;
+    }
+
+    /// The numeric policy of the network
+    pub fn numerics(&self) -> &NumericsPolicy {
+        &self.numerics
+    }
+
+    /// Compute one step of the Loopy Belief Propagation, and return how much it changed the messages
+    ///
+    /// The change is the largest difference of probability of a value of a message between before and
+    /// after the step, which is a cheap stopping criterion: the messages are fixed at convergence.
+    /// The change is NaN if any message is NaN, as after impossible evidence, so that it is never
+    /// below a tolerance.
+    pub fn step_with_delta(&mut self) -> f32 {
+        let before = self.messages();
+        self.step();
+        max_change(&before, &self.messages())
+    }
+
+    /// Run steps until the beliefs stabilize
+    ///
+    /// The propagation is converged once no belief probability changes by more than the
+    /// `convergence_tolerance` of the numeric policy during a step. Returns the number of steps run,
+    /// or `None` if the propagation did not converge within `max_iterations` steps.
+    pub fn step_until_converged(&mut self, max_iterations: usize) -> Option<usize> {
+        let tolerance = self.numerics.convergence_tolerance;
+        match self.run_until_convergence(tolerance, max_iterations) {
+            (iterations, true) => Some(iterations),
+            (_, false) => None,
+        }
+    }
+
+    /// Run steps until no belief probability changes by more than `tolerance` during a step
+    ///
+    /// At most `max_iterations` steps are run. Returns the number of steps run, and whether the
+    /// propagation converged. Beliefs which are NaN, as after impossible evidence, are never
+    /// converged.
+    pub fn run_until_convergence(
+        &mut self,
+        tolerance: f32,
+        max_iterations: usize,
+    ) -> (usize, bool) {
+        let mut previous = self.beliefs();
+        for iteration in 1..=max_iterations {
+            self.step();
+            let beliefs = self.beliefs();
+            // NaN beliefs never converge
+            if max_change(&previous, &beliefs) <= tolerance {
+                return (iteration, true);
+            }
+            previous = beliefs;
+        }
+        (max_iterations, false)
+    }
+}
@@ -0,0 +1,195 @@
+//! Combination of the posteriors computed by several models about a same variable
+//!
+//! Each model provides a `LogProbVector` over the same set of values, and the pooled distribution
+//! is a weighted combination of them. Weights can be calibrated from validation data, as the ones
+//! maximizing the log-score of the pooled predictions.
+
+use crate::LogProbVector;
+use ndarray::Array1;
+
+// floor applied to log-probabilities when calibrating logarithmic pools, to keep the gradients finite
+const LOG_FLOOR: f32 = -23.0;
+
+/// How the distributions of the models are combined
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pooling {
+    /// Weighted average of the probabilities, `p(x) = sum_i w_i p_i(x)`
+    ///
+    /// The weights are normalized to sum to 1.
+    Linear,
+    /// Weighted geometric average of the probabilities, `p(x) ∝ prod_i p_i(x)^w_i`
+    ///
+    /// The weights are not normalized: weights summing to more than 1 make the pooled distribution
+    /// sharper than the individual ones, which is appropriate when the models are based on independent
+    /// information.
+    Logarithmic,
+}
+
+impl Pooling {
+    /// Pool the distributions of several models, with one non-negative weight per model
+    ///
+    /// The result is normalized. Panics if a weight is negative or NaN, or if all the weights of a
+    /// linear pool are zero.
+    pub fn pool(self, distributions: &[LogProbVector], weights: &[f32]) -> LogProbVector {
+        assert!(
+            distributions.len() == weights.len(),
+            "Pooling needs one weight per distribution"
+        );
+        assert!(
+            !distributions.is_empty(),
+            "Pooling needs at least one distribution"
+        );
+        if let Some(model) = weights.iter().position(|&w| w.is_nan() || w < 0.0) {
+            panic!(
+                "Pooling weights must be non-negative, the weight of model {} is {}",
+                model, weights[model]
+            );
+        }
+        let n = distributions[0].log_probabilities().len();
+        assert!(
+            distributions
+                .iter()
+                .all(|d| d.log_probabilities().len() == n),
+            "Pooled distributions must all have the same number of values"
+        );
+        let mut pooled = match self {
+            Pooling::Linear => {
+                let total: f32 = weights.iter().sum();
+                assert!(
+                    total > 0.0,
+                    "Linear pooling needs a positive weight, got {:?}",
+                    weights
+                );
+                let probas = distributions
+                    .iter()
+                    .zip(weights.iter())
+                    .fold(Array1::zeros(n), |acc: Array1<f32>, (d, &w)| {
+                        acc + d.as_probabilities() * (w / total)
+                    });
+                LogProbVector::from_log_probabilities(probas.mapv(f32::ln))
+            }
+            Pooling::Logarithmic => {
+                let log_probas = distributions
+                    .iter()
+                    .zip(weights.iter())
+                    .filter(|&(_, &w)| w > 0.0)
+                    .fold(Array1::zeros(n), |acc: Array1<f32>, (d, &w)| {
+                        acc + normalized(d) * w
+                    });
+                LogProbVector::from_log_probabilities(log_probas)
+            }
+        };
+        pooled.renormalize();
+        pooled
+    }
+
+    /// Calibrate the weights of the models from validation data
+    ///
+    /// `predictions[k]` contains the distribution predicted by each model for the `k`-th validation case,
+    /// and `outcomes[k]` the value that was actually observed. The weights maximizing the log-score of
+    /// the pooled predictions are searched for `iterations` iterations, starting from equal weights:
+    /// with the expectation-maximization algorithm for linear pools, and with gradient ascent for
+    /// logarithmic pools.
+    pub fn calibrate(
+        self,
+        predictions: &[Vec<LogProbVector>],
+        outcomes: &[usize],
+        iterations: usize,
+    ) -> Vec<f32> {
+        assert!(
+            predictions.len() == outcomes.len(),
+            "Calibration needs one outcome per validation case"
+        );
+        let n_models = predictions.first().map(|p| p.len()).unwrap_or(0);
+        assert!(
+            predictions.iter().all(|p| p.len() == n_models),
+            "Each validation case must have one prediction per model"
+        );
+        if n_models == 0 || predictions.is_empty() {
+            return vec![1.0; n_models];
+        }
+        match self {
+            Pooling::Linear => calibrate_linear(predictions, outcomes, n_models, iterations),
+            Pooling::Logarithmic => {
+                calibrate_logarithmic(predictions, outcomes, n_models, iterations)
+            }
+        }
+    }
+}
+
+fn normalized(distribution: &LogProbVector) -> Array1<f32> {
+    let mut d = distribution.clone();
+    d.renormalize();
+    d.log_probabilities().to_owned()
+}
+
+fn calibrate_linear(
+    predictions: &[Vec<LogProbVector>],
+    outcomes: &[usize],
+    n_models: usize,
+    iterations: usize,
+) -> Vec<f32> {
+    // probability given by each model to the observed outcome
+    let likelihoods: Vec<Vec<f32>> = predictions
+        .iter()
+        .zip(outcomes.iter())
+        .map(|(models, &y)| models.iter().map(|d| d.as_probabilities()[y]).collect())
+        .collect();
+    let mut weights = vec![1.0 / n_models as f32; n_models];
+    for _ in 0..iterations {
+        // E-step: responsibility of each model for each outcome, M-step: their averages
+        let mut totals = vec![0.0; n_models];
+        for case in &likelihoods {
+            let mixture: f32 = case.iter().zip(weights.iter()).map(|(&l, &w)| l * w).sum();
+            if mixture > 0.0 {
+                for ((t, &l), &w) in totals.iter_mut().zip(case.iter()).zip(weights.iter()) {
+                    *t += l * w / mixture;
+                }
+            }
+        }
+        let sum: f32 = totals.iter().sum();
+        if sum <= 0.0 {
+            break;
+        }
+        weights = totals.into_iter().map(|t| t / sum).collect();
+    }
+    weights
+}
+
+fn calibrate_logarithmic(
+    predictions: &[Vec<LogProbVector>],
+    outcomes: &[usize],
+    n_models: usize,
+    iterations: usize,
+) -> Vec<f32> {
+    let cases: Vec<Vec<Array1<f32>>> = predictions
+        .iter()
+        .map(|models| {
+            models
+                .iter()
+                .map(|d| normalized(d).mapv(|v| v.max(LOG_FLOOR)))
+                .collect()
+        })
+        .collect();
+    let step = 0.1 / cases.len() as f32;
+    let mut weights = vec![1.0 / n_models as f32; n_models];
+    for _ in 0..iterations {
+        // gradient of the log-score: log p_i(y) - E_pool[log p_i]
+        let mut gradient = vec![0.0; n_models];
+        for (models, &y) in cases.iter().zip(outcomes.iter()) {
+            let mut pooled = models.iter().zip(weights.iter()).fold(
+                Array1::zeros(models[0].len()),
+                |acc: Array1<f32>, (d, &w)| acc + d * w,
+            );
+            let norm = crate::math::log_sum_exp_vec(pooled.view());
+            pooled.mapv_inplace(|v| (v - norm).exp());
+            for (g, d) in gradient.iter_mut().zip(models.iter()) {
+                *g += d[y] - (&pooled * d).sum();
+            }
+        }
+        for (w, g) in weights.iter_mut().zip(gradient.iter()) {
+            *w = (*w + step * g).max(0.0);
+        }
+    }
+    weights
+}
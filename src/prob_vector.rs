@@ -26,7 +26,7 @@ impl LogProbVector {
     ///
     /// If `i >= n`, this returns a vector assigning 0 probability to every value.
     pub fn deterministic(n: usize, i: usize) -> LogProbVector {
-        let mut data = vec![std::f32::NEG_INFINITY; n];
+        let mut data = vec![f32::NEG_INFINITY; n];
         if i < n {
             data[i] = 0.0;
         }
@@ -41,7 +41,7 @@ impl LogProbVector {
     }
 
     /// Access the underlying array of log-probas
-    pub fn log_probabilities(&self) -> ArrayView1<f32> {
+    pub fn log_probabilities(&self) -> ArrayView1<'_, f32> {
         self.log_probabilities.view()
     }
 
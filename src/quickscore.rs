@@ -0,0 +1,205 @@
+use crate::{BayesNet, LogProbVector};
+use ndarray::Array1;
+
+// above this number of positive findings, the 2^n terms of the Quickscore sum are too costly
+const MAX_POSITIVE_FINDINGS: usize = 20;
+
+// a two-layer network of binary nodes, in which each finding is a noisy-OR of its diseases
+struct NoisyOrModel {
+    diseases: Vec<usize>,
+    findings: Vec<usize>,
+    // probability of each disease, given its evidence
+    priors: Vec<f64>,
+    // probability of each finding being negative without any disease
+    leaks: Vec<f64>,
+    // for each finding and disease, the probability that the disease does not make it positive
+    inhibitions: Vec<Vec<f64>>,
+}
+
+impl NoisyOrModel {
+    // P(the findings of `negative` are negative), and the same jointly with each disease present
+    fn negative_probability(&self, negative: &[usize]) -> (f64, Vec<f64>) {
+        let leak: f64 = negative.iter().map(|&f| self.leaks[f]).product();
+        let factors: Vec<(f64, f64)> = (0..self.diseases.len())
+            .map(|d| {
+                let inhibited: f64 = negative.iter().map(|&f| self.inhibitions[f][d]).product();
+                (1.0 - self.priors[d], self.priors[d] * inhibited)
+            })
+            .collect();
+        let total: f64 = factors
+            .iter()
+            .map(|&(absent, present)| absent + present)
+            .product();
+        let with_disease = factors
+            .iter()
+            .map(|&(absent, present)| {
+                if absent + present > 0.0 {
+                    leak * total * present / (absent + present)
+                } else {
+                    0.0
+                }
+            })
+            .collect();
+        (leak * total, with_disease)
+    }
+
+    // P(evidence) and P(disease present, evidence) for each disease, by inclusion-exclusion over the
+    // positive findings
+    fn evidence_probability(&self, positive: &[usize], negative: &[usize]) -> (f64, Vec<f64>) {
+        let mut total = 0.0;
+        let mut with_disease = vec![0.0; self.diseases.len()];
+        let mut findings = negative.to_vec();
+        for subset in 0..1usize << positive.len() {
+            findings.truncate(negative.len());
+            findings.extend(
+                (0..positive.len())
+                    .filter(|&i| (subset >> i) & 1 == 1)
+                    .map(|i| positive[i]),
+            );
+            let sign = if (findings.len() - negative.len()).is_multiple_of(2) {
+                1.0
+            } else {
+                -1.0
+            };
+            let (p, p_diseases) = self.negative_probability(&findings);
+            total += sign * p;
+            for (acc, p) in with_disease.iter_mut().zip(p_diseases) {
+                *acc += sign * p;
+            }
+        }
+        (total, with_disease)
+    }
+}
+
+// the probability of the first value of a table, for the given values of the parents
+fn probability(net: &BayesNet, node: usize, parent_values: &[usize]) -> f64 {
+    let mut index = vec![0];
+    index.extend_from_slice(parent_values);
+    f64::from(net.nodes[node].log_probas.get(&index)).exp()
+}
+
+impl BayesNet {
+    // the noisy-OR model of the network, if it is a two-layer noisy-OR network
+    fn noisy_or_model(&self) -> Option<NoisyOrModel> {
+        let binary = (0..self.nodes.len()).all(|n| self.num_values(n) == 2);
+        let soft = self.nodes.iter().any(|n| n.soft_likelihood().is_some());
+        if !binary || soft || self.nodes.is_empty() {
+            return None;
+        }
+        let diseases: Vec<usize> = (0..self.nodes.len())
+            .filter(|&n| self.nodes[n].parents.is_empty())
+            .collect();
+        let findings: Vec<usize> = (0..self.nodes.len())
+            .filter(|&n| !self.nodes[n].parents.is_empty())
+            .collect();
+        let mut index_of = vec![0; self.nodes.len()];
+        for (i, &d) in diseases.iter().enumerate() {
+            index_of[d] = i;
+        }
+        let mut priors = Vec::with_capacity(diseases.len());
+        for &d in &diseases {
+            let prior = 1.0 - probability(self, d, &[]);
+            priors.push(match self.nodes[d].evidence {
+                // impossible evidence
+                Some(1) if prior <= 0.0 => return None,
+                Some(0) if prior >= 1.0 => return None,
+                Some(value) => value as f64,
+                None => prior,
+            });
+        }
+
+        let mut leaks = Vec::with_capacity(findings.len());
+        let mut inhibitions = Vec::with_capacity(findings.len());
+        for &f in &findings {
+            let parents = self.parents(f);
+            if parents.iter().any(|&p| !self.nodes[p].parents.is_empty()) {
+                return None;
+            }
+            let leak = probability(self, f, &vec![0; parents.len()]);
+            if leak <= 0.0 {
+                return None;
+            }
+            let mut inhibition = vec![1.0; diseases.len()];
+            for (i, &p) in parents.iter().enumerate() {
+                let mut values = vec![0; parents.len()];
+                values[i] = 1;
+                inhibition[index_of[p]] = probability(self, f, &values) / leak;
+            }
+            // every column must factorize as a noisy-OR
+            for config in 0..1usize << parents.len() {
+                let values: Vec<usize> = (0..parents.len()).map(|i| (config >> i) & 1).collect();
+                let expected: f64 = leak
+                    * parents
+                        .iter()
+                        .zip(&values)
+                        .filter(|&(_, &v)| v == 1)
+                        .map(|(&p, _)| inhibition[index_of[p]])
+                        .product::<f64>();
+                if (probability(self, f, &values) - expected).abs() > 1e-5 {
+                    return None;
+                }
+            }
+            leaks.push(leak);
+            inhibitions.push(inhibition);
+        }
+        Some(NoisyOrModel {
+            diseases,
+            findings,
+            priors,
+            leaks,
+            inhibitions,
+        })
+    }
+
+    /// Compute the exact beliefs of a two-layer noisy-OR network with the Quickscore algorithm
+    ///
+    /// This applies to networks of binary nodes in which every node is either a root (a disease) or
+    /// has only roots as parents (a finding), with noisy-OR tables for the findings: value `1` of a
+    /// finding is caused independently by each of its diseases with value `1`, or by a leak. The cost
+    /// is exponential in the number of positive findings only, instead of the treewidth of the
+    /// network, and `exact_beliefs` uses this algorithm automatically when it applies.
+    ///
+    /// Returns `None` if the network does not have this structure, has soft evidence, has more than
+    /// 20 findings observed as positive, or if the evidence is impossible.
+    pub fn quickscore_beliefs(&self) -> Option<Vec<LogProbVector>> {
+        let model = self.noisy_or_model()?;
+        let mut positive = Vec::new();
+        let mut negative = Vec::new();
+        for (i, &f) in model.findings.iter().enumerate() {
+            match self.nodes[f].evidence {
+                Some(0) => negative.push(i),
+                Some(_) => positive.push(i),
+                None => {}
+            }
+        }
+        if positive.len() > MAX_POSITIVE_FINDINGS {
+            return None;
+        }
+        let (total, with_disease) = model.evidence_probability(&positive, &negative);
+        if total <= 0.0 {
+            return None;
+        }
+
+        let mut beliefs = vec![LogProbVector::uniform(2); self.nodes.len()];
+        let belief = |p: f64| {
+            let p = (p / total).clamp(0.0, 1.0) as f32;
+            LogProbVector::from_log_probabilities(Array1::from(vec![(1.0 - p).ln(), p.ln()]))
+        };
+        for (&d, p) in model.diseases.iter().zip(with_disease) {
+            beliefs[d] = belief(p);
+        }
+        for (i, &f) in model.findings.iter().enumerate() {
+            beliefs[f] = match self.nodes[f].evidence {
+                Some(value) => LogProbVector::deterministic(2, value),
+                None => {
+                    // P(finding negative, evidence)
+                    negative.push(i);
+                    let (p_negative, _) = model.evidence_probability(&positive, &negative);
+                    negative.pop();
+                    belief(total - p_negative)
+                }
+            };
+        }
+        Some(beliefs)
+    }
+}
@@ -0,0 +1,96 @@
+use crate::math::normalize_table;
+use crate::semiring::MinPlus;
+use crate::table::Table;
+use crate::BayesNet;
+use ndarray::{Array, Array1, Dimension, RemoveAxis};
+
+/// The rank of an impossible world
+pub const INFINITE_RANK: u32 = u32::MAX;
+
+/// A qualitative network over ranked worlds (Spohn's ranking functions, or kappa calculus)
+///
+/// Instead of probabilities, each node holds conditional ranks `κ(x | parents)`: non-negative integers
+/// measuring how surprising each value is, `0` meaning "not surprising at all" and `INFINITE_RANK`
+/// meaning impossible. Ranks combine by addition and are marginalized by taking the minimum, so the
+/// ranks can be seen as the orders of magnitude of infinitesimal probabilities `ε^κ`.
+///
+/// Inference is done by the same message passing as `BayesNet`, over the min-plus semiring. It is
+/// exact for networks without loops.
+#[derive(Debug, Clone, Default)]
+pub struct RankingNet {
+    net: BayesNet,
+}
+
+impl RankingNet {
+    /// Create a new empty ranking network
+    pub fn new() -> RankingNet {
+        RankingNet {
+            net: BayesNet::new(),
+        }
+    }
+
+    /// Add a new node to the network
+    ///
+    /// The array of ranks has the same shape as the probability tables of `BayesNet`: `(N, N_p1, ... N_pk)`.
+    /// The ranks do not need to be normalized: for each configuration of the parents, the smallest rank
+    /// is subtracted from all the ranks, so that at least one value has rank `0`.
+    pub fn add_node<D: Dimension + RemoveAxis>(
+        &mut self,
+        parents: &[usize],
+        ranks: Array<u32, D>,
+    ) -> usize {
+        let mut table = ranks.mapv(|r| {
+            if r == INFINITE_RANK {
+                f32::NEG_INFINITY
+            } else {
+                -(r as f32)
+            }
+        });
+        normalize_table::<MinPlus, _>(table.view_mut());
+        let id = self
+            .net
+            .add_node_from_log_probabilities(parents, table.clone());
+        // overwrite the table normalized for probabilities
+        self.net.nodes[id].log_probas = Table::Dense(self.net.tables.intern(table.into_dyn()));
+        id
+    }
+
+    /// Number of nodes in the network
+    pub fn num_nodes(&self) -> usize {
+        self.net.num_nodes()
+    }
+
+    /// Sets the evidence for the network, as for `BayesNet::set_evidence`
+    pub fn set_evidence(&mut self, evidence: &[(usize, usize)]) {
+        self.net.set_evidence(evidence);
+    }
+
+    /// Resets the internal state of the inference algorithm, as for `BayesNet::reset_state`
+    pub fn reset_state(&mut self) {
+        self.net.reset_state();
+    }
+
+    /// Compute one step of the message passing
+    pub fn step(&mut self) {
+        self.net.step_in::<MinPlus>();
+    }
+
+    /// The ranks of the values of each node given the evidence, `κ(x | evidence)`
+    ///
+    /// The most plausible values of each node have rank `0`.
+    pub fn ranks(&self) -> Vec<Array1<u32>> {
+        self.net
+            .beliefs_in::<MinPlus>()
+            .iter()
+            .map(|belief| belief.log_probabilities().mapv(to_rank))
+            .collect()
+    }
+}
+
+fn to_rank(value: f32) -> u32 {
+    if value.is_finite() {
+        (-value).round() as u32
+    } else {
+        INFINITE_RANK
+    }
+}
@@ -0,0 +1,196 @@
+use crate::{BayesNet, LogProbVector};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::sync::{Arc, Mutex, RwLock};
+
+/// A network registered in a `ModelRegistry`, together with its version
+///
+/// Handles are immutable snapshots: reloading a model replaces the handle stored
+/// in the registry, but handles obtained before the reload keep the previous network.
+#[derive(Debug)]
+pub struct ModelHandle {
+    name: String,
+    version: u64,
+    net: BayesNet,
+}
+
+impl ModelHandle {
+    /// Name under which this model is registered
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Version of this model
+    ///
+    /// Versions start at `1` and are incremented each time a model is (re)loaded under the same name,
+    /// even if it was removed in between, so that a name and a version always identify one network.
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    /// Access the network of this model
+    pub fn net(&self) -> &BayesNet {
+        &self.net
+    }
+
+    /// Run an inference query on this model
+    ///
+    /// The query runs on a private copy of the network, so concurrent queries on the same handle
+    /// don't interfere with each other. The loopy belief propagation is run for `iterations` steps
+    /// from a freshly reset state, and the resulting beliefs are returned, calibrated for the nodes
+    /// having a calibration (see `BayesNet::set_calibration`).
+    pub fn query(&self, evidence: &[(usize, usize)], iterations: usize) -> Vec<LogProbVector> {
+        let mut net = self.net.clone();
+        net.reset_state();
+        net.set_evidence(evidence);
+        for _ in 0..iterations {
+            net.step();
+        }
+        net.calibrated_beliefs()
+    }
+}
+
+/// Errors that can occur when interacting with a `ModelRegistry`
+#[derive(Debug)]
+pub enum RegistryError {
+    /// No model is registered under this name
+    UnknownModel(String),
+    /// The artifact of a model could not be decoded
+    Decode {
+        /// Name of the model that was being loaded
+        name: String,
+        /// The error returned by the decoder
+        source: Box<dyn Error + Send + Sync>,
+    },
+}
+
+impl fmt::Display for RegistryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RegistryError::UnknownModel(name) => write!(f, "no model registered as \"{}\"", name),
+            RegistryError::Decode { name, source } => {
+                write!(
+                    f,
+                    "failed to decode artifact of model \"{}\": {}",
+                    name, source
+                )
+            }
+        }
+    }
+}
+
+impl Error for RegistryError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            RegistryError::UnknownModel(_) => None,
+            RegistryError::Decode { source, .. } => Some(&**source),
+        }
+    }
+}
+
+/// A thread-safe collection of named and versioned Bayesian Networks
+///
+/// This is meant for services embedding several models: each model is registered under a name,
+/// queries are routed to the current version of the requested model, and models can be atomically
+/// replaced (hot-reloaded) while queries are running.
+#[derive(Debug, Default)]
+pub struct ModelRegistry {
+    models: RwLock<HashMap<String, Arc<ModelHandle>>>,
+    // the last version registered under each name, kept when the model is removed
+    versions: Mutex<HashMap<String, u64>>,
+}
+
+impl ModelRegistry {
+    /// Create a new empty registry
+    pub fn new() -> ModelRegistry {
+        ModelRegistry::default()
+    }
+
+    /// Register a network under the given name
+    ///
+    /// If a model with this name already exists, it is atomically replaced. The version is incremented
+    /// from the last one registered under this name, even if that model was removed since. Returns the
+    /// version of the newly registered model.
+    pub fn insert(&self, name: &str, net: BayesNet) -> u64 {
+        let mut models = self.models.write().unwrap();
+        let mut versions = self.versions.lock().unwrap();
+        let version = versions.entry(name.to_owned()).or_insert(0);
+        *version += 1;
+        let version = *version;
+        models.insert(
+            name.to_owned(),
+            Arc::new(ModelHandle {
+                name: name.to_owned(),
+                version,
+                net,
+            }),
+        );
+        version
+    }
+
+    /// Load (or hot-reload) a model from a serialized artifact
+    ///
+    /// The artifact is decoded by the provided `decode` function, which allows using any
+    /// serialization format. Decoding happens before the registry is locked, and if it fails the
+    /// previously registered version (if any) is kept untouched.
+    ///
+    /// Returns the version of the newly registered model.
+    pub fn load_artifact<F, E>(
+        &self,
+        name: &str,
+        artifact: &[u8],
+        decode: F,
+    ) -> Result<u64, RegistryError>
+    where
+        F: FnOnce(&[u8]) -> Result<BayesNet, E>,
+        E: Into<Box<dyn Error + Send + Sync>>,
+    {
+        let net = decode(artifact).map_err(|e| RegistryError::Decode {
+            name: name.to_owned(),
+            source: e.into(),
+        })?;
+        Ok(self.insert(name, net))
+    }
+
+    /// Remove a model from the registry, returning its last version if it existed
+    pub fn remove(&self, name: &str) -> Option<Arc<ModelHandle>> {
+        self.models.write().unwrap().remove(name)
+    }
+
+    /// Get the current version of a model
+    pub fn get(&self, name: &str) -> Result<Arc<ModelHandle>, RegistryError> {
+        self.models
+            .read()
+            .unwrap()
+            .get(name)
+            .cloned()
+            .ok_or_else(|| RegistryError::UnknownModel(name.to_owned()))
+    }
+
+    /// List the names and current versions of all registered models, sorted by name
+    pub fn models(&self) -> Vec<(String, u64)> {
+        let mut list: Vec<_> = self
+            .models
+            .read()
+            .unwrap()
+            .values()
+            .map(|m| (m.name.clone(), m.version))
+            .collect();
+        list.sort();
+        list
+    }
+
+    /// Route an inference query to the current version of the named model
+    ///
+    /// See `ModelHandle::query` for details. The model is resolved once at the start of the query,
+    /// so a concurrent reload does not affect a running query.
+    pub fn query(
+        &self,
+        name: &str,
+        evidence: &[(usize, usize)],
+        iterations: usize,
+    ) -> Result<Vec<LogProbVector>, RegistryError> {
+        Ok(self.get(name)?.query(evidence, iterations))
+    }
+}
@@ -0,0 +1,156 @@
+//! Reliability metrics of fault trees, see `BayesNet::from_fault_tree`
+//!
+//! All the nodes are binary, value `1` meaning that the event happened (a component failed), and the
+//! basic events are the roots of the network.
+
+use crate::causal::interventional_posterior;
+use crate::BayesNet;
+
+/// The importance of a basic event for the top event, see `importance_measures`
+#[derive(Debug, Clone, PartialEq)]
+pub struct EventImportance {
+    /// The node of the basic event
+    pub event: usize,
+    /// Birnbaum importance: `P(top | event) - P(top | no event)`
+    ///
+    /// This is the derivative of the probability of the top event with respect to the probability
+    /// of the basic event.
+    pub birnbaum: f32,
+    /// Fussell-Vesely importance: `(P(top) - P(top | no event)) / P(top)`
+    ///
+    /// This is the fraction of the probability of the top event that would be removed by making the
+    /// basic event impossible.
+    pub fussell_vesely: f32,
+    /// Risk achievement worth: `P(top | event) / P(top)`
+    pub risk_achievement_worth: f32,
+}
+
+fn assert_binary(net: &BayesNet) {
+    for node in 0..net.num_nodes() {
+        assert!(
+            net.num_values(node) == 2,
+            "Reliability metrics need binary nodes, but {} has {} values",
+            net.node_ref(node),
+            net.num_values(node)
+        );
+    }
+}
+
+/// The probability of the top event, after `iterations` steps with the current evidence
+///
+/// The network itself is not modified.
+pub fn top_event_probability(net: &BayesNet, top: usize, iterations: usize) -> f32 {
+    assert_binary(net);
+    let mut net = net.clone();
+    net.reset_state();
+    for _ in 0..iterations {
+        net.step();
+    }
+    net.beliefs()[top].as_probabilities()[1]
+}
+
+/// The importance measures of every basic event for the top event
+///
+/// The probabilities of the top event given that a basic event happened or not are computed by
+/// forcing the basic event with `causal::intervene`, and running `iterations` steps with the current
+/// evidence of the network. The basic events are the roots of the network, in increasing order. The
+/// Fussell-Vesely importance and the risk achievement worth are not finite if the top event is
+/// impossible.
+pub fn importance_measures(net: &BayesNet, top: usize, iterations: usize) -> Vec<EventImportance> {
+    assert_binary(net);
+    let evidence: Vec<(usize, usize)> = (0..net.num_nodes())
+        .filter_map(|node| net.nodes[node].evidence.map(|value| (node, value)))
+        .collect();
+    let p_top = top_event_probability(net, top, iterations);
+    (0..net.num_nodes())
+        .filter(|&node| net.nodes[node].parents.is_empty() && node != top)
+        .map(|event| {
+            let evidence: Vec<(usize, usize)> = evidence
+                .iter()
+                .copied()
+                .filter(|&(n, _)| n != event)
+                .collect();
+            let failed =
+                interventional_posterior(net, &[(event, 1)], top, &evidence, iterations)[1];
+            let working =
+                interventional_posterior(net, &[(event, 0)], top, &evidence, iterations)[1];
+            EventImportance {
+                event,
+                birnbaum: failed - working,
+                fussell_vesely: (p_top - working) / p_top,
+                risk_achievement_worth: failed / p_top,
+            }
+        })
+        .collect()
+}
+
+// remove the sets containing another set of the list, and sort the remaining ones
+fn minimize(mut sets: Vec<Vec<usize>>) -> Vec<Vec<usize>> {
+    sets.sort_by_key(|set| set.len());
+    let mut minimal: Vec<Vec<usize>> = Vec::new();
+    for set in sets {
+        if !minimal
+            .iter()
+            .any(|m| m.iter().all(|e| set.binary_search(e).is_ok()))
+        {
+            minimal.push(set);
+        }
+    }
+    minimal.sort();
+    minimal
+}
+
+/// The minimal cut sets of the top event
+///
+/// A cut set is a set of basic events whose occurrence can cause the top event, the other basic
+/// events not happening. It is minimal when none of its subsets is a cut set. Each cut set is given
+/// as its sorted basic events, and the cut sets are sorted. A node whose table gives a non-zero
+/// probability of happening for some values of its parents (such as a noisy-OR gate) is considered
+/// to possibly happen for these values.
+///
+/// The number of cut sets can grow exponentially with the size of the fault tree.
+pub fn minimal_cut_sets(net: &BayesNet, top: usize) -> Vec<Vec<usize>> {
+    assert_binary(net);
+    let mut cut_sets: Vec<Vec<Vec<usize>>> = Vec::with_capacity(top + 1);
+    for node in 0..=top {
+        let parents = net.parents(node);
+        if parents.is_empty() {
+            cut_sets.push(vec![vec![node]]);
+            continue;
+        }
+        let table = &net.nodes[node].log_probas;
+        let mut index = vec![1; parents.len() + 1];
+        let mut sets = Vec::new();
+        // the configurations of the parents in which the node can happen
+        for config in 0..1usize << parents.len() {
+            for (i, slot) in index[1..].iter_mut().enumerate() {
+                *slot = (config >> i) & 1;
+            }
+            if table.get(&index) == f32::NEG_INFINITY {
+                continue;
+            }
+            // all the combinations of cut sets of the parents that happened
+            let mut combinations = vec![Vec::new()];
+            for (i, &parent) in parents.iter().enumerate() {
+                if index[i + 1] == 0 {
+                    continue;
+                }
+                combinations = combinations
+                    .iter()
+                    .flat_map(|combination: &Vec<usize>| {
+                        cut_sets[parent].iter().map(move |set| {
+                            let mut union = combination.clone();
+                            union.extend(set);
+                            union.sort_unstable();
+                            union.dedup();
+                            union
+                        })
+                    })
+                    .collect();
+            }
+            sets.extend(combinations);
+        }
+        cut_sets.push(minimize(sets));
+    }
+    cut_sets.pop().unwrap()
+}
@@ -0,0 +1,320 @@
+//! Human-readable reports of inference results, in Markdown or HTML
+//!
+//! A report lists the evidence of a network, the beliefs that changed the most from their prior, the
+//! weight of each piece of evidence in a conclusion, and whether the propagation converged. Nodes and
+//! values are shown by their display names in the locale of the report, see
+//! `BayesNet::display_name`.
+
+use crate::BayesNet;
+use ndarray::Array1;
+use std::fmt::Write;
+
+/// The options of `report`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReportOptions {
+    /// The conclusion to explain, as a node and one of its values
+    ///
+    /// When set, the report gives the weight of each piece of evidence in its posterior probability.
+    pub target: Option<(usize, usize)>,
+    /// The maximum number of steps of each propagation
+    pub max_iterations: usize,
+    /// The maximum number of belief changes listed
+    pub max_changes: usize,
+    /// The locale of the display names
+    pub locale: String,
+}
+
+impl Default for ReportOptions {
+    fn default() -> ReportOptions {
+        ReportOptions {
+            target: None,
+            max_iterations: 100,
+            max_changes: 5,
+            locale: String::new(),
+        }
+    }
+}
+
+/// The change of the belief of a node from its prior, see `InferenceReport`
+#[derive(Debug, Clone, PartialEq)]
+pub struct BeliefChange {
+    /// The node
+    pub node: usize,
+    /// Its prior marginal, without any evidence
+    pub prior: Array1<f32>,
+    /// Its posterior given the evidence
+    pub posterior: Array1<f32>,
+    /// The total variation distance between the prior and the posterior
+    pub shift: f32,
+}
+
+/// The weight of a piece of evidence in the conclusion of a report
+#[derive(Debug, Clone, PartialEq)]
+pub struct EvidenceWeight {
+    /// The observed node
+    pub node: usize,
+    /// Its observed value
+    pub value: usize,
+    /// How much this evidence increases the log-odds of the conclusion, in nats
+    ///
+    /// This is the difference between the log-odds of the conclusion given all the evidence and given
+    /// all the evidence but this one. It is negative for evidence against the conclusion.
+    pub weight: f32,
+}
+
+/// A summary of the inference on a network, see `report`
+#[derive(Debug, Clone, PartialEq)]
+pub struct InferenceReport {
+    /// The evidence of the network, as `(node, value)`
+    pub evidence: Vec<(usize, usize)>,
+    /// The unobserved nodes whose beliefs changed the most, by decreasing shift
+    pub changes: Vec<BeliefChange>,
+    /// The conclusion explained by the report, and its posterior probability
+    pub target: Option<((usize, usize), f32)>,
+    /// The weight of each piece of evidence in the conclusion, by decreasing absolute weight
+    pub weights: Vec<EvidenceWeight>,
+    /// The number of steps run by the propagation
+    pub iterations: usize,
+    /// Whether the propagation converged
+    pub converged: bool,
+    // the display names of the nodes and of their values
+    names: Vec<String>,
+    states: Vec<Vec<String>>,
+}
+
+fn log_odds(p: f32) -> f32 {
+    p.ln() - (1.0 - p).ln()
+}
+
+// the beliefs of a copy of the network, propagated from a reset state until convergence
+fn propagate(net: &BayesNet, max_iterations: usize) -> (Vec<Array1<f32>>, usize, bool) {
+    let mut net = net.clone();
+    net.reset_state();
+    let (iterations, converged) =
+        net.run_until_convergence(net.numerics().convergence_tolerance, max_iterations);
+    let beliefs = net.beliefs().iter().map(|b| b.as_probabilities()).collect();
+    (beliefs, iterations, converged)
+}
+
+/// Build a report of the inference on a network with its current evidence
+///
+/// The propagation is run from a reset state until convergence (see `run_until_convergence` and the
+/// `convergence_tolerance` of the numeric policy of the network), at most `max_iterations` steps. The
+/// priors are the prior marginals of the nodes, see `BayesNet::prior_marginals`. Computing the weights
+/// of the evidence runs one more propagation per observed node. The network itself is not modified.
+pub fn report(net: &BayesNet, options: &ReportOptions) -> InferenceReport {
+    let evidence: Vec<(usize, usize)> = (0..net.num_nodes())
+        .filter_map(|node| net.nodes[node].evidence.map(|value| (node, value)))
+        .collect();
+    let (posteriors, iterations, converged) = propagate(net, options.max_iterations);
+    let priors = net.prior_marginals();
+
+    let mut changes: Vec<BeliefChange> = (0..net.num_nodes())
+        .filter(|&node| net.nodes[node].evidence.is_none())
+        .map(|node| {
+            let prior = priors[node].as_probabilities();
+            let posterior = posteriors[node].clone();
+            let shift = 0.5 * (&prior - &posterior).mapv(f32::abs).sum();
+            BeliefChange {
+                node,
+                prior,
+                posterior,
+                shift,
+            }
+        })
+        .collect();
+    changes.sort_by(|a, b| b.shift.total_cmp(&a.shift));
+    changes.truncate(options.max_changes);
+
+    let mut weights = Vec::new();
+    let target = options.target.map(|(node, value)| {
+        let p = posteriors[node][value];
+        for &(observed, observed_value) in &evidence {
+            let others: Vec<(usize, usize)> = evidence
+                .iter()
+                .copied()
+                .filter(|&(n, _)| n != observed)
+                .collect();
+            let mut without = net.clone();
+            without.set_evidence(&others);
+            let (beliefs, _, _) = propagate(&without, options.max_iterations);
+            weights.push(EvidenceWeight {
+                node: observed,
+                value: observed_value,
+                weight: log_odds(p) - log_odds(beliefs[node][value]),
+            });
+        }
+        ((node, value), p)
+    });
+    weights.sort_by(|a, b| b.weight.abs().total_cmp(&a.weight.abs()));
+
+    InferenceReport {
+        evidence,
+        changes,
+        target,
+        weights,
+        iterations,
+        converged,
+        names: (0..net.num_nodes())
+            .map(|node| net.display_name(node, &options.locale))
+            .collect(),
+        states: (0..net.num_nodes())
+            .map(|node| {
+                (0..net.num_values(node))
+                    .map(|value| net.display_state(node, value, &options.locale))
+                    .collect()
+            })
+            .collect(),
+    }
+}
+
+fn escape_markdown(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('|', "\\|")
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+// a report as a title and sections of paragraphs and tables, rendered by each format
+enum Block {
+    Paragraph(String),
+    Table(Vec<&'static str>, Vec<Vec<String>>),
+}
+
+impl InferenceReport {
+    fn distribution(&self, node: usize, probabilities: &Array1<f32>) -> String {
+        let values: Vec<String> = probabilities
+            .iter()
+            .zip(&self.states[node])
+            .map(|(p, state)| format!("{} {:.3}", state, p))
+            .collect();
+        values.join(", ")
+    }
+
+    fn finding(&self, node: usize, value: usize) -> String {
+        format!("{} = {}", self.names[node], self.states[node][value])
+    }
+
+    fn sections(&self) -> Vec<(String, Vec<Block>)> {
+        let mut sections = Vec::new();
+        let evidence = if self.evidence.is_empty() {
+            Block::Paragraph("No evidence.".to_owned())
+        } else {
+            Block::Table(
+                vec!["Node", "Value"],
+                self.evidence
+                    .iter()
+                    .map(|&(node, value)| {
+                        vec![self.names[node].clone(), self.states[node][value].clone()]
+                    })
+                    .collect(),
+            )
+        };
+        sections.push(("Evidence".to_owned(), vec![evidence]));
+
+        let changes = if self.changes.is_empty() {
+            Block::Paragraph("No unobserved nodes.".to_owned())
+        } else {
+            Block::Table(
+                vec!["Node", "Prior", "Posterior", "Shift"],
+                self.changes
+                    .iter()
+                    .map(|change| {
+                        vec![
+                            self.names[change.node].clone(),
+                            self.distribution(change.node, &change.prior),
+                            self.distribution(change.node, &change.posterior),
+                            format!("{:.3}", change.shift),
+                        ]
+                    })
+                    .collect(),
+            )
+        };
+        sections.push(("Largest changes".to_owned(), vec![changes]));
+
+        if let Some(((node, value), p)) = self.target {
+            let mut blocks = vec![Block::Paragraph(format!("Posterior probability: {:.3}", p))];
+            if !self.weights.is_empty() {
+                blocks.push(Block::Table(
+                    vec!["Evidence", "Weight (nats)"],
+                    self.weights
+                        .iter()
+                        .map(|w| vec![self.finding(w.node, w.value), format!("{:+.3}", w.weight)])
+                        .collect(),
+                ));
+            }
+            sections.push((
+                format!("Explanation of {}", self.finding(node, value)),
+                blocks,
+            ));
+        }
+
+        let status = if self.converged {
+            format!("Converged after {} iterations.", self.iterations)
+        } else {
+            format!("Did not converge within {} iterations.", self.iterations)
+        };
+        sections.push(("Convergence".to_owned(), vec![Block::Paragraph(status)]));
+        sections
+    }
+
+    /// Render the report as a Markdown document
+    pub fn to_markdown(&self) -> String {
+        let mut markdown = "# Inference report\n".to_owned();
+        for (title, blocks) in self.sections() {
+            write!(markdown, "\n## {}\n", escape_markdown(&title)).unwrap();
+            for block in blocks {
+                match block {
+                    Block::Paragraph(text) => {
+                        write!(markdown, "\n{}\n", escape_markdown(&text)).unwrap()
+                    }
+                    Block::Table(header, rows) => {
+                        write!(markdown, "\n| {} |\n", header.join(" | ")).unwrap();
+                        writeln!(markdown, "|{}", "---|".repeat(header.len())).unwrap();
+                        for row in rows {
+                            let cells: Vec<String> =
+                                row.iter().map(|c| escape_markdown(c)).collect();
+                            writeln!(markdown, "| {} |", cells.join(" | ")).unwrap();
+                        }
+                    }
+                }
+            }
+        }
+        markdown
+    }
+
+    /// Render the report as an HTML fragment, to be embedded in a page
+    pub fn to_html(&self) -> String {
+        let mut html = "<h1>Inference report</h1>\n".to_owned();
+        for (title, blocks) in self.sections() {
+            writeln!(html, "<h2>{}</h2>", escape_html(&title)).unwrap();
+            for block in blocks {
+                match block {
+                    Block::Paragraph(text) => {
+                        writeln!(html, "<p>{}</p>", escape_html(&text)).unwrap()
+                    }
+                    Block::Table(header, rows) => {
+                        html.push_str("<table>\n<tr>");
+                        for cell in header {
+                            write!(html, "<th>{}</th>", cell).unwrap();
+                        }
+                        html.push_str("</tr>\n");
+                        for row in rows {
+                            html.push_str("<tr>");
+                            for cell in row {
+                                write!(html, "<td>{}</td>", escape_html(&cell)).unwrap();
+                            }
+                            html.push_str("</tr>\n");
+                        }
+                        html.push_str("</table>\n");
+                    }
+                }
+            }
+        }
+        html
+    }
+}
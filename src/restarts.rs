@@ -0,0 +1,73 @@
+use crate::{BayesNet, LogProbVector};
+use rand::Rng;
+
+impl BayesNet {
+    /// The Bethe free energy of the current messages
+    ///
+    /// This is the approximation of `-log P(evidence)` minimized by the fixed points of the Loopy Belief
+    /// Propagation, and is exact for networks without loops once the propagation has converged. When
+    /// the propagation can converge to different fixed points, the one with the lowest free energy is
+    /// usually the most accurate.
+    pub fn bethe_free_energy(&self) -> f32 {
+        -self.bethe_log_evidence()
+    }
+
+    /// Replace every message by a random one, as if perturbing uniform messages
+    ///
+    /// Each log-probability of each message is drawn uniformly in `[-noise, noise]`.
+    pub(crate) fn randomize_messages<R: Rng + ?Sized>(&mut self, rng: &mut R, noise: f32) {
+        for node in &mut self.nodes {
+            for (_, msg) in node.parents.iter_mut().chain(node.children.iter_mut()) {
+                let random = msg
+                    .log_probabilities()
+                    .mapv(|_| rng.gen_range(-noise..=noise));
+                *msg = LogProbVector::from_log_probabilities(random);
+                msg.renormalize();
+            }
+            node.lambda = None;
+            node.pi = None;
+        }
+    }
+
+    /// Run the Loopy Belief Propagation several times from perturbed messages, keeping the best run
+    ///
+    /// The first run starts from uniform messages, and each of the `restarts` other runs from messages
+    /// perturbed by `noise` (log-probabilities are drawn uniformly in `[-noise, noise]`). Each run is made
+    /// of `iterations` steps, and the network is left in the state of the run reaching the lowest Bethe
+    /// free energy, which is returned. The evidence of the network is kept.
+    pub fn step_with_restarts<R: Rng + ?Sized>(
+        &mut self,
+        rng: &mut R,
+        restarts: usize,
+        iterations: usize,
+        noise: f32,
+    ) -> f32 {
+        let mut best: Option<(f32, BayesNet)> = None;
+        for run in 0..=restarts {
+            let mut net = self.clone();
+            net.reset_state();
+            if run > 0 {
+                net.randomize_messages(rng, noise);
+            }
+            for _ in 0..iterations {
+                net.step();
+            }
+            let energy = net.bethe_free_energy();
+            // NaN energies are never kept
+            if best
+                .as_ref()
+                .map(|&(e, _)| energy < e)
+                .unwrap_or(!energy.is_nan())
+            {
+                best = Some((energy, net));
+            }
+        }
+        match best {
+            Some((energy, net)) => {
+                *self = net;
+                energy
+            }
+            None => f32::NAN,
+        }
+    }
+}
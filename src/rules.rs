@@ -0,0 +1,137 @@
+use crate::{BayesNet, CptTree};
+use ndarray::Array1;
+
+// a list of (parent, value) conditions
+type Conditions = Vec<(usize, usize)>;
+
+/// A conditional distribution defined by prioritized rules
+///
+/// Each rule is a set of conditions on the values of the parents of the node, associated with the
+/// distribution of the node when all of them hold. Rules are tried in the order they were added and
+/// the first matching rule gives the distribution, the default distribution being used when no rule
+/// matches. For example, with the parents `alarm` and `battery`:
+///
+/// ```text
+/// if alarm = on and battery = ok then [0.1, 0.9]
+/// else if alarm = on then [0.8, 0.2]
+/// else [0.99, 0.01]
+/// ```
+///
+/// Conditions refer to the parents by their node id. Conditions given with node and state names can
+/// be converted with `BayesNet::validate_evidence`.
+#[derive(Debug, Clone)]
+pub struct CptRules {
+    rules: Vec<(Conditions, Array1<f32>)>,
+    default: Array1<f32>,
+}
+
+impl CptRules {
+    /// Create a rule set with only a default distribution, as probabilities that do not need to be normalized
+    pub fn new(default: Array1<f32>) -> CptRules {
+        CptRules {
+            rules: Vec::new(),
+            default,
+        }
+    }
+
+    /// Add a rule, with a lower priority than all previously added rules
+    ///
+    /// `conditions` is a list of `(parent, value)` that must all hold for the rule to apply, and
+    /// `probabilities` is the distribution of the node in this case, which does not need to be normalized.
+    pub fn add_rule(&mut self, conditions: &[(usize, usize)], probabilities: Array1<f32>) {
+        assert!(
+            probabilities.len() == self.default.len(),
+            "Rule distribution has {} values but the default distribution has {}",
+            probabilities.len(),
+            self.default.len()
+        );
+        self.rules.push((conditions.to_vec(), probabilities));
+    }
+
+    /// Number of rules, not counting the default distribution
+    pub fn len(&self) -> usize {
+        self.rules.len()
+    }
+
+    /// Whether there are no rules besides the default distribution
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Compile the rules into a tree-structured probability table for a node with the given parents
+    ///
+    /// The tree only tests the parents needed to decide which rule applies, so its size is driven
+    /// by the number of rules rather than the number of parent configurations.
+    pub fn compile(&self, net: &BayesNet, parents: &[usize]) -> CptTree {
+        // conditions as (position in the parent list, value)
+        let rules: Vec<(Conditions, &Array1<f32>)> = self
+            .rules
+            .iter()
+            .map(|(conditions, probas)| {
+                let conditions = conditions
+                    .iter()
+                    .map(|&(node, value)| {
+                        let position =
+                            parents.iter().position(|&p| p == node).unwrap_or_else(|| {
+                                panic!("Rule condition on node {} which is not a parent", node)
+                            });
+                        assert!(
+                            value < net.num_values(node),
+                            "Rule condition on value {} of node {}, which only has {} values",
+                            value,
+                            node,
+                            net.num_values(node)
+                        );
+                        (position, value)
+                    })
+                    .collect();
+                (conditions, probas)
+            })
+            .collect();
+        let sizes: Vec<usize> = parents.iter().map(|&p| net.num_values(p)).collect();
+        self.compile_rules(&rules, &sizes)
+    }
+
+    fn compile_rules(&self, rules: &[(Conditions, &Array1<f32>)], sizes: &[usize]) -> CptTree {
+        let (conditions, probas) = match rules.first() {
+            None => return CptTree::leaf(self.default.clone()),
+            Some(rule) => rule,
+        };
+        let parent = match conditions.first() {
+            // the highest priority rule always applies in this context
+            None => return CptTree::leaf((*probas).clone()),
+            Some(&(parent, _)) => parent,
+        };
+        let children = (0..sizes[parent])
+            .map(|value| {
+                // drop the rules contradicted by this value, and the conditions it satisfies
+                let remaining: Vec<_> = rules
+                    .iter()
+                    .filter(|(conditions, _)| {
+                        conditions.iter().all(|&(p, v)| p != parent || v == value)
+                    })
+                    .map(|(conditions, probas)| {
+                        let conditions = conditions
+                            .iter()
+                            .cloned()
+                            .filter(|&(p, _)| p != parent)
+                            .collect();
+                        (conditions, *probas)
+                    })
+                    .collect();
+                self.compile_rules(&remaining, sizes)
+            })
+            .collect();
+        CptTree::split(parent, children)
+    }
+}
+
+impl BayesNet {
+    /// Add a new node to the network whose probability table is defined by rules
+    ///
+    /// The rules are compiled into a tree with `CptRules::compile`, see `add_node_from_cpt_tree`.
+    pub fn add_node_from_rules(&mut self, parents: &[usize], rules: &CptRules) -> usize {
+        let tree = rules.compile(self, parents);
+        self.add_node_from_cpt_tree(parents, tree)
+    }
+}
@@ -0,0 +1,312 @@
+//! Sampling from the joint distribution of a network
+//!
+//! Samples are complete records following the conventions of `learning`: a `Vec<usize>` holding the
+//! value of every node, indexed by node id. They can be used as synthetic datasets, or to check the
+//! beliefs computed by the propagation with `empirical_marginals`.
+//!
+//! Evidence sets drawn from the model can also be fed back to the propagation, to study how the
+//! posterior of a node is distributed over the situations the model describes, see
+//! `BayesNet::simulate_evidence`.
+//!
+//! The posterior given the evidence can be sampled with `BayesNet::gibbs_sampling`, which remains
+//! usable on networks where the propagation does not converge.
+
+use crate::{BayesNet, LogProbVector};
+use ndarray::Array1;
+use rand::Rng;
+
+/// The posteriors of a node over simulated evidence sets, see `BayesNet::simulate_evidence`
+#[derive(Debug, Clone)]
+pub struct EvidenceSimulation {
+    /// The posterior probabilities of the values of the target, for each simulated evidence set
+    pub posteriors: Vec<Array1<f32>>,
+}
+
+impl EvidenceSimulation {
+    // the posterior probability of a value in each simulation
+    fn probabilities(&self, value: usize) -> Vec<f32> {
+        self.posteriors.iter().map(|p| p[value]).collect()
+    }
+
+    /// The mean posterior probability of a value of the target
+    pub fn mean(&self, value: usize) -> f32 {
+        let probabilities = self.probabilities(value);
+        probabilities.iter().sum::<f32>() / probabilities.len() as f32
+    }
+
+    /// A quantile of the posterior probability of a value of the target, `q` being between 0 and 1
+    ///
+    /// This is the smallest posterior probability such that a fraction `q` of the simulations are at
+    /// most this probability.
+    pub fn quantile(&self, value: usize, q: f32) -> f32 {
+        assert!((0.0..=1.0).contains(&q), "Quantile {} is not in [0, 1]", q);
+        let mut probabilities = self.probabilities(value);
+        probabilities.sort_by(|a, b| a.total_cmp(b));
+        let rank = (q * probabilities.len() as f32).ceil() as usize;
+        probabilities[rank.clamp(1, probabilities.len()) - 1]
+    }
+
+    /// The fraction of the simulations where the posterior probability of a value of the target is at
+    /// least `threshold`, such as the rate at which an alarm on this posterior would be raised
+    pub fn fraction_above(&self, value: usize, threshold: f32) -> f32 {
+        let probabilities = self.probabilities(value);
+        let count = probabilities.iter().filter(|&&p| p >= threshold).count();
+        count as f32 / probabilities.len() as f32
+    }
+}
+
+/// Options of `BayesNet::gibbs_sampling`
+#[derive(Debug, Clone)]
+pub struct GibbsOptions {
+    /// Number of sweeps over the nodes discarded before the samples are recorded
+    pub burn_in: usize,
+    /// Number of sweeps between two recorded samples
+    pub thinning: usize,
+    /// Number of samples to record
+    pub samples: usize,
+}
+
+impl Default for GibbsOptions {
+    fn default() -> GibbsOptions {
+        GibbsOptions {
+            burn_in: 100,
+            thinning: 1,
+            samples: 1000,
+        }
+    }
+}
+
+/// The result of `BayesNet::gibbs_sampling`
+#[derive(Debug, Clone)]
+pub struct GibbsResult {
+    /// The estimate of the posterior marginal of each node
+    pub marginals: Vec<LogProbVector>,
+    /// The recorded samples, approximately drawn from the posterior
+    pub samples: Vec<Vec<usize>>,
+}
+
+impl BayesNet {
+    /// Draw `n` independent samples from the joint distribution of the network
+    ///
+    /// Each node is drawn from its table given the values already drawn for its parents, in the order
+    /// of the nodes (ancestral sampling). The evidence of the network is ignored, so this samples the
+    /// prior distribution of the model.
+    pub fn sample_forward<R: Rng + ?Sized>(&self, rng: &mut R, n: usize) -> Vec<Vec<usize>> {
+        (0..n).map(|_| self.forward_sample(rng)).collect()
+    }
+
+    /// Draw `n` samples from the network under an intervention
+    ///
+    /// The nodes of the intervention, given as `(node, value)` pairs, are set to their value regardless
+    /// of their parents, as with the `do` operator, and the other nodes are drawn as in
+    /// `sample_forward`. The descendants of the nodes of the intervention are thus affected, but not
+    /// their ancestors.
+    pub fn sample_forward_under<R: Rng + ?Sized>(
+        &self,
+        rng: &mut R,
+        n: usize,
+        intervention: &[(usize, usize)],
+    ) -> Vec<Vec<usize>> {
+        let mut forced = vec![None; self.nodes.len()];
+        for &(node, value) in intervention {
+            assert!(
+                value < self.num_values(node),
+                "Intervention sets {} to value {}, but it only has {} values",
+                self.node_ref(node),
+                value,
+                self.num_values(node)
+            );
+            forced[node] = Some(value);
+        }
+        (0..n)
+            .map(|_| self.forward_sample_under(rng, &forced))
+            .collect()
+    }
+
+    /// Simulate the posterior of a node over evidence sets drawn from the model
+    ///
+    /// This draws `n` samples with `sample_forward_under` (use an empty intervention to sample the model
+    /// itself), sets the values of the `observed` nodes in each sample as the evidence of the network,
+    /// and records the belief of `target` after `iterations` steps from a reset state. For example, the
+    /// fraction of the posteriors of a fault above an alarm threshold, with an intervention setting the
+    /// fault to absent, is the false alarm rate of a monitoring model.
+    ///
+    /// The inference uses the network without the intervention, as the monitoring would. The network
+    /// itself is not modified.
+    pub fn simulate_evidence<R: Rng + ?Sized>(
+        &self,
+        rng: &mut R,
+        n: usize,
+        intervention: &[(usize, usize)],
+        observed: &[usize],
+        target: usize,
+        iterations: usize,
+    ) -> EvidenceSimulation {
+        assert!(n > 0, "Evidence simulation needs at least one sample");
+        let mut net = self.clone();
+        let posteriors = self
+            .sample_forward_under(rng, n, intervention)
+            .into_iter()
+            .map(|sample| {
+                let evidence: Vec<(usize, usize)> =
+                    observed.iter().map(|&node| (node, sample[node])).collect();
+                net.reset_state();
+                net.set_evidence(&evidence);
+                for _ in 0..iterations {
+                    net.step();
+                }
+                net.beliefs()[target].as_probabilities()
+            })
+            .collect();
+        EvidenceSimulation { posteriors }
+    }
+
+    /// Sample the posterior of the nodes given the evidence of the network with Gibbs sampling
+    ///
+    /// Starting from a forward sample with the observed values, each sweep draws every node without hard
+    /// evidence in turn from its distribution given the current values of its Markov blanket (its
+    /// parents, its children and their other parents), including its soft evidence. The marginals are
+    /// the averages of these distributions over the recorded sweeps, which have a lower variance than
+    /// the frequencies of the values in the samples.
+    ///
+    /// Unlike the propagation, this converges to the exact posterior on any network, but it can mix
+    /// very slowly between values separated by near-deterministic tables, and cannot cross values of
+    /// probability zero. Panics if no sample is recorded.
+    pub fn gibbs_sampling<R: Rng + ?Sized>(
+        &self,
+        rng: &mut R,
+        options: &GibbsOptions,
+    ) -> GibbsResult {
+        assert!(
+            options.samples > 0 && options.thinning > 0,
+            "Gibbs sampling needs to record at least one sample, with a thinning of at least 1"
+        );
+        let evidence: Vec<Array1<f32>> = self
+            .nodes
+            .iter()
+            .map(|node| node.evidence_vec().log_probabilities().to_owned())
+            .collect();
+        let forced: Vec<Option<usize>> = self
+            .nodes
+            .iter()
+            .map(|node| node.evidence.filter(|&v| v < node.log_probas.shape()[0]))
+            .collect();
+        let mut state = self.forward_sample_under(rng, &forced);
+        let mut sums: Vec<Array1<f64>> = (0..self.nodes.len())
+            .map(|node| Array1::zeros(self.num_values(node)))
+            .collect();
+        let mut samples = Vec::with_capacity(options.samples);
+        let mut index = Vec::new();
+        for sweep in 0..options.burn_in + options.samples * options.thinning {
+            let recorded = sweep >= options.burn_in
+                && (sweep - options.burn_in + 1).is_multiple_of(options.thinning);
+            for (id, node) in self.nodes.iter().enumerate() {
+                if let Some(value) = forced[id] {
+                    if recorded {
+                        sums[id][value] += 1.0;
+                    }
+                    continue;
+                }
+                let previous = state[id];
+                let mut logits = evidence[id].clone();
+                for (value, logit) in logits.iter_mut().enumerate() {
+                    state[id] = value;
+                    for family in std::iter::once(id).chain(node.children.iter().map(|&(c, _)| c)) {
+                        let data = &self.nodes[family];
+                        index.clear();
+                        index.push(state[family]);
+                        index.extend(data.parents.iter().map(|&(p, _)| state[p]));
+                        *logit += data.log_probas.get(&index);
+                    }
+                }
+                let max = logits.fold(f32::NEG_INFINITY, |m, &l| m.max(l));
+                if !max.is_finite() {
+                    // no value is possible given the blanket, keep the previous one
+                    state[id] = previous;
+                    if recorded {
+                        sums[id][previous] += 1.0;
+                    }
+                    continue;
+                }
+                let probas: Vec<f64> = logits.iter().map(|&l| f64::from(l - max).exp()).collect();
+                let total: f64 = probas.iter().sum();
+                let u = rng.gen::<f64>() * total;
+                let mut acc = 0.0;
+                state[id] = probas
+                    .iter()
+                    .position(|&p| {
+                        acc += p;
+                        u < acc
+                    })
+                    .unwrap_or(probas.len() - 1);
+                if recorded {
+                    for (sum, p) in sums[id].iter_mut().zip(&probas) {
+                        *sum += p / total;
+                    }
+                }
+            }
+            if recorded {
+                samples.push(state.clone());
+            }
+        }
+        let marginals = sums
+            .into_iter()
+            .map(|sum| LogProbVector::from_log_probabilities(sum.mapv(|s| s.ln() as f32)))
+            .collect();
+        GibbsResult { marginals, samples }
+    }
+
+    // draw a configuration of all the nodes from the joint distribution
+    pub(crate) fn forward_sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Vec<usize> {
+        self.forward_sample_under(rng, &vec![None; self.nodes.len()])
+    }
+
+    // same as `forward_sample`, with the values of some nodes forced
+    fn forward_sample_under<R: Rng + ?Sized>(
+        &self,
+        rng: &mut R,
+        forced: &[Option<usize>],
+    ) -> Vec<usize> {
+        let mut sample: Vec<usize> = Vec::with_capacity(self.nodes.len());
+        for (node, &forced) in self.nodes.iter().zip(forced) {
+            if let Some(value) = forced {
+                sample.push(value);
+                continue;
+            }
+            let parent_values: Vec<usize> = node.parents.iter().map(|&(p, _)| sample[p]).collect();
+            let column = node.log_probas.column(&parent_values);
+            let u: f32 = rng.gen();
+            let mut acc = 0.0;
+            let n = column.len();
+            let value = column
+                .iter()
+                .position(|&l| {
+                    acc += l.exp();
+                    u < acc
+                })
+                .unwrap_or(n - 1);
+            sample.push(value);
+        }
+        sample
+    }
+}
+
+/// The frequency of each value of each node in the samples
+///
+/// This is an estimate of the marginal of each node, to compare with the beliefs computed by the
+/// propagation. Panics if a sample does not have a valid value for each node of the network.
+pub fn empirical_marginals(net: &BayesNet, samples: &[Vec<usize>]) -> Vec<LogProbVector> {
+    let mut counts: Vec<Array1<f32>> = (0..net.num_nodes())
+        .map(|node| Array1::zeros(net.num_values(node)))
+        .collect();
+    for (index, sample) in samples.iter().enumerate() {
+        crate::learning::check_record(net, sample, index);
+        for (count, &value) in counts.iter_mut().zip(sample) {
+            count[value] += 1.0;
+        }
+    }
+    counts
+        .into_iter()
+        .map(|count| LogProbVector::from_log_probabilities(count.mapv(f32::ln)))
+        .collect()
+}
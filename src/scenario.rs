@@ -0,0 +1,47 @@
+use crate::BayesNet;
+use ndarray::{Array, Dimension, RemoveAxis};
+
+impl BayesNet {
+    /// Replace the probability table of a node
+    ///
+    /// The array has the same shape as for `add_node_from_probabilities`, which must be the shape of
+    /// the current table of the node, and does not need to be normalized. Any special representation
+    /// of the node (CPT tree, softmax weights or aggregate) is dropped. The messages of the network are
+    /// kept, so that the next steps start from the previous fixed point.
+    ///
+    /// Other networks sharing this table, such as the network this one was cloned from, are not
+    /// affected: the tables are copied on write.
+    pub fn set_probabilities<D: Dimension + RemoveAxis>(
+        &mut self,
+        node: usize,
+        probabilities: Array<f32, D>,
+    ) {
+        self.replace_log_probas(node, probabilities.mapv(f32::ln).into_dyn());
+    }
+
+    /// A scenario derived from this network, with the probability table of a node replaced
+    ///
+    /// This is `clone` followed by `set_probabilities`, but cheap even for large networks: the tables
+    /// of the other nodes are shared with this network rather than copied, so many scenarios can be
+    /// derived from the same base model, for example to sweep the value of a parameter. The scenario
+    /// starts from the messages and evidence of this network.
+    pub fn with_probabilities<D: Dimension + RemoveAxis>(
+        &self,
+        node: usize,
+        probabilities: Array<f32, D>,
+    ) -> BayesNet {
+        let mut scenario = self.clone();
+        scenario.set_probabilities(node, probabilities);
+        scenario
+    }
+
+    /// Whether a node shares its probability table with the node of the same id in another network
+    ///
+    /// This is the case for the nodes of a scenario whose table was not modified, see
+    /// `with_probabilities`.
+    pub fn shares_table(&self, other: &BayesNet, node: usize) -> bool {
+        self.nodes[node]
+            .log_probas
+            .ptr_eq(&other.nodes[node].log_probas)
+    }
+}
@@ -0,0 +1,137 @@
+use crate::BayesNet;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+
+/// Errors reported when validating external evidence against the names of a network
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SchemaError {
+    /// No node of the network has this name
+    UnknownNode(String),
+    /// The node exists, but has no value with this name
+    UnknownState {
+        /// Name of the node
+        node: String,
+        /// The state name that was not found
+        state: String,
+        /// The names of the states this node actually has
+        available: Vec<String>,
+    },
+    /// The node exists, but its states were never named
+    UnnamedStates(String),
+}
+
+impl fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SchemaError::UnknownNode(node) => write!(f, "unknown node \"{}\"", node),
+            SchemaError::UnknownState {
+                node,
+                state,
+                available,
+            } => write!(
+                f,
+                "node \"{}\" has no state \"{}\" (expected one of: {})",
+                node,
+                state,
+                available.join(", ")
+            ),
+            SchemaError::UnnamedStates(node) => {
+                write!(f, "the states of node \"{}\" are not named", node)
+            }
+        }
+    }
+}
+
+impl Error for SchemaError {}
+
+impl BayesNet {
+    /// Set the name of a node
+    ///
+    /// Names are used to refer to nodes from external data (see `validate_evidence`), and must thus
+    /// be unique in the network. This function panics if another node already has this name.
+    pub fn set_node_name(&mut self, node: usize, name: &str) {
+        if let Some(other) = self.find_node(name) {
+            assert!(
+                other == node,
+                "Node name \"{}\" is already used by node {}",
+                name,
+                other
+            );
+        }
+        self.nodes[node].name = Some(name.to_owned());
+    }
+
+    /// Set the names of the possible values of a node
+    ///
+    /// This function panics if the number of names does not match the number of values of the node.
+    pub fn set_state_names<S: AsRef<str>>(&mut self, node: usize, names: &[S]) {
+        let n_values = self.num_values(node);
+        assert!(
+            names.len() == n_values,
+            "Node {} has {} values, but {} state names were given",
+            node,
+            n_values,
+            names.len()
+        );
+        self.nodes[node].state_names = Some(names.iter().map(|s| s.as_ref().to_owned()).collect());
+    }
+
+    /// Get the name of a node, if it has one
+    pub fn node_name(&self, node: usize) -> Option<&str> {
+        self.nodes[node].name.as_deref()
+    }
+
+    /// Get the names of the values of a node, if they were set
+    pub fn state_names(&self, node: usize) -> Option<&[String]> {
+        self.nodes[node].state_names.as_deref()
+    }
+
+    /// Find a node by its name
+    pub fn find_node(&self, name: &str) -> Option<usize> {
+        self.nodes
+            .iter()
+            .position(|n| n.name.as_deref() == Some(name))
+    }
+
+    /// Find a value of a node by its name
+    pub fn find_state(&self, node: usize, state: &str) -> Option<usize> {
+        self.nodes[node]
+            .state_names
+            .as_ref()
+            .and_then(|names| names.iter().position(|s| s == state))
+    }
+
+    /// Translate string-keyed evidence into the `(node_id, node_value)` form used by `set_evidence`
+    ///
+    /// The keys of the map are node names, and the values are state names. The returned evidence is
+    /// sorted by node id. If several entries are invalid, the error is reported for the first one in
+    /// the alphabetical order of node names.
+    pub fn validate_evidence(
+        &self,
+        map: &HashMap<String, String>,
+    ) -> Result<Vec<(usize, usize)>, SchemaError> {
+        let mut entries: Vec<_> = map.iter().collect();
+        entries.sort();
+        let mut evidence = Vec::with_capacity(entries.len());
+        for (node_name, state_name) in entries {
+            let node = self
+                .find_node(node_name)
+                .ok_or_else(|| SchemaError::UnknownNode(node_name.clone()))?;
+            let names = self.nodes[node]
+                .state_names
+                .as_ref()
+                .ok_or_else(|| SchemaError::UnnamedStates(node_name.clone()))?;
+            let value = names.iter().position(|s| s == state_name).ok_or_else(|| {
+                SchemaError::UnknownState {
+                    node: node_name.clone(),
+                    state: state_name.clone(),
+                    available: names.clone(),
+                }
+            })?;
+            evidence.push((node, value));
+        }
+        evidence.sort();
+        Ok(evidence)
+    }
+}
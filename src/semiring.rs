@@ -0,0 +1,146 @@
+//! Semirings over which the messages are computed
+//!
+//! The message passing of `BayesNet::step_in` only relies on two operations: combining values, and
+//! summing them out. Values always live on the log-scale of `LogProbVector`, where they are combined by
+//! addition (`0` is the neutral element, and `-inf` the absorbing one), so a semiring only defines how
+//! values are summed, which changes what the propagation computes:
+//!
+//! - `SumProduct` gives the marginal probabilities (the default of `BayesNet::step`),
+//! - `MaxProduct` gives the max-marginals, used for the most probable explanation,
+//! - `MinPlus` gives ranks of surprise, used by `RankingNet`,
+//! - `Boolean` gives which values are possible at all.
+
+use crate::{BayesNet, LogProbVector};
+use ndarray::{ArrayView1, ArrayViewMut1};
+
+/// A commutative semiring on values in log-scale
+pub trait Semiring {
+    /// The sum of several values, used to marginalize the messages
+    fn sum(values: ArrayView1<f32>) -> f32;
+
+    /// Normalize a vector of values, by subtracting their sum
+    fn normalize(mut values: ArrayViewMut1<f32>) {
+        let sum = Self::sum(values.view());
+        values -= sum;
+    }
+}
+
+/// The sum-product semiring on log-probabilities, where the sum is a log-sum-exp
+#[derive(Debug, Clone, Copy)]
+pub struct SumProduct;
+
+impl Semiring for SumProduct {
+    fn sum(values: ArrayView1<f32>) -> f32 {
+        crate::math::log_sum_exp_vec(values)
+    }
+}
+
+/// The max-product semiring on log-probabilities, where the sum is a maximum
+#[derive(Debug, Clone, Copy)]
+pub struct MaxProduct;
+
+impl Semiring for MaxProduct {
+    fn sum(values: ArrayView1<f32>) -> f32 {
+        values.fold(f32::NEG_INFINITY, |acc, &v| acc.max(v))
+    }
+}
+
+/// The min-plus semiring on ranks
+///
+/// Ranks are stored negated to share the log-scale of probabilities: a rank `κ` is the value `-κ`, so
+/// that the minimum of ranks is the maximum of values.
+#[derive(Debug, Clone, Copy)]
+pub struct MinPlus;
+
+impl Semiring for MinPlus {
+    fn sum(values: ArrayView1<f32>) -> f32 {
+        MaxProduct::sum(values)
+    }
+}
+
+/// The boolean semiring, where `0` is true and `-inf` is false
+///
+/// Any finite value counts as true, so probability tables can be used directly: the result tells which
+/// values have a non-zero probability.
+#[derive(Debug, Clone, Copy)]
+pub struct Boolean;
+
+impl Semiring for Boolean {
+    fn sum(values: ArrayView1<f32>) -> f32 {
+        if values.iter().any(|&v| v > f32::NEG_INFINITY) {
+            0.0
+        } else {
+            f32::NEG_INFINITY
+        }
+    }
+
+    fn normalize(mut values: ArrayViewMut1<f32>) {
+        values.mapv_inplace(|v| if v > f32::NEG_INFINITY { 0.0 } else { v });
+    }
+}
+
+pub(crate) fn normalize<S: Semiring>(vector: &mut LogProbVector) {
+    let mut values = vector.log_probabilities().to_owned();
+    S::normalize(values.view_mut());
+    *vector = LogProbVector::from_log_probabilities(values);
+}
+
+impl BayesNet {
+    /// Find the most probable explanation of the evidence
+    ///
+    /// This runs `iterations` steps of the message passing over the max-product semiring, and picks for
+    /// each node the value with the highest max-marginal. For networks without loops and once the
+    /// propagation converged, this is the most probable joint assignment of all the nodes given the
+    /// evidence, provided it is unique. The evidence is kept, and the state is reset afterwards.
+    pub fn most_probable_explanation(&mut self, iterations: usize) -> Vec<usize> {
+        self.reset_state();
+        for _ in 0..iterations {
+            self.step_in::<MaxProduct>();
+        }
+        let assignment = self.decode_max_marginals();
+        self.reset_state();
+        assignment
+    }
+
+    /// Decode an assignment from the current max-marginals
+    ///
+    /// The messages must have been computed by `step_in::<MaxProduct>`, this picks for each node the
+    /// value with the highest max-marginal, the smallest one in case of ties. See
+    /// `most_probable_explanation`, which runs the propagation beforehand.
+    pub fn decode_max_marginals(&self) -> Vec<usize> {
+        self.beliefs_in::<MaxProduct>()
+            .iter()
+            .map(|belief| {
+                let values = belief.log_probabilities();
+                (0..values.len())
+                    .rev()
+                    .max_by(|&a, &b| values[a].total_cmp(&values[b]))
+                    .unwrap_or(0)
+            })
+            .collect()
+    }
+
+    /// Log-probability of a joint assignment of all the nodes under the model
+    ///
+    /// This is the sum of the log-probabilities given by the table of each node to its value given
+    /// the values of its parents, ignoring the evidence. It can be used to compare candidate
+    /// explanations, such as the result of `most_probable_explanation`.
+    pub fn log_joint_probability(&self, assignment: &[usize]) -> f32 {
+        assert!(
+            assignment.len() == self.nodes.len(),
+            "Got an assignment of {} values for {} nodes",
+            assignment.len(),
+            self.nodes.len()
+        );
+        self.nodes
+            .iter()
+            .zip(assignment.iter())
+            .map(|(node, &value)| {
+                let index: Vec<usize> = std::iter::once(value)
+                    .chain(node.parents.iter().map(|&(p, _)| assignment[p]))
+                    .collect();
+                node.log_probas.get(&index)
+            })
+            .sum()
+    }
+}
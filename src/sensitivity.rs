@@ -0,0 +1,300 @@
+use crate::BayesNet;
+use ndarray::{Array1, Array2};
+
+/// How much observing a node would shift the posterior of a target, see
+/// `BayesNet::sensitivity_to_findings`
+#[derive(Debug, Clone, PartialEq)]
+pub struct FindingSensitivity {
+    /// The node that could be observed
+    pub node: usize,
+    /// Expected shift of the target posterior: the total variation distance between the posterior after
+    /// and before the observation, averaged over the predictive distribution of the observation
+    pub expected_shift: f32,
+    /// Largest shift of the target posterior (total variation distance) over the possible observations
+    pub max_shift: f32,
+    /// Expected reduction of the entropy of the target, that is the mutual information between the node
+    /// and the target given the current evidence, in nats
+    pub information_gain: f32,
+}
+
+/// A step of an observation plan, see `BayesNet::plan_observations`
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlannedObservation {
+    /// The node to observe
+    pub node: usize,
+    /// The cost of observing it
+    pub cost: f32,
+    /// Expected information gained on the targets by this observation, after the previous ones of the
+    /// plan (sum of the mutual information with each target, in nats)
+    pub information_gain: f32,
+}
+
+/// The value of observing a node, see `BayesNet::value_of_information`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ObservationValue {
+    /// The node that could be observed
+    pub node: usize,
+    /// Expected reduction of the entropy of the target (mutual information with it), in nats
+    pub information_gain: f32,
+    /// Expected gain of utility from the best decision, when utilities are given
+    pub utility_gain: Option<f32>,
+}
+
+// the effect of observing a node on the posterior of a target
+pub(crate) struct FindingEffect {
+    // predictive distribution of the observation
+    pub(crate) predictive: Array1<f32>,
+    // posterior of the target before the observation
+    pub(crate) prior: Array1<f32>,
+    // posterior of the target after observing each value, for values with a non-zero probability
+    pub(crate) posteriors: Vec<Option<Array1<f32>>>,
+}
+
+impl FindingEffect {
+    fn expected<F: Fn(&Array1<f32>) -> f32>(&self, f: F) -> f32 {
+        self.predictive
+            .iter()
+            .zip(self.posteriors.iter())
+            .filter_map(|(&p, posterior)| posterior.as_ref().map(|post| p * f(post)))
+            .sum()
+    }
+
+    pub(crate) fn information_gain(&self) -> f32 {
+        self.expected(|post| kl_divergence(post, &self.prior))
+            .max(0.0)
+    }
+
+    // the expected utility of the best decision after the observation, minus the one before
+    pub(crate) fn utility_gain(&self, utilities: &Array2<f32>) -> f32 {
+        let best = |p: &Array1<f32>| utilities.dot(p).fold(f32::NEG_INFINITY, |m, &u| m.max(u));
+        (self.expected(best) - best(&self.prior)).max(0.0)
+    }
+
+    fn shifts(&self) -> impl Iterator<Item = f32> + '_ {
+        self.posteriors
+            .iter()
+            .flatten()
+            .map(move |post| total_variation(post, &self.prior))
+    }
+}
+
+fn kl_divergence(p: &Array1<f32>, q: &Array1<f32>) -> f32 {
+    p.iter()
+        .zip(q.iter())
+        .filter(|&(&p, _)| p > 0.0)
+        .map(|(&p, &q)| p * (p / q).ln())
+        .sum()
+}
+
+fn total_variation(p: &Array1<f32>, q: &Array1<f32>) -> f32 {
+    0.5 * p
+        .iter()
+        .zip(q.iter())
+        .map(|(&p, &q)| (p - q).abs())
+        .sum::<f32>()
+}
+
+impl BayesNet {
+    /// Rank the unobserved nodes by how much observing them would shift the posterior of a target
+    ///
+    /// The posteriors are computed by running `iterations` steps of the Loopy Belief Propagation with the
+    /// current evidence of the network, then for each candidate node with each of its values as
+    /// additional evidence, warm-started as by `what_if_targets`. Nodes with evidence and the target itself
+    /// are not candidates. The result is sorted by decreasing expected shift. The network itself is not
+    /// modified.
+    pub fn sensitivity_to_findings(
+        &self,
+        target: usize,
+        iterations: usize,
+    ) -> Vec<FindingSensitivity> {
+        let base = self.propagated(iterations);
+        let mut report: Vec<FindingSensitivity> = (0..self.nodes.len())
+            .filter(|&node| node != target && self.nodes[node].evidence.is_none())
+            .map(|node| {
+                let effect = base.finding_effect(node, target, iterations);
+                FindingSensitivity {
+                    node,
+                    expected_shift: effect.expected(|post| total_variation(post, &effect.prior)),
+                    max_shift: effect.shifts().fold(0.0, f32::max),
+                    information_gain: effect.information_gain(),
+                }
+            })
+            .collect();
+        report.sort_by(|a, b| b.expected_shift.total_cmp(&a.expected_shift));
+        report
+    }
+
+    /// The value of observing each candidate node, to decide which one to observe next
+    ///
+    /// The value of a candidate is the expected reduction of the uncertainty on the target, and when
+    /// `utilities` is given, the expected gain of utility: `utilities[[d, v]]` is the utility of
+    /// decision `d` when the target has value `v`, and the best decision is taken before and after the
+    /// observation. Both gains are averaged over the predictive distribution of the observation, and are
+    /// never negative.
+    ///
+    /// The posteriors are computed as by `sensitivity_to_findings`, and the result is sorted by
+    /// decreasing utility gain when utilities are given, and by decreasing information gain otherwise.
+    /// The network itself is not modified.
+    ///
+    /// Panics if a candidate is the target or has evidence, or if `utilities` does not have one column
+    /// per value of the target.
+    pub fn value_of_information(
+        &self,
+        target: usize,
+        candidates: &[usize],
+        utilities: Option<&Array2<f32>>,
+        iterations: usize,
+    ) -> Vec<ObservationValue> {
+        if let Some(utilities) = utilities {
+            assert!(
+                utilities.ncols() == self.num_values(target),
+                "The utilities need one column per value of {}",
+                self.node_ref(target)
+            );
+        }
+        for &node in candidates {
+            assert!(
+                node != target && self.nodes[node].evidence.is_none(),
+                "{} cannot be observed: it is the target or already has evidence",
+                self.node_ref(node)
+            );
+        }
+        let base = self.propagated(iterations);
+        let mut values: Vec<ObservationValue> = candidates
+            .iter()
+            .map(|&node| {
+                let effect = base.finding_effect(node, target, iterations);
+                ObservationValue {
+                    node,
+                    information_gain: effect.information_gain(),
+                    utility_gain: utilities.map(|u| effect.utility_gain(u)),
+                }
+            })
+            .collect();
+        values.sort_by(|a, b| match (a.utility_gain, b.utility_gain) {
+            (Some(a), Some(b)) => b.total_cmp(&a),
+            _ => b.information_gain.total_cmp(&a.information_gain),
+        });
+        values
+    }
+
+    // a copy of the network after `iterations` steps from its current state
+    pub(crate) fn propagated(&self, iterations: usize) -> BayesNet {
+        let mut net = self.clone();
+        for _ in 0..iterations {
+            net.step();
+        }
+        net
+    }
+
+    // the effect of observing `node` on `target`, for a network already propagated
+    pub(crate) fn finding_effect(
+        &self,
+        node: usize,
+        target: usize,
+        iterations: usize,
+    ) -> FindingEffect {
+        let beliefs = self.beliefs();
+        let predictive = beliefs[node].as_probabilities();
+        let posteriors = self
+            .what_if_targets(node, &[target], iterations)
+            .into_iter()
+            .zip(predictive.iter())
+            .map(|(posterior, &p)| {
+                if p > 0.0 {
+                    Some(posterior[0].as_probabilities())
+                } else {
+                    None
+                }
+            })
+            .collect();
+        FindingEffect {
+            predictive,
+            prior: beliefs[target].as_probabilities(),
+            posteriors,
+        }
+    }
+
+    /// Plan a sequence of observations to learn about some targets within a budget
+    ///
+    /// `costs` gives the cost of observing each node of the network. The plan is built greedily: each
+    /// step picks the affordable node with the best ratio of expected information gain (summed over the
+    /// targets) to cost. The gain of a node is computed in expectation over the outcomes of the previous
+    /// observations of the plan, so nodes redundant with them are not favored. Planning stops when the
+    /// budget is exhausted or no node brings more information than the `comparison_epsilon` of the
+    /// numeric policy. Nodes with evidence and the targets are not candidates.
+    ///
+    /// Each step enumerates the joint outcomes of the previous observations, and each posterior is
+    /// computed with `iterations` steps of the Loopy Belief Propagation, so this is meant for short plans.
+    ///
+    /// Panics if `costs` does not have one strictly positive cost per node.
+    pub fn plan_observations(
+        &self,
+        targets: &[usize],
+        budget: f32,
+        costs: &[f32],
+        iterations: usize,
+    ) -> Vec<PlannedObservation> {
+        assert!(
+            costs.len() == self.nodes.len() && costs.iter().all(|&c| c > 0.0),
+            "plan_observations needs one strictly positive cost per node"
+        );
+        // the possible outcomes of the observations planned so far, with their probability
+        let mut branches: Vec<(BayesNet, f32)> = vec![(self.propagated(iterations), 1.0)];
+        let mut plan: Vec<PlannedObservation> = Vec::new();
+        let mut remaining = budget;
+        loop {
+            let candidates = (0..self.nodes.len()).filter(|&node| {
+                !targets.contains(&node)
+                    && self.nodes[node].evidence.is_none()
+                    && plan.iter().all(|step| step.node != node)
+                    && costs[node] <= remaining
+            });
+            let best = candidates
+                .map(|node| {
+                    let gain: f32 = branches
+                        .iter()
+                        .map(|(net, p)| {
+                            p * targets
+                                .iter()
+                                .map(|&t| {
+                                    net.finding_effect(node, t, iterations).information_gain()
+                                })
+                                .sum::<f32>()
+                        })
+                        .sum();
+                    (node, gain)
+                })
+                .filter(|&(_, gain)| gain > self.numerics.comparison_epsilon)
+                .max_by(|a, b| (a.1 / costs[a.0]).total_cmp(&(b.1 / costs[b.0])));
+            let (node, gain) = match best {
+                Some(best) => best,
+                None => break,
+            };
+            plan.push(PlannedObservation {
+                node,
+                cost: costs[node],
+                information_gain: gain,
+            });
+            remaining -= costs[node];
+            branches = branches
+                .into_iter()
+                .flat_map(|(net, p)| {
+                    let predictive = net.beliefs()[node].as_probabilities();
+                    (0..predictive.len())
+                        .filter(|&v| predictive[v] > 0.0)
+                        .map(|v| {
+                            let mut branch = net.clone();
+                            branch.nodes[node].evidence = Some(v);
+                            for _ in 0..iterations {
+                                branch.step();
+                            }
+                            (branch, p * predictive[v])
+                        })
+                        .collect::<Vec<_>>()
+                })
+                .collect();
+        }
+        plan
+    }
+}
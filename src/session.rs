@@ -0,0 +1,207 @@
+use crate::BayesNet;
+use std::error::Error;
+use std::fmt;
+
+/// An operation of an inference session, see `Session`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SessionEvent {
+    /// The evidence was replaced, as a list of `(node_id, node_value)`
+    SetEvidence(Vec<(usize, usize)>),
+    /// The state of the inference was reset
+    ResetState,
+    /// This number of steps of inference were run
+    Steps(usize),
+}
+
+/// Error reported when parsing the text form of a `Session`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionParseError {
+    /// The line of the error, starting at 1
+    pub line: usize,
+    /// The content of the invalid line
+    pub content: String,
+}
+
+impl fmt::Display for SessionParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "invalid session event at line {}: \"{}\"",
+            self.line, self.content
+        )
+    }
+}
+
+impl Error for SessionParseError {}
+
+/// A recorded sequence of evidence changes and inference steps
+///
+/// Sessions are recorded by a `SessionRecorder`, and replaying one on the same model reproduces the
+/// exact inference, message by message. They have a line-based text form, one event per line:
+///
+/// ```text
+/// evidence 0=1 3=0
+/// reset
+/// steps 10
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Session {
+    events: Vec<SessionEvent>,
+}
+
+impl Session {
+    /// Create an empty session
+    pub fn new() -> Session {
+        Session { events: Vec::new() }
+    }
+
+    /// The events of the session, in order
+    pub fn events(&self) -> &[SessionEvent] {
+        &self.events
+    }
+
+    /// Append an event to the session, successive steps being merged
+    pub fn push(&mut self, event: SessionEvent) {
+        if let SessionEvent::Steps(n) = event {
+            if let Some(SessionEvent::Steps(ref mut previous)) = self.events.last_mut() {
+                *previous += n;
+                return;
+            }
+        }
+        self.events.push(event);
+    }
+
+    /// Apply all the events of the session to a network
+    pub fn replay(&self, net: &mut BayesNet) {
+        for event in &self.events {
+            match *event {
+                SessionEvent::SetEvidence(ref evidence) => net.set_evidence(evidence),
+                SessionEvent::ResetState => net.reset_state(),
+                SessionEvent::Steps(n) => {
+                    for _ in 0..n {
+                        net.step();
+                    }
+                }
+            }
+        }
+    }
+
+    /// The text form of the session
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        for event in &self.events {
+            match *event {
+                SessionEvent::SetEvidence(ref evidence) => {
+                    text.push_str("evidence");
+                    for &(node, value) in evidence {
+                        text.push_str(&format!(" {}={}", node, value));
+                    }
+                }
+                SessionEvent::ResetState => text.push_str("reset"),
+                SessionEvent::Steps(n) => text.push_str(&format!("steps {}", n)),
+            }
+            text.push('\n');
+        }
+        text
+    }
+
+    /// Parse the text form of a session, ignoring empty lines
+    pub fn from_text(text: &str) -> Result<Session, SessionParseError> {
+        let mut session = Session::new();
+        for (i, line) in text.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let event = parse_event(line).ok_or_else(|| SessionParseError {
+                line: i + 1,
+                content: line.to_owned(),
+            })?;
+            session.events.push(event);
+        }
+        Ok(session)
+    }
+}
+
+pub(crate) fn parse_event(line: &str) -> Option<SessionEvent> {
+    let mut words = line.split_whitespace();
+    let event = match words.next()? {
+        "evidence" => {
+            let evidence = words
+                .by_ref()
+                .map(|pair| {
+                    let (node, value) = pair.split_once('=')?;
+                    Some((node.parse().ok()?, value.parse().ok()?))
+                })
+                .collect::<Option<Vec<_>>>()?;
+            SessionEvent::SetEvidence(evidence)
+        }
+        "reset" => SessionEvent::ResetState,
+        "steps" => SessionEvent::Steps(words.next()?.parse().ok()?),
+        _ => return None,
+    };
+    match words.next() {
+        Some(_) => None,
+        None => Some(event),
+    }
+}
+
+/// A network recording the evidence changes and inference steps run on it
+///
+/// The recording starts from the evidence of the network, whose inference state is reset. The soft
+/// evidence is not recorded.
+#[derive(Debug, Clone)]
+pub struct SessionRecorder {
+    net: BayesNet,
+    session: Session,
+}
+
+impl SessionRecorder {
+    /// Start recording a session on a network
+    pub fn new(mut net: BayesNet) -> SessionRecorder {
+        let evidence = net
+            .nodes
+            .iter()
+            .enumerate()
+            .filter_map(|(id, node)| node.evidence.map(|value| (id, value)))
+            .collect();
+        net.reset_state();
+        let mut session = Session::new();
+        session.push(SessionEvent::SetEvidence(evidence));
+        session.push(SessionEvent::ResetState);
+        SessionRecorder { net, session }
+    }
+
+    /// The network, in its current state
+    pub fn net(&self) -> &BayesNet {
+        &self.net
+    }
+
+    /// The session recorded so far
+    pub fn session(&self) -> &Session {
+        &self.session
+    }
+
+    /// Set the evidence of the network, see `BayesNet::set_evidence`
+    pub fn set_evidence(&mut self, evidence: &[(usize, usize)]) {
+        self.net.set_evidence(evidence);
+        self.session
+            .push(SessionEvent::SetEvidence(evidence.to_vec()));
+    }
+
+    /// Reset the state of the inference, see `BayesNet::reset_state`
+    pub fn reset_state(&mut self) {
+        self.net.reset_state();
+        self.session.push(SessionEvent::ResetState);
+    }
+
+    /// Run one step of inference, see `BayesNet::step`
+    pub fn step(&mut self) {
+        self.net.step();
+        self.session.push(SessionEvent::Steps(1));
+    }
+
+    /// Stop recording, returning the network and the session
+    pub fn into_parts(self) -> (BayesNet, Session) {
+        (self.net, self.session)
+    }
+}
@@ -0,0 +1,101 @@
+use crate::BayesNet;
+use ndarray::{Array1, Array2};
+
+/// The beliefs of a network at some point, see `BayesNet::snapshot`
+#[derive(Debug, Clone, PartialEq)]
+pub struct BeliefSnapshot {
+    beliefs: Vec<Array1<f32>>,
+    iteration: usize,
+}
+
+impl BeliefSnapshot {
+    /// The belief probabilities of each node
+    pub fn beliefs(&self) -> &[Array1<f32>] {
+        &self.beliefs
+    }
+
+    /// Number of steps the network had run when the snapshot was taken
+    pub fn iteration(&self) -> usize {
+        self.iteration
+    }
+
+    /// The belief probabilities as a matrix, see `BayesNet::beliefs_matrix`
+    pub fn matrix(&self) -> Array2<f32> {
+        padded_matrix(&self.beliefs)
+    }
+
+    /// How much the belief of each node changed from this snapshot to a later one
+    ///
+    /// Returns `(node, kl, linf)` for each node: `kl` is the Kullback-Leibler divergence of the new
+    /// belief from the old one, `KL(other || self)` (in nats, infinite if the new belief gives a
+    /// positive probability to a value that was impossible), and `linf` is the largest change of the
+    /// probability of a value.
+    ///
+    /// Panics if the snapshots were not taken on the same network structure.
+    pub fn diff(&self, other: &BeliefSnapshot) -> Vec<(usize, f32, f32)> {
+        assert!(
+            self.beliefs.len() == other.beliefs.len(),
+            "Cannot diff snapshots of {} and {} nodes",
+            self.beliefs.len(),
+            other.beliefs.len()
+        );
+        self.beliefs
+            .iter()
+            .zip(other.beliefs.iter())
+            .enumerate()
+            .map(|(node, (old, new))| {
+                assert!(
+                    old.len() == new.len(),
+                    "Node {} has {} values in a snapshot and {} in the other",
+                    node,
+                    old.len(),
+                    new.len()
+                );
+                let kl = new
+                    .iter()
+                    .zip(old.iter())
+                    .filter(|&(&p, _)| p > 0.0)
+                    .map(|(&p, &q)| p * (p / q).ln())
+                    .sum::<f32>()
+                    .max(0.0);
+                let linf = new
+                    .iter()
+                    .zip(old.iter())
+                    .fold(0.0f32, |m, (&p, &q)| m.max((p - q).abs()));
+                (node, kl, linf)
+            })
+            .collect()
+    }
+}
+
+impl BayesNet {
+    /// Take a snapshot of the current beliefs, to compare them later with `BeliefSnapshot::diff`
+    pub fn snapshot(&self) -> BeliefSnapshot {
+        BeliefSnapshot {
+            beliefs: self
+                .beliefs()
+                .iter()
+                .map(|b| b.as_probabilities())
+                .collect(),
+            iteration: self.iteration,
+        }
+    }
+
+    /// The current belief probabilities of all the nodes, as a matrix
+    ///
+    /// Row `i` is the belief of node `i`, and there are as many columns as the largest number of
+    /// values of a node. The columns past the number of values of a node are padded with NaN.
+    pub fn beliefs_matrix(&self) -> Array2<f32> {
+        self.snapshot().matrix()
+    }
+}
+
+// one row per distribution, padded with NaN
+fn padded_matrix(rows: &[Array1<f32>]) -> Array2<f32> {
+    let width = rows.iter().map(|r| r.len()).max().unwrap_or(0);
+    let mut matrix = Array2::from_elem((rows.len(), width), f32::NAN);
+    for (mut line, row) in matrix.outer_iter_mut().zip(rows.iter()) {
+        line.slice_mut(ndarray::s![..row.len()]).assign(row);
+    }
+    matrix
+}
@@ -0,0 +1,80 @@
+use crate::BayesNet;
+use ndarray::{Array2, ArrayD, IxDyn};
+
+impl BayesNet {
+    /// Add a new node whose distribution is a softmax of a linear function of its parents
+    ///
+    /// Each parent `pi` is encoded as a one-hot vector of its `N_pi` values, and `weights` has one row
+    /// per value of the node and `1 + N_p1 + ... + N_pk` columns: the first column is a bias, followed
+    /// by the weights of the values of each parent in order. The probability of value `v` given the
+    /// parents is proportional to `exp(weights[v, 0] + sum_i weights[v, offset_i + value_i])`.
+    ///
+    /// This needs far fewer parameters than a full table when the parents have many values, see
+    /// `learning::fit_softmax` to learn the weights. Panics if the shape of `weights` does not match
+    /// the parents.
+    pub fn add_softmax_node(&mut self, parents: &[usize], weights: Array2<f32>) -> usize {
+        let table = self.softmax_table(parents, &weights);
+        let id = self.add_node_from_log_probabilities(parents, table);
+        self.nodes[id].softmax = Some(weights);
+        id
+    }
+
+    /// The weights of a softmax node, see `add_softmax_node`
+    ///
+    /// Returns `None` if the node is not a softmax node, or if its table was replaced since, for
+    /// example by `learning::fit_parameters`.
+    pub fn softmax_weights(&self, node: usize) -> Option<&Array2<f32>> {
+        self.nodes[node].softmax.as_ref()
+    }
+
+    /// Replace the weights of a node, which becomes a softmax node, see `add_softmax_node`
+    pub fn set_softmax_weights(&mut self, node: usize, weights: Array2<f32>) {
+        assert!(
+            weights.nrows() == self.num_values(node),
+            "Softmax weights have {} rows for {} values of {}",
+            weights.nrows(),
+            self.num_values(node),
+            self.node_ref(node)
+        );
+        let table = self.softmax_table(&self.parents(node), &weights);
+        self.replace_log_probas(node, table);
+        self.nodes[node].softmax = Some(weights);
+    }
+
+    // the log-probability table of a softmax node, unnormalized
+    fn softmax_table(&self, parents: &[usize], weights: &Array2<f32>) -> ArrayD<f32> {
+        let sizes: Vec<usize> = parents.iter().map(|&p| self.num_values(p)).collect();
+        let columns = 1 + sizes.iter().sum::<usize>();
+        assert!(
+            weights.ncols() == columns,
+            "Softmax weights have {} columns instead of {} for parents {:?}",
+            weights.ncols(),
+            columns,
+            parents
+        );
+        let offsets = one_hot_offsets(&sizes);
+        let mut shape = vec![weights.nrows()];
+        shape.extend(sizes.iter().cloned());
+        ArrayD::from_shape_fn(IxDyn(&shape), |index| {
+            let value = index[0];
+            weights[(value, 0)]
+                + offsets
+                    .iter()
+                    .enumerate()
+                    .map(|(i, &offset)| weights[(value, offset + index[i + 1])])
+                    .sum::<f32>()
+        })
+    }
+}
+
+// column of the weights of the first value of each parent
+pub(crate) fn one_hot_offsets(sizes: &[usize]) -> Vec<usize> {
+    sizes
+        .iter()
+        .scan(1, |offset, &size| {
+            let current = *offset;
+            *offset += size;
+            Some(current)
+        })
+        .collect()
+}
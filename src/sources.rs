@@ -0,0 +1,132 @@
+use crate::{BayesNet, LogProbVector};
+use ndarray::Array1;
+use std::collections::HashMap;
+
+/// A piece of evidence reported by a source of limited reliability
+///
+/// The source (a sensor, a reviewer...) claims that `node` has the value `value`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Report {
+    /// The node the report is about
+    pub node: usize,
+    /// The reported value
+    pub value: usize,
+    /// Identifier of the source of the report
+    pub source: usize,
+}
+
+/// Reliabilities of the sources of reports
+///
+/// A source of reliability `r` reports the true value of a node with probability `r`, and otherwise
+/// reports a value drawn uniformly at random. A reliability of `1.0` makes a report equivalent to hard
+/// evidence, and a reliability of `0.0` makes it meaningless.
+#[derive(Debug, Clone)]
+pub struct SourceReliabilities {
+    reliabilities: HashMap<usize, f32>,
+    default: f32,
+}
+
+impl SourceReliabilities {
+    /// Create a set of reliabilities where every source has the reliability `default`
+    pub fn new(default: f32) -> SourceReliabilities {
+        assert!(
+            (0.0..=1.0).contains(&default),
+            "Reliabilities must be between 0 and 1"
+        );
+        SourceReliabilities {
+            reliabilities: HashMap::new(),
+            default,
+        }
+    }
+
+    /// Set the reliability of a source
+    pub fn set(&mut self, source: usize, reliability: f32) {
+        assert!(
+            (0.0..=1.0).contains(&reliability),
+            "Reliabilities must be between 0 and 1"
+        );
+        self.reliabilities.insert(source, reliability);
+    }
+
+    /// The reliability of a source
+    pub fn get(&self, source: usize) -> f32 {
+        self.reliabilities
+            .get(&source)
+            .cloned()
+            .unwrap_or(self.default)
+    }
+
+    /// The likelihood of each value of a node with `n_values` values given a report of `value` by `source`
+    pub fn likelihood(&self, n_values: usize, value: usize, source: usize) -> LogProbVector {
+        let r = self.get(source);
+        let noise = (1.0 - r) / n_values as f32;
+        let likelihood = Array1::from_shape_fn(n_values, |v| {
+            if v == value {
+                (r + noise).ln()
+            } else {
+                noise.ln()
+            }
+        });
+        LogProbVector::from_log_probabilities(likelihood)
+    }
+
+    /// Learn the reliabilities of the sources from past reports whose true value is known
+    ///
+    /// `history` contains reports along with the actual value of the reported node. For each source, the
+    /// expectation-maximization algorithm is run for `iterations` iterations starting from the current
+    /// reliability of the source, treating whether the source reported reliably or at random as a hidden
+    /// variable. Sources absent from the history keep their reliability.
+    pub fn learn(&mut self, net: &BayesNet, history: &[(Report, usize)], iterations: usize) {
+        // for each source: number of reports and, for each correct report, the number of values of its node
+        let mut stats: HashMap<usize, (usize, Vec<usize>)> = HashMap::new();
+        for &(report, truth) in history {
+            let entry = stats.entry(report.source).or_insert((0, Vec::new()));
+            entry.0 += 1;
+            if report.value == truth {
+                entry.1.push(net.num_values(report.node));
+            }
+        }
+        for (source, (n_reports, correct)) in stats {
+            let mut r = self.get(source);
+            for _ in 0..iterations {
+                // E-step: probability that each report was reliable, 0 for wrong reports
+                let expected: f32 = correct
+                    .iter()
+                    .map(|&n| {
+                        let noise = (1.0 - r) / n as f32;
+                        if r + noise > 0.0 {
+                            r / (r + noise)
+                        } else {
+                            0.0
+                        }
+                    })
+                    .sum();
+                // M-step
+                r = expected / n_reports as f32;
+            }
+            self.reliabilities.insert(source, r);
+        }
+    }
+}
+
+impl BayesNet {
+    /// Sets the reports of unreliable sources as soft evidence for the network
+    ///
+    /// Each report contributes the likelihood given by `SourceReliabilities::likelihood`, and the reports
+    /// on a same node are combined assuming the sources are independent. This replaces the previously
+    /// set reports, and adds to the hard evidence set with `set_evidence`.
+    pub fn set_reports(&mut self, reports: &[Report], reliabilities: &SourceReliabilities) {
+        for node in &mut self.nodes {
+            node.soft_evidence = None;
+        }
+        for report in reports {
+            let n_values = self.num_values(report.node);
+            let likelihood = reliabilities.likelihood(n_values, report.value, report.source);
+            let soft = &mut self.nodes[report.node].soft_evidence;
+            match soft {
+                Some(ref mut soft) => soft.prod(&likelihood),
+                None => *soft = Some(likelihood),
+            }
+        }
+    }
+}
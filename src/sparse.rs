@@ -0,0 +1,65 @@
+use crate::BayesNet;
+
+impl BayesNet {
+    /// Enable or disable the sparse propagation
+    ///
+    /// With the sparse propagation, `step` skips the connected components of the network that hold no
+    /// evidence: their messages are set once to their prior state (messages from parents are the prior
+    /// marginals of the parents, computed by an ancestral pass, and messages from children are uniform),
+    /// and are not updated anymore until the component receives evidence. This is a large speedup for
+    /// networks made of many independent fragments, of which only a few are observed at once.
+    ///
+    /// The beliefs of the skipped components are their prior marginals. They are exact for components
+    /// without loops, and for components with loops they are the result of the ancestral pass, which
+    /// ignores the loops, rather than the fixed point of the Loopy Belief Propagation.
+    pub fn set_sparse_propagation(&mut self, enabled: bool) {
+        self.sparse = if enabled {
+            Some(vec![false; self.nodes.len()])
+        } else {
+            None
+        };
+    }
+
+    /// Whether the sparse propagation is enabled, see `set_sparse_propagation`
+    pub fn sparse_propagation(&self) -> bool {
+        self.sparse.is_some()
+    }
+
+    // Put the components without evidence in their prior state if they are not already, and return
+    // which nodes the next step must skip.
+    pub(crate) fn settle_idle_components(&mut self) -> Vec<bool> {
+        let n = self.nodes.len();
+        let mut settled = match self.sparse.take() {
+            Some(settled) => settled,
+            None => return vec![false; n],
+        };
+        settled.resize(n, false);
+
+        let labels = self.component_labels();
+        let n_components = labels.iter().map(|&l| l + 1).max().unwrap_or(0);
+        let mut observed = vec![false; n_components];
+        for (node, &label) in self.nodes.iter().zip(labels.iter()) {
+            if node.evidence.is_some() || node.soft_likelihood().is_some() {
+                observed[label] = true;
+            }
+        }
+        let idle: Vec<bool> = labels.iter().map(|&l| !observed[l]).collect();
+
+        if idle.iter().zip(settled.iter()).any(|(&i, &s)| i && !s) {
+            let marginals = self.prior_marginals().to_vec();
+            for id in (0..n).filter(|&id| idle[id] && !settled[id]) {
+                let node = &mut self.nodes[id];
+                for (parent, msg) in &mut node.parents {
+                    *msg = marginals[*parent].clone();
+                }
+                for (_, msg) in &mut node.children {
+                    msg.reset();
+                }
+                node.lambda = None;
+                node.pi = None;
+            }
+        }
+        self.sparse = Some(idle.clone());
+        idle
+    }
+}
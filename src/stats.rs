@@ -0,0 +1,133 @@
+//! Statistical tests of conditional independence on data
+//!
+//! These test whether two nodes are independent given a set of other nodes in a dataset, for example
+//! to check the independencies implied by the structure of a network against the data. Datasets follow
+//! the conventions of `learning`: each record is a `Vec<usize>` holding the value of every node of the
+//! network, indexed by node id.
+
+use crate::learning::check_record;
+use crate::math::gamma_q;
+use crate::BayesNet;
+use ndarray::{Array2, Axis};
+use std::collections::BTreeMap;
+
+/// The statistic of an independence test
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndependenceTest {
+    /// Pearson's chi-squared statistic, `sum (observed - expected)² / expected`
+    ChiSquared,
+    /// The likelihood-ratio statistic `2 * sum observed * ln(observed / expected)`, which is `2 * N`
+    /// times the conditional mutual information
+    GSquared,
+}
+
+/// The result of `independence_test`
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndependenceResult {
+    /// The value of the statistic
+    pub statistic: f32,
+    /// The degrees of freedom of its chi-squared distribution under independence
+    pub degrees_of_freedom: usize,
+    /// The probability of a statistic at least this large if the nodes are independent
+    pub p_value: f32,
+    /// The conditional mutual information of the nodes in the data, in nats
+    ///
+    /// This is the effect size: it is zero when the nodes are independent in the data, and does not
+    /// grow with the number of records, unlike the statistic.
+    pub mutual_information: f32,
+}
+
+impl IndependenceResult {
+    /// Whether independence is rejected at significance level `alpha`, such as `0.05`
+    pub fn rejects_independence(&self, alpha: f32) -> bool {
+        self.p_value < alpha
+    }
+}
+
+/// Test whether nodes `x` and `y` are independent given the nodes `given` in the data
+///
+/// The counts of the values of `x` and `y` are compared to the counts expected under independence in
+/// each configuration of the values of `given` present in the data. The statistic follows a chi-squared
+/// distribution with `(N_x - 1) * (N_y - 1)` degrees of freedom per configuration of `given` present
+/// in the data, `N_x` and `N_y` being the numbers of values of the nodes in the network.
+///
+/// Panics if a record does not have a valid value for each node of the network, or if `x` or `y` is
+/// in `given`.
+pub fn independence_test(
+    net: &BayesNet,
+    data: &[Vec<usize>],
+    x: usize,
+    y: usize,
+    given: &[usize],
+    test: IndependenceTest,
+) -> IndependenceResult {
+    assert!(
+        x != y && !given.contains(&x) && !given.contains(&y),
+        "Cannot test the independence of {} and {} given {:?}",
+        x,
+        y,
+        given
+    );
+    for (index, record) in data.iter().enumerate() {
+        check_record(net, record, index);
+    }
+    let cardinalities: Vec<usize> = (0..net.num_nodes()).map(|n| net.num_values(n)).collect();
+    test_independence(data, &cardinalities, x, y, given, test)
+}
+
+// the independence test on records with valid values for the given numbers of values of the nodes
+pub(crate) fn test_independence(
+    data: &[Vec<usize>],
+    cardinalities: &[usize],
+    x: usize,
+    y: usize,
+    given: &[usize],
+    test: IndependenceTest,
+) -> IndependenceResult {
+    // the counts of x and y in each configuration of the values of `given` present in the data
+    let mut strata: BTreeMap<Vec<usize>, Array2<f64>> = BTreeMap::new();
+    for record in data {
+        let config = given.iter().map(|&g| record[g]).collect();
+        strata
+            .entry(config)
+            .or_insert_with(|| Array2::zeros((cardinalities[x], cardinalities[y])))
+            [(record[x], record[y])] += 1.0;
+    }
+
+    let total = data.len() as f64;
+    let (mut chi_squared, mut g_squared, mut degrees_of_freedom) = (0.0, 0.0, 0);
+    for stratum in strata.values() {
+        let n = stratum.sum();
+        degrees_of_freedom += (stratum.nrows() - 1) * (stratum.ncols() - 1);
+        let (rows, columns) = (stratum.sum_axis(Axis(1)), stratum.sum_axis(Axis(0)));
+        for ((i, j), &observed) in stratum.indexed_iter() {
+            let expected = rows[i] * columns[j] / n;
+            if expected == 0.0 {
+                continue;
+            }
+            chi_squared += (observed - expected) * (observed - expected) / expected;
+            if observed > 0.0 {
+                g_squared += 2.0 * observed * (observed / expected).ln();
+            }
+        }
+    }
+    let statistic = match test {
+        IndependenceTest::ChiSquared => chi_squared,
+        IndependenceTest::GSquared => g_squared,
+    };
+    let p_value = if degrees_of_freedom == 0 {
+        1.0
+    } else {
+        gamma_q(degrees_of_freedom as f64 / 2.0, statistic / 2.0)
+    };
+    IndependenceResult {
+        statistic: statistic as f32,
+        degrees_of_freedom,
+        p_value: p_value as f32,
+        mutual_information: if total > 0.0 {
+            (g_squared / (2.0 * total)) as f32
+        } else {
+            0.0
+        },
+    }
+}
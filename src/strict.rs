@@ -0,0 +1,107 @@
+use crate::{BayesNet, BuildError};
+use ndarray::{ArrayView, Axis, Dimension, RemoveAxis};
+use std::fmt;
+
+/// A suspicious but valid table given to a network in strict mode, see `BayesNet::set_strict_inputs`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InputWarning {
+    /// All the probabilities of a column of a table are zero
+    ///
+    /// The distribution of the node is then undefined for this configuration of its parents, and
+    /// the inference produces NaN as soon as this configuration is possible.
+    ZeroColumn {
+        /// The node of the table
+        node: usize,
+        /// The values of the parents of the node defining the column, in the order of the parents
+        parent_values: Vec<usize>,
+    },
+}
+
+impl fmt::Display for InputWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            InputWarning::ZeroColumn {
+                node,
+                ref parent_values,
+            } => write!(
+                f,
+                "all probabilities of node {} are zero for parent values {:?}",
+                node, parent_values
+            ),
+        }
+    }
+}
+
+impl BayesNet {
+    /// Enable or disable the validation of the tables of the nodes added afterwards
+    ///
+    /// In strict mode, `add_node_from_probabilities` rejects probabilities that are negative or not
+    /// finite, and `add_node_from_log_probabilities` rejects log-probabilities that are NaN or `+inf`
+    /// (see `BuildError`), instead of silently producing NaN during the inference. Columns whose
+    /// probabilities are all zero are accepted, but recorded in `input_warnings`.
+    pub fn set_strict_inputs(&mut self, strict: bool) {
+        self.strict = strict;
+    }
+
+    /// Whether the tables of new nodes are validated, see `set_strict_inputs`
+    pub fn strict_inputs(&self) -> bool {
+        self.strict
+    }
+
+    /// The warnings raised by the tables added in strict mode, in the order they were found
+    pub fn input_warnings(&self) -> &[InputWarning] {
+        &self.warnings
+    }
+
+    // reject probabilities that cannot be turned into log-probabilities
+    pub(crate) fn validate_probabilities<D: Dimension>(
+        &self,
+        probabilities: ArrayView<f32, D>,
+    ) -> Result<(), BuildError> {
+        let node = self.nodes.len();
+        match probabilities
+            .iter()
+            .position(|&p| !(p.is_finite() && p >= 0.0))
+        {
+            Some(index) => Err(BuildError::InvalidProbability {
+                node,
+                index,
+                value: probabilities.iter().nth(index).copied().unwrap(),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    // reject invalid log-probabilities, and record the columns of impossible values
+    pub(crate) fn validate_log_probabilities<D: Dimension + RemoveAxis>(
+        &mut self,
+        log_probabilities: ArrayView<f32, D>,
+    ) -> Result<(), BuildError> {
+        let id = self.nodes.len();
+        if let Some(index) = log_probabilities
+            .iter()
+            .position(|&l| l.is_nan() || l == f32::INFINITY)
+        {
+            return Err(BuildError::InvalidLogProbability {
+                node: id,
+                index,
+                value: log_probabilities.iter().nth(index).copied().unwrap(),
+            });
+        }
+        let table = log_probabilities.into_dyn();
+        let configurations = table.index_axis(Axis(0), 0);
+        for (column, (parent_values, _)) in table
+            .lanes(Axis(0))
+            .into_iter()
+            .zip(configurations.indexed_iter())
+        {
+            if column.iter().all(|&l| l == f32::NEG_INFINITY) {
+                self.warnings.push(InputWarning::ZeroColumn {
+                    node: id,
+                    parent_values: parent_values.slice().to_vec(),
+                });
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,76 @@
+//! Sweeps of the parameters of a model, for sensitivity plots
+//!
+//! A sweep runs the inference once for each value of a parameter, and reports the beliefs of some
+//! target nodes for each value. The runs are warm-started from the previous one, and the modified
+//! network shares all its other tables with the base model, see `BayesNet::with_probabilities`.
+
+use crate::{BayesNet, LogProbVector};
+use ndarray::Axis;
+
+/// The beliefs of the targets for each value of an entry of the table of a node
+///
+/// `entry` is the index of the parameter in the probability table of `node`, with the same layout as
+/// for `BayesNet::add_node_from_probabilities`: the value of the node, then the values of its parents.
+/// For each of `values`, the probability of this entry is set to the value, and the other probabilities
+/// of the same distribution (the same values of the parents) are scaled so that it stays normalized,
+/// keeping their ratios. Then `iterations` steps are run with the current evidence of the network, and
+/// the beliefs of the targets are recorded. The result holds, for each value, the beliefs of the
+/// targets in order.
+///
+/// The first run starts from the current messages of the network, and each of the next ones from the
+/// messages of the previous run. The network itself is not modified. Panics if the entry is not in
+/// the table of the node, or if a value is not a probability.
+pub fn over_parameter(
+    net: &BayesNet,
+    node: usize,
+    entry: &[usize],
+    values: &[f32],
+    targets: &[usize],
+    iterations: usize,
+) -> Vec<Vec<LogProbVector>> {
+    let base = net.nodes[node].log_probas.dense().mapv(f32::exp);
+    assert!(
+        entry.len() == base.ndim() && entry.iter().zip(base.shape()).all(|(&i, &n)| i < n),
+        "Entry {:?} is not in the table of {}, of shape {:?}",
+        entry,
+        net.node_ref(node),
+        base.shape()
+    );
+    assert!(
+        values.iter().all(|v| (0.0..=1.0).contains(v)),
+        "Swept values must be probabilities, got {:?}",
+        values
+    );
+    let mut scenario = net.clone();
+    values
+        .iter()
+        .map(|&value| {
+            let mut table = base.clone();
+            {
+                // the distribution containing the entry
+                let mut column = table.view_mut();
+                for (axis, &index) in entry.iter().enumerate().skip(1).rev() {
+                    column = column.index_axis_move(Axis(axis), index);
+                }
+                let rest = column.sum() - column[[entry[0]].as_slice()];
+                let others = column.len() - 1;
+                for (v, p) in column.iter_mut().enumerate() {
+                    *p = if v == entry[0] {
+                        value
+                    } else if rest > 0.0 {
+                        *p * (1.0 - value) / rest
+                    } else {
+                        // the entry had all the mass, share the remainder evenly
+                        (1.0 - value) / others.max(1) as f32
+                    };
+                }
+            }
+            scenario.set_probabilities(node, table);
+            for _ in 0..iterations {
+                scenario.step();
+            }
+            let beliefs = scenario.beliefs();
+            targets.iter().map(|&t| beliefs[t].clone()).collect()
+        })
+        .collect()
+}
@@ -0,0 +1,85 @@
+use crate::aggregate::AggregateCpd;
+use crate::cpt_tree::CptTree;
+use ndarray::{Array1, ArrayD, IxDyn};
+use std::borrow::Cow;
+use std::sync::Arc;
+
+// the log-probability table of a node, indexed by `[value, parent values...]`
+//
+// Tables given by a structured CPD are never stored densely: their entries are computed from the CPD
+// when needed, and the algorithms which need the whole table get a temporary dense copy.
+#[derive(Debug, Clone)]
+pub(crate) enum Table {
+    Dense(Arc<ArrayD<f32>>),
+    Aggregate(Arc<AggregateCpd>),
+    // a tree, and the shape of the table it represents
+    Tree(Arc<CptTree>, Vec<usize>),
+}
+
+impl Table {
+    pub(crate) fn shape(&self) -> &[usize] {
+        match *self {
+            Table::Dense(ref table) => table.shape(),
+            Table::Aggregate(ref cpd) => cpd.shape(),
+            Table::Tree(_, ref shape) => shape,
+        }
+    }
+
+    pub(crate) fn num_values(&self) -> usize {
+        self.shape()[0]
+    }
+
+    pub(crate) fn ndim(&self) -> usize {
+        self.shape().len()
+    }
+
+    // number of entries of the table
+    pub(crate) fn len(&self) -> usize {
+        self.shape().iter().product()
+    }
+
+    // the entry for a value of the node and values of its parents
+    pub(crate) fn get(&self, index: &[usize]) -> f32 {
+        match *self {
+            Table::Dense(ref table) => table[IxDyn(index)],
+            Table::Aggregate(ref cpd) => cpd.log_probability(index[0], &index[1..]),
+            Table::Tree(ref tree, _) => tree.log_probabilities(&index[1..])[index[0]],
+        }
+    }
+
+    // the log-distribution of the node given values of its parents
+    pub(crate) fn column(&self, parent_values: &[usize]) -> Array1<f32> {
+        if let Table::Tree(ref tree, _) = *self {
+            return tree.log_probabilities(parent_values).to_owned();
+        }
+        let mut index = Vec::with_capacity(parent_values.len() + 1);
+        index.push(0);
+        index.extend_from_slice(parent_values);
+        Array1::from_shape_fn(self.num_values(), |value| {
+            index[0] = value;
+            self.get(&index)
+        })
+    }
+
+    // the dense table, computed for the structured CPDs
+    pub(crate) fn dense(&self) -> Cow<'_, ArrayD<f32>> {
+        match *self {
+            Table::Dense(ref table) => Cow::Borrowed(table),
+            Table::Aggregate(ref cpd) => Cow::Owned(cpd.to_log_table()),
+            Table::Tree(ref tree, ref shape) => Cow::Owned(tree.to_log_table(shape)),
+        }
+    }
+
+    // whether the two tables are the same shared table
+    pub(crate) fn ptr_eq(&self, other: &Table) -> bool {
+        self.as_ptr() == other.as_ptr()
+    }
+
+    pub(crate) fn as_ptr(&self) -> *const () {
+        match *self {
+            Table::Dense(ref table) => Arc::as_ptr(table) as *const (),
+            Table::Aggregate(ref cpd) => Arc::as_ptr(cpd) as *const (),
+            Table::Tree(ref tree, _) => Arc::as_ptr(tree) as *const (),
+        }
+    }
+}
@@ -0,0 +1,118 @@
+use crate::{BayesNet, LogProbVector};
+
+/// How the timestamped evidence of a node ages, see `BayesNet::observe_at`
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Staleness {
+    /// The evidence never ages, it stays hard evidence until replaced
+    #[default]
+    Persistent,
+    /// The evidence is hard evidence until it is older than `lifetime`, and is then dropped
+    Expiry {
+        /// How long the evidence stays valid, in the unit of the timestamps
+        lifetime: f64,
+    },
+    /// The evidence fades towards no information: the probability the likelihood of the evidence
+    /// gives to the values other than the observed one, `0` for a fresh observation, tends to `1`,
+    /// its distance to `1` halving every `half_life`
+    Decay {
+        /// Half-life of the weight of the evidence, in the unit of the timestamps
+        half_life: f64,
+    },
+}
+
+// an observation made at some time, with its current likelihood
+#[derive(Debug, Clone)]
+pub(crate) struct TimedEvidence {
+    value: usize,
+    time: f64,
+    pub(crate) likelihood: LogProbVector,
+}
+
+impl BayesNet {
+    /// Set how the timestamped evidence of a node ages
+    ///
+    /// The policy applies from the next call to `advance_time`. Panics if the lifetime is negative or
+    /// the half-life is not positive.
+    pub fn set_staleness(&mut self, node: usize, staleness: Staleness) {
+        match staleness {
+            Staleness::Persistent => {}
+            Staleness::Expiry { lifetime } => assert!(
+                lifetime >= 0.0,
+                "The lifetime of the evidence of {} must be non-negative, got {}",
+                self.node_ref(node),
+                lifetime
+            ),
+            Staleness::Decay { half_life } => assert!(
+                half_life > 0.0,
+                "The half-life of the evidence of {} must be positive, got {}",
+                self.node_ref(node),
+                half_life
+            ),
+        }
+        self.nodes[node].staleness = staleness;
+    }
+
+    /// How the timestamped evidence of a node ages
+    pub fn staleness(&self, node: usize) -> Staleness {
+        self.nodes[node].staleness
+    }
+
+    /// Observe the value of a node at a given time
+    ///
+    /// The observation acts as hard evidence, then ages according to the `Staleness` of the node each
+    /// time `advance_time` is called. It replaces the previous timestamped observation of the node, and
+    /// combines with the evidence set by `set_evidence` and `set_reports`. Timestamps are in an
+    /// arbitrary unit, shared by all the nodes and the staleness policies.
+    pub fn observe_at(&mut self, node: usize, value: usize, time: f64) {
+        let n_values = self.num_values(node);
+        self.nodes[node].timed_evidence = Some(TimedEvidence {
+            value,
+            time,
+            likelihood: LogProbVector::deterministic(n_values, value),
+        });
+    }
+
+    /// Remove the timestamped observation of a node
+    pub fn forget_observation(&mut self, node: usize) {
+        self.nodes[node].timed_evidence = None;
+    }
+
+    /// The timestamped observation of a node, as `(value, time)`, if it has not expired
+    pub fn timed_observation(&self, node: usize) -> Option<(usize, f64)> {
+        self.nodes[node]
+            .timed_evidence
+            .as_ref()
+            .map(|timed| (timed.value, timed.time))
+    }
+
+    /// Age the timestamped observations to the time `now`
+    ///
+    /// Expired observations are removed, and decaying ones are weakened according to their age. The
+    /// messages are not reset: the next steps of propagation take the aged evidence into account.
+    pub fn advance_time(&mut self, now: f64) {
+        for node in &mut self.nodes {
+            let n_values = node.log_probas.shape()[0];
+            let timed = match node.timed_evidence {
+                Some(ref mut timed) => timed,
+                None => continue,
+            };
+            let age = (now - timed.time).max(0.0);
+            match node.staleness {
+                Staleness::Persistent => {}
+                Staleness::Expiry { lifetime } => {
+                    if age > lifetime {
+                        node.timed_evidence = None;
+                    }
+                }
+                Staleness::Decay { half_life } => {
+                    let weight = 0.5f64.powf(age / half_life);
+                    let others = (1.0 - weight).ln() as f32;
+                    let likelihood = (0..n_values)
+                        .map(|v| if v == timed.value { 0.0 } else { others })
+                        .collect();
+                    timed.likelihood = LogProbVector::from_log_probabilities(likelihood);
+                }
+            }
+        }
+    }
+}
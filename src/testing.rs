@@ -0,0 +1,8 @@
+//! Utilities to check models and the inference running on them
+//!
+//! These are meant to be used by applications as sanity gates, for example in their CI pipelines,
+//! against their own models and typical evidence.
+
+#[cfg(feature = "differential")]
+pub mod differential;
+pub mod invariants;
@@ -0,0 +1,161 @@
+//! Comparison of the inference with the results recorded by another version of the crate
+//!
+//! Changes to the approximate algorithms can silently shift the beliefs computed on a model. An
+//! `Expectation` records the beliefs obtained by replaying a `Session`, and can be saved as text and
+//! checked later against a newer version of the crate, reporting the values that diverged.
+
+use crate::session::parse_event;
+use crate::{BayesNet, NodeRef, Session, SessionParseError};
+use ndarray::Array1;
+use std::fmt;
+
+/// The beliefs obtained by replaying a session on a model, with the version of the crate
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expectation {
+    version: String,
+    session: Session,
+    beliefs: Vec<Array1<f32>>,
+}
+
+/// A belief probability that differs from its expected value
+#[derive(Debug, Clone, PartialEq)]
+pub struct Divergence {
+    /// The node of the belief
+    pub node: NodeRef,
+    /// The value of the node
+    pub value: usize,
+    /// The recorded probability, NaN if the node or value was not recorded
+    pub expected: f32,
+    /// The probability computed by the current version, NaN if the node or value does not exist
+    pub actual: f32,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "probability of value {} of {} is {} instead of {}",
+            self.value, self.node, self.actual, self.expected
+        )
+    }
+}
+
+impl Expectation {
+    /// Replay a session on a copy of the network, and record the final beliefs
+    pub fn record(net: &BayesNet, session: Session) -> Expectation {
+        let mut net = net.clone();
+        session.replay(&mut net);
+        Expectation {
+            version: env!("CARGO_PKG_VERSION").to_owned(),
+            beliefs: net.beliefs().iter().map(|b| b.as_probabilities()).collect(),
+            session,
+        }
+    }
+
+    /// Version of the crate that recorded the beliefs
+    pub fn version(&self) -> &str {
+        &self.version
+    }
+
+    /// The recorded session
+    pub fn session(&self) -> &Session {
+        &self.session
+    }
+
+    /// The recorded belief probabilities of each node
+    pub fn beliefs(&self) -> &[Array1<f32>] {
+        &self.beliefs
+    }
+
+    /// Replay the session on a copy of the network, and report the probabilities that differ from the
+    /// recorded ones by more than `tolerance`
+    pub fn check(&self, net: &BayesNet, tolerance: f32) -> Vec<Divergence> {
+        let mut net = net.clone();
+        self.session.replay(&mut net);
+        let actual: Vec<Array1<f32>> = net.beliefs().iter().map(|b| b.as_probabilities()).collect();
+        let mut divergences = Vec::new();
+        for node in 0..actual.len().max(self.beliefs.len()) {
+            let expected = self.beliefs.get(node);
+            let belief = actual.get(node);
+            let n_values = expected
+                .map_or(0, |e| e.len())
+                .max(belief.map_or(0, |b| b.len()));
+            for value in 0..n_values {
+                let expected = expected
+                    .and_then(|e| e.get(value))
+                    .copied()
+                    .unwrap_or(f32::NAN);
+                let actual = belief
+                    .and_then(|b| b.get(value))
+                    .copied()
+                    .unwrap_or(f32::NAN);
+                // NaN is only equal to NaN
+                let same = if expected.is_nan() || actual.is_nan() {
+                    expected.is_nan() && actual.is_nan()
+                } else {
+                    (expected - actual).abs() <= tolerance
+                };
+                if !same {
+                    divergences.push(Divergence {
+                        node: net.node_ref(node),
+                        value,
+                        expected,
+                        actual,
+                    });
+                }
+            }
+        }
+        divergences
+    }
+
+    /// The text form of the expectation
+    ///
+    /// It is made of a `version` line, a `belief` line per node with its probabilities, then the
+    /// text form of the session. The probabilities are written with full precision.
+    pub fn to_text(&self) -> String {
+        let mut text = format!("version {}\n", self.version);
+        for belief in &self.beliefs {
+            text.push_str("belief");
+            for p in belief {
+                text.push_str(&format!(" {:?}", p));
+            }
+            text.push('\n');
+        }
+        text.push_str(&self.session.to_text());
+        text
+    }
+
+    /// Parse the text form of an expectation
+    pub fn from_text(text: &str) -> Result<Expectation, SessionParseError> {
+        let mut version = None;
+        let mut beliefs = Vec::new();
+        let mut session = Session::new();
+        for (i, line) in text.lines().enumerate() {
+            let error = || SessionParseError {
+                line: i + 1,
+                content: line.to_owned(),
+            };
+            let mut words = line.split_whitespace();
+            match words.next() {
+                None => {}
+                Some("version") => match (words.next(), words.next()) {
+                    (Some(v), None) if version.is_none() => version = Some(v.to_owned()),
+                    _ => return Err(error()),
+                },
+                Some("belief") => {
+                    let belief = words
+                        .map(|p| p.parse().ok())
+                        .collect::<Option<Vec<f32>>>()
+                        .ok_or_else(error)?;
+                    beliefs.push(Array1::from(belief));
+                }
+                Some(_) => session.push(parse_event(line).ok_or_else(error)?),
+            }
+        }
+        Ok(Expectation {
+            version: version.unwrap_or_default(),
+            session,
+            beliefs,
+        })
+    }
+}
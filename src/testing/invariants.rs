@@ -0,0 +1,186 @@
+//! Properties that the results of the inference must satisfy on any model
+//!
+//! Each check runs the inference from scratch on a copy of the network, with the given evidence and
+//! number of steps, and compares probabilities up to `tolerance`.
+
+use crate::graph::{requisite_evidence, structure};
+use crate::{BayesNet, LogProbVector, NodeRef};
+use std::error::Error;
+use std::fmt;
+
+/// An invariant that does not hold
+#[derive(Debug, Clone, PartialEq)]
+pub enum InvariantViolation {
+    /// The belief of a node does not sum to 1
+    BeliefNotNormalized {
+        /// The node
+        node: NodeRef,
+        /// The sum of its belief
+        sum: f32,
+    },
+    /// The belief of a node with hard evidence is not deterministic
+    EvidenceNotDeterministic {
+        /// The node
+        node: NodeRef,
+        /// The observed value
+        value: usize,
+        /// The probability given to the observed value
+        probability: f32,
+    },
+    /// Adding evidence d-separated from a node changed its belief
+    IrrelevantEvidenceChangedBelief {
+        /// The node whose belief changed
+        node: NodeRef,
+        /// The added evidence, as `(node_id, node_value)`
+        evidence: (usize, usize),
+        /// The largest change of probability of a value of the node
+        difference: f32,
+    },
+}
+
+impl fmt::Display for InvariantViolation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InvariantViolation::BeliefNotNormalized { node, sum } => {
+                write!(f, "belief of {} sums to {}", node, sum)
+            }
+            InvariantViolation::EvidenceNotDeterministic {
+                node,
+                value,
+                probability,
+            } => write!(
+                f,
+                "{} is observed with value {}, but its belief gives it a probability of {}",
+                node, value, probability
+            ),
+            InvariantViolation::IrrelevantEvidenceChangedBelief {
+                node,
+                evidence,
+                difference,
+            } => write!(
+                f,
+                "observing value {} of node {} changed the belief of {} by {}, while they are d-separated",
+                evidence.1, evidence.0, node, difference
+            ),
+        }
+    }
+}
+
+impl Error for InvariantViolation {}
+
+fn infer(net: &BayesNet, evidence: &[(usize, usize)], iterations: usize) -> Vec<LogProbVector> {
+    let mut net = net.clone();
+    net.reset_state();
+    net.set_evidence(evidence);
+    for _ in 0..iterations {
+        net.step();
+    }
+    net.beliefs()
+}
+
+// false for NaN errors as well
+fn within(error: f32, tolerance: f32) -> bool {
+    error <= tolerance
+}
+
+/// Check that the belief of every node sums to 1
+pub fn beliefs_normalized(
+    net: &BayesNet,
+    evidence: &[(usize, usize)],
+    iterations: usize,
+    tolerance: f32,
+) -> Result<(), InvariantViolation> {
+    for (node, belief) in infer(net, evidence, iterations).iter().enumerate() {
+        let sum = belief.as_probabilities().sum();
+        if !within((sum - 1.0).abs(), tolerance) {
+            return Err(InvariantViolation::BeliefNotNormalized {
+                node: net.node_ref(node),
+                sum,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Check that the belief of every observed node gives a probability of 1 to the observed value
+pub fn evidence_deterministic(
+    net: &BayesNet,
+    evidence: &[(usize, usize)],
+    iterations: usize,
+    tolerance: f32,
+) -> Result<(), InvariantViolation> {
+    let beliefs = infer(net, evidence, iterations);
+    for &(node, value) in evidence {
+        let probability = beliefs[node].as_probabilities()[value];
+        if !within((1.0 - probability).abs(), tolerance) {
+            return Err(InvariantViolation::EvidenceNotDeterministic {
+                node: net.node_ref(node),
+                value,
+                probability,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Check that observing a node does not change the beliefs of the nodes it is d-separated from
+///
+/// Each value of each unobserved node that has a non-zero probability given `evidence` is added to it
+/// in turn, and the beliefs of the nodes it is d-separated from (given `evidence`) are compared to their
+/// beliefs without it. Loopy Belief Propagation is exact on networks without loops, and may violate this
+/// invariant on networks with loops.
+pub fn irrelevant_evidence(
+    net: &BayesNet,
+    evidence: &[(usize, usize)],
+    iterations: usize,
+    tolerance: f32,
+) -> Result<(), InvariantViolation> {
+    let parents = structure(net);
+    let reference = infer(net, evidence, iterations);
+    let observed: Vec<usize> = evidence.iter().map(|&(n, _)| n).collect();
+    for extra in (0..net.num_nodes()).filter(|n| !observed.contains(n)) {
+        let mut extended = observed.clone();
+        extended.push(extra);
+        let separated: Vec<usize> = (0..net.num_nodes())
+            .filter(|&node| !requisite_evidence(&parents, node, &extended).contains(&extra))
+            .collect();
+        if separated.is_empty() {
+            continue;
+        }
+        let probabilities = reference[extra].as_probabilities();
+        for value in (0..net.num_values(extra)).filter(|&v| probabilities[v] > tolerance) {
+            let mut extended_evidence = evidence.to_vec();
+            extended_evidence.push((extra, value));
+            let beliefs = infer(net, &extended_evidence, iterations);
+            for &node in &separated {
+                let before = reference[node].as_probabilities();
+                let after = beliefs[node].as_probabilities();
+                let difference = before
+                    .iter()
+                    .zip(after.iter())
+                    .map(|(a, b)| (a - b).abs())
+                    .fold(0.0, f32::max);
+                if !within(difference, tolerance) {
+                    return Err(InvariantViolation::IrrelevantEvidenceChangedBelief {
+                        node: net.node_ref(node),
+                        evidence: (extra, value),
+                        difference,
+                    });
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Run all the checks of this module
+pub fn check_all(
+    net: &BayesNet,
+    evidence: &[(usize, usize)],
+    iterations: usize,
+    tolerance: f32,
+) -> Result<(), InvariantViolation> {
+    beliefs_normalized(net, evidence, iterations, tolerance)?;
+    evidence_deterministic(net, evidence, iterations, tolerance)?;
+    irrelevant_evidence(net, evidence, iterations, tolerance)
+}
@@ -0,0 +1,265 @@
+use crate::bif::{build_network, table_with_node_last, Variable};
+use crate::{BayesNet, BifError};
+use ndarray::Axis;
+use std::fmt::Write;
+
+// the whitespace-separated words of the text, with their line
+struct Words<'a> {
+    words: Vec<(usize, &'a str)>,
+    pos: usize,
+}
+
+impl<'a> Words<'a> {
+    fn new(text: &'a str) -> Words<'a> {
+        let words = text
+            .lines()
+            .enumerate()
+            .flat_map(|(i, line)| line.split_whitespace().map(move |w| (i + 1, w)))
+            .collect();
+        Words { words, pos: 0 }
+    }
+
+    fn line(&self) -> usize {
+        self.words
+            .get(self.pos)
+            .or_else(|| self.words.last())
+            .map_or(1, |&(line, _)| line)
+    }
+
+    fn next(&mut self) -> Result<&'a str, BifError> {
+        match self.words.get(self.pos) {
+            Some(&(_, word)) => {
+                self.pos += 1;
+                Ok(word)
+            }
+            None => Err(BifError::Syntax {
+                line: self.line(),
+                message: "unexpected end of file".to_owned(),
+            }),
+        }
+    }
+
+    fn number<T: std::str::FromStr>(&mut self) -> Result<T, BifError> {
+        let line = self.line();
+        let word = self.next()?;
+        word.parse().map_err(|_| BifError::Syntax {
+            line,
+            message: format!("invalid number \"{}\"", word),
+        })
+    }
+}
+
+// the name of the node of the variable at this index of a UAI file
+fn uai_name(variable: usize) -> String {
+    format!("x{}", variable)
+}
+
+impl BayesNet {
+    /// Read a network in the format of the UAI inference competitions
+    ///
+    /// Only `BAYES` networks are supported, in which each function is the table of the last variable
+    /// of its scope given the others, the last variable of the scope varying the fastest. As the
+    /// variables of this format are anonymous, the node of the `i`-th variable is named `x{i}`, and the
+    /// nodes are added in the order of the variables, except that the parents of a node are always
+    /// added before it.
+    pub fn from_uai(text: &str) -> Result<BayesNet, BifError> {
+        let mut words = Words::new(text);
+        let line = words.line();
+        match words.next()? {
+            "BAYES" => {}
+            "MARKOV" => {
+                return Err(BifError::Syntax {
+                    line,
+                    message: "only BAYES networks are supported".to_owned(),
+                })
+            }
+            other => {
+                return Err(BifError::Syntax {
+                    line,
+                    message: format!("unknown network type \"{}\"", other),
+                })
+            }
+        }
+
+        let n_variables: usize = words.number()?;
+        let mut variables = Vec::with_capacity(n_variables);
+        for i in 0..n_variables {
+            let line = words.line();
+            let size: usize = words.number()?;
+            if size == 0 {
+                return Err(BifError::Syntax {
+                    line,
+                    message: format!("variable {} has no values", i),
+                });
+            }
+            variables.push(Variable {
+                name: uai_name(i),
+                line,
+                states: (0..size).map(|v| v.to_string()).collect(),
+                layout: None,
+            });
+        }
+
+        let n_functions: usize = words.number()?;
+        let mut scopes = Vec::with_capacity(n_functions);
+        for _ in 0..n_functions {
+            let line = words.line();
+            let size: usize = words.number()?;
+            let mut scope = Vec::with_capacity(size);
+            for _ in 0..size {
+                let line = words.line();
+                let variable: usize = words.number()?;
+                if variable >= n_variables {
+                    return Err(BifError::UnknownVariable {
+                        line,
+                        name: uai_name(variable),
+                    });
+                }
+                scope.push(variable);
+            }
+            if scope.is_empty() {
+                return Err(BifError::Syntax {
+                    line,
+                    message: "a function has an empty scope".to_owned(),
+                });
+            }
+            scopes.push((line, scope));
+        }
+
+        let mut tables = vec![None; n_variables];
+        for (line, mut parents) in scopes {
+            let variable = parents.pop().unwrap();
+            if tables[variable].is_some() {
+                return Err(BifError::Syntax {
+                    line,
+                    message: format!("variable {} has two functions", variable),
+                });
+            }
+            let table_line = words.line();
+            let count: usize = words.number()?;
+            let parent_sizes: Vec<usize> =
+                parents.iter().map(|&p| variables[p].states.len()).collect();
+            let size = variables[variable].states.len();
+            let expected = size * parent_sizes.iter().product::<usize>();
+            if count != expected {
+                return Err(BifError::TableSize {
+                    line: table_line,
+                    variable: uai_name(variable),
+                    expected,
+                    found: count,
+                });
+            }
+            let values = (0..count)
+                .map(|_| words.number())
+                .collect::<Result<Vec<f32>, BifError>>()?;
+            let table = table_with_node_last(values, &parent_sizes, size);
+            tables[variable] = Some((parents, table));
+        }
+        if words.pos < words.words.len() {
+            return Err(BifError::Syntax {
+                line: words.line(),
+                message: "unexpected data after the last function".to_owned(),
+            });
+        }
+
+        let mut net = build_network(&variables, tables)?;
+        // the values of the variables are anonymous too
+        for node in &mut net.nodes {
+            node.state_names = None;
+        }
+        Ok(net)
+    }
+
+    /// Read an evidence file of the UAI inference competitions, for a network read by `from_uai`
+    ///
+    /// The evidence is returned as `(node, value)` pairs for `set_evidence`. Both the current format, a
+    /// single line with the number of observed variables followed by their indices and values, and
+    /// the older one starting with the number of evidence samples are accepted. In the latter case,
+    /// only the first sample is read.
+    pub fn read_uai_evidence(&self, text: &str) -> Result<Vec<(usize, usize)>, BifError> {
+        let mut words = Words::new(text);
+        if words.words.is_empty() {
+            return Ok(Vec::new());
+        }
+        let first: usize = words.number()?;
+        if words.words.len() != 1 + 2 * first {
+            // the older format, whose first number is the number of samples
+            if first == 0 {
+                return Ok(Vec::new());
+            }
+        } else {
+            words.pos = 0;
+        }
+        let count: usize = words.number()?;
+        let mut evidence = Vec::with_capacity(count);
+        for _ in 0..count {
+            let line = words.line();
+            let variable: usize = words.number()?;
+            let value: usize = words.number()?;
+            let node = self
+                .find_node(&uai_name(variable))
+                .ok_or(BifError::UnknownVariable {
+                    line,
+                    name: uai_name(variable),
+                })?;
+            if value >= self.num_values(node) {
+                return Err(BifError::UnknownState {
+                    line,
+                    variable: uai_name(variable),
+                    state: value.to_string(),
+                });
+            }
+            evidence.push((node, value));
+        }
+        Ok(evidence)
+    }
+
+    /// Write the network in the format of the UAI inference competitions, see `from_uai`
+    ///
+    /// The `i`-th variable of the file is the node of id `i`, names and state names are not written.
+    pub fn to_uai(&self) -> String {
+        let mut uai = format!("BAYES\n{}\n", self.num_nodes());
+        let sizes: Vec<String> = (0..self.num_nodes())
+            .map(|node| self.num_values(node).to_string())
+            .collect();
+        writeln!(uai, "{}\n{}", sizes.join(" "), self.num_nodes()).unwrap();
+        for (node, data) in self.nodes.iter().enumerate() {
+            write!(uai, "{}", data.parents.len() + 1).unwrap();
+            for &(parent, _) in &data.parents {
+                write!(uai, " {}", parent).unwrap();
+            }
+            writeln!(uai, " {}", node).unwrap();
+        }
+        for data in &self.nodes {
+            write!(uai, "\n{}\n", data.log_probas.len()).unwrap();
+            // one row per combination of values of the parents, the node varying the fastest
+            let log_probas = data.log_probas.dense();
+            let mut table = log_probas.view();
+            for axis in 0..data.parents.len() {
+                table.swap_axes(axis, axis + 1);
+            }
+            let rows = table.lanes(Axis(table.ndim() - 1));
+            for row in rows {
+                let values: Vec<String> = row.iter().map(|l| l.exp().to_string()).collect();
+                writeln!(uai, " {}", values.join(" ")).unwrap();
+            }
+        }
+        uai
+    }
+
+    /// Write the evidence in the format of the UAI evidence files, for the network written by `to_uai`
+    pub fn to_uai_evidence(&self, evidence: &[(usize, usize)]) -> String {
+        let mut uai = evidence.len().to_string();
+        for &(node, value) in evidence {
+            assert!(
+                node < self.num_nodes(),
+                "Evidence on node {} of a network of {} nodes",
+                node,
+                self.num_nodes()
+            );
+            write!(uai, " {} {}", node, value).unwrap();
+        }
+        uai.push('\n');
+        uai
+    }
+}
@@ -0,0 +1,130 @@
+use crate::BayesNet;
+use ndarray::{Array, Array1, ArrayD, ArrayViewD, Axis, Dimension, RemoveAxis};
+use rand::Rng;
+use rand_distr::{Distribution, Gamma};
+
+/// Summary of the distribution of the posterior probabilities of a node
+///
+/// It is computed by `BayesNet::belief_uncertainty` from the second-order uncertainty over the
+/// conditional probability tables.
+#[derive(Debug, Clone)]
+pub struct BeliefStats {
+    /// Mean of the posterior probability of each value
+    pub mean: Array1<f32>,
+    /// Standard deviation of the posterior probability of each value
+    pub std_dev: Array1<f32>,
+}
+
+fn check_concentrations(concentrations: &ArrayD<f32>) {
+    assert!(
+        concentrations.iter().all(|&a| a > 0.0 && a.is_finite()),
+        "Dirichlet concentrations must be finite and strictly positive"
+    );
+}
+
+impl BayesNet {
+    /// Add a new node whose conditional probabilities follow a Dirichlet distribution
+    ///
+    /// The `concentrations` array has the same shape as for `add_node_from_probabilities`, each column
+    /// (configuration of the parents) being the concentration parameters of an independent Dirichlet
+    /// distribution. These are typically observed counts plus a prior pseudo-count.
+    ///
+    /// The node uses the mean of these distributions as its probabilities for regular inference, and
+    /// the concentrations are used by `belief_uncertainty`. They must all be finite and strictly positive.
+    pub fn add_node_from_dirichlet<D: Dimension + RemoveAxis>(
+        &mut self,
+        parents: &[usize],
+        concentrations: Array<f32, D>,
+    ) -> usize {
+        let concentrations = concentrations.into_dyn();
+        check_concentrations(&concentrations);
+        let id = self.add_node_from_probabilities(parents, concentrations.clone());
+        self.nodes[id].dirichlet = Some(concentrations);
+        id
+    }
+
+    /// Attach Dirichlet concentrations to an existing node, or remove them with `None`
+    ///
+    /// Unlike `add_node_from_dirichlet`, this does not change the probabilities of the node used for
+    /// regular inference. The concentrations must have the same shape as the probability table of the node.
+    pub fn set_dirichlet(&mut self, node: usize, concentrations: Option<ArrayD<f32>>) {
+        if let Some(ref c) = concentrations {
+            assert!(
+                c.shape() == self.nodes[node].log_probas.shape(),
+                "Dirichlet concentrations must have the same shape as the probabilities of the node"
+            );
+            check_concentrations(c);
+        }
+        self.nodes[node].dirichlet = concentrations;
+    }
+
+    /// Get the Dirichlet concentrations of a node, if any
+    pub fn dirichlet(&self, node: usize) -> Option<ArrayViewD<'_, f32>> {
+        self.nodes[node].dirichlet.as_ref().map(|c| c.view())
+    }
+
+    /// Estimate how uncertain the beliefs are due to the uncertainty over the probability tables
+    ///
+    /// This draws `n_samples` networks by sampling the probability tables of every node having Dirichlet
+    /// concentrations (other nodes are kept fixed), runs the Loopy Belief Propagation for `iterations`
+    /// steps on each of them with the given evidence, and returns the mean and standard deviation of the
+    /// resulting posterior probabilities of each node.
+    ///
+    /// A posterior of `0.5` with a small standard deviation comes from balanced evidence, while a large
+    /// standard deviation means the parameters of the model are not known well enough to conclude.
+    pub fn belief_uncertainty<R: Rng + ?Sized>(
+        &self,
+        rng: &mut R,
+        evidence: &[(usize, usize)],
+        n_samples: usize,
+        iterations: usize,
+    ) -> Vec<BeliefStats> {
+        let mut net = self.clone();
+        net.set_evidence(evidence);
+        let mut sums: Vec<Array1<f64>> = (0..net.num_nodes())
+            .map(|i| Array1::zeros(net.num_values(i)))
+            .collect();
+        let mut sq_sums = sums.clone();
+
+        for _ in 0..n_samples {
+            for id in 0..self.nodes.len() {
+                if let Some(ref concentrations) = self.nodes[id].dirichlet {
+                    let mut sample = concentrations
+                        .mapv(|a| Gamma::new(f64::from(a), 1.0).unwrap().sample(rng) as f32);
+                    for mut column in sample.lanes_mut(Axis(0)) {
+                        let total = column.sum();
+                        if total > 0.0 {
+                            column.mapv_inplace(|v| (v / total).ln());
+                        } else {
+                            // all gamma draws underflowed, fall back to uniform
+                            column.fill(0.0);
+                        }
+                    }
+                    net.replace_log_probas(id, sample);
+                }
+            }
+            net.reset_state();
+            for _ in 0..iterations {
+                net.step();
+            }
+            for (i, belief) in net.beliefs().iter().enumerate() {
+                let probas = belief.as_probabilities().mapv(f64::from);
+                sq_sums[i] += &probas.mapv(|p| p * p);
+                sums[i] += &probas;
+            }
+        }
+
+        let n = n_samples.max(1) as f64;
+        sums.into_iter()
+            .zip(sq_sums)
+            .map(|(sum, sq_sum)| {
+                let mean = sum / n;
+                let var = (sq_sum / n - mean.mapv(|m| m * m)).mapv(|v| v.max(0.0));
+                BeliefStats {
+                    mean: mean.mapv(|m| m as f32),
+                    std_dev: var.mapv(|v| v.sqrt() as f32),
+                }
+            })
+            .collect()
+    }
+}
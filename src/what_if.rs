@@ -0,0 +1,39 @@
+use crate::{BayesNet, LogProbVector};
+
+impl BayesNet {
+    /// What-if analysis: the beliefs of all the nodes for each possible value of a node
+    ///
+    /// See `what_if_targets`, which this calls with all the nodes as targets.
+    pub fn what_if(&self, node: usize, iterations: usize) -> Vec<Vec<LogProbVector>> {
+        let targets: Vec<usize> = (0..self.nodes.len()).collect();
+        self.what_if_targets(node, &targets, iterations)
+    }
+
+    /// What-if analysis: the beliefs of some target nodes for each possible value of a node
+    ///
+    /// For each value of `node`, this value is set as evidence in addition to the current evidence of the
+    /// network (replacing the evidence of `node` if it has one), and `iterations` steps are run. The
+    /// result holds, for each value of `node`, the beliefs of the targets in order.
+    ///
+    /// The runs are warm-started: the first one starts from the current messages of the network, and
+    /// each of the next ones from the messages of the previous run, which are usually close to the
+    /// fixed point. The network itself is not modified.
+    pub fn what_if_targets(
+        &self,
+        node: usize,
+        targets: &[usize],
+        iterations: usize,
+    ) -> Vec<Vec<LogProbVector>> {
+        let mut net = self.clone();
+        (0..self.num_values(node))
+            .map(|value| {
+                net.nodes[node].evidence = Some(value);
+                for _ in 0..iterations {
+                    net.step();
+                }
+                let beliefs = net.beliefs();
+                targets.iter().map(|&t| beliefs[t].clone()).collect()
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,332 @@
+use crate::bif::{build_network, parse_position, table_with_node_last, Variable};
+use crate::{BayesNet, BifError, NodeLayout};
+use std::collections::HashMap;
+
+// an XML element, with its text and its child elements
+struct Element {
+    name: String,
+    line: usize,
+    text: String,
+    children: Vec<Element>,
+}
+
+impl Element {
+    fn children<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Element> + 'a {
+        self.children
+            .iter()
+            .filter(move |c| c.name.eq_ignore_ascii_case(name))
+    }
+
+    fn child(&self, name: &str) -> Option<&Element> {
+        self.children
+            .iter()
+            .find(|c| c.name.eq_ignore_ascii_case(name))
+    }
+
+    // the text of the first child with this name
+    fn child_text(&self, name: &str) -> Result<String, BifError> {
+        match self.child(name) {
+            Some(child) => Ok(child.text.trim().to_owned()),
+            None => Err(BifError::Syntax {
+                line: self.line,
+                message: format!("<{}> has no <{}>", self.name, name),
+            }),
+        }
+    }
+}
+
+// a minimal XML reader, ignoring attributes, comments, declarations and processing instructions
+struct XmlReader {
+    chars: Vec<char>,
+    pos: usize,
+    line: usize,
+}
+
+impl XmlReader {
+    fn error<T>(&self, message: &str) -> Result<T, BifError> {
+        Err(BifError::Syntax {
+            line: self.line,
+            message: message.to_owned(),
+        })
+    }
+
+    fn starts_with(&self, prefix: &str) -> bool {
+        prefix
+            .chars()
+            .enumerate()
+            .all(|(i, c)| self.chars.get(self.pos + i) == Some(&c))
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.chars.get(self.pos).copied()?;
+        self.pos += 1;
+        if c == '\n' {
+            self.line += 1;
+        }
+        Some(c)
+    }
+
+    // consume everything up to and including `end`
+    fn skip_past(&mut self, end: &str) -> Result<String, BifError> {
+        let mut skipped = String::new();
+        while !self.starts_with(end) {
+            match self.bump() {
+                Some(c) => skipped.push(c),
+                None => return self.error(&format!("missing \"{}\"", end)),
+            }
+        }
+        self.pos += end.chars().count();
+        Ok(skipped)
+    }
+
+    // skip a `<!...>` declaration, which may contain a bracketed internal subset
+    fn skip_declaration(&mut self) -> Result<(), BifError> {
+        let mut depth = 0;
+        loop {
+            match self.bump() {
+                Some('[') => depth += 1,
+                Some(']') => depth -= 1,
+                Some('>') if depth == 0 => return Ok(()),
+                Some(_) => {}
+                None => return self.error("unterminated declaration"),
+            }
+        }
+    }
+
+    // skip the markup that is not an element, returning false at a tag
+    fn skip_misc(&mut self, text: &mut String) -> Result<bool, BifError> {
+        if self.starts_with("<!--") {
+            self.skip_past("-->")?;
+        } else if self.starts_with("<![CDATA[") {
+            self.pos += "<![CDATA[".len();
+            text.push_str(&self.skip_past("]]>")?);
+        } else if self.starts_with("<!") {
+            self.skip_declaration()?;
+        } else if self.starts_with("<?") {
+            self.skip_past("?>")?;
+        } else {
+            return Ok(false);
+        }
+        Ok(true)
+    }
+
+    fn name(&mut self) -> String {
+        let mut name = String::new();
+        while let Some(&c) = self.chars.get(self.pos) {
+            if c.is_whitespace() || c == '>' || c == '/' {
+                break;
+            }
+            name.push(c);
+            self.pos += 1;
+        }
+        name
+    }
+
+    fn element(&mut self) -> Result<Element, BifError> {
+        let line = self.line;
+        self.pos += 1;
+        let name = self.name();
+        if name.is_empty() {
+            return self.error("invalid tag");
+        }
+        // skip the attributes
+        let mut quote = None;
+        let empty = loop {
+            match (self.bump(), quote) {
+                (Some(c), Some(q)) if c == q => quote = None,
+                (Some(_), Some(_)) => {}
+                (Some(c @ '"'), None) | (Some(c @ '\''), None) => quote = Some(c),
+                (Some('>'), None) => break self.chars[self.pos - 2] == '/',
+                (Some(_), None) => {}
+                (None, _) => return self.error(&format!("unterminated tag <{}>", name)),
+            }
+        };
+        let mut element = Element {
+            name,
+            line,
+            text: String::new(),
+            children: Vec::new(),
+        };
+        if empty {
+            return Ok(element);
+        }
+        loop {
+            if self.pos >= self.chars.len() {
+                return self.error(&format!("unclosed <{}>", element.name));
+            }
+            if self.skip_misc(&mut element.text)? {
+                continue;
+            }
+            if self.starts_with("</") {
+                self.pos += 2;
+                let closing = self.name();
+                self.skip_past(">")?;
+                if closing != element.name {
+                    return self.error(&format!("<{}> closed by </{}>", element.name, closing));
+                }
+                element.text = decode_entities(&element.text);
+                return Ok(element);
+            }
+            if self.starts_with("<") {
+                let child = self.element()?;
+                element.children.push(child);
+            } else {
+                element.text.extend(self.bump());
+            }
+        }
+    }
+
+    fn document(&mut self) -> Result<Element, BifError> {
+        let mut ignored = String::new();
+        loop {
+            while self.chars.get(self.pos).is_some_and(|c| c.is_whitespace()) {
+                self.bump();
+            }
+            if self.pos >= self.chars.len() {
+                return self.error("no root element");
+            }
+            if !self.skip_misc(&mut ignored)? {
+                break;
+            }
+        }
+        if !self.starts_with("<") {
+            return self.error("text outside of the root element");
+        }
+        self.element()
+    }
+}
+
+fn decode_entities(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+impl BayesNet {
+    /// Read a network in the XMLBIF format
+    ///
+    /// Many published benchmark networks, such as ASIA or ALARM, are distributed in this format. The
+    /// nodes are named after the variables, with their outcomes as state names, and are added in the
+    /// order of declaration of the variables, except that the parents of a node are always added
+    /// before it. As specified by the format, the tables list the probabilities of the node for
+    /// each combination of values of the `GIVEN` variables, the last one varying the fastest.
+    ///
+    /// The `position = (x, y)` properties of the variables are read as layout hints, the other
+    /// properties and the attributes of the elements are ignored.
+    pub fn from_xmlbif(text: &str) -> Result<BayesNet, BifError> {
+        let mut reader = XmlReader {
+            chars: text.chars().collect(),
+            pos: 0,
+            line: 1,
+        };
+        let root = reader.document()?;
+        let network = if root.name.eq_ignore_ascii_case("NETWORK") {
+            &root
+        } else {
+            root.child("NETWORK").ok_or(BifError::Syntax {
+                line: root.line,
+                message: "no <NETWORK> element".to_owned(),
+            })?
+        };
+
+        let mut variables: Vec<Variable> = Vec::new();
+        for element in network.children("VARIABLE") {
+            let name = element.child_text("NAME")?;
+            if variables.iter().any(|v| v.name == name) {
+                return Err(BifError::Syntax {
+                    line: element.line,
+                    message: format!("variable \"{}\" is declared twice", name),
+                });
+            }
+            // older versions of the format use VALUE instead of OUTCOME
+            let states: Vec<String> = element
+                .children("OUTCOME")
+                .chain(element.children("VALUE"))
+                .map(|o| o.text.trim().to_owned())
+                .collect();
+            if states.is_empty() {
+                return Err(BifError::Syntax {
+                    line: element.line,
+                    message: format!("variable \"{}\" has no outcome", name),
+                });
+            }
+            let layout = element
+                .children("PROPERTY")
+                .filter_map(|p| {
+                    let (key, value) = p.text.split_once('=')?;
+                    if key.trim() == "position" {
+                        parse_position(value)
+                    } else {
+                        None
+                    }
+                })
+                .map(|(x, y)| NodeLayout { x, y, color: None })
+                .next();
+            variables.push(Variable {
+                name,
+                line: element.line,
+                states,
+                layout,
+            });
+        }
+
+        let ids: HashMap<&str, usize> = variables
+            .iter()
+            .enumerate()
+            .map(|(i, v)| (v.name.as_str(), i))
+            .collect();
+        let find = |name: &str, line: usize| {
+            ids.get(name).copied().ok_or(BifError::UnknownVariable {
+                line,
+                name: name.to_owned(),
+            })
+        };
+        let mut tables = vec![None; variables.len()];
+        // older versions of the format use PROBABILITY instead of DEFINITION
+        for element in network
+            .children("DEFINITION")
+            .chain(network.children("PROBABILITY"))
+        {
+            let name = element.child_text("FOR")?;
+            let variable = find(&name, element.line)?;
+            let parents = element
+                .children("GIVEN")
+                .map(|g| find(g.text.trim(), g.line))
+                .collect::<Result<Vec<usize>, BifError>>()?;
+            if tables[variable].is_some() {
+                return Err(BifError::Syntax {
+                    line: element.line,
+                    message: format!("the probabilities of variable \"{}\" are given twice", name),
+                });
+            }
+            let table_line = element.child("TABLE").map_or(element.line, |t| t.line);
+            let values = element
+                .child_text("TABLE")?
+                .split_whitespace()
+                .map(|v| {
+                    v.parse::<f32>().map_err(|_| BifError::Syntax {
+                        line: table_line,
+                        message: format!("invalid probability \"{}\"", v),
+                    })
+                })
+                .collect::<Result<Vec<f32>, BifError>>()?;
+            let parent_sizes: Vec<usize> =
+                parents.iter().map(|&p| variables[p].states.len()).collect();
+            let size = variables[variable].states.len();
+            let expected = size * parent_sizes.iter().product::<usize>();
+            if values.len() != expected {
+                return Err(BifError::TableSize {
+                    line: table_line,
+                    variable: name,
+                    expected,
+                    found: values.len(),
+                });
+            }
+            let table = table_with_node_last(values, &parent_sizes, size);
+            tables[variable] = Some((parents, table));
+        }
+        build_network(&variables, tables)
+    }
+}
@@ -0,0 +1,45 @@
+use loopybayesnet::{AndersonAcceleration, BayesNet};
+use ndarray::{Array1, Array2, Array3};
+
+// a strongly coupled loop 0 -> 1 -> ... -> k and 0 -> k+1 -> ... -> 2k, both ends feeding into an observed node
+fn long_loop(k: usize, coupling: f32) -> BayesNet {
+    let (hi, lo) = (0.5 + coupling / 2.0, 0.5 - coupling / 2.0);
+    let edge = Array2::from(vec![[hi, lo], [lo, hi]]);
+    let mut net = BayesNet::new();
+    let root = net.add_node_from_probabilities(&[], Array1::from(vec![0.6, 0.4]));
+    let mut ends = Vec::new();
+    for _ in 0..2 {
+        let mut last = root;
+        for _ in 0..k {
+            last = net.add_node_from_probabilities(&[last], edge.clone());
+        }
+        ends.push(last);
+    }
+    let sink = net.add_node_from_probabilities(
+        &ends,
+        Array3::from(vec![[[hi, 0.5], [0.5, lo]], [[lo, 0.5], [0.5, hi]]]),
+    );
+    net.set_evidence(&[(sink, 0)]);
+    net
+}
+
+fn steps_to_converge(net: &mut BayesNet, accelerator: &mut AndersonAcceleration) -> usize {
+    for step in 1..=1000 {
+        if accelerator.step(net) < 1e-5 {
+            return step;
+        }
+    }
+    1000
+}
+
+#[test]
+fn anderson_converges_faster() {
+    let mut plain = long_loop(3, 0.99);
+    let plain_steps = steps_to_converge(&mut plain, &mut AndersonAcceleration::new(0));
+    let mut accelerated = long_loop(3, 0.99);
+    let accelerated_steps = steps_to_converge(&mut accelerated, &mut AndersonAcceleration::new(8));
+    assert!(2 * accelerated_steps < plain_steps);
+    for (a, b) in plain.beliefs().iter().zip(accelerated.beliefs().iter()) {
+        assert!((a.as_probabilities()[0] - b.as_probabilities()[0]).abs() < 1e-3);
+    }
+}
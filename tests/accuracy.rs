@@ -0,0 +1,52 @@
+use loopybayesnet::{AccuracyGrade, BayesNet};
+use ndarray::{Array1, Array2, Array3};
+
+#[test]
+fn trees_are_exact_once_converged() {
+    let mut net = BayesNet::new();
+    let a = net.add_node_from_probabilities(&[], Array1::from(vec![0.3, 0.7]));
+    let b = net.add_node_from_probabilities(&[a], Array2::from(vec![[0.9, 0.2], [0.1, 0.8]]));
+    net.add_node_from_probabilities(&[b], Array2::from(vec![[0.9, 0.2], [0.1, 0.8]]));
+    net.step();
+    assert!(net.accuracy().iter().all(|a| a.grade == AccuracyGrade::Low));
+    net.step();
+    net.step();
+    for accuracy in net.accuracy() {
+        assert_eq!(accuracy.grade, AccuracyGrade::Exact);
+        assert_eq!(accuracy.error_estimate, Some(0.0));
+    }
+}
+
+#[test]
+fn loop_strength_grades() {
+    // a diamond 0 -> {1, 2} -> 3, with weak or strong edges
+    let diamond = |strength: f32| {
+        let (hi, lo) = (0.5 + strength / 2.0, 0.5 - strength / 2.0);
+        let mut net = BayesNet::new();
+        let a = net.add_node_from_probabilities(&[], Array1::from(vec![0.5, 0.5]));
+        let b = net.add_node_from_probabilities(&[a], Array2::from(vec![[hi, lo], [lo, hi]]));
+        let c = net.add_node_from_probabilities(&[a], Array2::from(vec![[hi, lo], [lo, hi]]));
+        net.add_node_from_probabilities(
+            &[b, c],
+            Array3::from(vec![[[hi, 0.5], [0.5, lo]], [[lo, 0.5], [0.5, hi]]]),
+        );
+        net
+    };
+
+    let mut weak = diamond(0.1);
+    let accuracy = weak.accuracy();
+    assert_eq!(accuracy[3].grade, AccuracyGrade::High);
+    // each node has two edges of strength at most 0.1, and the loop has length 4
+    let estimate = accuracy[3].error_estimate.unwrap();
+    assert!((estimate - 0.2f32.powi(4)).abs() < 1e-6);
+
+    let mut strong = diamond(0.9);
+    assert_eq!(strong.accuracy()[0].grade, AccuracyGrade::Low);
+    assert_eq!(strong.accuracy()[0].error_estimate, None);
+
+    // observed nodes are always exact
+    weak.set_evidence(&[(1, 0)]);
+    strong.set_evidence(&[(1, 0)]);
+    assert_eq!(weak.accuracy()[1].grade, AccuracyGrade::Exact);
+    assert_eq!(strong.accuracy()[1].grade, AccuracyGrade::Exact);
+}
@@ -0,0 +1,149 @@
+use loopybayesnet::{Aggregate, BayesNet};
+use ndarray::{Array1, Array2, ArrayD, IxDyn};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+// four ternary parents, the aggregate, and a noisy sensor of the aggregate
+fn network(aggregate: Aggregate, noise: f32) -> (BayesNet, usize) {
+    let mut net = BayesNet::new();
+    let priors = [
+        [0.2, 0.5, 0.3],
+        [0.6, 0.1, 0.3],
+        [0.3, 0.3, 0.4],
+        [0.1, 0.2, 0.7],
+    ];
+    let parents: Vec<usize> = priors
+        .iter()
+        .map(|p| net.add_node_from_probabilities(&[], Array1::from(p.to_vec())))
+        .collect();
+    let agg = net.add_aggregate_node(&parents, aggregate, noise);
+    let n = net.num_values(agg);
+    net.add_node_from_probabilities(
+        &[agg],
+        Array2::from_shape_fn((2, n), |(v, a)| {
+            let p = (a as f32 + 1.0) / (n as f32 + 1.0);
+            if v == 1 {
+                p
+            } else {
+                1.0 - p
+            }
+        }),
+    );
+    (net, agg)
+}
+
+fn assert_close(net: &BayesNet, expected: &[Array1<f32>]) {
+    for (belief, expected) in net.beliefs().iter().zip(expected) {
+        for (b, e) in belief.as_probabilities().iter().zip(expected.iter()) {
+            assert!((b - e).abs() < 1e-4, "{} != {}", b, e);
+        }
+    }
+}
+
+#[test]
+fn aggregate_beliefs_are_exact() {
+    for &aggregate in &[
+        Aggregate::Count(2),
+        Aggregate::Sum,
+        Aggregate::Min,
+        Aggregate::Max,
+    ] {
+        for &noise in &[0.0, 0.1] {
+            let (mut net, agg) = network(aggregate, noise);
+            assert_eq!(net.aggregate(agg), Some(aggregate));
+            for evidence in &[vec![], vec![(5, 1)], vec![(0, 2), (5, 0)]] {
+                net.set_evidence(evidence);
+                net.reset_state();
+                for _ in 0..6 {
+                    net.step();
+                }
+                let exact: Vec<_> = net
+                    .exact_beliefs()
+                    .iter()
+                    .map(|b| b.as_probabilities())
+                    .collect();
+                assert_close(&net, &exact);
+            }
+        }
+    }
+}
+
+#[test]
+fn aggregate_sizes() {
+    assert_eq!(network(Aggregate::Count(0), 0.0).0.num_values(4), 5);
+    assert_eq!(network(Aggregate::Sum, 0.0).0.num_values(4), 9);
+    assert_eq!(network(Aggregate::Max, 0.0).0.num_values(4), 3);
+}
+
+#[test]
+fn aggregate_most_probable_explanation() {
+    let (mut aggregated, agg) = network(Aggregate::Sum, 0.05);
+    // the same network, with the dense table of the aggregate
+    let mut dense = BayesNet::new();
+    for node in 0..4 {
+        let priors = aggregated.exact_beliefs()[node]
+            .log_probabilities()
+            .to_owned();
+        dense.add_node_from_log_probabilities(&[], priors);
+    }
+    let n = aggregated.num_values(agg);
+    let table = ArrayD::from_shape_fn(IxDyn(&[n, 3, 3, 3, 3]), |index| {
+        let sum: usize = (1..5).map(|i| index[i]).sum();
+        let exact = if index[0] == sum { 0.95 } else { 0.0 };
+        exact + 0.05 / n as f32
+    });
+    dense.add_node_from_probabilities(&[0, 1, 2, 3], table);
+    dense.add_node_from_probabilities(
+        &[agg],
+        Array2::from_shape_fn((2, n), |(v, a)| {
+            let p = (a as f32 + 1.0) / (n as f32 + 1.0);
+            if v == 1 {
+                p
+            } else {
+                1.0 - p
+            }
+        }),
+    );
+    for net in [&mut aggregated, &mut dense] {
+        net.set_evidence(&[(5, 1), (1, 0)]);
+    }
+    assert_eq!(
+        aggregated.most_probable_explanation(6),
+        dense.most_probable_explanation(6)
+    );
+}
+
+#[test]
+fn aggregate_of_many_parents() {
+    // the dense table would have 41 * 2^40 entries
+    let mut net = BayesNet::new();
+    let parents: Vec<usize> = (0..40)
+        .map(|_| net.add_node_from_probabilities(&[], Array1::from(vec![0.9, 0.1])))
+        .collect();
+    let count = net.add_aggregate_node(&parents, Aggregate::Count(1), 0.0);
+    assert_eq!(net.num_values(count), 41);
+    net.set_evidence(&[(count, 0)]);
+    for _ in 0..2 {
+        net.step();
+    }
+    let belief = net.beliefs()[0].as_probabilities();
+    assert!((belief[0] - 1.0).abs() < 1e-4);
+
+    net.set_evidence(&[]);
+    let samples = net.sample_forward(&mut StdRng::seed_from_u64(3), 10);
+    for sample in &samples {
+        let ones = sample[..40].iter().filter(|&&v| v == 1).count();
+        assert_eq!(sample[count], ones);
+        let expected = ones as f32 * 0.1f32.ln() + (40 - ones) as f32 * 0.9f32.ln();
+        assert!((net.log_joint_probability(sample) - expected).abs() < 1e-3);
+    }
+}
+
+#[test]
+#[should_panic(expected = "Parents of an aggregation node must have the same number of values")]
+fn aggregate_parent_size_mismatch() {
+    let mut net = BayesNet::new();
+    let a = net.add_node_from_probabilities(&[], Array1::from(vec![0.5, 0.5]));
+    let b = net.add_node_from_probabilities(&[], Array1::from(vec![0.2, 0.3, 0.5]));
+    net.add_aggregate_node(&[a, b], Aggregate::Max, 0.0);
+}
@@ -0,0 +1,49 @@
+use loopybayesnet::BayesNet;
+use ndarray::{Array1, Array2, Array3};
+
+// 0 -> 1 -> 3 -> 4, 0 -> 2 -> 3, and 5 disconnected
+fn diamond() -> BayesNet {
+    let mut net = BayesNet::new();
+    let a = net.add_node_from_probabilities(&[], Array1::from(vec![0.5, 0.5]));
+    let b = net.add_node_from_probabilities(&[a], Array2::from(vec![[0.7, 0.3], [0.2, 0.8]]));
+    let c = net.add_node_from_probabilities(&[a], Array2::from(vec![[0.6, 0.4], [0.1, 0.9]]));
+    let d = net.add_node_from_probabilities(
+        &[b, c],
+        Array3::from(vec![[[0.9, 0.1], [0.5, 0.5]], [[0.4, 0.6], [0.2, 0.8]]]),
+    );
+    net.add_node_from_probabilities(&[d], Array2::from(vec![[0.8, 0.2], [0.3, 0.7]]));
+    net.add_node_from_probabilities(&[], Array1::from(vec![0.5, 0.5]));
+    net
+}
+
+#[test]
+fn ancestors_and_descendants() {
+    let net = diamond();
+    assert_eq!(net.ancestors(0), Vec::<usize>::new());
+    assert_eq!(net.ancestors(3), vec![0, 1, 2]);
+    assert_eq!(net.ancestors(4), vec![0, 1, 2, 3]);
+    assert_eq!(net.descendants(0), vec![1, 2, 3, 4]);
+    assert_eq!(net.descendants(2), vec![3, 4]);
+    assert_eq!(net.descendants(5), Vec::<usize>::new());
+
+    assert!(net.is_ancestor(0, 4));
+    assert!(net.is_ancestor(2, 3));
+    assert!(!net.is_ancestor(3, 2));
+    assert!(!net.is_ancestor(1, 2));
+    assert!(!net.is_ancestor(3, 3));
+    assert!(!net.is_ancestor(0, 5));
+}
+
+#[test]
+fn directed_paths() {
+    let net = diamond();
+    assert_eq!(
+        net.all_directed_paths(0, 4),
+        vec![vec![0, 1, 3, 4], vec![0, 2, 3, 4]]
+    );
+    assert_eq!(net.all_directed_paths(1, 4), vec![vec![1, 3, 4]]);
+    assert_eq!(net.all_directed_paths(2, 2), vec![vec![2]]);
+    assert!(net.all_directed_paths(4, 0).is_empty());
+    assert!(net.all_directed_paths(1, 2).is_empty());
+    assert!(net.all_directed_paths(0, 5).is_empty());
+}
@@ -0,0 +1,115 @@
+use loopybayesnet::{AuditLog, AuditRecord, BayesNet, BifError};
+use ndarray::{Array1, Array2};
+
+fn alarm() -> BayesNet {
+    let mut net = BayesNet::new();
+    let burglary = net.add_node_from_probabilities(&[], Array1::from(vec![0.9, 0.1]));
+    net.add_node_from_probabilities(&[burglary], Array2::from(vec![[0.95, 0.2], [0.05, 0.8]]));
+    net
+}
+
+#[test]
+fn fingerprints() {
+    let net = alarm();
+    let mut observed = alarm();
+    observed.set_evidence(&[(1, 1)]);
+    observed.step();
+    // the evidence and the inference state are not part of the model
+    assert_eq!(net.fingerprint(), observed.fingerprint());
+    let mut named = alarm();
+    named.set_node_name(0, "burglary");
+    assert_ne!(net.fingerprint(), named.fingerprint());
+    let mut other = BayesNet::new();
+    other.add_node_from_probabilities(&[], Array1::from(vec![0.8, 0.2]));
+    other.add_node_from_probabilities(&[0], Array2::from(vec![[0.95, 0.2], [0.05, 0.8]]));
+    assert_ne!(net.fingerprint(), other.fingerprint());
+}
+
+#[test]
+fn audit_log_records_queries() {
+    let net = alarm();
+    let mut log = AuditLog::new();
+    let beliefs = log.query(&net, &[(1, 1)], 3);
+    let record = &log.records()[0];
+    assert_eq!(record.sequence, 0);
+    assert_eq!(record.model, net.fingerprint());
+    assert_eq!(record.evidence, vec![(1, 1)]);
+    assert_eq!(record.iterations, 3);
+    assert_eq!(record.beliefs[0], beliefs[0].as_probabilities());
+    assert_eq!(record.previous, None);
+
+    let json = log.to_json_lines();
+    assert_eq!(json.lines().count(), 1);
+    assert!(json.starts_with(&format!(
+        "{{\"sequence\": 0, \"model\": \"{:016x}\", \"evidence\": [[1, 1]], \"soft_evidence\": []",
+        net.fingerprint()
+    )));
+    assert!(json.contains("\"options\": {\"engine\": \"V1\", \"damping\": 0"));
+    assert!(json.contains("\"normalization_floor\": \"-inf\""));
+    assert!(!json.contains("hash"));
+}
+
+#[test]
+fn hash_chained_audit_log() {
+    let net = alarm();
+    let mut log = AuditLog::chained();
+    log.query(&net, &[], 2);
+    log.query(&net, &[(1, 1)], 2);
+    let mut resumed = AuditLog::resume(log.records().last().unwrap());
+    resumed.query(&net, &[(1, 0)], 2);
+
+    let mut records = log.records().to_vec();
+    records.extend_from_slice(resumed.records());
+    assert_eq!(records[0].previous, Some(0));
+    assert_eq!(records[2].sequence, 2);
+    assert_eq!(AuditLog::verify_chain(&records), Ok(()));
+    let line = resumed.to_json_lines();
+    assert!(line.contains(&format!("\"previous\": \"{:016x}\"", records[1].hash())));
+    assert!(line.ends_with(&format!("\"hash\": \"{:016x}\"}}\n", records[2].hash())));
+
+    // tampering with a record breaks the chain after it
+    let mut tampered = records.clone();
+    tampered[1].evidence = vec![(1, 0)];
+    assert_eq!(AuditLog::verify_chain(&tampered), Err(2));
+    // and so does removing one
+    records.remove(1);
+    assert_eq!(AuditLog::verify_chain(&records), Err(1));
+}
+
+#[test]
+fn audit_log_round_trip() {
+    let net = alarm();
+    let mut log = AuditLog::chained();
+    log.query(&net, &[], 2);
+    log.query(&net, &[(1, 1)], 3);
+    let mut text = log.to_json_lines();
+
+    let records = AuditLog::parse_json_lines(&text).unwrap();
+    assert_eq!(records, log.records());
+    assert_eq!(AuditLog::verify_chain(&records), Ok(()));
+    // a log resumed from the parsed records continues the chain
+    let mut resumed = AuditLog::resume(records.last().unwrap());
+    resumed.query(&net, &[(0, 1)], 2);
+    text += &resumed.to_json_lines();
+    let records = AuditLog::parse_json_lines(&text).unwrap();
+    assert_eq!(records.len(), 3);
+    assert_eq!(AuditLog::verify_chain(&records), Ok(()));
+
+    let mut unchained = AuditLog::new();
+    unchained.query(&net, &[(1, 0)], 1);
+    let parsed = AuditLog::parse_json_lines(&unchained.to_json_lines()).unwrap();
+    assert_eq!(parsed, unchained.records());
+
+    // an edited record does not match its hash
+    let line = text.lines().nth(1).unwrap().replace("[[1, 1]]", "[[1, 0]]");
+    match AuditRecord::from_json(&line) {
+        Err(BifError::Syntax { message, .. }) => {
+            assert_eq!(message, "the hash does not match the record")
+        }
+        other => panic!("unexpected result {:?}", other),
+    }
+    match AuditLog::parse_json_lines(&(text + "{\"sequence\": 3}\n")) {
+        Err(BifError::Syntax { line, .. }) => assert_eq!(line, 4),
+        other => panic!("unexpected result {:?}", other),
+    }
+}
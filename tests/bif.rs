@@ -0,0 +1,182 @@
+use loopybayesnet::{BayesNet, BifError, NodeLayout};
+use ndarray::{Array1, Array3};
+
+const ASIA: &str = r#"
+network unknown {
+}
+variable asia {
+  type discrete [ 2 ] { yes, no };
+}
+variable tub {
+  type discrete [ 2 ] { yes, no };
+  property position = (10, -2.5);
+}
+variable smoke {
+  type discrete [ 2 ] { yes, no };
+}
+/* children may be declared before their parents */
+variable either {
+  type discrete [ 2 ] { yes, no };
+}
+variable lung {
+  type discrete [ 2 ] { yes, no };
+}
+probability ( either | lung, tub ) {
+  (yes, yes) 1.0, 0.0;
+  default 0.0, 1.0;
+  (no, yes) 1.0, 0.0;
+  (yes, no) 1.0, 0.0;
+}
+probability ( asia ) {
+  table 0.01, 0.99;
+}
+probability ( tub | asia ) {
+  (yes) 0.05, 0.95;
+  (no) 0.01, 0.99;
+}
+probability ( smoke ) {
+  table 0.5, 0.5;
+}
+// the node varies the slowest
+probability ( lung | smoke ) {
+  table 0.1, 0.01, 0.9, 0.99;
+}
+"#;
+
+fn probas(net: &BayesNet, name: &str) -> Vec<f32> {
+    let node = net.find_node(name).unwrap();
+    net.exact_beliefs()[node].as_probabilities().to_vec()
+}
+
+#[test]
+fn read_bif() {
+    let net = BayesNet::from_bif(ASIA).unwrap();
+    assert_eq!(net.num_nodes(), 5);
+    let either = net.find_node("either").unwrap();
+    let lung = net.find_node("lung").unwrap();
+    let tub = net.find_node("tub").unwrap();
+    assert_eq!(net.parents(either), vec![lung, tub]);
+    assert!(lung < either && tub < either);
+    assert_eq!(net.state_names(either).unwrap(), ["yes", "no"]);
+    assert_eq!(
+        net.layout(tub),
+        Some(&NodeLayout {
+            x: 10.0,
+            y: -2.5,
+            color: None
+        })
+    );
+
+    let lung_yes = 0.5 * 0.1 + 0.5 * 0.01;
+    assert!((probas(&net, "lung")[0] - lung_yes).abs() < 1e-5);
+    let tub_yes = 0.01 * 0.05 + 0.99 * 0.01;
+    let either_yes = 1.0 - (1.0 - lung_yes) * (1.0 - tub_yes);
+    assert!((probas(&net, "either")[0] - either_yes).abs() < 1e-5);
+}
+
+#[test]
+fn bif_round_trip() {
+    let mut net = BayesNet::new();
+    let a = net.add_node_from_probabilities(&[], Array1::from(vec![0.3, 0.7]));
+    let b = net.add_node_from_probabilities(&[], Array1::from(vec![0.2, 0.5, 0.3]));
+    let c = net.add_node_from_probabilities(
+        &[a, b],
+        Array3::from(vec![
+            [[0.9, 0.5, 0.3], [0.4, 0.1, 0.6]],
+            [[0.1, 0.5, 0.7], [0.6, 0.9, 0.4]],
+        ]),
+    );
+    net.set_node_name(a, "weather/rain");
+    net.set_state_names(a, &["dry", "wet"]);
+    net.set_node_name(c, "wet grass");
+    net.set_layout(
+        c,
+        Some(NodeLayout {
+            x: 1.5,
+            y: 2.0,
+            color: Some("#00ff00".to_owned()),
+        }),
+    );
+
+    let bif = net.to_bif();
+    assert!(bif.contains("variable wet_grass {"));
+    assert!(bif.contains("probability ( wet_grass | weather/rain, node1 ) {"));
+    assert!(bif.contains("  (wet, s2) 0.6, 0.4;"));
+
+    let copy = BayesNet::from_bif(&bif).unwrap();
+    assert_eq!(copy.num_nodes(), 3);
+    assert_eq!(copy.node_name(0), Some("weather/rain"));
+    assert_eq!(copy.state_names(1).unwrap(), ["s0", "s1", "s2"]);
+    assert_eq!(copy.layout(2), net.layout(2));
+    for (original, copied) in net.exact_beliefs().iter().zip(copy.exact_beliefs()) {
+        for (x, y) in original
+            .as_probabilities()
+            .iter()
+            .zip(copied.as_probabilities().iter())
+        {
+            assert!((x - y).abs() < 1e-6);
+        }
+    }
+    assert_eq!(
+        copy.to_bif(),
+        BayesNet::from_bif(&copy.to_bif()).unwrap().to_bif()
+    );
+}
+
+#[test]
+fn bif_errors() {
+    let variable = "variable a { type discrete [ 2 ] { x, y }; }\n";
+    let error = |text: &str| BayesNet::from_bif(text).unwrap_err();
+
+    assert_eq!(
+        error("variable a { type discrete [ 3 ] { x, y }; }"),
+        BifError::Syntax {
+            line: 1,
+            message: "variable \"a\" declares 3 states but lists 2".to_owned()
+        }
+    );
+    assert_eq!(
+        error(&format!("{}probability ( a | b ) {{ }}", variable)),
+        BifError::UnknownVariable {
+            line: 2,
+            name: "b".to_owned()
+        }
+    );
+    assert_eq!(
+        error(&format!("{}probability ( a ) {{\n table 0.5; }}", variable)),
+        BifError::TableSize {
+            line: 3,
+            variable: "a".to_owned(),
+            expected: 2,
+            found: 1
+        }
+    );
+    assert_eq!(
+        error(variable),
+        BifError::MissingProbabilities("a".to_owned())
+    );
+    let cycle = "variable a { type discrete [ 2 ] { x, y }; }
+        variable b { type discrete [ 2 ] { x, y }; }
+        probability ( a | b ) { table 0.5, 0.5, 0.5, 0.5; }
+        probability ( b | a ) { (x) 0.5, 0.5; (z) 0.5, 0.5; }";
+    assert_eq!(
+        error(cycle),
+        BifError::UnknownState {
+            line: 4,
+            variable: "a".to_owned(),
+            state: "z".to_owned()
+        }
+    );
+    assert_eq!(
+        error(&cycle.replace("(z)", "(y)")),
+        BifError::Cycle("a".to_owned())
+    );
+    assert_eq!(
+        error(&format!(
+            "{}probability ( a ) {{ table 0.5, 0.5 }}",
+            variable
+        ))
+        .to_string(),
+        "line 2: expected a name, found \"}\""
+    );
+}
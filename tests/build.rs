@@ -0,0 +1,55 @@
+use loopybayesnet::{BayesNet, BuildError};
+use ndarray::{Array1, Array2, Array3};
+
+fn two_nodes() -> BayesNet {
+    let mut net = BayesNet::new();
+    net.add_node_from_probabilities(&[], Array1::from(vec![0.5, 0.5]));
+    net.add_node_from_probabilities(&[], Array1::from(vec![0.2, 0.3, 0.5]));
+    net
+}
+
+#[test]
+fn valid_node_is_added() {
+    let mut net = two_nodes();
+    let id = net
+        .try_add_node_from_probabilities(&[0, 1], Array3::from_elem((2, 2, 3), 0.5))
+        .unwrap();
+    assert_eq!(id, 2);
+    assert_eq!(net.parents(2), vec![0, 1]);
+}
+
+#[test]
+fn construction_errors() {
+    let mut net = two_nodes();
+    assert_eq!(
+        net.try_add_node_from_probabilities(&[0, 1], Array2::from_elem((2, 2), 0.5)),
+        Err(BuildError::DimensionMismatch {
+            node: 2,
+            dimensions: 2,
+            parents: 2
+        })
+    );
+    assert_eq!(
+        net.try_add_node_from_log_probabilities(&[5], Array2::zeros((2, 2))),
+        Err(BuildError::UnknownParent { node: 2, parent: 5 })
+    );
+    net.set_node_name(1, "size");
+    let error = net
+        .try_add_node_from_probabilities(&[0, 1], Array3::from_elem((2, 2, 2), 0.5))
+        .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Dimension 2 of log_probas array of node 2 does not match its associated parent number of \
+         element: got 2 but node 1 (\"size\") has 3."
+    );
+    // nothing was added
+    assert_eq!(net.num_nodes(), 2);
+    assert!(net.children(0).is_empty());
+}
+
+#[test]
+#[should_panic(expected = "Parent 3 of node 2 does not exist, the network only has 2 nodes")]
+fn panicking_variant() {
+    let mut net = two_nodes();
+    net.add_node_from_probabilities(&[3], Array2::from_elem((2, 2), 0.5));
+}
@@ -0,0 +1,59 @@
+use loopybayesnet::{BayesNet, InferenceCache};
+use ndarray::{Array1, Array2};
+
+#[test]
+fn unrelated_evidence_hits_cache() {
+    // two independent chains: 0 -> 1 and 2 -> 3
+    let mut net = BayesNet::new();
+    let a = net.add_node_from_probabilities(&[], Array1::from(vec![0.3, 0.7]));
+    let b = net.add_node_from_probabilities(&[a], Array2::from(vec![[0.9, 0.2], [0.1, 0.8]]));
+    let c = net.add_node_from_probabilities(&[], Array1::from(vec![0.5, 0.5]));
+    let d = net.add_node_from_probabilities(&[c], Array2::from(vec![[0.6, 0.3], [0.4, 0.7]]));
+    let mut cache = InferenceCache::new(net.clone(), 5);
+
+    let first = cache.posterior(&[(b, 1), (d, 0)], a);
+    assert_eq!(cache.misses(), 1);
+    // changing the evidence on the other chain does not invalidate the answer
+    let second = cache.posterior(&[(d, 1), (b, 1)], a);
+    let third = cache.posterior(&[(b, 1)], a);
+    assert_eq!(cache.hits(), 2);
+    assert_eq!(first.log_probabilities(), second.log_probabilities());
+    assert_eq!(first.log_probabilities(), third.log_probabilities());
+
+    // the first run also cached the posterior of the other nodes given its evidence
+    let c_posterior = cache.posterior(&[(b, 0)], c);
+    assert_eq!(cache.misses(), 1);
+    // while relevant evidence changes are recomputed
+    cache.posterior(&[(b, 0)], a);
+    assert_eq!(cache.misses(), 2);
+
+    net.set_evidence(&[(b, 0)]);
+    for _ in 0..5 {
+        net.step();
+    }
+    let expected = net.beliefs()[c].as_probabilities();
+    let actual = c_posterior.as_probabilities();
+    assert!((expected[0] - actual[0]).abs() < 1e-6);
+}
+
+#[test]
+fn repeated_evidence_keeps_the_last_value() {
+    let mut net = BayesNet::new();
+    let a = net.add_node_from_probabilities(&[], Array1::from(vec![0.3, 0.7]));
+    let b = net.add_node_from_probabilities(&[a], Array2::from(vec![[0.9, 0.2], [0.1, 0.8]]));
+    let mut cache = InferenceCache::new(net.clone(), 5);
+    let evidence = [(b, 1), (b, 0)];
+    let posterior = cache.posterior(&evidence, a);
+
+    // the same evidence on the network itself
+    net.set_evidence(&evidence);
+    for _ in 0..5 {
+        net.step();
+    }
+    let expected = net.beliefs()[a].as_probabilities();
+    assert!((posterior.as_probabilities()[0] - expected[0]).abs() < 1e-6);
+    assert_eq!(
+        cache.posterior(&[(b, 0)], a).log_probabilities(),
+        posterior.log_probabilities()
+    );
+}
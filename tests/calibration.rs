@@ -0,0 +1,69 @@
+use loopybayesnet::{BayesNet, Calibration, LogProbVector, ModelRegistry};
+use ndarray::Array1;
+
+fn belief(probas: &[f32]) -> LogProbVector {
+    LogProbVector::from_log_probabilities(Array1::from(probas.to_vec()).mapv(f32::ln))
+}
+
+fn assert_probas(belief: &LogProbVector, expected: &[f32]) {
+    for (p, e) in belief.as_probabilities().iter().zip(expected) {
+        assert!((p - e).abs() < 1e-4, "{} != {}", p, e);
+    }
+}
+
+#[test]
+fn temperature_scaling() {
+    let calibrated = Calibration::Temperature(2.0).apply(&belief(&[0.8, 0.2]));
+    assert_probas(&calibrated, &[2.0 / 3.0, 1.0 / 3.0]);
+
+    // the beliefs say 90%, but the outcome is only right 70% of the time
+    let predictions = vec![belief(&[0.9, 0.1]); 10];
+    let outcomes = [0, 0, 1, 0, 0, 1, 0, 0, 1, 0];
+    match Calibration::fit_temperature(&predictions, &outcomes) {
+        Calibration::Temperature(t) => {
+            let expected = 9f32.ln() / (7.0f32 / 3.0).ln();
+            assert!((t - expected).abs() < 1e-2, "{} != {}", t, expected);
+        }
+        other => panic!("unexpected calibration {:?}", other),
+    }
+}
+
+#[test]
+fn isotonic_calibration() {
+    let predictions: Vec<_> = [0.9, 0.8, 0.7, 0.6, 0.3, 0.2]
+        .iter()
+        .map(|&p| belief(&[p, 1.0 - p]))
+        .collect();
+    // 0.7 is right more often than 0.8, which is pooled with it
+    let outcomes = [0, 1, 0, 1, 1, 1];
+    let calibration = Calibration::fit_isotonic(&predictions, &outcomes);
+    match calibration {
+        Calibration::Isotonic(ref functions) => {
+            assert_eq!(functions.len(), 2);
+            let xs: Vec<f32> = functions[0].iter().map(|&(x, _)| x).collect();
+            let ys: Vec<f32> = functions[0].iter().map(|&(_, y)| y).collect();
+            assert!(xs.windows(2).all(|w| w[0] <= w[1]));
+            assert!(ys.windows(2).all(|w| w[0] <= w[1]));
+            assert_eq!(ys.last(), Some(&1.0));
+        }
+        ref other => panic!("unexpected calibration {:?}", other),
+    }
+    let calibrated = calibration.apply(&belief(&[0.75, 0.25]));
+    let sum: f32 = calibrated.as_probabilities().sum();
+    assert!((sum - 1.0).abs() < 1e-5);
+}
+
+#[test]
+fn calibrated_queries() {
+    let mut net = BayesNet::new();
+    net.add_node_from_probabilities(&[], Array1::from(vec![0.8, 0.2]));
+    net.set_calibration(0, Some(Calibration::Temperature(2.0)));
+    assert_eq!(net.calibration(0), Some(&Calibration::Temperature(2.0)));
+    assert_probas(&net.beliefs()[0], &[0.8, 0.2]);
+    assert_probas(&net.calibrated_beliefs()[0], &[2.0 / 3.0, 1.0 / 3.0]);
+
+    let registry = ModelRegistry::new();
+    registry.insert("calibrated", net);
+    let beliefs = registry.query("calibrated", &[], 1).unwrap();
+    assert_probas(&beliefs[0], &[2.0 / 3.0, 1.0 / 3.0]);
+}
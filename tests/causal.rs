@@ -0,0 +1,51 @@
+use loopybayesnet::{causal, BayesNet};
+use ndarray::{Array1, Array2, Array3};
+
+// a confounder z of the treatment t and the outcome y
+fn confounded() -> BayesNet {
+    let mut net = BayesNet::new();
+    let z = net.add_node_from_probabilities(&[], Array1::from(vec![0.7, 0.3]));
+    let t = net.add_node_from_probabilities(&[z], Array2::from(vec![[0.8, 0.2], [0.2, 0.8]]));
+    net.add_node_from_probabilities(
+        &[t, z],
+        Array3::from(vec![[[0.9, 0.5], [0.6, 0.1]], [[0.1, 0.5], [0.4, 0.9]]]),
+    );
+    net
+}
+
+#[test]
+fn interventions_do_not_flow_to_ancestors() {
+    let net = confounded();
+    let mut mutilated = causal::intervene(&net, &[(1, 1)]);
+    for _ in 0..10 {
+        mutilated.step();
+    }
+    let beliefs = mutilated.beliefs();
+    assert!((beliefs[0].as_probabilities()[1] - 0.3).abs() < 1e-4);
+    assert!((beliefs[1].as_probabilities()[1] - 1.0).abs() < 1e-6);
+
+    // observing the treatment instead changes the belief in the confounder
+    let mut observed = net.clone();
+    observed.set_evidence(&[(1, 1)]);
+    for _ in 0..10 {
+        observed.step();
+    }
+    assert!(observed.beliefs()[0].as_probabilities()[1] > 0.5);
+}
+
+#[test]
+fn average_causal_effect() {
+    let net = confounded();
+    // 0.7 * (0.4 - 0.1) + 0.3 * (0.9 - 0.5)
+    let effect = causal::ace(&net, 1, 2, &[], 10);
+    assert!((effect[1] - 0.33).abs() < 1e-3);
+    assert!((effect[0] + effect[1]).abs() < 1e-5);
+
+    // conditioning on the confounder
+    let effect = causal::ace(&net, 1, 2, &[(0, 1)], 10);
+    assert!((effect[1] - 0.4).abs() < 1e-4);
+
+    // the intervention on the outcome does not reach the treatment
+    let effect = causal::ace(&net, 2, 1, &[], 10);
+    assert!(effect.iter().all(|e| e.abs() < 1e-5));
+}
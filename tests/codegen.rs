@@ -0,0 +1,77 @@
+use loopybayesnet::BayesNet;
+use ndarray::{Array1, Array2};
+
+// the bindings of `model()`, regenerated by `bindings_are_up_to_date` with BLESS=1
+#[allow(dead_code)]
+mod model {
+    include!("codegen/model.rs");
+}
+
+fn model() -> BayesNet {
+    let mut net = BayesNet::new();
+    let season = net.add_node_from_probabilities(&[], Array1::from(vec![0.25; 4]));
+    net.set_node_name(season, "season");
+    net.set_state_names(season, &["winter", "spring", "summer", "autumn"]);
+    let rain = net.add_node_from_probabilities(
+        &[season],
+        Array2::from(vec![[0.4, 0.6, 0.8, 0.5], [0.6, 0.4, 0.2, 0.5]]),
+    );
+    net.set_node_name(rain, "weather/rain");
+    net.set_state_names(rain, &["no", "yes"]);
+    let wind = net.add_node_from_probabilities(&[], Array1::from(vec![0.7, 0.2, 0.1]));
+    net.set_node_name(wind, "weather/wind speed");
+    // nodes without a name are skipped
+    net.add_node_from_probabilities(&[rain], Array2::from(vec![[0.9, 0.3], [0.1, 0.7]]));
+    net
+}
+
+#[test]
+fn bindings_are_up_to_date() {
+    let bindings = model().rust_bindings();
+    if std::env::var_os("BLESS").is_some() {
+        std::fs::write("tests/codegen/model.rs", &bindings).unwrap();
+    }
+    assert_eq!(bindings, include_str!("codegen/model.rs"));
+}
+
+#[test]
+fn bindings_give_typed_evidence() {
+    let mut net = model();
+    assert!(model::matches(&net));
+    assert_eq!(model::NUM_NODES, 4);
+    assert_eq!(model::weather::rain::ID, 1);
+    assert_eq!(model::season::State::ALL.len(), 4);
+    assert_eq!(model::weather::wind_speed::State::V2.value(), 2);
+
+    assert_eq!(model::season::State::Summer.evidence(), (0, 2));
+    net.set_evidence(&[model::weather::rain::State::Yes.evidence()]);
+    net.step();
+    assert!(net.beliefs()[model::weather::rain::ID].as_probabilities()[1] > 0.999);
+
+    net.set_state_names(2, &["calm", "breeze", "storm"]);
+    assert!(!model::matches(&net));
+}
+
+#[test]
+fn identifiers_are_sanitized() {
+    let mut net = BayesNet::new();
+    let node = net.add_node_from_probabilities(&[], Array1::from(vec![0.5, 0.5]));
+    net.set_node_name(node, "match/2nd-Try");
+    net.set_state_names(node, &["self", "not ok"]);
+    let bindings = net.rust_bindings();
+    assert!(bindings.contains("pub mod r#match {"));
+    assert!(bindings.contains("pub mod _2nd_try {"));
+    assert!(bindings.contains("Self_ = 0,"));
+    assert!(bindings.contains("NotOk = 1,"));
+}
+
+#[test]
+#[should_panic(expected = "Nodes 0 and 1 are both bound to the module rain_fall")]
+fn module_collision() {
+    let mut net = BayesNet::new();
+    for name in &["rain fall", "rain-fall"] {
+        let node = net.add_node_from_probabilities(&[], Array1::from(vec![0.5, 0.5]));
+        net.set_node_name(node, name);
+    }
+    net.rust_bindings();
+}
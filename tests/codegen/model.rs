@@ -0,0 +1,112 @@
+// Generated by loopybayesnet, do not edit
+
+pub const NUM_NODES: usize = 4;
+
+pub mod season {
+    /// The node "season"
+    pub const ID: usize = 0;
+    pub const NUM_VALUES: usize = 4;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub enum State {
+        Winter = 0,
+        Spring = 1,
+        Summer = 2,
+        Autumn = 3,
+    }
+
+    impl State {
+        pub const ALL: [State; NUM_VALUES] = [State::Winter, State::Spring, State::Summer, State::Autumn];
+
+        /// The value of the node for this state
+        pub fn value(self) -> usize {
+            self as usize
+        }
+
+        /// This state as evidence, for `BayesNet::set_evidence`
+        pub fn evidence(self) -> (usize, usize) {
+            (ID, self as usize)
+        }
+    }
+}
+
+pub mod weather {
+    pub mod rain {
+        /// The node "weather/rain"
+        pub const ID: usize = 1;
+        pub const NUM_VALUES: usize = 2;
+
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub enum State {
+            No = 0,
+            Yes = 1,
+        }
+
+        impl State {
+            pub const ALL: [State; NUM_VALUES] = [State::No, State::Yes];
+
+            /// The value of the node for this state
+            pub fn value(self) -> usize {
+                self as usize
+            }
+
+            /// This state as evidence, for `BayesNet::set_evidence`
+            pub fn evidence(self) -> (usize, usize) {
+                (ID, self as usize)
+            }
+        }
+    }
+
+    pub mod wind_speed {
+        /// The node "weather/wind speed"
+        pub const ID: usize = 2;
+        pub const NUM_VALUES: usize = 3;
+
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub enum State {
+            V0 = 0,
+            V1 = 1,
+            V2 = 2,
+        }
+
+        impl State {
+            pub const ALL: [State; NUM_VALUES] = [State::V0, State::V1, State::V2];
+
+            /// The value of the node for this state
+            pub fn value(self) -> usize {
+                self as usize
+            }
+
+            /// This state as evidence, for `BayesNet::set_evidence`
+            pub fn evidence(self) -> (usize, usize) {
+                (ID, self as usize)
+            }
+        }
+    }
+}
+
+/// Whether a network has the nodes these bindings were generated from
+pub fn matches(net: &::loopybayesnet::BayesNet) -> bool {
+    net.num_nodes() == NUM_NODES
+        && node_matches(net, 0, "season", 4, Some(&["winter", "spring", "summer", "autumn"]))
+        && node_matches(net, 1, "weather/rain", 2, Some(&["no", "yes"]))
+        && node_matches(net, 2, "weather/wind speed", 3, None)
+}
+
+fn node_matches(
+    net: &::loopybayesnet::BayesNet,
+    id: usize,
+    name: &str,
+    num_values: usize,
+    states: Option<&[&str]>,
+) -> bool {
+    net.node_name(id) == Some(name)
+        && net.num_values(id) == num_values
+        && match (net.state_names(id), states) {
+            (Some(names), Some(states)) => {
+                names.iter().map(String::as_str).eq(states.iter().copied())
+            }
+            (None, None) => true,
+            _ => false,
+        }
+}
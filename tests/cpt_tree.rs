@@ -0,0 +1,180 @@
+use loopybayesnet::{BayesNet, CptTree};
+use ndarray::{Array1, Array2, Array3};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+fn network() -> BayesNet {
+    let mut net = BayesNet::new();
+    let a = net.add_node_from_probabilities(&[], Array1::from(vec![0.3, 0.7]));
+    let b = net.add_node_from_probabilities(&[], Array1::from(vec![0.6, 0.4]));
+    // when a is 0, c does not depend on b
+    let c = net.add_node_from_probabilities(
+        &[a, b],
+        Array3::from(vec![[[0.9, 0.9], [0.2, 0.6]], [[0.1, 0.1], [0.8, 0.4]]]),
+    );
+    net.add_node_from_probabilities(&[c], Array2::from(vec![[0.7, 0.1], [0.3, 0.9]]));
+    net
+}
+
+fn beliefs(net: &mut BayesNet, evidence: &[(usize, usize)]) -> Vec<Array1<f32>> {
+    net.reset_state();
+    net.set_evidence(evidence);
+    for _ in 0..10 {
+        net.step();
+    }
+    net.beliefs().iter().map(|b| b.as_probabilities()).collect()
+}
+
+#[test]
+fn context_specific_reduction() {
+    let mut net = network();
+    let reductions = net.reduce_cpts(0.0);
+    assert_eq!(reductions.len(), 1);
+    assert_eq!(reductions[0].node, 2);
+    assert_eq!(reductions[0].dense_parameters, 8);
+    assert_eq!(reductions[0].tree_parameters, 6);
+
+    let tree = net.cpt_tree(2).unwrap();
+    assert_eq!(tree.num_leaves(), 3);
+    match tree {
+        CptTree::Split { parent, .. } => assert_eq!(*parent, 0),
+        _ => panic!("expected a split on the first parent"),
+    }
+    assert!(net.cpt_tree(3).is_none());
+
+    // inference through the tree gives the same results as with the dense table
+    let mut dense = network();
+    for evidence in &[vec![], vec![(3, 1)], vec![(2, 0)], vec![(1, 1), (3, 0)]] {
+        let expected = beliefs(&mut dense, evidence);
+        let actual = beliefs(&mut net, evidence);
+        for (e, a) in expected.iter().zip(actual.iter()) {
+            assert!(e.iter().zip(a.iter()).all(|(x, y)| (x - y).abs() < 1e-4));
+        }
+    }
+}
+
+#[test]
+fn tolerance_merges_close_distributions() {
+    let mut net = network();
+    // the two columns for a = 1 differ by 0.2
+    assert_eq!(net.reduce_cpt(2, 0.1).tree_parameters, 6);
+    let mut net = network();
+    assert_eq!(net.reduce_cpt(2, 0.25).tree_parameters, 4);
+    let probas = net
+        .cpt_tree(2)
+        .unwrap()
+        .log_probabilities(&[1, 0])
+        .mapv(f32::exp);
+    assert!((probas[0] - 0.4).abs() < 1e-5);
+}
+
+#[test]
+fn node_from_cpt_tree() {
+    // the alarm is silent whenever its battery is dead, and otherwise rings on burglaries or earthquakes
+    let build = |tree: bool| {
+        let mut net = BayesNet::new();
+        let battery = net.add_node_from_probabilities(&[], Array1::from(vec![0.1, 0.9]));
+        let burglary = net.add_node_from_probabilities(&[], Array1::from(vec![0.99, 0.01]));
+        let earthquake = net.add_node_from_probabilities(&[], Array1::from(vec![0.98, 0.02]));
+        let parents = [battery, burglary, earthquake];
+        let alarm = if tree {
+            net.add_node_from_cpt_tree(
+                &parents,
+                CptTree::split(
+                    0,
+                    vec![
+                        CptTree::leaf(Array1::from(vec![1.0, 0.0])),
+                        CptTree::split(
+                            1,
+                            vec![
+                                CptTree::split(
+                                    2,
+                                    vec![
+                                        CptTree::leaf(Array1::from(vec![0.999, 0.001])),
+                                        CptTree::leaf(Array1::from(vec![0.3, 0.7])),
+                                    ],
+                                ),
+                                CptTree::leaf(Array1::from(vec![0.05, 0.95])),
+                            ],
+                        ),
+                    ],
+                ),
+            )
+        } else {
+            net.add_node_from_probabilities(
+                &parents,
+                ndarray::Array4::from_shape_vec(
+                    (2, 2, 2, 2),
+                    vec![
+                        1.0, 1.0, 1.0, 1.0, 0.999, 0.3, 0.05, 0.05, // silent
+                        0.0, 0.0, 0.0, 0.0, 0.001, 0.7, 0.95, 0.95, // ringing
+                    ],
+                )
+                .unwrap(),
+            )
+        };
+        net.add_node_from_probabilities(&[alarm], Array2::from(vec![[0.95, 0.1], [0.05, 0.9]]));
+        net
+    };
+
+    let mut tree_net = build(true);
+    let mut dense_net = build(false);
+    assert_eq!(tree_net.num_values(3), 2);
+    assert_eq!(tree_net.cpt_tree(3).unwrap().num_leaves(), 4);
+    for evidence in &[vec![], vec![(3, 1)], vec![(4, 1)], vec![(0, 1), (4, 1)]] {
+        let expected = beliefs(&mut dense_net, evidence);
+        let actual = beliefs(&mut tree_net, evidence);
+        for (e, a) in expected.iter().zip(actual.iter()) {
+            assert!(e.iter().zip(a.iter()).all(|(x, y)| (x - y).abs() < 1e-4));
+        }
+    }
+}
+
+#[test]
+fn cpt_tree_of_many_parents() {
+    // the dense table would have 2^31 entries, but only the first parent matters
+    let mut net = BayesNet::new();
+    let parents: Vec<usize> = (0..30)
+        .map(|_| net.add_node_from_probabilities(&[], Array1::from(vec![0.5, 0.5])))
+        .collect();
+    let child = net.add_node_from_cpt_tree(
+        &parents,
+        CptTree::split(
+            0,
+            vec![
+                CptTree::leaf(Array1::from(vec![0.9, 0.1])),
+                CptTree::leaf(Array1::from(vec![0.2, 0.8])),
+            ],
+        ),
+    );
+    let belief = &beliefs(&mut net, &[(child, 1)])[0];
+    assert!((belief[1] - 0.8 / 0.9).abs() < 1e-4);
+
+    net.set_evidence(&[]);
+    for sample in net.sample_forward(&mut StdRng::seed_from_u64(7), 10) {
+        let p = if sample[0] == 0 {
+            [0.9, 0.1]
+        } else {
+            [0.2, 0.8]
+        };
+        let expected = 30.0 * 0.5f32.ln() + f32::ln(p[sample[child]]);
+        assert!((net.log_joint_probability(&sample) - expected).abs() < 1e-3);
+    }
+}
+
+#[test]
+#[should_panic]
+fn cpt_tree_wrong_branching() {
+    let mut net = BayesNet::new();
+    let parent = net.add_node_from_probabilities(&[], Array1::from(vec![0.2, 0.3, 0.5]));
+    net.add_node_from_cpt_tree(
+        &[parent],
+        CptTree::split(
+            0,
+            vec![
+                CptTree::leaf(Array1::from(vec![0.5, 0.5])),
+                CptTree::leaf(Array1::from(vec![0.1, 0.9])),
+            ],
+        ),
+    );
+}
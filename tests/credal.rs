@@ -0,0 +1,38 @@
+use loopybayesnet::CredalNet;
+use ndarray::{Array1, Array2};
+
+#[test]
+fn interval_prior_bounds() {
+    let mut net = CredalNet::new();
+    let a = net.add_node_from_intervals(
+        &[],
+        Array1::from(vec![0.6, 0.2]),
+        Array1::from(vec![0.8, 0.4]),
+    );
+    let b = net.add_node_from_intervals(
+        &[a],
+        Array2::from(vec![[0.9, 0.1], [0.1, 0.9]]),
+        Array2::from(vec![[0.9, 0.1], [0.1, 0.9]]),
+    );
+
+    // without evidence, the marginal of a spans its interval
+    let bounds = net.posterior_bounds(&[], a, 5);
+    assert!((bounds[1].0 - 0.2).abs() < 1e-4 && (bounds[1].1 - 0.4).abs() < 1e-4);
+
+    // P(a = 1 | b = 1) is monotone in the prior: 0.18 / 0.26 to 0.36 / 0.42
+    let bounds = net.posterior_bounds(&[(b, 1)], a, 5);
+    assert!((bounds[1].0 - 0.6923).abs() < 1e-3, "{:?}", bounds);
+    assert!((bounds[1].1 - 0.8571).abs() < 1e-3, "{:?}", bounds);
+    assert!((bounds[0].0 - (1.0 - 0.8571)).abs() < 1e-3, "{:?}", bounds);
+}
+
+#[test]
+#[should_panic]
+fn empty_credal_set() {
+    let mut net = CredalNet::new();
+    net.add_node_from_intervals(
+        &[],
+        Array1::from(vec![0.1, 0.2]),
+        Array1::from(vec![0.3, 0.4]),
+    );
+}
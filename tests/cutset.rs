@@ -0,0 +1,113 @@
+use loopybayesnet::BayesNet;
+use ndarray::{ArrayD, IxDyn};
+
+// build a network and compute its exact marginals given evidence by enumeration
+struct Model {
+    nodes: Vec<(Vec<usize>, ArrayD<f32>)>,
+}
+
+impl Model {
+    fn net(&self) -> BayesNet {
+        let mut net = BayesNet::new();
+        for (parents, table) in &self.nodes {
+            net.add_node_from_probabilities(parents, table.clone());
+        }
+        net
+    }
+
+    fn exact(&self, evidence: &[(usize, usize)]) -> Vec<Vec<f32>> {
+        let sizes: Vec<usize> = self.nodes.iter().map(|(_, t)| t.shape()[0]).collect();
+        let mut marginals: Vec<Vec<f32>> = sizes.iter().map(|&s| vec![0.0; s]).collect();
+        let mut config = vec![0; sizes.len()];
+        loop {
+            if evidence.iter().all(|&(n, v)| config[n] == v) {
+                let p: f32 = self
+                    .nodes
+                    .iter()
+                    .enumerate()
+                    .map(|(i, (parents, table))| {
+                        let mut index = vec![config[i]];
+                        index.extend(parents.iter().map(|&p| config[p]));
+                        // tables are given normalized
+                        table[IxDyn(&index)]
+                    })
+                    .product();
+                for (m, &v) in marginals.iter_mut().zip(config.iter()) {
+                    m[v] += p;
+                }
+            }
+            let mut i = 0;
+            while i < config.len() {
+                config[i] += 1;
+                if config[i] < sizes[i] {
+                    break;
+                }
+                config[i] = 0;
+                i += 1;
+            }
+            if i == config.len() {
+                break;
+            }
+        }
+        for m in &mut marginals {
+            let z: f32 = m.iter().sum();
+            m.iter_mut().for_each(|v| *v /= z);
+        }
+        marginals
+    }
+}
+
+fn table(shape: &[usize], values: &[f32]) -> ArrayD<f32> {
+    ArrayD::from_shape_vec(IxDyn(shape), values.to_vec()).unwrap()
+}
+
+#[test]
+fn cutset_conditioning_is_exact() {
+    // two loops sharing node 1: 0 -> 1 -> 3, 0 -> 2 -> 3, and 1 -> 4 -> 5, 1 -> 5
+    let model = Model {
+        nodes: vec![
+            (vec![], table(&[2], &[0.3, 0.7])),
+            (vec![0], table(&[3, 2], &[0.7, 0.1, 0.2, 0.3, 0.1, 0.6])),
+            (vec![0], table(&[2, 2], &[0.9, 0.4, 0.1, 0.6])),
+            (
+                vec![1, 2],
+                table(
+                    &[2, 3, 2],
+                    &[0.9, 0.6, 0.5, 0.2, 0.1, 0.05, 0.1, 0.4, 0.5, 0.8, 0.9, 0.95],
+                ),
+            ),
+            (vec![1], table(&[2, 3], &[0.8, 0.5, 0.1, 0.2, 0.5, 0.9])),
+            (
+                vec![1, 4],
+                table(
+                    &[2, 3, 2],
+                    &[
+                        0.99, 0.5, 0.7, 0.2, 0.4, 0.01, 0.01, 0.5, 0.3, 0.8, 0.6, 0.99,
+                    ],
+                ),
+            ),
+        ],
+    };
+    let net = model.net();
+    assert_eq!(net.loop_cutset(), vec![1]);
+
+    for evidence in &[
+        vec![],
+        vec![(3, 1)],
+        vec![(3, 0), (5, 1)],
+        vec![(1, 2), (5, 0)],
+    ] {
+        let beliefs = net.cutset_beliefs(evidence);
+        let exact = model.exact(evidence);
+        for (b, e) in beliefs.iter().zip(exact.iter()) {
+            let b = b.as_probabilities();
+            assert!(
+                b.iter().zip(e.iter()).all(|(x, y)| (x - y).abs() < 1e-4),
+                "{:?} != {:?} with evidence {:?}",
+                b,
+                e,
+                evidence
+            );
+        }
+    }
+}
@@ -0,0 +1,57 @@
+use loopybayesnet::BayesNet;
+use ndarray::{Array1, Array2, Array3};
+
+fn loopy() -> BayesNet {
+    // 0 -> 1 -> 3, 0 -> 2 -> 3
+    let mut net = BayesNet::new();
+    let a = net.add_node_from_probabilities(&[], Array1::from(vec![0.3, 0.7]));
+    let b = net.add_node_from_probabilities(&[a], Array2::from(vec![[0.9, 0.2], [0.1, 0.8]]));
+    let c = net.add_node_from_probabilities(&[a], Array2::from(vec![[0.6, 0.3], [0.4, 0.7]]));
+    net.add_node_from_probabilities(
+        &[b, c],
+        Array3::from(vec![[[0.99, 0.5], [0.4, 0.1]], [[0.01, 0.5], [0.6, 0.9]]]),
+    );
+    net.set_evidence(&[(3, 1)]);
+    net
+}
+
+#[test]
+fn damped_step_interpolates_messages() {
+    let mut net = loopy();
+    net.step();
+    let undamped = net.message_view(0, 1).to_owned();
+
+    let mut damped = loopy();
+    damped.step_damped(0.5);
+    // the previous messages are uniform
+    let expected = (&undamped * 0.5).mapv(f32::exp);
+    let expected = &expected / expected.sum();
+    let actual = damped.message_view(0, 1).mapv(f32::exp);
+    for (a, e) in actual.iter().zip(expected.iter()) {
+        assert!((a - e).abs() < 1e-6);
+    }
+}
+
+#[test]
+fn damping_keeps_the_fixed_point() {
+    let mut net = loopy();
+    for _ in 0..50 {
+        net.step();
+    }
+    let mut damped = loopy();
+    damped.set_damping(0.7);
+    assert_eq!(damped.damping(), 0.7);
+    for _ in 0..200 {
+        damped.step();
+    }
+    for (a, b) in net.beliefs().iter().zip(damped.beliefs().iter()) {
+        let diff = a.as_probabilities() - b.as_probabilities();
+        assert!(diff.iter().all(|d| d.abs() < 1e-5));
+    }
+}
+
+#[test]
+#[should_panic(expected = "Damping must be in [0, 1), got 1")]
+fn full_damping_is_rejected() {
+    loopy().set_damping(1.0);
+}
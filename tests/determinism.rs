@@ -0,0 +1,64 @@
+use loopybayesnet::{BayesNet, DeterminismWarning};
+use ndarray::{Array1, Array2, Array3};
+
+fn or_gate() -> Array3<f32> {
+    Array3::from(vec![[[1.0, 0.0], [0.0, 0.0]], [[0.0, 1.0], [1.0, 1.0]]])
+}
+
+#[test]
+fn find_functional_dependencies() {
+    let mut net = BayesNet::new();
+    let a = net.add_node_from_probabilities(&[], Array1::from(vec![0.3, 0.7]));
+    let b = net.add_node_from_probabilities(&[], Array1::from(vec![0.0, 1.0]));
+    // c is certainly 0 when a is 0, and random otherwise
+    let c = net.add_node_from_probabilities(&[a], Array2::from(vec![[1.0, 0.4], [0.0, 0.6]]));
+    net.add_node_from_probabilities(&[b, c], or_gate());
+
+    let dependencies = net.functional_dependencies();
+    assert_eq!(dependencies.len(), 3);
+    assert_eq!(dependencies[0].node, b);
+    assert_eq!(dependencies[0].values, vec![Some(1)]);
+    assert!(dependencies[0].is_functional());
+    assert_eq!(dependencies[1].node, c);
+    assert_eq!(dependencies[1].values, vec![Some(0), None]);
+    assert_eq!(dependencies[1].deterministic_columns(), 1);
+    assert!(!dependencies[1].is_functional());
+    assert_eq!(dependencies[2].parents, vec![b, c]);
+    assert_eq!(
+        dependencies[2].values,
+        vec![Some(0), Some(1), Some(1), Some(1)]
+    );
+
+    // no loop, no warning
+    assert!(net.determinism_warnings().is_empty());
+}
+
+#[test]
+fn warn_about_deterministic_loops() {
+    let mut net = BayesNet::new();
+    let a = net.add_node_from_probabilities(&[], Array1::from(vec![0.5, 0.5]));
+    let b = net.add_node_from_probabilities(&[a], Array2::from(vec![[0.9, 0.2], [0.1, 0.8]]));
+    let c = net.add_node_from_probabilities(&[a], Array2::from(vec![[0.7, 0.0], [0.3, 1.0]]));
+    let d = net.add_node_from_probabilities(&[b, c], or_gate());
+    // a deterministic leaf hanging from the loop is not on it
+    net.add_node_from_probabilities(&[d], Array2::from(vec![[0.0, 1.0], [1.0, 0.0]]));
+
+    let warnings = net.determinism_warnings();
+    assert_eq!(
+        warnings,
+        vec![
+            DeterminismWarning::InLoop {
+                node: c,
+                functional: false
+            },
+            DeterminismWarning::InLoop {
+                node: d,
+                functional: true
+            },
+        ]
+    );
+    assert_eq!(
+        warnings[1].to_string(),
+        "node 3 is fully deterministic and lies on a loop"
+    );
+}
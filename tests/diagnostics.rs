@@ -0,0 +1,143 @@
+// most of these tests produce NaN on purpose, and are disabled with the `nan-checks` feature
+#![cfg_attr(feature = "nan-checks", allow(unused_imports))]
+
+use loopybayesnet::semiring::MaxProduct;
+use loopybayesnet::{BayesNet, InferenceError, MessageAudit, MessageIssue, NodeRef};
+use ndarray::{Array1, Array2};
+
+// impossible evidence produces NaN on purpose, which panics with the `nan-checks` feature
+#[cfg(not(feature = "nan-checks"))]
+#[test]
+fn impossible_evidence_is_reported() {
+    let mut net = BayesNet::new();
+    let parent = net.add_node_from_probabilities(&[], Array1::from(vec![0.5, 0.5]));
+    let child =
+        net.add_node_from_probabilities(&[parent], Array2::from(vec![[1.0, 1.0], [0.0, 0.0]]));
+    net.set_node_name(child, "sensors/door");
+
+    net.set_evidence(&[(parent, 0)]);
+    assert_eq!(net.try_step(), Ok(()));
+    assert_eq!(net.iteration(), 1);
+
+    net.reset_state();
+    assert_eq!(net.iteration(), 0);
+    net.set_evidence(&[(child, 1)]);
+    let error = net.try_step().unwrap_err();
+    assert_eq!(
+        error,
+        InferenceError::InvalidMessage {
+            from: NodeRef {
+                id: child,
+                name: Some("sensors/door".to_owned()),
+            },
+            to: NodeRef {
+                id: parent,
+                name: None,
+            },
+            iteration: 0,
+        }
+    );
+    assert_eq!(
+        error.to_string(),
+        "invalid message from node 1 (\"sensors/door\") to node 0 at step 0 (is the evidence impossible?)"
+    );
+}
+
+#[test]
+#[should_panic(expected = "got 3 but node 0 (\"weather\") has 2")]
+fn construction_errors_name_nodes() {
+    let mut net = BayesNet::new();
+    let weather = net.add_node_from_probabilities(&[], Array1::from(vec![0.5, 0.5]));
+    net.set_node_name(weather, "weather");
+    net.add_node_from_probabilities(&[weather], Array2::from_elem((2, 3), 1.0));
+}
+
+// impossible evidence produces NaN on purpose, which panics with the `nan-checks` feature
+#[cfg(not(feature = "nan-checks"))]
+#[test]
+fn audited_runs() {
+    let mut net = BayesNet::new();
+    let parent = net.add_node_from_probabilities(&[], Array1::from(vec![0.5, 0.5]));
+    let child =
+        net.add_node_from_probabilities(&[parent], Array2::from(vec![[0.9, 0.2], [0.1, 0.8]]));
+    let sink =
+        net.add_node_from_probabilities(&[child], Array2::from(vec![[1.0, 1.0], [0.0, 0.0]]));
+    let audit = MessageAudit {
+        period: 10,
+        ..MessageAudit::default()
+    };
+
+    net.set_evidence(&[(child, 1)]);
+    assert_eq!(net.run_audited(1000, &audit), Ok(Vec::new()));
+    assert_eq!(net.iteration(), 1000);
+
+    // impossible evidence corrupts the messages
+    net.reset_state();
+    net.set_evidence(&[(sink, 1)]);
+    let strict = MessageAudit {
+        repair: false,
+        ..audit.clone()
+    };
+    // by the first audit, the NaN spread to all the messages, the first one being checked is reported
+    assert_eq!(
+        net.run_audited(25, &strict),
+        Err(InferenceError::CorruptedMessage {
+            from: net.node_ref(child),
+            to: net.node_ref(parent),
+            iteration: 10,
+            issue: MessageIssue::NotANumber,
+        })
+    );
+
+    net.reset_state();
+    let repairs = net.run_audited(25, &audit).unwrap();
+    // the 2 messages going up are repaired at each of the 3 audits
+    assert_eq!(repairs.len(), 3 * 2);
+    assert!(repairs.iter().all(|r| r.issue == MessageIssue::NotANumber));
+    assert_eq!(repairs[2].iteration, 20);
+    assert_eq!(repairs[5].iteration, 25);
+}
+
+#[cfg(not(feature = "nan-checks"))]
+#[test]
+fn invalid_beliefs_are_reported() {
+    let mut net = BayesNet::new();
+    let parent = net.add_node_from_probabilities(&[], Array1::from(vec![0.5, 0.5]));
+    let child =
+        net.add_node_from_probabilities(&[parent], Array2::from(vec![[1.0, 1.0], [0.0, 0.0]]));
+    assert!(net.try_beliefs().is_ok());
+    net.set_evidence(&[(child, 1)]);
+    assert_eq!(
+        net.try_beliefs().unwrap_err(),
+        InferenceError::InvalidBelief {
+            node: net.node_ref(child),
+            iteration: 0,
+        }
+    );
+}
+
+#[cfg(feature = "nan-checks")]
+#[test]
+#[should_panic(expected = "NaN produced by the message from node 1 to its parent 0 at step 0")]
+fn nan_checks_name_the_operation() {
+    let mut net = BayesNet::new();
+    let parent = net.add_node_from_probabilities(&[], Array1::from(vec![0.5, 0.5]));
+    let child =
+        net.add_node_from_probabilities(&[parent], Array2::from(vec![[1.0, 1.0], [0.0, 0.0]]));
+    net.set_evidence(&[(child, 1)]);
+    net.step();
+}
+
+#[test]
+fn audit_of_max_product_messages() {
+    let mut net = BayesNet::new();
+    let parent = net.add_node_from_probabilities(&[], Array1::from(vec![0.5, 0.5]));
+    let child =
+        net.add_node_from_probabilities(&[parent], Array2::from(vec![[0.9, 0.2], [0.1, 0.8]]));
+    net.set_evidence(&[(child, 1)]);
+    let audit = MessageAudit::default();
+    assert_eq!(net.run_audited_in::<MaxProduct>(10, &audit), Ok(Vec::new()));
+    assert!(net.audit_messages_in::<MaxProduct>(&audit).is_empty());
+    // the max-product messages are not normalized for the sum-product semiring
+    assert!(!net.audit_messages(&audit).is_empty());
+}
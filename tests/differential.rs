@@ -0,0 +1,59 @@
+#![cfg(feature = "differential")]
+
+use loopybayesnet::testing::differential::Expectation;
+use loopybayesnet::{BayesNet, Session, SessionRecorder};
+use ndarray::{Array1, Array2};
+
+fn chain(prior: f32) -> BayesNet {
+    let mut net = BayesNet::new();
+    let a = net.add_node_from_probabilities(&[], Array1::from(vec![prior, 1.0 - prior]));
+    let b = net.add_node_from_probabilities(&[a], Array2::from(vec![[0.9, 0.2], [0.1, 0.8]]));
+    net.add_node_from_probabilities(&[b], Array2::from(vec![[0.6, 0.3], [0.4, 0.7]]));
+    net
+}
+
+fn recorded_session() -> Session {
+    let mut recorder = SessionRecorder::new(chain(0.3));
+    recorder.set_evidence(&[(2, 0)]);
+    for _ in 0..4 {
+        recorder.step();
+    }
+    recorder.into_parts().1
+}
+
+#[test]
+fn expectation_round_trip() {
+    let net = chain(0.3);
+    let expectation = Expectation::record(&net, recorded_session());
+    assert_eq!(expectation.version(), env!("CARGO_PKG_VERSION"));
+    assert_eq!(expectation.beliefs().len(), 3);
+
+    let parsed = Expectation::from_text(&expectation.to_text()).unwrap();
+    assert_eq!(parsed, expectation);
+    assert!(parsed.check(&net, 0.0).is_empty());
+}
+
+#[test]
+fn divergences_are_reported() {
+    let expectation = Expectation::record(&chain(0.3), recorded_session());
+
+    // a new version computing a different model
+    let mut changed = chain(0.5);
+    changed.set_node_name(0, "cause");
+    let divergences = expectation.check(&changed, 1e-3);
+    assert!(!divergences.is_empty());
+    assert!(divergences
+        .iter()
+        .all(|d| (d.expected - d.actual).abs() > 1e-3));
+    assert_eq!(divergences[0].node.id, 0);
+    assert!(divergences[0]
+        .to_string()
+        .starts_with("probability of value 0 of node 0 (\"cause\") is"));
+    assert!(expectation.check(&changed, 1.0).is_empty());
+}
+
+#[test]
+fn invalid_expectation() {
+    let error = Expectation::from_text("version 0.1.0\nbelief 0.5 half\n").unwrap_err();
+    assert_eq!(error.line, 2);
+}
@@ -0,0 +1,91 @@
+use loopybayesnet::{BayesNet, DynamicBayesNet, FixedLagSmoother, SliceParent};
+use ndarray::{Array1, Array2};
+
+// the umbrella world: whether it rains, and whether the director carries an umbrella
+fn umbrella() -> DynamicBayesNet {
+    let sensor = Array2::from(vec![[0.8, 0.1], [0.2, 0.9]]);
+    let mut prior = BayesNet::new();
+    let rain = prior.add_node_from_probabilities(&[], Array1::from(vec![0.5, 0.5]));
+    let umbrella = prior.add_node_from_probabilities(&[rain], sensor.clone());
+    prior.set_node_name(rain, "rain");
+
+    let mut dbn = DynamicBayesNet::new(prior);
+    dbn.set_transition(
+        rain,
+        &[SliceParent::Previous(rain)],
+        Array2::from(vec![[0.7, 0.3], [0.3, 0.7]]),
+    );
+    dbn.set_transition(umbrella, &[SliceParent::Current(rain)], sensor);
+    dbn
+}
+
+#[test]
+fn unrolling_shares_tables() {
+    let dbn = umbrella();
+    let net = dbn.unroll(10);
+    assert_eq!(net.num_nodes(), 20);
+    assert_eq!(dbn.node_id(3, 1), 7);
+    assert_eq!(net.parents(7), vec![6]);
+    assert_eq!(net.parents(6), vec![4]);
+    assert_eq!(net.find_node("rain_3"), Some(6));
+    assert_eq!(net.node_name(7), None);
+    // the prior of the rain, its transition, and the sensor model
+    assert_eq!(net.num_distinct_tables(), 3);
+}
+
+#[test]
+fn filtering_and_smoothing() {
+    let dbn = umbrella();
+    let beliefs = dbn.beliefs(2, &[(0, 1, 1), (1, 1, 1)], 5);
+    assert_eq!(beliefs.len(), 2);
+    assert!((beliefs[1][0].as_probabilities()[1] - 0.883).abs() < 1e-3);
+    assert!((beliefs[0][0].as_probabilities()[1] - 0.883).abs() < 1e-3);
+
+    let beliefs = dbn.beliefs(1, &[(0, 1, 1)], 5);
+    assert!((beliefs[0][0].as_probabilities()[1] - 0.818).abs() < 1e-3);
+}
+
+#[test]
+#[should_panic]
+fn missing_transition() {
+    let mut prior = BayesNet::new();
+    prior.add_node_from_probabilities(&[], Array1::from(vec![0.5, 0.5]));
+    DynamicBayesNet::new(prior).unroll(2);
+}
+
+#[test]
+fn fixed_lag_smoothing() {
+    let dbn = umbrella();
+    let umbrellas = [1, 1, 0, 1, 1, 0, 0, 1];
+    let mut smoother = FixedLagSmoother::new(dbn.clone(), 2, 10);
+    for (t, &u) in umbrellas.iter().enumerate() {
+        let smoothed = smoother.push(&[(1, u)]);
+        let evidence: Vec<(usize, usize, usize)> = umbrellas[..=t]
+            .iter()
+            .enumerate()
+            .map(|(s, &u)| (s, 1, u))
+            .collect();
+        let exact = dbn.beliefs(t + 1, &evidence, 2 * t + 10);
+        let rain = |beliefs: &[loopybayesnet::LogProbVector]| beliefs[0].as_probabilities()[1];
+
+        // the last time step is filtered exactly
+        assert_eq!(smoother.first_time(), t.saturating_sub(2));
+        assert_eq!(smoother.beliefs().len(), t.min(2) + 1);
+        assert!((rain(smoother.beliefs().last().unwrap()) - rain(&exact[t])).abs() < 1e-4);
+
+        // the time step leaving the window is smoothed by the two next ones
+        match smoothed {
+            Some(beliefs) => {
+                assert!(t >= 3);
+                let evidence: Vec<(usize, usize, usize)> = umbrellas[..t]
+                    .iter()
+                    .enumerate()
+                    .map(|(s, &u)| (s, 1, u))
+                    .collect();
+                let lagged = dbn.beliefs(t, &evidence, 2 * t + 10);
+                assert!((rain(&beliefs) - rain(&lagged[t - 3])).abs() < 1e-4);
+            }
+            None => assert!(t < 3),
+        }
+    }
+}
@@ -0,0 +1,22 @@
+use loopybayesnet::{BayesNet, EngineVersion};
+use ndarray::{Array1, Array2};
+
+#[test]
+fn engine_version_is_pinned_per_network() {
+    let mut net = BayesNet::new();
+    assert_eq!(net.engine_version(), EngineVersion::latest());
+    let a = net.add_node_from_probabilities(&[], Array1::from(vec![0.3, 0.7]));
+    let b = net.add_node_from_probabilities(&[a], Array2::from(vec![[0.9, 0.2], [0.1, 0.8]]));
+    net.set_evidence(&[(b, 0)]);
+
+    let mut pinned = net.clone();
+    pinned.set_engine_version(EngineVersion::V1);
+    assert_eq!(pinned.engine_version(), EngineVersion::V1);
+    for _ in 0..3 {
+        net.step();
+        pinned.step();
+    }
+    for (a, b) in net.beliefs().iter().zip(pinned.beliefs().iter()) {
+        assert_eq!(a.log_probabilities(), b.log_probabilities());
+    }
+}
@@ -0,0 +1,64 @@
+use loopybayesnet::evaluation::compare;
+use loopybayesnet::BayesNet;
+use ndarray::{Array1, Array2};
+
+// rain -> wet, with a sensor of the given quality
+fn model(quality: f32) -> BayesNet {
+    let mut net = BayesNet::new();
+    let rain = net.add_node_from_probabilities(&[], Array1::from(vec![0.7, 0.3]));
+    net.add_node_from_probabilities(
+        &[rain],
+        Array2::from(vec![[quality, 1.0 - quality], [1.0 - quality, quality]]),
+    );
+    net
+}
+
+#[test]
+fn compare_models() {
+    let good = model(0.9);
+    let bad = model(0.4);
+    let data = vec![
+        vec![Some(0), Some(0)],
+        vec![Some(1), Some(1)],
+        vec![Some(1), Some(1)],
+        vec![Some(0), None],
+        vec![None, Some(1)],
+    ];
+    let comparison = compare(&[&good, &bad], &data, &[0], 5);
+
+    // the last record has no observed target
+    let good_metrics = &comparison.models[0].overall;
+    assert_eq!(good_metrics.count, 4);
+    assert_eq!(comparison.models[1].per_target[0].count, 4);
+
+    // the good model predicts P(rain | wet) = 0.27 / 0.34 and P(dry | dry ground) = 0.63 / 0.66
+    let p_rain: f32 = 0.3 * 0.9 / (0.3 * 0.9 + 0.7 * 0.1);
+    let p_dry: f32 = 0.7 * 0.9 / (0.7 * 0.9 + 0.3 * 0.1);
+    assert!((good_metrics.accuracy - 1.0).abs() < 1e-6);
+    let log_loss = -(p_dry.ln() + 2.0 * p_rain.ln() + 0.7f32.ln()) / 4.0;
+    assert!((good_metrics.log_loss - log_loss).abs() < 1e-4);
+    let brier =
+        (2.0 * (1.0 - p_dry).powi(2) + 4.0 * (1.0 - p_rain).powi(2) + 2.0 * 0.3f32.powi(2)) / 4.0;
+    assert!((good_metrics.brier - brier).abs() < 1e-4);
+    assert_eq!(comparison.best_by_log_loss(), Some(0));
+
+    // the bad model predicts no rain when the ground is wet
+    assert_eq!(comparison.disagreements.len(), 2);
+    let disagreement = &comparison.disagreements[0];
+    assert_eq!((disagreement.record, disagreement.target), (1, 0));
+    assert_eq!(disagreement.observed, 1);
+    assert_eq!(disagreement.predictions, vec![1, 0]);
+    assert!((disagreement.probabilities[0] - p_rain).abs() < 1e-4);
+
+    let report = comparison.to_string();
+    assert!(report.starts_with("model  accuracy  log-loss     brier\n    0    1.0000"));
+    assert!(report.ends_with("2 disagreements"));
+}
+
+#[test]
+#[should_panic(expected = "Models have different numbers of values for target 0: [2, 3]")]
+fn compare_mismatched_models() {
+    let mut other = BayesNet::new();
+    other.add_node_from_probabilities(&[], Array1::from(vec![0.2, 0.3, 0.5]));
+    compare(&[&model(0.9), &other], &[], &[0], 1);
+}
@@ -0,0 +1,65 @@
+use loopybayesnet::BayesNet;
+use ndarray::{Array1, Array2, Array3};
+
+fn assert_close(a: &[loopybayesnet::LogProbVector], b: &[loopybayesnet::LogProbVector]) {
+    assert_eq!(a.len(), b.len());
+    for (a, b) in a.iter().zip(b.iter()) {
+        let diff = a.as_probabilities() - b.as_probabilities();
+        assert!(diff.iter().all(|d| d.abs() < 1e-5), "{:?} != {:?}", a, b);
+    }
+}
+
+#[test]
+fn exact_beliefs_match_propagation_on_polytree() {
+    let mut net = BayesNet::new();
+    let a = net.add_node_from_probabilities(&[], Array1::from(vec![0.3, 0.7]));
+    let b = net.add_node_from_probabilities(&[], Array1::from(vec![0.6, 0.4]));
+    let c = net.add_node_from_probabilities(
+        &[a, b],
+        Array3::from(vec![[[0.9, 0.5], [0.4, 0.1]], [[0.1, 0.5], [0.6, 0.9]]]),
+    );
+    net.add_node_from_probabilities(&[c], Array2::from(vec![[0.8, 0.3], [0.2, 0.7]]));
+    // an independent node
+    net.add_node_from_probabilities(&[], Array1::from(vec![0.1, 0.2, 0.7]));
+    net.set_evidence(&[(3, 1)]);
+    for _ in 0..5 {
+        net.step();
+    }
+    assert_close(&net.exact_beliefs(), &net.beliefs());
+}
+
+#[test]
+fn exact_beliefs_on_loopy_network() {
+    // 0 -> 1 -> 3, 0 -> 2 -> 3
+    let mut net = BayesNet::new();
+    let a = net.add_node_from_probabilities(&[], Array1::from(vec![0.3, 0.7]));
+    let b = net.add_node_from_probabilities(&[a], Array2::from(vec![[0.9, 0.2], [0.1, 0.8]]));
+    let c = net.add_node_from_probabilities(&[a], Array2::from(vec![[0.6, 0.3], [0.4, 0.7]]));
+    net.add_node_from_probabilities(
+        &[b, c],
+        Array3::from(vec![[[0.99, 0.5], [0.4, 0.1]], [[0.01, 0.5], [0.6, 0.9]]]),
+    );
+    net.set_evidence(&[(3, 1)]);
+    let exact = net.exact_beliefs();
+    assert_close(&exact, &net.cutset_beliefs(&[(3, 1)]));
+    assert_eq!(exact[3].as_probabilities()[1], 1.0);
+}
+
+#[test]
+fn log_evidence() {
+    let mut net = BayesNet::new();
+    let a = net.add_node_from_probabilities(&[], Array1::from(vec![0.3, 0.7]));
+    let b = net.add_node_from_probabilities(&[a], Array2::from(vec![[0.9, 0.2], [0.1, 0.8]]));
+    let c = net.add_node_from_probabilities(&[b], Array2::from(vec![[0.6, 0.3], [0.4, 0.7]]));
+    net.add_node_from_probabilities(&[], Array1::from(vec![0.25, 0.75]));
+    assert!(net.exact_log_evidence().abs() < 1e-6);
+
+    net.set_evidence(&[(c, 1), (3, 0)]);
+    // P(b = 0) = 0.41, P(c = 1) = 0.41 * 0.4 + 0.59 * 0.7
+    let expected = ((0.41f32 * 0.4 + 0.59 * 0.7) * 0.25).ln();
+    assert!((net.exact_log_evidence() - expected).abs() < 1e-5);
+    for _ in 0..5 {
+        net.step();
+    }
+    assert!((net.log_evidence() - expected).abs() < 1e-5);
+}
@@ -0,0 +1,33 @@
+use loopybayesnet::BayesNet;
+use ndarray::{Array1, Array2};
+
+#[test]
+fn minimal_explanations() {
+    // a target with a strong, a weak and a medium sensor
+    let mut net = BayesNet::new();
+    let target = net.add_node_from_probabilities(&[], Array1::from(vec![0.5, 0.5]));
+    let strong =
+        net.add_node_from_probabilities(&[target], Array2::from(vec![[0.9, 0.1], [0.1, 0.9]]));
+    let weak =
+        net.add_node_from_probabilities(&[target], Array2::from(vec![[0.6, 0.4], [0.4, 0.6]]));
+    let medium =
+        net.add_node_from_probabilities(&[target], Array2::from(vec![[0.7, 0.3], [0.3, 0.7]]));
+    net.set_evidence(&[(strong, 0), (weak, 0), (medium, 0)]);
+
+    // the strong sensor alone gives 0.9, the two others together 0.78, and any other subset less
+    assert_eq!(
+        net.minimal_explanations(target, 0, 0.75, 3),
+        vec![vec![(strong, 0)], vec![(weak, 0), (medium, 0)]]
+    );
+    // with the strong sensor, the medium one reaches 0.955 but the weak one only 0.931
+    assert_eq!(
+        net.minimal_explanations(target, 0, 0.95, 3),
+        vec![vec![(strong, 0), (medium, 0)]]
+    );
+    // no evidence is needed for a low threshold, and nothing is enough for an unreachable one
+    assert_eq!(
+        net.minimal_explanations(target, 0, 0.4, 3),
+        vec![Vec::<(usize, usize)>::new()]
+    );
+    assert!(net.minimal_explanations(target, 1, 0.6, 3).is_empty());
+}
@@ -0,0 +1,79 @@
+use loopybayesnet::{BayesNet, BifError};
+
+const TREE: &str = "
+# cooling is lost if the pump or the valve fails
+top = and cooling backup
+cooling = or pump valve
+basic pump 0.1
+basic valve 0.2   # a comment
+basic backup 0.5
+";
+
+fn failure(net: &mut BayesNet, node: &str) -> f32 {
+    net.reset_state();
+    for _ in 0..10 {
+        net.step();
+    }
+    let node = net.find_node(node).unwrap();
+    net.beliefs()[node].as_probabilities()[1]
+}
+
+#[test]
+fn read_fault_tree() {
+    let mut net = BayesNet::from_fault_tree(TREE).unwrap();
+    assert_eq!(net.num_nodes(), 5);
+    let top = net.find_node("top").unwrap();
+    let cooling = net.find_node("cooling").unwrap();
+    assert_eq!(
+        net.parents(top),
+        vec![cooling, net.find_node("backup").unwrap()]
+    );
+    assert_eq!(net.state_names(top).unwrap(), ["working", "failed"]);
+    assert!((failure(&mut net, "cooling") - 0.28).abs() < 1e-5);
+    assert!((failure(&mut net, "top") - 0.14).abs() < 1e-5);
+
+    // the top event being observed, the pump is more likely to have failed
+    net.set_evidence(&[(top, 1)]);
+    assert!((failure(&mut net, "pump") - 0.1 / 0.28).abs() < 1e-4);
+}
+
+#[test]
+fn vote_and_noisy_gates() {
+    let text =
+        "basic a 0.1\nbasic b 0.1\nbasic c 0.1\ntwo = vote 2 a b c\nnoisy = noisy-or a:0.5 b:1\n";
+    let mut net = BayesNet::from_fault_tree(text).unwrap();
+    assert!((failure(&mut net, "two") - 0.028).abs() < 1e-5);
+    // 1 - (1 - 0.1 * 0.5) * (1 - 0.1)
+    assert!((failure(&mut net, "noisy") - 0.145).abs() < 1e-5);
+}
+
+#[test]
+fn fault_tree_errors() {
+    assert_eq!(
+        BayesNet::from_fault_tree("basic a 0.1\ntop = or a b\n").unwrap_err(),
+        BifError::UnknownVariable {
+            line: 2,
+            name: "b".to_owned()
+        }
+    );
+    assert!(matches!(
+        BayesNet::from_fault_tree("basic a 1.5\n"),
+        Err(BifError::Syntax { line: 1, .. })
+    ));
+    assert!(matches!(
+        BayesNet::from_fault_tree("basic a 0.1\n\na = or a\n"),
+        Err(BifError::Syntax { line: 3, .. })
+    ));
+    assert!(matches!(
+        BayesNet::from_fault_tree("basic a 0.1\nb = xor a\n"),
+        Err(BifError::Syntax { line: 2, .. })
+    ));
+    assert!(matches!(
+        BayesNet::from_fault_tree("basic a 0.1\nb = vote 2 a\n"),
+        Err(BifError::Syntax { line: 2, .. })
+    ));
+    assert_eq!(
+        BayesNet::from_fault_tree("x = or y\ny = and x\n").unwrap_err(),
+        BifError::Cycle("x".to_owned())
+    );
+}
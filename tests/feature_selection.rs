@@ -0,0 +1,44 @@
+use loopybayesnet::feature_selection::{markov_blanket, markov_blanket_with};
+use loopybayesnet::stats::IndependenceTest;
+use loopybayesnet::BayesNet;
+use ndarray::{Array1, Array2, Array3};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+#[test]
+fn blanket_of_a_network() {
+    // a -> t -> c <- s, t -> d -> e, and an unrelated node u
+    let mut net = BayesNet::new();
+    let a = net.add_node_from_probabilities(&[], Array1::from(vec![0.4, 0.6]));
+    let t = net.add_node_from_probabilities(&[a], Array2::from(vec![[0.9, 0.2], [0.1, 0.8]]));
+    let s = net.add_node_from_probabilities(&[], Array1::from(vec![0.5, 0.5]));
+    let c = net.add_node_from_probabilities(
+        &[t, s],
+        Array3::from(vec![[[0.9, 0.3], [0.4, 0.05]], [[0.1, 0.7], [0.6, 0.95]]]),
+    );
+    let d = net.add_node_from_probabilities(&[t], Array2::from(vec![[0.85, 0.2], [0.15, 0.8]]));
+    let e = net.add_node_from_probabilities(&[d], Array2::from(vec![[0.9, 0.1], [0.1, 0.9]]));
+    let u = net.add_node_from_probabilities(&[], Array1::from(vec![0.3, 0.7]));
+    let data = net.sample_forward(&mut StdRng::seed_from_u64(21), 20000);
+
+    assert_eq!(markov_blanket(&data, t), vec![a, s, c, d]);
+    assert_eq!(
+        markov_blanket_with(&data, t, IndependenceTest::ChiSquared, 0.01),
+        vec![a, s, c, d]
+    );
+    // with several candidates tested at each step, a lower level avoids false positives
+    let strict = |target| markov_blanket_with(&data, target, IndependenceTest::GSquared, 0.001);
+    assert_eq!(strict(e), vec![d]);
+    assert!(strict(u).is_empty());
+}
+
+#[test]
+fn blanket_of_no_data() {
+    assert!(markov_blanket(&[], 0).is_empty());
+}
+
+#[test]
+#[should_panic(expected = "Record 1 has 2 values but the first record has 3")]
+fn records_of_different_lengths() {
+    markov_blanket(&[vec![0, 1, 0], vec![1, 0]], 0);
+}
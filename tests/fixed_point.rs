@@ -0,0 +1,50 @@
+#![cfg(feature = "fixed-point")]
+
+use loopybayesnet::fixed_point::{Fixed, FixedPointNet};
+use loopybayesnet::BayesNet;
+use ndarray::{Array1, Array2, Array3};
+
+#[test]
+fn log_add_accuracy() {
+    for &(a, b) in &[(0.0f32, 0.0f32), (-1.0, -3.5), (-0.3, -20.0), (2.0, 1.99)] {
+        let exact = (a.exp() + b.exp()).ln();
+        let fixed = Fixed::from_f32(a).log_add(Fixed::from_f32(b)).to_f32();
+        assert!((exact - fixed).abs() < 1.3e-4, "{} != {}", exact, fixed);
+    }
+    assert_eq!(
+        Fixed::NEG_INFINITY.log_add(Fixed::from_f32(-2.0)),
+        Fixed::from_f32(-2.0)
+    );
+}
+
+#[test]
+fn matches_float_inference() {
+    let mut net = BayesNet::new();
+    let a = net.add_node_from_probabilities(&[], Array1::from(vec![0.5, 0.4, 0.1]));
+    let b =
+        net.add_node_from_probabilities(&[a], Array2::from(vec![[0.8, 0.2, 1.0], [0.2, 0.8, 0.0]]));
+    let _c = net.add_node_from_probabilities(
+        &[a, b],
+        Array3::from(vec![
+            [[0.1, 0.3], [1.0, 0.0], [0.5, 0.0]],
+            [[0.9, 0.7], [0.0, 1.0], [0.5, 1.0]],
+        ]),
+    );
+    net.set_evidence(&[(2, 1)]);
+    let mut fixed = FixedPointNet::from_net(&net);
+    net.reset_state();
+    for _ in 0..10 {
+        net.step();
+        fixed.step();
+    }
+    for (f, x) in fixed.beliefs().iter().zip(net.beliefs().iter()) {
+        let diff = &f.as_probabilities() - &x.as_probabilities();
+        assert!(diff.iter().all(|d| d.abs() < 1e-3), "{:?}", diff);
+    }
+    // results are bit-for-bit reproducible
+    let mut again = FixedPointNet::from_net(&net);
+    for _ in 0..10 {
+        again.step();
+    }
+    assert_eq!(again.beliefs_fixed(), fixed.beliefs_fixed());
+}
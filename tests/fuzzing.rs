@@ -0,0 +1,29 @@
+#![cfg(feature = "arbitrary")]
+
+use arbitrary::{Arbitrary, Unstructured};
+use loopybayesnet::fuzzing::NetWithEvidence;
+use rand::rngs::StdRng;
+use rand::{RngCore, SeedableRng};
+
+#[test]
+fn random_networks_infer_without_nan() {
+    let mut rng = StdRng::seed_from_u64(7);
+    let mut bytes = vec![0u8; 512];
+    for _ in 0..500 {
+        rng.fill_bytes(&mut bytes);
+        let mut u = Unstructured::new(&bytes);
+        let NetWithEvidence { mut net, evidence } = NetWithEvidence::arbitrary(&mut u).unwrap();
+        for &(node, value) in &evidence {
+            assert!(value < net.num_values(node));
+        }
+        net.set_evidence(&evidence);
+        for _ in 0..10 {
+            net.try_step().unwrap();
+        }
+        for belief in net.beliefs() {
+            let p = belief.as_probabilities();
+            assert!(p.iter().all(|v| v.is_finite()));
+            assert!((p.sum() - 1.0).abs() < 1e-3);
+        }
+    }
+}
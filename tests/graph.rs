@@ -0,0 +1,73 @@
+use loopybayesnet::graph::{
+    cpdag, find_loop_cutset, requisite_evidence, CutsetHeuristic, EdgeOrientation, LoopCutset,
+};
+
+use EdgeOrientation::*;
+
+#[test]
+fn cpdag_of_chain_is_reversible() {
+    // 0 -> 1 -> 2
+    let edges = cpdag(&[vec![], vec![0], vec![1]]);
+    assert_eq!(edges, vec![(0, 1, Reversible), (1, 2, Reversible)]);
+}
+
+#[test]
+fn cpdag_propagates_v_structures() {
+    // the v-structure 0 -> 2 <- 1 compels 2 -> 3, which in turn compels 3 -> 4
+    let parents = vec![vec![], vec![], vec![0, 1], vec![2], vec![3]];
+    let edges = cpdag(&parents);
+    assert_eq!(
+        edges,
+        vec![
+            (0, 2, Compelled),
+            (1, 2, Compelled),
+            (2, 3, Compelled),
+            (3, 4, Compelled)
+        ]
+    );
+
+    // a fully connected triangle has no compelled edges
+    let edges = cpdag(&[vec![], vec![0], vec![0, 1]]);
+    assert!(edges.iter().all(|&(_, _, k)| k == Reversible));
+}
+
+#[test]
+fn requisite_evidence_follows_d_separation() {
+    // 0 -> 2 <- 1, 2 -> 3, and 4 disconnected
+    let parents = vec![vec![], vec![], vec![0, 1], vec![2], vec![]];
+    // the parents are independent until their common child or its descendant is observed
+    assert_eq!(requisite_evidence(&parents, 0, &[1]), Vec::<usize>::new());
+    assert_eq!(requisite_evidence(&parents, 0, &[1, 3]), vec![1, 3]);
+    assert_eq!(requisite_evidence(&parents, 0, &[1, 2, 3]), vec![1, 2]);
+    // observing the middle of the chain blocks it
+    assert_eq!(requisite_evidence(&parents, 3, &[0, 2, 4]), vec![2]);
+}
+
+#[test]
+fn loop_cutsets() {
+    // a polytree has no loops
+    let tree = vec![vec![], vec![], vec![0, 1], vec![2]];
+    assert_eq!(
+        find_loop_cutset(&tree, &[2, 2, 2, 2], CutsetHeuristic::MaxDegree),
+        LoopCutset {
+            nodes: vec![],
+            assignments: Some(1),
+            independent_loops: 0,
+        }
+    );
+
+    // two loops: 0 -> {1, 2} -> 3 and 1 -> 4 -> 5 <- 1
+    let parents = vec![vec![], vec![0], vec![0], vec![1, 2], vec![1], vec![1, 4]];
+    let cutset = find_loop_cutset(&parents, &[2, 3, 2, 2, 2, 2], CutsetHeuristic::MaxDegree);
+    assert_eq!(cutset.nodes, vec![1]);
+    assert_eq!(cutset.assignments, Some(3));
+    assert_eq!(cutset.independent_loops, 2);
+
+    // with many values, node 1 becomes too costly to condition on
+    let cardinalities = [2, 20, 2, 2, 2, 2];
+    let by_degree = find_loop_cutset(&parents, &cardinalities, CutsetHeuristic::MaxDegree);
+    assert_eq!(by_degree.nodes, vec![1]);
+    let by_weight = find_loop_cutset(&parents, &cardinalities, CutsetHeuristic::MinWeight);
+    assert_eq!(by_weight.nodes, vec![0, 4]);
+    assert_eq!(by_weight.assignments, Some(4));
+}
@@ -0,0 +1,60 @@
+use loopybayesnet::BayesNet;
+use ndarray::{Array1, Array2};
+
+#[test]
+fn identical_tables_are_shared() {
+    let mut net = BayesNet::new();
+    let mut previous = net.add_node_from_probabilities(&[], Array1::from(vec![0.5, 0.5]));
+    // a chain built from a template, the same transition table being repeated
+    for _ in 0..1000 {
+        previous = net
+            .add_node_from_probabilities(&[previous], Array2::from(vec![[0.9, 0.2], [0.1, 0.8]]));
+    }
+    net.add_node_from_probabilities(&[previous], Array2::from(vec![[0.9, 0.2], [0.1, 0.8]]));
+    net.add_node_from_probabilities(&[previous], Array2::from(vec![[0.8, 0.2], [0.2, 0.8]]));
+    assert_eq!(net.num_nodes(), 1003);
+    assert_eq!(net.num_distinct_tables(), 3);
+
+    net.set_evidence(&[(previous, 0)]);
+    for _ in 0..5 {
+        net.step();
+    }
+    let beliefs = net.beliefs();
+    assert!((beliefs[1001].as_probabilities()[0] - 0.9).abs() < 1e-5);
+    assert!((beliefs[1002].as_probabilities()[0] - 0.8).abs() < 1e-5);
+}
+
+#[test]
+fn modified_tables_are_not_shared() {
+    let mut net = BayesNet::new();
+    let a = net.add_node_from_probabilities(&[], Array1::from(vec![0.5, 0.5]));
+    let b = net.add_node_from_probabilities(&[a], Array2::from(vec![[0.9, 0.2], [0.1, 0.8]]));
+    let c = net.add_node_from_probabilities(&[a], Array2::from(vec![[0.9, 0.2], [0.1, 0.8]]));
+    assert_eq!(net.num_distinct_tables(), 2);
+
+    // fitting the network on data makes the tables of b and c differ
+    let data = vec![vec![0, 1, 0], vec![1, 1, 0]];
+    let fitted = loopybayesnet::learning::fit_parameters(&net, &data, 1.0);
+    assert_eq!(fitted.num_distinct_tables(), 3);
+    let copy = fitted.clone();
+    assert_eq!(copy.num_distinct_tables(), 3);
+    let beliefs = fitted.exact_beliefs();
+    assert!(beliefs[b].as_probabilities()[1] > 0.6);
+    assert!(beliefs[c].as_probabilities()[0] > 0.6);
+    // the original network is unchanged
+    let beliefs = net.exact_beliefs();
+    assert!((beliefs[b].as_probabilities()[0] - beliefs[c].as_probabilities()[0]).abs() < 1e-6);
+}
+
+#[test]
+fn replaced_tables_leave_the_pool() {
+    let mut net = BayesNet::new();
+    let a = net.add_node_from_probabilities(&[], Array1::from(vec![0.5, 0.5]));
+    let b = net.add_node_from_probabilities(&[a], Array2::from(vec![[0.9, 0.2], [0.1, 0.8]]));
+    for i in 0..10_000 {
+        let p = (i % 5000) as f32 / 5000.0;
+        net.set_probabilities(b, Array2::from(vec![[p, 0.2], [1.0 - p, 0.8]]));
+    }
+    assert_eq!(net.num_distinct_tables(), 2);
+    assert!(net.num_pooled_tables() <= 128);
+}
@@ -0,0 +1,143 @@
+use loopybayesnet::{BayesNet, BifError, NodeLayout};
+use ndarray::{Array1, Array3};
+
+const NET: &str = r#"
+net
+{
+    node_size = (80 40);
+    HR_Desc = "";
+}
+
+node Rain
+{
+    label = "Is it raining?";
+    position = (100 200);
+    states = ("no" "yes");
+}
+
+% a discrete node, with a table over two parents
+discrete node Grass
+{
+    label = "";
+    position = (150 300);
+    states = ("dry" "wet");
+    HR_Group = "0";
+}
+
+node Sprinkler
+{
+    states = ("off" "on");
+}
+
+potential ( Grass | Rain Sprinkler )
+{
+    data = ((( 1.0 0.0 )	%  Rain=no  Sprinkler=off
+	     ( 0.1 0.9 ))	%  Rain=no  Sprinkler=on
+	    (( 0.2 0.8 )	%  Rain=yes  Sprinkler=off
+	     ( 0.01 0.99 )));	%  Rain=yes  Sprinkler=on
+}
+
+potential ( Rain )
+{
+    data = ( 0.8 0.2 );
+}
+
+potential ( Sprinkler | )
+{
+    data = ( 0.6 0.4 );
+}
+"#;
+
+#[test]
+fn read_hugin_net() {
+    let mut net = BayesNet::from_hugin_net(NET).unwrap();
+    assert_eq!(net.num_nodes(), 3);
+    let rain = net.find_node("Rain").unwrap();
+    let sprinkler = net.find_node("Sprinkler").unwrap();
+    let grass = net.find_node("Grass").unwrap();
+    assert_eq!(net.parents(grass), vec![rain, sprinkler]);
+    assert_eq!(net.state_names(grass).unwrap(), ["dry", "wet"]);
+    assert_eq!(net.tag(rain, "label"), Some("Is it raining?"));
+    assert_eq!(net.tag(sprinkler, "label"), None);
+    assert_eq!(
+        net.layout(rain),
+        Some(&NodeLayout {
+            x: 100.0,
+            y: 200.0,
+            color: None
+        })
+    );
+
+    // P(wet | rain, sprinkler off) = 0.8
+    net.set_evidence(&[(rain, 1), (sprinkler, 0)]);
+    net.step();
+    net.step();
+    assert!((net.beliefs()[grass].as_probabilities()[1] - 0.8).abs() < 1e-5);
+}
+
+#[test]
+fn hugin_net_round_trip() {
+    let mut net = BayesNet::new();
+    let a = net.add_node_from_probabilities(&[], Array1::from(vec![0.3, 0.7]));
+    let b = net.add_node_from_probabilities(&[], Array1::from(vec![0.2, 0.5, 0.3]));
+    net.add_node_from_probabilities(
+        &[a, b],
+        Array3::from(vec![
+            [[0.9, 0.5, 0.3], [0.4, 0.1, 0.6]],
+            [[0.1, 0.5, 0.7], [0.6, 0.9, 0.4]],
+        ]),
+    );
+    net.set_node_name(a, "weather/rain");
+    net.set_tag(a, "label", "Rain \"today\"");
+    net.set_state_names(b, &["low", "mid", "high"]);
+
+    let text = net.to_hugin_net();
+    assert!(text.contains("node weather_rain\n{\n    label = \"Rain \\\"today\\\"\";\n"));
+    assert!(text.contains("potential ( node2 | weather_rain node1 )"));
+
+    let copy = BayesNet::from_hugin_net(&text).unwrap();
+    assert_eq!(copy.node_name(0), Some("weather_rain"));
+    assert_eq!(copy.tag(0, "label"), Some("Rain \"today\""));
+    assert_eq!(copy.state_names(0).unwrap(), ["s0", "s1"]);
+    assert_eq!(copy.state_names(1).unwrap(), ["low", "mid", "high"]);
+    for (original, copied) in net.exact_beliefs().iter().zip(copy.exact_beliefs()) {
+        for (x, y) in original
+            .as_probabilities()
+            .iter()
+            .zip(copied.as_probabilities().iter())
+        {
+            assert!((x - y).abs() < 1e-6);
+        }
+    }
+    assert_eq!(
+        copy.to_hugin_net().replace("weather_rain", "x"),
+        text.replace("weather_rain", "x")
+    );
+}
+
+#[test]
+fn hugin_net_errors() {
+    assert_eq!(
+        BayesNet::from_hugin_net(&NET.replace("( 0.6 0.4 )", "( 0.6 0.4 0.0 )")).unwrap_err(),
+        BifError::TableSize {
+            line: 44,
+            variable: "Sprinkler".to_owned(),
+            expected: 2,
+            found: 3
+        }
+    );
+    assert_eq!(
+        BayesNet::from_hugin_net(&NET.replace("Rain Sprinkler", "Rain Wind")).unwrap_err(),
+        BifError::UnknownVariable {
+            line: 29,
+            name: "Wind".to_owned()
+        }
+    );
+    assert_eq!(
+        BayesNet::from_hugin_net("continuous node x { }").unwrap_err(),
+        BifError::Syntax {
+            line: 1,
+            message: "unexpected \"continuous\"".to_owned()
+        }
+    );
+}
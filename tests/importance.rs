@@ -0,0 +1,70 @@
+use loopybayesnet::BayesNet;
+use ndarray::{Array1, Array2, Array3};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+// a loop whose two paths carry correlated information, which BP double counts
+fn diamond() -> BayesNet {
+    let mut net = BayesNet::new();
+    let a = net.add_node_from_probabilities(&[], Array1::from(vec![0.7, 0.3]));
+    let b = net.add_node_from_probabilities(&[a], Array2::from(vec![[0.9, 0.2], [0.1, 0.8]]));
+    let c = net.add_node_from_probabilities(&[a], Array2::from(vec![[0.8, 0.1], [0.2, 0.9]]));
+    let d = net.add_node_from_probabilities(
+        &[b, c],
+        Array3::from(vec![[[0.99, 0.1], [0.1, 0.01]], [[0.01, 0.9], [0.9, 0.99]]]),
+    );
+    net.set_evidence(&[(d, 1)]);
+    for _ in 0..50 {
+        net.step();
+    }
+    net
+}
+
+fn max_error(net: &BayesNet, marginals: &[loopybayesnet::LogProbVector]) -> f32 {
+    net.exact_beliefs()
+        .iter()
+        .zip(marginals)
+        .flat_map(|(exact, estimate)| {
+            let (exact, estimate) = (exact.as_probabilities(), estimate.as_probabilities());
+            (0..exact.len())
+                .map(|v| (exact[v] - estimate[v]).abs())
+                .collect::<Vec<_>>()
+        })
+        .fold(0.0, f32::max)
+}
+
+#[test]
+fn corrects_loopy_beliefs() {
+    let net = diamond();
+    let mut rng = StdRng::seed_from_u64(7);
+    let estimate = net.importance_refinement(&mut rng, 20000);
+
+    let bp_error = max_error(&net, &net.beliefs());
+    let refined_error = max_error(&net, &estimate.marginals);
+    assert!(bp_error > 0.02);
+    assert!(refined_error < 0.01);
+    assert!(refined_error < bp_error / 2.0);
+
+    assert!(estimate.effective_sample_size > 1000.0);
+    assert!(estimate.effective_sample_size <= 20000.0);
+    assert_eq!(estimate.samples.len(), 20000);
+    // the resampled configurations follow the evidence and the corrected marginals
+    assert!(estimate.samples.iter().all(|s| s[3] == 1));
+    let a_true = estimate.samples.iter().filter(|s| s[0] == 1).count() as f32 / 20000.0;
+    assert!((a_true - estimate.marginals[0].as_probabilities()[1]).abs() < 0.02);
+}
+
+#[test]
+fn impossible_evidence() {
+    let mut net = BayesNet::new();
+    let a = net.add_node_from_probabilities(&[], Array1::from(vec![1.0, 0.0]));
+    let b = net.add_node_from_probabilities(&[a], Array2::from(vec![[1.0, 0.0], [0.0, 1.0]]));
+    net.set_evidence(&[(b, 1)]);
+    let estimate = net.importance_refinement(&mut StdRng::seed_from_u64(1), 100);
+    assert_eq!(estimate.effective_sample_size, 0.0);
+    assert!(estimate.samples.is_empty());
+    assert_eq!(
+        estimate.marginals[a].as_probabilities(),
+        net.beliefs()[a].as_probabilities()
+    );
+}
@@ -0,0 +1,104 @@
+use loopybayesnet::learning::{em, EmOptions};
+use loopybayesnet::BayesNet;
+use ndarray::{Array1, Array2, Array3};
+
+// c depends on a, and not on b
+fn net() -> BayesNet {
+    let mut net = BayesNet::new();
+    let a = net.add_node_from_probabilities(&[], Array1::from(vec![0.5, 0.5]));
+    let b = net.add_node_from_probabilities(&[], Array1::from(vec![0.5, 0.5]));
+    net.add_node_from_probabilities(
+        &[a, b],
+        Array3::from(vec![[[0.9, 0.9], [0.2, 0.2]], [[0.1, 0.1], [0.8, 0.8]]]),
+    );
+    net
+}
+
+#[test]
+fn influence_strength() {
+    let net = net();
+    let kl_ab = 0.9f32 * (0.9f32 / 0.2).ln() + 0.1 * (0.1f32 / 0.8).ln();
+    let kl_ba = 0.2f32 * (0.2f32 / 0.9).ln() + 0.8 * (0.8f32 / 0.1).ln();
+    let strong = net.influence_strength(0, 2);
+    assert!((strong.max_kl - kl_ba).abs() < 1e-5);
+    assert!((strong.mean_kl - (kl_ab + kl_ba) / 2.0).abs() < 1e-5);
+    let weak = net.influence_strength(1, 2);
+    assert!(weak.max_kl.abs() < 1e-6);
+
+    let all = net.influence_strengths();
+    assert_eq!(all.len(), 2);
+    assert_eq!((all[1].0, all[1].1), (1, 2));
+
+    let dot = net.to_dot_with_influence();
+    assert!(dot.contains("n0 -> n2 [penwidth=3.98];"));
+    assert!(dot.contains("n1 -> n2 [penwidth=1.00];"));
+    assert!(dot.ends_with("}\n"));
+}
+
+#[test]
+#[should_panic(expected = "node 1 is not a parent of node 0")]
+fn influence_needs_an_edge() {
+    net().influence_strength(1, 0);
+}
+
+#[test]
+fn simplification() {
+    use loopybayesnet::learning::Query;
+    // c depends strongly on a, and weakly on b
+    let mut net = BayesNet::new();
+    let a = net.add_node_from_probabilities(&[], Array1::from(vec![0.5, 0.5]));
+    let b = net.add_node_from_probabilities(&[], Array1::from(vec![0.5, 0.5]));
+    let c = net.add_node_from_probabilities(
+        &[a, b],
+        Array3::from(vec![[[0.9, 0.8], [0.2, 0.2]], [[0.1, 0.2], [0.8, 0.8]]]),
+    );
+    let queries = vec![
+        Query {
+            evidence: vec![(c, 0)],
+            target: b,
+        },
+        Query {
+            evidence: vec![(c, 0)],
+            target: a,
+        },
+    ];
+
+    let simplified = net.simplify(0.1, &queries, 3);
+    assert_eq!(simplified.removed_edges, vec![(b, c)]);
+    assert_eq!(simplified.net.parents(c), vec![a]);
+    assert!(simplified.net.children(b).is_empty());
+    // b is now independent of c: P(b = 0 | c = 0) goes from 0.55 / 1.05 to 0.5
+    assert!((simplified.query_shifts[0] - (0.55 / 1.05 - 0.5)).abs() < 1e-5);
+    assert!(simplified.max_shift() < 0.03);
+
+    // the table of c is the average over b
+    let mut simple = simplified.net;
+    simple.set_evidence(&[(a, 0)]);
+    for _ in 0..2 {
+        simple.step();
+    }
+    assert!((simple.beliefs()[c].as_probabilities()[0] - 0.85).abs() < 1e-5);
+}
+
+#[test]
+fn simplify_drops_softmax_weights() {
+    let mut net = BayesNet::new();
+    let a = net.add_node_from_probabilities(&[], Array1::from(vec![0.5, 0.5]));
+    let b = net.add_node_from_probabilities(&[], Array1::from(vec![0.5, 0.5]));
+    // c depends strongly on a, and barely on b
+    let weights = Array2::from(vec![
+        [0.0, 2.0, -2.0, 0.001, 0.0],
+        [0.0, -2.0, 2.0, 0.0, 0.001],
+    ]);
+    let c = net.add_softmax_node(&[a, b], weights);
+
+    let simplified = net.simplify(0.01, &[], 10);
+    assert_eq!(simplified.removed_edges, vec![(b, c)]);
+    assert!(simplified.net.softmax_weights(c).is_none());
+    // learning on the simplified network uses its table, not the stale weights
+    let data = vec![
+        vec![Some(0), Some(1), Some(0)],
+        vec![Some(1), Some(0), Some(1)],
+    ];
+    em(&simplified.net, &data, &EmOptions::default());
+}
@@ -0,0 +1,72 @@
+use loopybayesnet::{BayesNet, LogProbVector, MessageInit};
+use ndarray::{Array1, Array2};
+
+fn chain() -> BayesNet {
+    let mut net = BayesNet::new();
+    let a = net.add_node_from_probabilities(&[], Array1::from(vec![0.2, 0.8]));
+    let b = net.add_node_from_probabilities(&[a], Array2::from(vec![[0.9, 0.3], [0.1, 0.7]]));
+    net.add_node_from_probabilities(&[b], Array2::from(vec![[0.5, 0.1], [0.5, 0.9]]));
+    net
+}
+
+#[test]
+fn message_initialization() {
+    let mut net = chain();
+    net.reset_state_with(MessageInit::PriorMarginals);
+    // on a chain without evidence, the prior marginals are the exact beliefs without any step
+    let beliefs = net.beliefs();
+    assert!((beliefs[1].as_probabilities()[0] - 0.42).abs() < 1e-5);
+    assert!((beliefs[2].as_probabilities()[0] - (0.42 * 0.5 + 0.58 * 0.1)).abs() < 1e-5);
+
+    net.reset_state_with(MessageInit::Beliefs(vec![
+        LogProbVector::deterministic(2, 1),
+        LogProbVector::uniform(2),
+        LogProbVector::uniform(2),
+    ]));
+    assert!((net.beliefs()[1].as_probabilities()[0] - 0.3).abs() < 1e-5);
+
+    net.reset_state_with(MessageInit::Random {
+        seed: 1,
+        noise: 1.0,
+    });
+    let random = net.beliefs();
+    net.reset_state_with(MessageInit::Random {
+        seed: 1,
+        noise: 1.0,
+    });
+    assert_eq!(
+        random[2].log_probabilities(),
+        net.beliefs()[2].log_probabilities()
+    );
+    assert!((random[2].as_probabilities()[0] - 0.5).abs() > 1e-3);
+
+    // whatever the initialization, the propagation converges to the same result on a chain
+    net.set_evidence(&[(2, 0)]);
+    for _ in 0..5 {
+        net.step();
+    }
+    let from_random = net.beliefs();
+    net.reset_state_with(MessageInit::Uniform);
+    for _ in 0..5 {
+        net.step();
+    }
+    let from_uniform = net.beliefs();
+    for (a, b) in from_random.iter().zip(from_uniform.iter()) {
+        assert!((a.as_probabilities()[0] - b.as_probabilities()[0]).abs() < 1e-5);
+    }
+}
+
+#[test]
+fn cached_prior_marginals() {
+    let mut net = chain();
+    let priors = net.prior_marginals().to_vec();
+    assert!((priors[0].as_probabilities()[0] - 0.2).abs() < 1e-5);
+    assert!((priors[1].as_probabilities()[0] - 0.42).abs() < 1e-5);
+
+    // the cache follows the changes of the network
+    let d = net.add_node_from_probabilities(&[0], Array2::from(vec![[1.0, 0.0], [0.0, 1.0]]));
+    assert_eq!(net.prior_marginals().len(), 4);
+    assert!((net.prior_marginals()[d].as_probabilities()[0] - 0.2).abs() < 1e-5);
+    let fitted = loopybayesnet::learning::fit_parameters(&net, &[vec![0, 0, 0, 0]], 1e-3);
+    assert!((fitted.prior_marginals()[2].as_probabilities()[0] - 1.0).abs() < 1e-2);
+}
@@ -0,0 +1,43 @@
+// the test of impossible evidence is disabled with the `nan-checks` feature
+#![cfg_attr(feature = "nan-checks", allow(unused_imports))]
+
+use loopybayesnet::testing::invariants::{self, InvariantViolation};
+use loopybayesnet::BayesNet;
+use ndarray::{Array1, Array2, Array3};
+
+fn polytree() -> BayesNet {
+    // 0 -> 2 <- 1, 2 -> 3
+    let mut net = BayesNet::new();
+    let a = net.add_node_from_probabilities(&[], Array1::from(vec![0.3, 0.7]));
+    let b = net.add_node_from_probabilities(&[], Array1::from(vec![0.6, 0.4]));
+    let c = net.add_node_from_probabilities(
+        &[a, b],
+        Array3::from(vec![[[0.9, 0.5], [0.4, 0.1]], [[0.1, 0.5], [0.6, 0.9]]]),
+    );
+    net.add_node_from_probabilities(&[c], Array2::from(vec![[0.8, 0.0], [0.2, 1.0]]));
+    net
+}
+
+#[test]
+fn invariants_hold_on_polytree() {
+    let net = polytree();
+    for evidence in &[vec![], vec![(0, 1)], vec![(3, 1)], vec![(1, 0), (2, 1)]] {
+        invariants::check_all(&net, evidence, 10, 1e-4).unwrap();
+    }
+}
+
+// impossible evidence produces NaN on purpose, which panics with the `nan-checks` feature
+#[cfg(not(feature = "nan-checks"))]
+#[test]
+fn impossible_evidence_violates_normalization() {
+    let net = polytree();
+    // node 3 is always 1 when node 2 is 1
+    let error = invariants::check_all(&net, &[(2, 1), (3, 0)], 10, 1e-4).unwrap_err();
+    match error {
+        InvariantViolation::BeliefNotNormalized { node, sum } => {
+            assert_eq!(node.id, 0);
+            assert!(sum.is_nan());
+        }
+        other => panic!("unexpected violation: {}", other),
+    }
+}
@@ -0,0 +1,149 @@
+use loopybayesnet::{BayesNet, BifError, NodeLayout};
+use ndarray::{Array1, Array3};
+
+const JSON: &str = r#"{
+  "version": 1,
+  "nodes": [
+    {
+      "name": "grass",
+      "states": ["dry", "wet"],
+      "parents": ["rain", "sprinkler"],
+      "probabilities": [
+        [[1.0, 0.0], [0.1, 0.9]],
+        [[0.2, 0.8], [0.01, 0.99]]
+      ],
+      "tags": {"unit": "none", "label": "Is the grass \"wet\"?"}
+    },
+    {
+      "name": "rain",
+      "states": ["no", "yes"],
+      "probabilities": [8, 2],
+      "layout": {"x": 10, "y": -2.5, "color": "blue"},
+      "comment": "ignored"
+    },
+    {
+      "name": "sprinkler",
+      "states": ["off", "on"],
+      "parents": [],
+      "probabilities": [0.6, 0.4],
+      "layout": {"x": 1e2, "y": 0}
+    }
+  ]
+}"#;
+
+#[test]
+fn read_json() {
+    let mut net = BayesNet::from_json(JSON).unwrap();
+    let rain = net.find_node("rain").unwrap();
+    let sprinkler = net.find_node("sprinkler").unwrap();
+    let grass = net.find_node("grass").unwrap();
+    assert_eq!(net.parents(grass), vec![rain, sprinkler]);
+    assert_eq!(net.state_names(sprinkler).unwrap(), ["off", "on"]);
+    assert_eq!(net.tag(grass, "label"), Some("Is the grass \"wet\"?"));
+    assert_eq!(
+        net.layout(rain),
+        Some(&NodeLayout {
+            x: 10.0,
+            y: -2.5,
+            color: Some("blue".to_owned())
+        })
+    );
+    assert_eq!(net.layout(sprinkler).unwrap().x, 100.0);
+    assert!((net.exact_beliefs()[rain].as_probabilities()[1] - 0.2).abs() < 1e-6);
+
+    // P(wet | rain, sprinkler off) = 0.8
+    net.set_evidence(&[(rain, 1), (sprinkler, 0)]);
+    net.step();
+    net.step();
+    assert!((net.beliefs()[grass].as_probabilities()[1] - 0.8).abs() < 1e-5);
+}
+
+#[test]
+fn json_round_trip() {
+    let mut net = BayesNet::new();
+    let a = net.add_node_from_probabilities(&[], Array1::from(vec![0.25, 0.75]));
+    let b = net.add_node_from_probabilities(&[], Array1::from(vec![0.25, 0.5, 0.25]));
+    net.add_node_from_probabilities(
+        &[a, b],
+        Array3::from(vec![
+            [[0.5, 0.5, 0.25], [0.375, 0.125, 0.5]],
+            [[0.5, 0.5, 0.75], [0.625, 0.875, 0.5]],
+        ]),
+    );
+    net.set_node_name(a, "weather/rain");
+    net.set_state_names(b, &["low", "mid", "high"]);
+    net.set_tag(b, "note", "line\nbreak");
+    net.set_layout(
+        a,
+        Some(NodeLayout {
+            x: 1.5,
+            y: 2.0,
+            color: None,
+        }),
+    );
+
+    let text = net.to_json();
+    assert!(text.contains(
+        "      \"name\": \"node2\",\n      \"states\": [\"s0\", \"s1\"],\n      \"parents\": [\"weather/rain\", \"node1\"],\n"
+    ));
+    assert!(text.contains("\"layout\": {\"x\": 1.5, \"y\": 2}"));
+    assert!(text.contains("\"tags\": {\"note\": \"line\\nbreak\"}"));
+
+    let copy = BayesNet::from_json(&text).unwrap();
+    assert_eq!(copy.to_json().lines().count(), text.lines().count());
+    assert_eq!(copy.tag(1, "note"), Some("line\nbreak"));
+    for (original, copied) in net.exact_beliefs().iter().zip(copy.exact_beliefs()) {
+        for (x, y) in original
+            .as_probabilities()
+            .iter()
+            .zip(copied.as_probabilities().iter())
+        {
+            assert!((x - y).abs() < 1e-6);
+        }
+    }
+    assert_eq!(
+        BayesNet::new().to_json(),
+        "{\n  \"version\": 1,\n  \"nodes\": []\n}\n"
+    );
+}
+
+#[test]
+fn json_errors() {
+    assert_eq!(
+        BayesNet::from_json(&JSON.replace("[8, 2]", "[8, 2, 1]")).unwrap_err(),
+        BifError::TableSize {
+            line: 17,
+            variable: "rain".to_owned(),
+            expected: 2,
+            found: 3
+        }
+    );
+    assert_eq!(
+        BayesNet::from_json(&JSON.replace("\"sprinkler\"]", "\"wind\"]")).unwrap_err(),
+        BifError::UnknownVariable {
+            line: 7,
+            name: "wind".to_owned()
+        }
+    );
+    assert_eq!(
+        BayesNet::from_json(&JSON.replace("\"states\": [\"no\", \"yes\"],", "")).unwrap_err(),
+        BifError::Syntax {
+            line: 14,
+            message: "node \"rain\" has no \"states\"".to_owned()
+        }
+    );
+    assert_eq!(
+        BayesNet::from_json(&JSON.replace("[0.6, 0.4],", "[0.6, 0.4]")).unwrap_err(),
+        BifError::Syntax {
+            line: 26,
+            message: "expected ','".to_owned()
+        }
+    );
+    assert_eq!(
+        BayesNet::from_json(&JSON.replace("\"version\": 1", "\"version\": 2")).unwrap_err(),
+        BifError::Syntax {
+            line: 2,
+            message: "unsupported version".to_owned()
+        }
+    );
+}
@@ -0,0 +1,81 @@
+use loopybayesnet::{BayesNet, JunctionTree, LogProbVector};
+use ndarray::{Array1, Array2, Array3};
+
+fn assert_close(a: &[LogProbVector], b: &[LogProbVector]) {
+    assert_eq!(a.len(), b.len());
+    for (a, b) in a.iter().zip(b.iter()) {
+        let diff = a.as_probabilities() - b.as_probabilities();
+        assert!(diff.iter().all(|d| d.abs() < 1e-5), "{:?} != {:?}", a, b);
+    }
+}
+
+// the `multi_valued` network of the trivial cases, on which loopy propagation is wrong
+fn multi_valued() -> BayesNet {
+    let mut net = BayesNet::new();
+    let node1 = net.add_node_from_probabilities(&[], Array1::from(vec![0.5, 0.4, 0.1]));
+    let node2 = net.add_node_from_probabilities(
+        &[node1],
+        Array2::from(vec![[0.8, 0.2, 1.0], [0.2, 0.8, 0.0]]),
+    );
+    net.add_node_from_probabilities(
+        &[node1, node2],
+        Array3::from(vec![
+            [[0.0, 0.0], [1.0, 0.0], [0.0, 0.0]],
+            [[1.0, 0.0], [0.0, 1.0], [0.0, 0.0]],
+            [[0.0, 1.0], [0.0, 0.0], [0.0, 0.0]],
+            [[0.0, 0.0], [0.0, 0.0], [1.0, 1.0]],
+        ]),
+    );
+    net
+}
+
+#[test]
+fn exact_on_multi_valued_loop() {
+    let net = multi_valued();
+    let tree = JunctionTree::new(&net);
+    assert_eq!(tree.cliques(), &[vec![0, 1, 2]]);
+    assert_eq!(tree.width(), 2);
+    assert_eq!(tree.max_clique_states(), 24);
+    let beliefs = tree.beliefs(&net);
+    let expected = [0.08, 0.72, 0.1, 0.1];
+    for (p, e) in beliefs[2].as_probabilities().iter().zip(expected.iter()) {
+        assert!((p - e).abs() < 1e-5);
+    }
+}
+
+#[test]
+fn junction_tree_matches_variable_elimination() {
+    // two loops sharing node 3, and a separate component
+    let mut net = BayesNet::new();
+    let a = net.add_node_from_probabilities(&[], Array1::from(vec![0.3, 0.7]));
+    let b = net.add_node_from_probabilities(&[a], Array2::from(vec![[0.9, 0.2], [0.1, 0.8]]));
+    let c = net
+        .add_node_from_probabilities(&[a], Array2::from(vec![[0.6, 0.3], [0.3, 0.3], [0.1, 0.4]]));
+    let d = net.add_node_from_probabilities(
+        &[b, c],
+        Array3::from(vec![
+            [[0.99, 0.5, 0.2], [0.4, 0.1, 0.3]],
+            [[0.01, 0.5, 0.8], [0.6, 0.9, 0.7]],
+        ]),
+    );
+    let e = net.add_node_from_probabilities(&[d], Array2::from(vec![[0.7, 0.1], [0.3, 0.9]]));
+    let f = net.add_node_from_probabilities(&[d], Array2::from(vec![[0.5, 0.2], [0.5, 0.8]]));
+    net.add_node_from_probabilities(
+        &[e, f],
+        Array3::from(vec![[[0.9, 0.4], [0.3, 0.2]], [[0.1, 0.6], [0.7, 0.8]]]),
+    );
+    net.add_node_from_probabilities(&[], Array1::from(vec![0.25, 0.75]));
+
+    let tree = JunctionTree::new(&net);
+    assert_eq!(tree.edges().len(), tree.cliques().len() - 2);
+    for evidence in &[
+        vec![],
+        vec![(6, 1)],
+        vec![(6, 0), (2, 2)],
+        vec![(1, 0), (7, 1)],
+    ] {
+        net.set_evidence(evidence);
+        assert_close(&tree.beliefs(&net), &net.exact_beliefs());
+        assert_close(&net.junction_tree_beliefs(), &net.exact_beliefs());
+    }
+}
@@ -0,0 +1,51 @@
+use loopybayesnet::{BayesNet, NodeLayout};
+use ndarray::{Array1, Array2};
+
+fn net() -> BayesNet {
+    let mut net = BayesNet::new();
+    let rain = net.add_node_from_probabilities(&[], Array1::from(vec![0.8, 0.2]));
+    net.add_node_from_probabilities(&[rain], Array2::from(vec![[0.9, 0.2], [0.1, 0.8]]));
+    net.set_node_name(rain, "rain \"heavy\"");
+    net
+}
+
+#[test]
+fn dot_layout_round_trip() {
+    let mut original = net();
+    original.set_layout(
+        0,
+        Some(NodeLayout {
+            x: 10.0,
+            y: -2.5,
+            color: Some("#ff0000".to_owned()),
+        }),
+    );
+    original.set_layout(
+        1,
+        Some(NodeLayout {
+            x: 10.0,
+            y: 50.0,
+            color: None,
+        }),
+    );
+    let dot = original.to_dot();
+    assert!(dot.contains("n0 [label=\"rain \\\"heavy\\\"\", pos=\"10,-2.5!\", color=\"#ff0000\"];"));
+    assert!(dot.contains("n0 -> n1;"));
+
+    let mut copy = net();
+    assert_eq!(copy.import_dot_layout(&dot), vec![0, 1]);
+    assert_eq!(copy.layout(0), original.layout(0));
+    assert_eq!(copy.layout(1), original.layout(1));
+
+    // as rewritten by a drawing tool
+    let edited = "digraph {\n  graph [bb=\"0,0,100,100\"];\n  n1 [height=0.5, pos=\"27,18\", color=blue, width=0.75];\n  n0 -> n1 [pos=\"e,27,36 27,71\"];\n}\n";
+    assert_eq!(copy.import_dot_layout(edited), vec![1]);
+    assert_eq!(
+        copy.layout(1),
+        Some(&NodeLayout {
+            x: 27.0,
+            y: 18.0,
+            color: Some("blue".to_owned()),
+        })
+    );
+}
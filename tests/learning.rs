@@ -0,0 +1,445 @@
+use loopybayesnet::learning::{self, Query};
+use loopybayesnet::BayesNet;
+use ndarray::{Array1, Array2};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+fn structure() -> BayesNet {
+    let mut net = BayesNet::new();
+    let a = net.add_node_from_probabilities(&[], Array1::from(vec![0.5, 0.5]));
+    net.add_node_from_probabilities(&[a], Array2::from(vec![[0.5, 0.5], [0.5, 0.5]]));
+    net
+}
+
+fn dataset(n: usize) -> Vec<Vec<usize>> {
+    // a is 1 a quarter of the time, b copies a 80% of the time
+    (0..n)
+        .map(|i| {
+            let a = if i % 4 == 0 { 1 } else { 0 };
+            let b = if i % 5 == 0 { 1 - a } else { a };
+            vec![a, b]
+        })
+        .collect()
+}
+
+#[test]
+fn fit_by_counting() {
+    let net = learning::fit_parameters(&structure(), &dataset(400), 0.0);
+    let mut net = net;
+    net.reset_state();
+    for _ in 0..3 {
+        net.step();
+    }
+    let beliefs = net.beliefs();
+    assert!((beliefs[0].as_probabilities()[1] - 0.25).abs() < 1e-4);
+    net.set_evidence(&[(0, 1)]);
+    for _ in 0..3 {
+        net.step();
+    }
+    assert!((net.beliefs()[1].as_probabilities()[1] - 0.8).abs() < 1e-4);
+}
+
+#[test]
+fn fit_from_structure() {
+    // a -> b as in `dataset`, and c copies b
+    let data: Vec<Vec<usize>> = dataset(400)
+        .into_iter()
+        .map(|mut record| {
+            record.push(2 * record[1]);
+            record
+        })
+        .collect();
+    let mut net = learning::fit_structure(&[vec![], vec![0], vec![1]], &[2, 2, 3], &data, 0.0);
+    assert_eq!(net.num_nodes(), 3);
+    assert_eq!(net.parents(2), vec![1]);
+    assert_eq!(net.num_values(2), 3);
+    net.set_evidence(&[(0, 1)]);
+    for _ in 0..3 {
+        net.step();
+    }
+    let beliefs = net.beliefs();
+    assert!((beliefs[1].as_probabilities()[1] - 0.8).abs() < 1e-4);
+    let c = beliefs[2].as_probabilities();
+    assert!((c[2] - 0.8).abs() < 1e-4);
+    assert_eq!(c[1], 0.0);
+}
+
+#[test]
+#[should_panic]
+fn fit_from_unordered_structure() {
+    learning::fit_structure(&[vec![1], vec![]], &[2, 2], &dataset(10), 1.0);
+}
+
+#[test]
+fn fit_unseen_configurations_uniformly() {
+    // a and b are independent roots, but are never both 1 in the data
+    let data = vec![vec![0, 0, 0], vec![0, 1, 1], vec![1, 0, 1]];
+    let mut net = learning::fit_structure(&[vec![], vec![], vec![0, 1]], &[2, 2, 2], &data, 0.0);
+    net.set_evidence(&[(0, 1), (1, 1)]);
+    for _ in 0..3 {
+        net.step();
+    }
+    let c = net.beliefs()[2].as_probabilities();
+    assert!((c[0] - 0.5).abs() < 1e-5);
+}
+
+#[test]
+fn bootstrap_shrinks_with_data() {
+    let mut rng = StdRng::seed_from_u64(3);
+    let queries = [Query {
+        evidence: vec![(1, 1)],
+        target: 0,
+    }];
+    let small = learning::bootstrap(&mut rng, &structure(), &dataset(40), 200, 1.0, &queries, 3);
+    let large = learning::bootstrap(
+        &mut rng,
+        &structure(),
+        &dataset(4000),
+        200,
+        1.0,
+        &queries,
+        3,
+    );
+    assert_eq!(small[0].samples.len(), 200);
+    let (lo, hi) = large[0].interval(0.95)[1];
+    assert!(lo < large[0].mean()[1] && large[0].mean()[1] < hi);
+    assert!(small[0].std_dev()[1] > 2.0 * large[0].std_dev()[1]);
+}
+
+#[test]
+fn edge_confidence() {
+    let mut rng = StdRng::seed_from_u64(7);
+    let data = dataset(100);
+    // a dummy learner which always finds the edge 0 -> 1
+    let edges = learning::bootstrap_structure(&mut rng, &data, 10, |_| vec![vec![], vec![0]]);
+    assert_eq!(edges.len(), 1);
+    assert_eq!((edges[0].from, edges[0].to), (0, 1));
+    assert_eq!(edges[0].directed, 1.0);
+    assert_eq!(edges[0].adjacent, 1.0);
+    // a single edge can be reversed without changing the equivalence class
+    assert_eq!(edges[0].compelled, 0.0);
+}
+
+#[test]
+fn structure_constraints() {
+    use learning::{ConstraintViolation, StructureConstraints};
+
+    let mut constraints = StructureConstraints::new();
+    constraints.set_tiers(&[&[0], &[1, 2]]);
+    constraints.require_edge(0, 1);
+    constraints.forbid_edge(1, 2);
+    assert_eq!(constraints.validate(3), Ok(()));
+
+    assert!(!constraints.allows_edge(1, 0));
+    assert!(!constraints.allows_edge(1, 2));
+    assert!(constraints.allows_edge(2, 1));
+    assert_eq!(
+        constraints.initial_structure(3),
+        vec![vec![], vec![0], vec![]]
+    );
+
+    assert_eq!(
+        constraints.check(&[vec![], vec![0], vec![1]]),
+        Err(ConstraintViolation::ForbiddenEdge(1, 2))
+    );
+    assert_eq!(
+        constraints.check(&[vec![2], vec![0], vec![]]),
+        Err(ConstraintViolation::TierOrder(2, 0))
+    );
+    assert_eq!(
+        constraints.check(&[vec![], vec![], vec![0]]),
+        Err(ConstraintViolation::MissingRequiredEdge(0, 1))
+    );
+
+    assert_eq!(
+        constraints.validate(2),
+        Err(ConstraintViolation::UnknownNode(2))
+    );
+    constraints.require_edge(2, 0);
+    assert_eq!(
+        constraints.validate(3),
+        Err(ConstraintViolation::TierOrder(2, 0))
+    );
+}
+
+#[test]
+fn cyclic_required_edges() {
+    use learning::{ConstraintViolation, HillClimbOptions, StructureConstraints};
+
+    let mut constraints = StructureConstraints::new();
+    constraints.require_edge(0, 1);
+    constraints.require_edge(1, 2);
+    constraints.require_edge(2, 0);
+    assert_eq!(
+        constraints.validate(3),
+        Err(ConstraintViolation::RequiredCycle(0, 1))
+    );
+    let options = HillClimbOptions {
+        constraints,
+        ..HillClimbOptions::default()
+    };
+    let data = vec![vec![0, 1, 0], vec![1, 0, 1]];
+    assert_eq!(
+        learning::hill_climb(&data, &[2, 2, 2], &options).err(),
+        Some(ConstraintViolation::RequiredCycle(0, 1))
+    );
+
+    let mut constraints = StructureConstraints::new();
+    constraints.forbid_edge(0, 5);
+    let options = HillClimbOptions {
+        constraints,
+        ..HillClimbOptions::default()
+    };
+    assert_eq!(
+        learning::hill_climb(&data, &[2, 2, 2], &options).err(),
+        Some(ConstraintViolation::UnknownNode(5))
+    );
+}
+
+#[test]
+fn tree_log_likelihood() {
+    let mut net = BayesNet::new();
+    let a = net.add_node_from_probabilities(&[], Array1::from(vec![0.3, 0.7]));
+    net.add_node_from_probabilities(&[a], Array2::from(vec![[0.9, 0.2], [0.1, 0.8]]));
+    let data = vec![
+        vec![Some(0), Some(0)],
+        vec![None, Some(1)],
+        vec![None, None],
+    ];
+    let expected = (0.3f32 * 0.9).ln() + (0.3f32 * 0.1 + 0.7 * 0.8).ln();
+    assert!((learning::log_likelihood(&net, &data, 5) - expected).abs() < 1e-4);
+}
+
+#[test]
+fn em_with_missing_values() {
+    let data: Vec<Vec<Option<usize>>> = dataset(400)
+        .into_iter()
+        .enumerate()
+        .map(|(i, record)| {
+            let a = if i % 7 == 3 { None } else { Some(record[0]) };
+            let b = if i % 3 == 1 { None } else { Some(record[1]) };
+            vec![a, b]
+        })
+        .collect();
+    let options = learning::EmOptions {
+        pseudo_count: 0.0,
+        tolerance: 1e-6,
+        ..Default::default()
+    };
+    let result = learning::em(&structure(), &data, &options);
+    assert!(result.converged);
+    assert_eq!(result.log_likelihoods.len(), result.iterations + 1);
+    assert_eq!(
+        *result.log_likelihoods.last().unwrap(),
+        result.log_likelihood
+    );
+    assert!(result
+        .log_likelihoods
+        .windows(2)
+        .all(|w| w[1] >= w[0] - 1e-3));
+
+    let mut net = result.net;
+    for _ in 0..3 {
+        net.step();
+    }
+    assert!((net.beliefs()[0].as_probabilities()[1] - 0.25).abs() < 0.02);
+    net.set_evidence(&[(0, 1)]);
+    for _ in 0..3 {
+        net.step();
+    }
+    assert!((net.beliefs()[1].as_probabilities()[1] - 0.8).abs() < 0.05);
+
+    // stopping before convergence, the log-likelihood is still the one of the returned network
+    let options = learning::EmOptions {
+        max_iterations: 1,
+        ..options
+    };
+    let result = learning::em(&structure(), &data, &options);
+    assert!(!result.converged);
+    assert_eq!(result.log_likelihoods.len(), 2);
+    let expected = learning::log_likelihood(&result.net, &data, options.bp_iterations);
+    assert!((result.log_likelihood - expected).abs() < 1e-3);
+    assert!(result.log_likelihoods[1] > result.log_likelihoods[0]);
+}
+
+#[test]
+fn latent_class_em() {
+    use rand::Rng;
+
+    let mut rng = StdRng::seed_from_u64(11);
+    // a latent class with 2 states, and 3 noisy binary indicators of it
+    let sample = |rng: &mut StdRng| {
+        let z = rng.gen_bool(0.5);
+        (0..3)
+            .map(|_| {
+                Some(if rng.gen_bool(if z { 0.9 } else { 0.1 }) {
+                    1
+                } else {
+                    0
+                })
+            })
+            .collect::<Vec<_>>()
+    };
+    let mut records: Vec<Vec<Option<usize>>> = (0..600)
+        .map(|_| {
+            let mut r = vec![None];
+            r.extend(sample(&mut rng));
+            r
+        })
+        .collect();
+    let held_out = records.split_off(400);
+
+    let mut net = BayesNet::new();
+    let z = net.add_node_from_probabilities(&[], Array1::from(vec![0.5, 0.5]));
+    for _ in 0..3 {
+        net.add_node_from_probabilities(&[z], Array2::from(vec![[0.5, 0.5], [0.5, 0.5]]));
+    }
+    net.set_latent(z, true);
+
+    let options = learning::EmOptions::default();
+    let result = learning::em_with_restarts(&mut rng, &net, &records, &held_out, 3, &options);
+    assert!(result.held_out_log_likelihood.is_some());
+
+    // the latent node must have been recovered up to a permutation of its states
+    let mut fitted = result.net;
+    for state in 0..2 {
+        fitted.reset_state();
+        fitted.set_evidence(&[(z, state)]);
+        for _ in 0..3 {
+            fitted.step();
+        }
+        let p = fitted.beliefs()[1].as_probabilities()[1];
+        assert!((p - 0.1).abs() < 0.08 || (p - 0.9).abs() < 0.08, "{}", p);
+    }
+    let uniform_ll = learning::log_likelihood(&net, &held_out, 3);
+    assert!(result.held_out_log_likelihood.unwrap() > uniform_ll);
+}
+
+#[test]
+fn mixture_model() {
+    // two well separated clusters over two ternary variables
+    let data: Vec<Vec<Option<usize>>> = (0..200)
+        .map(|i| match i % 4 {
+            0 => vec![Some(0), Some(0)],
+            1 => vec![Some(0), None],
+            2 => vec![Some(2), Some(2)],
+            _ => vec![Some(2), Some(1)],
+        })
+        .collect();
+    let model = learning::MixtureModel::fit(&data, 2);
+    assert_eq!(model.k(), 2);
+    let post = model.class_posteriors();
+    let class_of = |p: &ndarray::Array1<f32>| if p[0] > 0.5 { 0 } else { 1 };
+    assert!(post[0].iter().any(|&p| p > 0.95));
+    assert_eq!(class_of(&post[0]), class_of(&post[1]));
+    assert_eq!(class_of(&post[2]), class_of(&post[3]));
+    assert_ne!(class_of(&post[0]), class_of(&post[2]));
+    assert_eq!(
+        class_of(&model.predict(&[None, Some(2)])),
+        class_of(&post[2])
+    );
+}
+
+#[test]
+fn structure_scores() {
+    use learning::ScoreCriterion::*;
+
+    let data = dataset(400);
+    let mut independent = BayesNet::new();
+    independent.add_node_from_probabilities(&[], Array1::from(vec![0.5, 0.5]));
+    independent.add_node_from_probabilities(&[], Array1::from(vec![0.5, 0.5]));
+    let independent = learning::fit_parameters(&independent, &data, 0.0);
+    let dependent = learning::fit_parameters(&structure(), &data, 0.0);
+
+    // uniform parameters: each record has probability 1/4
+    let ll = structure().score(&data, LogLikelihood);
+    assert!((ll - 400.0 * 0.25f32.ln()).abs() < 1e-2);
+    assert!((structure().score(&data, Aic) - (ll - 3.0)).abs() < 1e-2);
+
+    for &criterion in &[
+        LogLikelihood,
+        Aic,
+        Bic,
+        Bdeu {
+            equivalent_sample_size: 1.0,
+        },
+    ] {
+        assert!(
+            dependent.score(&data, criterion) > independent.score(&data, criterion),
+            "{:?}",
+            criterion
+        );
+    }
+
+    // on a single binary node, BDeu with ess 2 is the Beta(1, 1) marginal likelihood
+    let mut single = BayesNet::new();
+    single.add_node_from_probabilities(&[], Array1::from(vec![0.5, 0.5]));
+    let data = vec![vec![0], vec![0], vec![1]];
+    let bdeu = single.score(
+        &data,
+        Bdeu {
+            equivalent_sample_size: 2.0,
+        },
+    );
+    // integral of p^2 (1 - p) = 1/12
+    assert!((bdeu - (1.0f32 / 12.0).ln()).abs() < 1e-5);
+}
+
+#[test]
+fn hill_climbing_search() {
+    use learning::{HillClimbOptions, ScoreCriterion, StructureConstraints};
+
+    // a -> b -> c, and an independent d
+    let mut truth = BayesNet::new();
+    let a = truth.add_node_from_probabilities(&[], Array1::from(vec![0.6, 0.4]));
+    let b = truth.add_node_from_probabilities(&[a], Array2::from(vec![[0.9, 0.2], [0.1, 0.8]]));
+    truth.add_node_from_probabilities(&[b], Array2::from(vec![[0.85, 0.3], [0.15, 0.7]]));
+    truth.add_node_from_probabilities(&[], Array1::from(vec![0.5, 0.5]));
+    let mut rng = StdRng::seed_from_u64(5);
+    let data = truth.sample_forward(&mut rng, 2000);
+
+    let learned = learning::hill_climb(&data, &[2, 2, 2, 2], &HillClimbOptions::default()).unwrap();
+    let mut edges: Vec<(usize, usize)> = learned
+        .parents
+        .iter()
+        .enumerate()
+        .flat_map(|(to, parents)| {
+            parents
+                .iter()
+                .map(move |&from| (from.min(to), from.max(to)))
+        })
+        .collect();
+    edges.sort_unstable();
+    assert_eq!(edges, vec![(0, 1), (1, 2)]);
+    assert!(learned.iterations >= 2);
+
+    // the tiers orient the chain, and the score is the one of the fitted network
+    let mut constraints = StructureConstraints::new();
+    constraints.set_tiers(&[&[0], &[1], &[2]]);
+    let options = HillClimbOptions {
+        constraints,
+        pseudo_count: 0.0,
+        ..Default::default()
+    };
+    let learned = learning::hill_climb(&data, &[2, 2, 2, 2], &options).unwrap();
+    assert_eq!(learned.parents, vec![vec![], vec![0], vec![1], vec![]]);
+    assert_eq!(learned.order, vec![0, 1, 2, 3]);
+    let score = learned.net.score(&data, ScoreCriterion::Bic);
+    assert!((score - learned.score).abs() < 1e-2 * score.abs().max(1.0));
+}
+
+#[test]
+fn hill_climbing_reorders_variables() {
+    // the second variable causes the first one
+    let data: Vec<Vec<usize>> = dataset(400).into_iter().map(|r| vec![r[1], r[0]]).collect();
+    let mut constraints = learning::StructureConstraints::new();
+    constraints.require_edge(1, 0);
+    let options = learning::HillClimbOptions {
+        constraints,
+        ..Default::default()
+    };
+    let learned = learning::hill_climb(&data, &[2, 2], &options).unwrap();
+    assert_eq!(learned.parents, vec![vec![1], vec![]]);
+    assert_eq!(learned.order, vec![1, 0]);
+    assert_eq!(learned.net.parents(1), vec![0]);
+}
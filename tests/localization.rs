@@ -0,0 +1,69 @@
+use loopybayesnet::{BayesNet, LogProbVector};
+use ndarray::{Array1, Array2};
+
+fn rain_network() -> BayesNet {
+    let mut net = BayesNet::new();
+    let rain = net.add_node_from_probabilities(&[], Array1::from(vec![0.7, 0.3]));
+    let wet = net.add_node_from_probabilities(&[rain], Array2::from(vec![[0.9, 0.2], [0.1, 0.8]]));
+    net.set_node_name(rain, "rain");
+    net.set_state_names(rain, &["no", "yes"]);
+    net.set_node_name(wet, "wet");
+    net.set_localized_name(rain, "fr", "Pluie");
+    net.set_localized_state_names(rain, "fr", &["non", "oui"]);
+    net.set_localized_name(rain, "fr-CA", "Averse");
+    net.set_localized_name(wet, "de", "Nass");
+    net
+}
+
+#[test]
+fn localized_names_fall_back() {
+    let net = rain_network();
+    assert_eq!(net.localized_name(0, "fr"), Some("Pluie"));
+    // a regional locale uses its own names, then the ones of its language
+    assert_eq!(net.localized_name(0, "fr-CA"), Some("Averse"));
+    assert_eq!(net.localized_name(0, "fr_BE"), Some("Pluie"));
+    assert_eq!(
+        net.localized_state_names(0, "fr-CA"),
+        Some(&["non".to_owned(), "oui".to_owned()][..])
+    );
+    assert_eq!(net.localized_name(0, "de"), None);
+    assert_eq!(net.locales(), vec!["de", "fr", "fr-CA"]);
+
+    assert_eq!(net.display_name(0, "de"), "rain");
+    assert_eq!(net.display_name(1, "de"), "Nass");
+    assert_eq!(net.display_state(0, 1, "fr-CA"), "oui");
+    assert_eq!(net.display_state(0, 1, "en"), "yes");
+    assert_eq!(net.display_state(1, 1, "fr"), "1");
+
+    let beliefs = vec![
+        LogProbVector::from_log_probabilities(Array1::from(vec![0.7f32.ln(), 0.3f32.ln()])),
+        LogProbVector::uniform(2),
+    ];
+    assert_eq!(
+        net.belief_report(&beliefs, "fr"),
+        "Pluie: non 0.700, oui 0.300\nwet: 0 0.500, 1 0.500\n"
+    );
+}
+
+#[test]
+#[should_panic]
+fn localized_state_names_count() {
+    let mut net = rain_network();
+    net.set_localized_state_names(1, "fr", &["sec"]);
+}
+
+#[test]
+fn localized_names_in_json() {
+    let net = rain_network();
+    let json = net.to_json();
+    let parsed = BayesNet::from_json(&json).unwrap();
+    assert_eq!(parsed.locales(), net.locales());
+    assert_eq!(parsed.localized_name(0, "fr-CA"), Some("Averse"));
+    assert_eq!(parsed.display_state(0, 0, "fr"), "non");
+    assert_eq!(parsed.display_name(1, "de"), "Nass");
+    assert_eq!(parsed.to_json(), json);
+
+    let bad = r#"{"nodes": [{"name": "a", "states": ["x", "y"], "probabilities": [0.5, 0.5],
+        "labels": {"fr": {"states": ["x"]}}}]}"#;
+    assert!(BayesNet::from_json(bad).is_err());
+}
@@ -0,0 +1,78 @@
+use loopybayesnet::BayesNet;
+use ndarray::{Array1, Array2, Array3};
+
+// the `multi_valued` network of the trivial cases, with an extra observed child outside of the loop
+fn net() -> BayesNet {
+    let mut net = BayesNet::new();
+    let node1 = net.add_node_from_probabilities(&[], Array1::from(vec![0.5, 0.4, 0.1]));
+    let node2 = net.add_node_from_probabilities(
+        &[node1],
+        Array2::from(vec![[0.8, 0.2, 1.0], [0.2, 0.8, 0.0]]),
+    );
+    net.add_node_from_probabilities(
+        &[node1, node2],
+        Array3::from(vec![
+            [[0.0, 0.0], [1.0, 0.0], [0.0, 0.0]],
+            [[1.0, 0.0], [0.0, 1.0], [0.0, 0.0]],
+            [[0.0, 1.0], [0.0, 0.0], [0.0, 0.0]],
+            [[0.0, 0.0], [0.0, 0.0], [1.0, 1.0]],
+        ]),
+    );
+    net.add_node_from_probabilities(&[node2], Array2::from(vec![[0.3, 0.9], [0.7, 0.1]]));
+    net
+}
+
+fn assert_close(a: &Array1<f32>, b: &[f32]) {
+    assert!(
+        a.iter().zip(b.iter()).all(|(x, y)| (x - y).abs() < 1e-4),
+        "{:?} != {:?}",
+        a,
+        b
+    );
+}
+
+#[test]
+fn corrects_multi_valued_loop() {
+    let mut net = net();
+    for _ in 0..10 {
+        net.step();
+    }
+    // plain loopy belief propagation is wrong on this network
+    let beliefs = net.beliefs();
+    assert_close(&beliefs[2].as_probabilities(), &[0.232, 0.458, 0.21, 0.1]);
+    // the loop correction recovers the exact marginals
+    let corrected = net.loop_corrected_beliefs(100);
+    assert_close(&corrected[2].as_probabilities(), &[0.08, 0.72, 0.1, 0.1]);
+    assert_close(&corrected[0].as_probabilities(), &[0.5, 0.4, 0.1]);
+    // clusters too large are left as is
+    let uncorrected = net.loop_corrected_beliefs(10);
+    assert_close(
+        &uncorrected[2].as_probabilities(),
+        &[0.232, 0.458, 0.21, 0.1],
+    );
+
+    // with evidence outside of the loop
+    net.reset_state();
+    net.set_evidence(&[(3, 0)]);
+    for _ in 0..10 {
+        net.step();
+    }
+    // P(node2 = 1 | node4 = 0) = 0.42 * 0.9 / (0.58 * 0.3 + 0.42 * 0.9)
+    let p = 0.42 * 0.9 / (0.58 * 0.3 + 0.42 * 0.9);
+    // P(node1, node2 | evidence) is proportional to P(node1) P(node2 | node1) P(evidence | node2)
+    let joint = [
+        [0.5 * 0.8 * 0.3, 0.5 * 0.2 * 0.9],
+        [0.4 * 0.2 * 0.3, 0.4 * 0.8 * 0.9],
+        [0.1 * 1.0 * 0.3, 0.0],
+    ];
+    let z: f32 = joint.iter().flat_map(|r| r.iter()).sum();
+    let expected = [
+        joint[1][0] / z,
+        (joint[0][0] + joint[1][1]) / z,
+        joint[0][1] / z,
+        joint[2][0] / z,
+    ];
+    let corrected = net.loop_corrected_beliefs(100);
+    assert_close(&corrected[1].as_probabilities(), &[1.0 - p, p]);
+    assert_close(&corrected[2].as_probabilities(), &expected);
+}
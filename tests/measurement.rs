@@ -0,0 +1,107 @@
+use loopybayesnet::{BayesNet, Measurement};
+use ndarray::{Array1, Array2};
+
+fn posterior(net: &mut BayesNet, node: usize) -> Array1<f32> {
+    net.reset_state();
+    for _ in 0..4 {
+        net.step();
+    }
+    net.beliefs()[node].as_probabilities()
+}
+
+#[test]
+fn gaussian_measurement() {
+    let mut net = BayesNet::new();
+    let state = net.add_node_from_probabilities(&[], Array1::from(vec![0.5, 0.5]));
+    let sensor = net.add_measurement_node(
+        state,
+        Measurement::Gaussian {
+            means: vec![0.0, 2.0],
+            std_devs: vec![1.0, 1.0],
+        },
+    );
+    assert_eq!(net.num_values(sensor), 1);
+    assert!((posterior(&mut net, state)[0] - 0.5).abs() < 1e-6);
+
+    net.observe_measurement(sensor, 1.5);
+    // likelihood ratio exp(-0.5 * 1.5^2) / exp(-0.5 * 0.5^2)
+    let ratio = (-1.0f32).exp();
+    let expected = ratio / (1.0 + ratio);
+    assert!((posterior(&mut net, state)[0] - expected).abs() < 1e-5);
+
+    net.clear_measurement(sensor);
+    assert!((posterior(&mut net, state)[0] - 0.5).abs() < 1e-6);
+}
+
+#[test]
+fn poisson_measurements_through_a_chain() {
+    let mut net = BayesNet::new();
+    let cause = net.add_node_from_probabilities(&[], Array1::from(vec![0.8, 0.2]));
+    let rate =
+        net.add_node_from_probabilities(&[cause], Array2::from(vec![[0.9, 0.1], [0.1, 0.9]]));
+    let counter = net.add_measurement_node(
+        rate,
+        Measurement::Poisson {
+            rates: vec![1.0, 4.0],
+        },
+    );
+    net.observe_measurement(counter, 3.0);
+    // P(3 | rate) = rate^3 exp(-rate) / 6
+    let l = [(-1.0f32).exp() / 6.0, 64.0 * (-4.0f32).exp() / 6.0];
+    let likelihood_cause = [0.9 * l[0] + 0.1 * l[1], 0.1 * l[0] + 0.9 * l[1]];
+    let expected =
+        0.8 * likelihood_cause[0] / (0.8 * likelihood_cause[0] + 0.2 * likelihood_cause[1]);
+    assert!((posterior(&mut net, cause)[0] - expected).abs() < 1e-5);
+
+    // large counts, close to a normal distribution
+    let model = Measurement::Poisson { rates: vec![1e6] };
+    let expected = -0.5 * (2.0 * std::f32::consts::PI * 1e6).ln();
+    assert!((model.log_likelihood(1e6)[0] - expected).abs() < 1e-3);
+
+    // negative durations are impossible under any rate
+    let model = Measurement::Exponential {
+        rates: vec![1.0, 2.0],
+    };
+    assert_eq!(model.log_likelihood(-1.0)[0], f32::NEG_INFINITY);
+    assert!((model.log_likelihood(1.0)[1] - (2.0f32.ln() - 2.0)).abs() < 1e-6);
+}
+
+#[test]
+#[should_panic(expected = "Measurement model has parameters for 3 values, but node 0 has 2")]
+fn measurement_size_mismatch() {
+    let mut net = BayesNet::new();
+    net.add_node_from_probabilities(&[], Array1::from(vec![0.5, 0.5]));
+    net.add_measurement_node(
+        0,
+        Measurement::Exponential {
+            rates: vec![1.0, 2.0, 3.0],
+        },
+    );
+}
+
+#[test]
+#[should_panic(expected = "Measurement standard deviations must be positive, got 0")]
+fn measurement_zero_std_dev() {
+    let mut net = BayesNet::new();
+    net.add_node_from_probabilities(&[], Array1::from(vec![0.5, 0.5]));
+    net.add_measurement_node(
+        0,
+        Measurement::Gaussian {
+            means: vec![0.0, 1.0],
+            std_devs: vec![1.0, 0.0],
+        },
+    );
+}
+
+#[test]
+#[should_panic(expected = "Measurement rates must be non-negative, got -1")]
+fn measurement_negative_rate() {
+    let mut net = BayesNet::new();
+    net.add_node_from_probabilities(&[], Array1::from(vec![0.5, 0.5]));
+    net.add_measurement_node(
+        0,
+        Measurement::Poisson {
+            rates: vec![2.0, -1.0],
+        },
+    );
+}
@@ -0,0 +1,36 @@
+use loopybayesnet::BayesNet;
+use ndarray::{Array1, Array2};
+
+#[test]
+fn message_views() {
+    let mut net = BayesNet::new();
+    let a = net.add_node_from_probabilities(&[], Array1::from(vec![0.2, 0.8]));
+    let b = net.add_node_from_probabilities(
+        &[a],
+        Array2::from(vec![[0.9, 0.5], [0.05, 0.25], [0.05, 0.25]]),
+    );
+    net.set_evidence(&[(b, 0)]);
+    net.step();
+
+    // from a to b: the prior of a
+    let pi = net.message_view(a, b).mapv(f32::exp);
+    assert!((pi[0] - 0.2).abs() < 1e-6 && (pi[1] - 0.8).abs() < 1e-6);
+    // from b to a: the likelihood of the evidence for each value of a
+    let lambda = net.message_view(b, a).mapv(f32::exp);
+    assert!((lambda[0] / lambda[1] - 0.9 / 0.5).abs() < 1e-5);
+
+    let all: Vec<(usize, usize, usize)> = net
+        .message_views()
+        .map(|(from, to, msg)| (from, to, msg.len()))
+        .collect();
+    assert_eq!(all, vec![(b, a, 2), (a, b, 2)]);
+}
+
+#[test]
+#[should_panic(expected = "node 0 and node 1 are not connected")]
+fn message_without_edge() {
+    let mut net = BayesNet::new();
+    net.add_node_from_probabilities(&[], Array1::from(vec![0.2, 0.8]));
+    net.add_node_from_probabilities(&[], Array1::from(vec![0.2, 0.8]));
+    net.message_view(0, 1);
+}
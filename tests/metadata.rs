@@ -0,0 +1,56 @@
+use loopybayesnet::BayesNet;
+use ndarray::{Array1, Array2};
+
+#[test]
+fn tags_and_namespaces() {
+    let mut net = BayesNet::new();
+    let season = net.add_node_from_probabilities(&[], Array1::from(vec![0.5, 0.5]));
+    let indoor =
+        net.add_node_from_probabilities(&[season], Array2::from(vec![[0.8, 0.4], [0.2, 0.6]]));
+    let outdoor =
+        net.add_node_from_probabilities(&[season], Array2::from(vec![[0.9, 0.1], [0.1, 0.9]]));
+    let door = net.add_node_from_probabilities(&[], Array1::from(vec![0.7, 0.3]));
+    let unnamed = net.add_node_from_probabilities(&[], Array1::from(vec![0.7, 0.3]));
+    net.set_node_name(season, "season");
+    net.set_node_name(indoor, "sensors/temperature/室内");
+    net.set_node_name(outdoor, "sensors/temperature/outdoor");
+    net.set_node_name(door, "sensors/door");
+
+    net.set_tag(indoor, "unit", "celsius");
+    net.set_tag(outdoor, "unit", "fahrenheit");
+    net.set_tag(door, "vendor", "acme");
+    assert_eq!(net.tag(indoor, "unit"), Some("celsius"));
+    assert_eq!(net.nodes_with_tag("unit", None), vec![indoor, outdoor]);
+    assert_eq!(net.nodes_with_tag("unit", Some("celsius")), vec![indoor]);
+    assert_eq!(net.remove_tag(door, "vendor"), Some("acme".to_owned()));
+    assert!(net.tags(door).is_empty());
+
+    assert_eq!(net.namespace(indoor), Some("sensors/temperature"));
+    assert_eq!(net.namespace(season), Some(""));
+    assert_eq!(net.namespace(unnamed), None);
+    assert_eq!(
+        net.nodes_in_namespace("sensors"),
+        vec![indoor, outdoor, door]
+    );
+    assert_eq!(
+        net.nodes_in_namespace("sensors/temperature/"),
+        vec![indoor, outdoor]
+    );
+    // namespaces match whole components only
+    assert!(net.nodes_in_namespace("sensors/temp").is_empty());
+    assert_eq!(net.nodes_in_namespace("").len(), 4);
+    assert_eq!(net.sub_namespaces(""), vec!["sensors".to_owned()]);
+    assert_eq!(
+        net.sub_namespaces("sensors"),
+        vec!["sensors/temperature".to_owned()]
+    );
+
+    net.set_evidence(&[(season, 1)]);
+    for _ in 0..3 {
+        net.step();
+    }
+    let beliefs = net.beliefs_in_namespace("sensors/temperature");
+    assert_eq!(beliefs.len(), 2);
+    assert_eq!(beliefs[0].0, indoor);
+    assert!((beliefs[0].1.as_probabilities()[1] - 0.6).abs() < 1e-5);
+}
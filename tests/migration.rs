@@ -0,0 +1,43 @@
+use loopybayesnet::{Migration, MigrationChain};
+use std::collections::HashMap;
+
+fn evidence(entries: &[(&str, &str)]) -> HashMap<String, String> {
+    entries
+        .iter()
+        .map(|&(k, v)| (k.to_owned(), v.to_owned()))
+        .collect()
+}
+
+#[test]
+fn migrate_across_versions() {
+    // v1 -> v2: "temp" renamed to "temperature", its "hot" state renamed to "high"
+    let mut m1 = Migration::new();
+    m1.rename_node("temp", "temperature");
+    m1.rename_state("temp", "hot", "high");
+    // v2 -> v3: "humidity" was removed from the model
+    let mut m2 = Migration::new();
+    m2.remove_node("humidity");
+
+    let mut chain = MigrationChain::new();
+    chain.add_step(1, m1);
+    chain.add_step(2, m2);
+
+    let old = evidence(&[("temp", "hot"), ("humidity", "low"), ("wind", "none")]);
+    assert_eq!(
+        chain.migrate_evidence(1, 3, &old),
+        evidence(&[("temperature", "high"), ("wind", "none")])
+    );
+    assert_eq!(
+        chain.migrate_evidence(1, 2, &old),
+        evidence(&[
+            ("temperature", "high"),
+            ("humidity", "low"),
+            ("wind", "none")
+        ])
+    );
+    assert_eq!(
+        chain.migrate_nodes(1, 3, &["humidity", "temp"]),
+        vec!["temperature".to_owned()]
+    );
+    assert_eq!(chain.migrate_evidence(3, 3, &old), old);
+}
@@ -0,0 +1,75 @@
+use loopybayesnet::{BayesNet, Monotonicity, ViolationContext};
+use ndarray::{Array1, Array2, Array3};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+// a -> b, and c depends on both, increasing with each of them for a fixed value of the other one,
+// but a makes b unlikely
+fn confounded() -> BayesNet {
+    let mut net = BayesNet::new();
+    let a = net.add_node_from_probabilities(&[], Array1::from(vec![0.5, 0.5]));
+    let b = net.add_node_from_probabilities(&[a], Array2::from(vec![[0.1, 0.9], [0.9, 0.1]]));
+    net.add_node_from_probabilities(
+        &[a, b],
+        Array3::from(vec![[[0.9, 0.8], [0.2, 0.1]], [[0.1, 0.2], [0.8, 0.9]]]),
+    );
+    net
+}
+
+#[test]
+fn table_monotonicity() {
+    let net = confounded();
+    let mut rng = StdRng::seed_from_u64(0);
+    let increasing = Monotonicity {
+        parent: 0,
+        child: 2,
+        increasing: true,
+    };
+    assert!(net
+        .verify_monotonicity(&[increasing], &mut rng, 10, 3)
+        .is_empty());
+
+    let decreasing = Monotonicity {
+        increasing: false,
+        ..increasing
+    };
+    let violations = net.verify_monotonicity(&[decreasing], &mut rng, 0, 3);
+    assert_eq!(violations.len(), 2);
+    assert_eq!(
+        violations[1].context,
+        ViolationContext::Table {
+            other_parents: vec![(1, 1)]
+        }
+    );
+    assert_eq!(
+        (violations[1].parent_value, violations[1].child_value),
+        (0, 1)
+    );
+    assert!((violations[1].before - 0.2).abs() < 1e-6);
+    assert!((violations[1].after - 0.9).abs() < 1e-6);
+}
+
+#[test]
+fn inference_monotonicity() {
+    let net = confounded();
+    let mut rng = StdRng::seed_from_u64(0);
+    let relation = Monotonicity {
+        parent: 1,
+        child: 2,
+        increasing: true,
+    };
+    let violations = net.verify_monotonicity(&[relation], &mut rng, 20, 3);
+    // the table is increasing, but not the posterior when a is unknown: 0.73 for b = 0, 0.27 for b = 1
+    assert!(!violations.is_empty());
+    for violation in &violations {
+        assert_eq!(
+            violation.context,
+            ViolationContext::Inference {
+                evidence: Vec::new()
+            }
+        );
+        assert!((violation.before - 0.73).abs() < 1e-5);
+        assert!((violation.after - 0.27).abs() < 1e-5);
+    }
+    assert!(violations[0].to_string().contains("in the posteriors"));
+}
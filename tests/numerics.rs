@@ -0,0 +1,93 @@
+use loopybayesnet::{BayesNet, NumericsPolicy};
+use ndarray::{Array1, Array2};
+
+// a -> b -> c, b and c copying their parent
+fn copies(prior: Vec<f32>) -> BayesNet {
+    let mut net = BayesNet::new();
+    let a = net.add_node_from_probabilities(&[], Array1::from(prior));
+    let b = net.add_node_from_probabilities(&[a], Array2::from(vec![[1.0, 0.0], [0.0, 1.0]]));
+    net.add_node_from_probabilities(&[b], Array2::from(vec![[1.0, 0.0], [0.0, 1.0]]));
+    net
+}
+
+#[test]
+fn convergence_tolerance() {
+    let mut net = copies(vec![0.3, 0.7]);
+    assert_eq!(net.numerics(), &NumericsPolicy::default());
+    net.set_evidence(&[(2, 0)]);
+    let steps = net.step_until_converged(20).unwrap();
+    assert!(steps <= 4);
+    assert!((net.beliefs()[0].as_probabilities()[0] - 1.0).abs() < 1e-5);
+    net.reset_state();
+    assert_eq!(net.step_until_converged(1), None);
+}
+
+#[test]
+fn run_until_convergence() {
+    let mut net = copies(vec![0.3, 0.7]);
+    net.set_evidence(&[(2, 0)]);
+    let (steps, converged) = net.run_until_convergence(1e-5, 20);
+    assert!(converged && steps <= 4);
+    assert_eq!(net.iteration(), steps);
+    net.reset_state();
+    assert_eq!(net.run_until_convergence(1e-5, 1), (1, false));
+    // any change is accepted with an infinite tolerance
+    net.reset_state();
+    assert_eq!(net.run_until_convergence(f32::INFINITY, 10), (1, true));
+}
+
+#[test]
+fn zero_detection() {
+    let mut net = copies(vec![1.0, 1e-20]);
+    net.step();
+    assert!(net.beliefs()[1].log_probabilities()[1] > f32::NEG_INFINITY);
+
+    net.set_numerics(NumericsPolicy::speed_first());
+    net.reset_state();
+    net.step();
+    assert_eq!(net.beliefs()[1].log_probabilities()[1], f32::NEG_INFINITY);
+}
+
+// impossible evidence produces NaN on purpose, which panics with the `nan-checks` feature
+#[cfg(not(feature = "nan-checks"))]
+#[test]
+fn normalization_guard() {
+    // contradictory evidence: b must copy a
+    let mut net = copies(vec![0.5, 0.5]);
+    net.set_evidence(&[(0, 0), (1, 1)]);
+    net.step();
+    assert!(net.try_step().is_err());
+
+    net.set_numerics(NumericsPolicy::speed_first());
+    net.reset_state();
+    net.step();
+    assert!(net.try_step().is_ok());
+}
+
+// impossible evidence produces NaN on purpose, which panics with the `nan-checks` feature
+#[cfg(not(feature = "nan-checks"))]
+#[test]
+fn impossible_evidence_never_converges() {
+    let mut net = copies(vec![0.5, 0.5]);
+    net.set_evidence(&[(0, 0), (1, 1)]);
+    assert_eq!(net.run_until_convergence(1e-5, 5), (5, false));
+    assert!(net.beliefs()[2].as_probabilities()[0].is_nan());
+    net.reset_state();
+    assert_eq!(net.step_until_converged(5), None);
+    net.reset_state();
+    net.step();
+    assert!(net.step_with_delta().is_nan());
+}
+
+#[test]
+fn step_delta_vanishes_at_convergence() {
+    let mut net = BayesNet::new();
+    let a = net.add_node_from_probabilities(&[], Array1::from(vec![0.3, 0.7]));
+    let b = net.add_node_from_probabilities(&[a], Array2::from(vec![[0.9, 0.2], [0.1, 0.8]]));
+    net.set_evidence(&[(b, 0)]);
+    // the first step sends the prior of a and the likelihood of the evidence on b
+    let first = net.step_with_delta();
+    assert!((first - (0.9 / 1.1 - 0.5)).abs() < 1e-6, "{}", first);
+    assert!(net.step_with_delta() < 1e-6);
+    assert_eq!(net.iteration(), 2);
+}
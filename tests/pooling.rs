@@ -0,0 +1,60 @@
+use loopybayesnet::pooling::Pooling;
+use loopybayesnet::LogProbVector;
+use ndarray::Array1;
+
+fn dist(p: &[f32]) -> LogProbVector {
+    LogProbVector::from_log_probabilities(Array1::from(p.to_vec()).mapv(f32::ln))
+}
+
+#[test]
+fn pool_and_calibrate() {
+    let a = dist(&[0.8, 0.2]);
+    let b = dist(&[0.4, 0.6]);
+
+    let linear = Pooling::Linear.pool(&[a.clone(), b.clone()], &[3.0, 1.0]);
+    let p = linear.as_probabilities();
+    assert!((p[0] - 0.7).abs() < 1e-5);
+
+    let log = Pooling::Logarithmic.pool(&[a.clone(), b.clone()], &[1.0, 1.0]);
+    let p = log.as_probabilities();
+    assert!((p[0] - 0.32 / (0.32 + 0.12)).abs() < 1e-5);
+
+    // the first model is well calibrated and informative, the second is noise
+    let mut predictions = Vec::new();
+    let mut outcomes = Vec::new();
+    for i in 0..100 {
+        let truth = i % 2;
+        let confident = if truth == 0 { [0.8, 0.2] } else { [0.2, 0.8] };
+        let noise = if i % 3 == 0 { [0.9, 0.1] } else { [0.1, 0.9] };
+        predictions.push(vec![dist(&confident), dist(&noise)]);
+        outcomes.push(if i % 10 == 0 { 1 - truth } else { truth });
+    }
+    let score = |pooling: Pooling, weights: &[f32]| -> f32 {
+        predictions
+            .iter()
+            .zip(outcomes.iter())
+            .map(|(p, &y)| pooling.pool(p, weights).log_probabilities()[y])
+            .sum()
+    };
+
+    let weights = Pooling::Linear.calibrate(&predictions, &outcomes, 100);
+    assert!((weights.iter().sum::<f32>() - 1.0).abs() < 1e-4);
+    assert!(weights[0] > 0.9);
+    assert!(score(Pooling::Linear, &weights) > score(Pooling::Linear, &[0.5, 0.5]));
+
+    let weights = Pooling::Logarithmic.calibrate(&predictions, &outcomes, 200);
+    assert!(weights[0] > 5.0 * weights[1]);
+    assert!(score(Pooling::Logarithmic, &weights) > score(Pooling::Logarithmic, &[0.5, 0.5]));
+}
+
+#[test]
+#[should_panic(expected = "Linear pooling needs a positive weight, got [0.0, 0.0]")]
+fn linear_pooling_zero_weights() {
+    Pooling::Linear.pool(&[dist(&[0.8, 0.2]), dist(&[0.4, 0.6])], &[0.0, 0.0]);
+}
+
+#[test]
+#[should_panic(expected = "the weight of model 1 is -0.5")]
+fn pooling_negative_weight() {
+    Pooling::Logarithmic.pool(&[dist(&[0.8, 0.2]), dist(&[0.4, 0.6])], &[1.0, -0.5]);
+}
@@ -0,0 +1,81 @@
+use loopybayesnet::BayesNet;
+use ndarray::{Array1, Array2, Array3, ArrayD, IxDyn};
+
+// the table of a noisy-OR finding, given the leak and the strength of each of its diseases
+fn noisy_or(leak: f32, strengths: &[f32]) -> ArrayD<f32> {
+    let n = strengths.len();
+    let mut shape = vec![2];
+    shape.extend(std::iter::repeat_n(2, n));
+    let mut table = ArrayD::zeros(IxDyn(&shape));
+    for config in 0..1usize << n {
+        let values: Vec<usize> = (0..n).map(|i| (config >> i) & 1).collect();
+        let negative: f32 = (1.0 - leak)
+            * values
+                .iter()
+                .zip(strengths)
+                .filter(|&(&v, _)| v == 1)
+                .map(|(_, &s)| 1.0 - s)
+                .product::<f32>();
+        let mut index = vec![0];
+        index.extend(&values);
+        table[IxDyn(&index)] = negative;
+        index[0] = 1;
+        table[IxDyn(&index)] = 1.0 - negative;
+    }
+    table
+}
+
+// three diseases and four findings, with loops
+fn diagnosis() -> BayesNet {
+    let mut net = BayesNet::new();
+    let d0 = net.add_node_from_probabilities(&[], Array1::from(vec![0.9, 0.1]));
+    let d1 = net.add_node_from_probabilities(&[], Array1::from(vec![0.8, 0.2]));
+    let d2 = net.add_node_from_probabilities(&[], Array1::from(vec![0.95, 0.05]));
+    net.add_node_from_probabilities(&[d0, d1], noisy_or(0.01, &[0.8, 0.6]));
+    net.add_node_from_probabilities(&[d0, d1, d2], noisy_or(0.05, &[0.5, 0.7, 0.9]));
+    net.add_node_from_probabilities(&[d1, d2], noisy_or(0.02, &[0.3, 0.8]));
+    net.add_node_from_probabilities(&[d2], noisy_or(0.1, &[0.6]));
+    net
+}
+
+fn assert_close(a: &[loopybayesnet::LogProbVector], b: &[loopybayesnet::LogProbVector]) {
+    for (a, b) in a.iter().zip(b) {
+        let (a, b) = (a.as_probabilities(), b.as_probabilities());
+        assert!((a[1] - b[1]).abs() < 1e-4, "{} != {}", a[1], b[1]);
+    }
+}
+
+#[test]
+fn quickscore_is_exact() {
+    let mut net = diagnosis();
+    for evidence in [
+        vec![],
+        vec![(3, 1)],
+        vec![(3, 1), (4, 1), (5, 0)],
+        vec![(3, 0), (4, 1), (5, 1), (6, 1)],
+        vec![(0, 1), (4, 1), (6, 0)],
+    ] {
+        net.set_evidence(&evidence);
+        let quickscore = net.quickscore_beliefs().unwrap();
+        assert_close(&quickscore, &net.junction_tree_beliefs());
+        assert_close(&net.exact_beliefs(), &quickscore);
+    }
+}
+
+#[test]
+fn quickscore_needs_noisy_or_findings() {
+    let mut net = BayesNet::new();
+    let a = net.add_node_from_probabilities(&[], Array1::from(vec![0.5, 0.5]));
+    let b = net.add_node_from_probabilities(&[], Array1::from(vec![0.5, 0.5]));
+    // an exclusive or is not a noisy-OR
+    net.add_node_from_probabilities(
+        &[a, b],
+        Array3::from(vec![[[0.9, 0.1], [0.1, 0.9]], [[0.1, 0.9], [0.9, 0.1]]]),
+    );
+    assert!(net.quickscore_beliefs().is_none());
+
+    // three layers
+    let mut net = diagnosis();
+    net.add_node_from_probabilities(&[3], Array2::from(vec![[0.9, 0.5], [0.1, 0.5]]));
+    assert!(net.quickscore_beliefs().is_none());
+}
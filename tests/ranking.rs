@@ -0,0 +1,31 @@
+use loopybayesnet::{RankingNet, INFINITE_RANK};
+use ndarray::{Array1, Array2};
+
+#[test]
+fn ranked_chain() {
+    let mut net = RankingNet::new();
+    let a = net.add_node(&[], Array1::from(vec![0, 2]));
+    // b normally copies a, with a surprise of 1 for an exception
+    let b = net.add_node(&[a], Array2::from(vec![[0, 1], [1, 0]]));
+    // c strictly copies b, and ranks are normalized
+    let c = net.add_node(
+        &[b],
+        Array2::from(vec![[3, INFINITE_RANK], [INFINITE_RANK, 5]]),
+    );
+
+    for _ in 0..3 {
+        net.step();
+    }
+    let ranks = net.ranks();
+    assert_eq!(ranks[a].to_vec(), vec![0, 2]);
+    assert_eq!(ranks[b].to_vec(), vec![0, 1]);
+    assert_eq!(ranks[c].to_vec(), vec![0, 1]);
+
+    net.set_evidence(&[(c, 1)]);
+    for _ in 0..3 {
+        net.step();
+    }
+    let ranks = net.ranks();
+    assert_eq!(ranks[a].to_vec(), vec![0, 1]);
+    assert_eq!(ranks[b].to_vec(), vec![INFINITE_RANK, 0]);
+}
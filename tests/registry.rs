@@ -0,0 +1,57 @@
+use loopybayesnet::{BayesNet, ModelRegistry, RegistryError};
+use ndarray::{Array1, Array2};
+
+// A tiny artifact format for the tests: the bytes are the prior weights of a single node,
+// with a child copying it.
+fn decode(bytes: &[u8]) -> Result<BayesNet, String> {
+    if bytes.is_empty() {
+        return Err("empty artifact".into());
+    }
+    let mut net = BayesNet::new();
+    let prior = net.add_node_from_probabilities(
+        &[],
+        Array1::from(bytes.iter().map(|&b| b as f32).collect::<Vec<_>>()),
+    );
+    net.add_node_from_probabilities(&[prior], Array2::eye(bytes.len()));
+    Ok(net)
+}
+
+#[test]
+fn hot_reload_and_routing() {
+    let registry = ModelRegistry::new();
+    assert_eq!(registry.load_artifact("a", &[1, 1], decode).unwrap(), 1);
+    assert_eq!(registry.load_artifact("b", &[3, 1], decode).unwrap(), 1);
+
+    let beliefs = registry.query("a", &[], 3).unwrap();
+    assert!((beliefs[1].as_probabilities()[0] - 0.5).abs() < 1e-4);
+    let beliefs = registry.query("b", &[], 3).unwrap();
+    assert!((beliefs[1].as_probabilities()[0] - 0.75).abs() < 1e-4);
+
+    // a handle taken before the reload keeps the old version
+    let old = registry.get("a").unwrap();
+    assert_eq!(registry.load_artifact("a", &[1, 3], decode).unwrap(), 2);
+    assert_eq!(old.version(), 1);
+    assert!((old.query(&[], 3)[1].as_probabilities()[0] - 0.5).abs() < 1e-4);
+    let beliefs = registry.query("a", &[], 3).unwrap();
+    assert!((beliefs[1].as_probabilities()[0] - 0.25).abs() < 1e-4);
+
+    // a failed reload keeps the current version
+    match registry.load_artifact("a", &[], decode) {
+        Err(RegistryError::Decode { .. }) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+    assert_eq!(registry.get("a").unwrap().version(), 2);
+
+    assert_eq!(
+        registry.models(),
+        vec![("a".to_owned(), 2), ("b".to_owned(), 1)]
+    );
+    match registry.query("c", &[], 1) {
+        Err(RegistryError::UnknownModel(ref name)) if name == "c" => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+    // versions are not reused after a removal
+    assert_eq!(registry.remove("a").unwrap().version(), 2);
+    assert!(registry.get("a").is_err());
+    assert_eq!(registry.load_artifact("a", &[1, 1], decode).unwrap(), 3);
+}
@@ -0,0 +1,53 @@
+use loopybayesnet::reliability::{importance_measures, minimal_cut_sets, top_event_probability};
+use loopybayesnet::BayesNet;
+
+const TREE: &str = "
+basic pump 0.1
+basic valve 0.2
+basic backup 0.5
+cooling = or pump valve
+top = and cooling backup
+";
+
+#[test]
+fn cut_sets_of_a_fault_tree() {
+    let net = BayesNet::from_fault_tree(TREE).unwrap();
+    let id = |name| net.find_node(name).unwrap();
+    let mut expected = vec![
+        vec![id("pump"), id("backup")],
+        vec![id("valve"), id("backup")],
+    ];
+    for set in &mut expected {
+        set.sort_unstable();
+    }
+    expected.sort();
+    assert_eq!(minimal_cut_sets(&net, id("top")), expected);
+    assert_eq!(minimal_cut_sets(&net, id("pump")), vec![vec![id("pump")]]);
+
+    // a 2-out-of-3 vote, where the AND of all the inputs is not minimal
+    let net =
+        BayesNet::from_fault_tree("basic a 0.1\nbasic b 0.1\nbasic c 0.1\ntop = vote 2 a b c\n")
+            .unwrap();
+    assert_eq!(
+        minimal_cut_sets(&net, 3),
+        vec![vec![0, 1], vec![0, 2], vec![1, 2]]
+    );
+}
+
+#[test]
+fn importance_of_basic_events() {
+    let net = BayesNet::from_fault_tree(TREE).unwrap();
+    let id = |name| net.find_node(name).unwrap();
+    let top = id("top");
+    assert!((top_event_probability(&net, top, 10) - 0.14).abs() < 1e-5);
+
+    let measures = importance_measures(&net, top, 10);
+    assert_eq!(measures.len(), 3);
+    let of = |name| measures.iter().find(|m| m.event == id(name)).unwrap();
+    // P(top | pump) = 0.5, P(top | no pump) = 0.2 * 0.5
+    assert!((of("pump").birnbaum - 0.4).abs() < 1e-5);
+    assert!((of("pump").fussell_vesely - 0.04 / 0.14).abs() < 1e-4);
+    assert!((of("backup").birnbaum - 0.28).abs() < 1e-5);
+    assert!((of("backup").fussell_vesely - 1.0).abs() < 1e-5);
+    assert!((of("backup").risk_achievement_worth - 2.0).abs() < 1e-4);
+}
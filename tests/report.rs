@@ -0,0 +1,76 @@
+use loopybayesnet::report::{report, ReportOptions};
+use loopybayesnet::BayesNet;
+use ndarray::{Array1, Array2};
+
+fn rain_network() -> BayesNet {
+    let mut net = BayesNet::new();
+    let rain = net.add_node_from_probabilities(&[], Array1::from(vec![0.8, 0.2]));
+    let wet = net.add_node_from_probabilities(&[rain], Array2::from(vec![[0.9, 0.1], [0.1, 0.9]]));
+    let traffic =
+        net.add_node_from_probabilities(&[rain], Array2::from(vec![[0.6, 0.3], [0.4, 0.7]]));
+    let sun = net.add_node_from_probabilities(&[], Array1::from(vec![0.5, 0.5]));
+    net.set_node_name(rain, "rain");
+    net.set_state_names(rain, &["no", "yes"]);
+    net.set_node_name(wet, "wet|grass");
+    net.set_state_names(wet, &["no", "yes"]);
+    net.set_node_name(traffic, "traffic");
+    net.set_state_names(traffic, &["light", "heavy"]);
+    net.set_node_name(sun, "sun");
+    net.set_localized_name(rain, "fr", "Pluie");
+    net
+}
+
+#[test]
+fn report_of_a_query() {
+    let mut net = rain_network();
+    net.set_evidence(&[(1, 1), (2, 1)]);
+    let options = ReportOptions {
+        target: Some((0, 1)),
+        ..ReportOptions::default()
+    };
+    let result = report(&net, &options);
+    assert_eq!(result.evidence, vec![(1, 1), (2, 1)]);
+    assert!(result.converged);
+    // the independent node did not change
+    assert_eq!(result.changes.len(), 2);
+    assert_eq!(result.changes[0].node, 0);
+    assert!(result.changes[1].shift < 1e-5);
+
+    // P(rain | wet, heavy traffic) = 0.2 * 0.9 * 0.7 / (0.2 * 0.9 * 0.7 + 0.8 * 0.1 * 0.4)
+    let ((target, value), p) = result.target.unwrap();
+    assert_eq!((target, value), (0, 1));
+    assert!((p - 0.126 / 0.158).abs() < 1e-4);
+    // the wet grass is the strongest evidence, each weight being its log likelihood ratio
+    assert_eq!(result.weights[0].node, 1);
+    assert!((result.weights[0].weight - 9.0f32.ln()).abs() < 1e-3);
+    assert!((result.weights[1].weight - 1.75f32.ln()).abs() < 1e-3);
+
+    let markdown = result.to_markdown();
+    assert!(markdown.starts_with("# Inference report\n"));
+    assert!(markdown.contains("| wet\\|grass | yes |"));
+    assert!(markdown.contains("## Explanation of rain = yes"));
+    assert!(markdown.contains("| rain | no 0.800, yes 0.200 | no 0.203, yes 0.797 | 0.597 |"));
+    assert!(markdown.contains("| wet\\|grass = yes | +2.197 |"));
+    assert!(markdown.contains("Converged after"));
+
+    let html = result.to_html();
+    assert!(html.contains("<h2>Evidence</h2>"));
+    assert!(html.contains("<td>wet|grass</td><td>yes</td>"));
+}
+
+#[test]
+fn report_without_evidence() {
+    let net = rain_network();
+    let options = ReportOptions {
+        max_iterations: 1,
+        locale: "fr".to_owned(),
+        ..ReportOptions::default()
+    };
+    let result = report(&net, &options);
+    assert!(result.weights.is_empty());
+    assert!(result.target.is_none());
+    let markdown = result.to_markdown();
+    assert!(markdown.contains("No evidence."));
+    assert!(markdown.contains("| Pluie |"));
+    assert!(!markdown.contains("Explanation"));
+}
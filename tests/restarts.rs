@@ -0,0 +1,48 @@
+use loopybayesnet::BayesNet;
+use ndarray::{Array1, Array2, Array3};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+#[test]
+fn bethe_free_energy_is_exact_on_trees() {
+    let mut net = BayesNet::new();
+    let a = net.add_node_from_probabilities(&[], Array1::from(vec![0.5, 0.5]));
+    net.add_node_from_probabilities(&[a], Array2::from(vec![[0.5, 1.0], [0.5, 0.0]]));
+    net.set_evidence(&[(1, 1)]);
+    for _ in 0..3 {
+        net.step();
+    }
+    assert!((net.bethe_free_energy() - 4.0f32.ln()).abs() < 1e-5);
+}
+
+#[test]
+fn restarts_keep_lowest_free_energy() {
+    // a strongly coupled loop, 0 -> {1, 2} -> 3
+    let mut net = BayesNet::new();
+    let a = net.add_node_from_probabilities(&[], Array1::from(vec![0.5, 0.5]));
+    let b = net.add_node_from_probabilities(&[a], Array2::from(vec![[0.95, 0.05], [0.05, 0.95]]));
+    let c = net.add_node_from_probabilities(&[a], Array2::from(vec![[0.05, 0.95], [0.95, 0.05]]));
+    net.add_node_from_probabilities(
+        &[b, c],
+        Array3::from(vec![[[0.9, 0.1], [0.1, 0.9]], [[0.1, 0.9], [0.9, 0.1]]]),
+    );
+    net.set_evidence(&[(3, 0)]);
+
+    let mut plain = net.clone();
+    for _ in 0..30 {
+        plain.step();
+    }
+    let plain_energy = plain.bethe_free_energy();
+
+    let mut rng = StdRng::seed_from_u64(3);
+    let energy = net.step_with_restarts(&mut rng, 5, 30, 2.0);
+    assert!(energy <= plain_energy);
+    assert_eq!(energy, net.bethe_free_energy());
+    // the evidence is kept
+    assert!((net.beliefs()[3].as_probabilities()[0] - 1.0).abs() < 1e-6);
+
+    // runs are reproducible
+    let mut other = net.clone();
+    let mut rng = StdRng::seed_from_u64(3);
+    assert_eq!(other.step_with_restarts(&mut rng, 5, 30, 2.0), energy);
+}
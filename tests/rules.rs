@@ -0,0 +1,56 @@
+use loopybayesnet::{BayesNet, CptRules};
+use ndarray::Array1;
+use std::collections::HashMap;
+
+fn condition(net: &BayesNet, entries: &[(&str, &str)]) -> Vec<(usize, usize)> {
+    let named: HashMap<String, String> = entries
+        .iter()
+        .map(|&(k, v)| (k.to_owned(), v.to_owned()))
+        .collect();
+    net.validate_evidence(&named).unwrap()
+}
+
+#[test]
+fn prioritized_rules() {
+    let mut net = BayesNet::new();
+    let alarm = net.add_node_from_probabilities(&[], Array1::from(vec![0.9, 0.1]));
+    let battery = net.add_node_from_probabilities(&[], Array1::from(vec![0.2, 0.7, 0.1]));
+    let weekend = net.add_node_from_probabilities(&[], Array1::from(vec![0.7, 0.3]));
+    net.set_node_name(alarm, "alarm");
+    net.set_state_names(alarm, &["off", "on"]);
+    net.set_node_name(battery, "battery");
+    net.set_state_names(battery, &["dead", "ok", "low"]);
+
+    // does the neighbour call?
+    let mut rules = CptRules::new(Array1::from(vec![0.99, 0.01]));
+    rules.add_rule(
+        &condition(&net, &[("alarm", "on"), ("battery", "dead")]),
+        Array1::from(vec![0.95, 0.05]),
+    );
+    rules.add_rule(
+        &condition(&net, &[("alarm", "on")]),
+        Array1::from(vec![0.2, 0.8]),
+    );
+    assert_eq!(rules.len(), 2);
+    let calls = net.add_node_from_rules(&[alarm, battery, weekend], &rules);
+
+    let tree = net.cpt_tree(calls).unwrap();
+    // the weekend is never tested: alarm off, then dead / ok / low battery
+    assert_eq!(tree.num_leaves(), 4);
+    for (weekend, &(a, b, expected)) in [0, 1].iter().zip(&[(0, 0, 0.01), (1, 0, 0.05)]) {
+        let p = tree.log_probabilities(&[a, b, *weekend]).mapv(f32::exp);
+        assert!((p[1] - expected).abs() < 1e-6);
+    }
+    for &b in &[1, 2] {
+        let p = tree.log_probabilities(&[1, b, 0]).mapv(f32::exp);
+        assert!((p[1] - 0.8).abs() < 1e-6);
+    }
+
+    // knowing the neighbour called makes the alarm likely
+    net.set_evidence(&[(calls, 1)]);
+    for _ in 0..5 {
+        net.step();
+    }
+    let beliefs = net.beliefs();
+    assert!(beliefs[alarm].as_probabilities()[1] > 0.8);
+}
@@ -0,0 +1,128 @@
+use loopybayesnet::{learning, sampling, BayesNet};
+use ndarray::{Array1, Array2, Array3};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+fn sprinkler() -> BayesNet {
+    let mut net = BayesNet::new();
+    let rain = net.add_node_from_probabilities(&[], Array1::from(vec![0.8, 0.2]));
+    let sprinkler =
+        net.add_node_from_probabilities(&[rain], Array2::from(vec![[0.6, 0.99], [0.4, 0.01]]));
+    net.add_node_from_probabilities(
+        &[rain, sprinkler],
+        Array3::from(vec![[[1.0, 0.1], [0.2, 0.01]], [[0.0, 0.9], [0.8, 0.99]]]),
+    );
+    net
+}
+
+#[test]
+fn samples_follow_the_marginals() {
+    let mut net = sprinkler();
+    // the evidence is ignored
+    net.set_evidence(&[(2, 1)]);
+    let mut rng = StdRng::seed_from_u64(3);
+    let samples = net.sample_forward(&mut rng, 20000);
+    assert_eq!(samples.len(), 20000);
+    assert!(samples.iter().all(|s| s.len() == 3));
+    // no sample has wet grass without rain nor sprinkler
+    assert!(samples
+        .iter()
+        .all(|s| !(s[0] == 0 && s[1] == 0 && s[2] == 1)));
+
+    let empirical = sampling::empirical_marginals(&net, &samples);
+    for (exact, estimate) in sprinkler().exact_beliefs().iter().zip(&empirical) {
+        let (exact, estimate) = (exact.as_probabilities(), estimate.as_probabilities());
+        for v in 0..exact.len() {
+            assert!((exact[v] - estimate[v]).abs() < 0.01);
+        }
+    }
+}
+
+#[test]
+fn samples_as_a_dataset() {
+    let net = sprinkler();
+    let samples = net.sample_forward(&mut StdRng::seed_from_u64(11), 20000);
+    // the parameters learned from the samples are close to the model
+    let fitted = learning::fit_parameters(&net, &samples, 1.0);
+    let (original, learned) = (net.exact_beliefs(), fitted.exact_beliefs());
+    for (x, y) in original.iter().zip(&learned) {
+        assert!((x.as_probabilities()[0] - y.as_probabilities()[0]).abs() < 0.01);
+    }
+}
+
+#[test]
+#[should_panic(expected = "Record 1 has 2 values but the network has 3 nodes")]
+fn empirical_marginals_check_the_samples() {
+    sampling::empirical_marginals(&sprinkler(), &[vec![0, 0, 0], vec![0, 0]]);
+}
+
+#[test]
+fn interventions_affect_descendants_only() {
+    let net = sprinkler();
+    let samples = net.sample_forward_under(&mut StdRng::seed_from_u64(5), 20000, &[(1, 1)]);
+    assert!(samples.iter().all(|s| s[1] == 1));
+    let marginals = sampling::empirical_marginals(&net, &samples);
+    // the rain keeps its prior, even though the sprinkler is rarely on when it rains
+    assert!((marginals[0].as_probabilities()[1] - 0.2).abs() < 0.01);
+    // P(wet | do(sprinkler on)) = 0.8 * 0.9 + 0.2 * 0.99
+    assert!((marginals[2].as_probabilities()[1] - 0.918).abs() < 0.01);
+}
+
+#[test]
+fn false_alarm_rate() {
+    // a fault, monitored by two noisy sensors
+    let mut net = BayesNet::new();
+    let fault = net.add_node_from_probabilities(&[], Array1::from(vec![0.95, 0.05]));
+    let sensor = Array2::from(vec![[0.9, 0.2], [0.1, 0.8]]);
+    let s1 = net.add_node_from_probabilities(&[fault], sensor.clone());
+    let s2 = net.add_node_from_probabilities(&[fault], sensor);
+
+    let mut rng = StdRng::seed_from_u64(8);
+    let normal = net.simulate_evidence(&mut rng, 10000, &[(fault, 0)], &[s1, s2], fault, 2);
+    assert_eq!(normal.posteriors.len(), 10000);
+    // the alarm is raised when both sensors fire, with probability 0.01 when there is no fault
+    let mut alarm = net.clone();
+    alarm.set_evidence(&[(s1, 1), (s2, 1)]);
+    alarm.step();
+    alarm.step();
+    let threshold = alarm.beliefs()[fault].as_probabilities()[1];
+    assert!((normal.fraction_above(1, threshold - 1e-4) - 0.01).abs() < 0.005);
+    assert!(normal.quantile(1, 0.5) < 0.05);
+    assert!(normal.quantile(1, 1.0) >= threshold - 1e-4);
+
+    let faulty = net.simulate_evidence(&mut rng, 10000, &[(fault, 1)], &[s1, s2], fault, 2);
+    // both sensors fire with probability 0.64 when there is a fault
+    assert!((faulty.fraction_above(1, threshold - 1e-4) - 0.64).abs() < 0.02);
+    assert!(faulty.mean(1) > normal.mean(1));
+}
+
+#[test]
+fn gibbs_sampling_of_a_loopy_posterior() {
+    // a loop whose two paths carry correlated information, on which BP is biased
+    let mut net = BayesNet::new();
+    let a = net.add_node_from_probabilities(&[], Array1::from(vec![0.7, 0.3]));
+    let b = net.add_node_from_probabilities(&[a], Array2::from(vec![[0.9, 0.2], [0.1, 0.8]]));
+    let c = net.add_node_from_probabilities(&[a], Array2::from(vec![[0.8, 0.1], [0.2, 0.9]]));
+    let d = net.add_node_from_probabilities(
+        &[b, c],
+        Array3::from(vec![[[0.99, 0.1], [0.1, 0.01]], [[0.01, 0.9], [0.9, 0.99]]]),
+    );
+    net.set_evidence(&[(d, 1)]);
+
+    let options = sampling::GibbsOptions {
+        burn_in: 200,
+        thinning: 2,
+        samples: 20000,
+    };
+    let result = net.gibbs_sampling(&mut StdRng::seed_from_u64(9), &options);
+    assert_eq!(result.samples.len(), 20000);
+    assert!(result.samples.iter().all(|s| s[d] == 1));
+    for (exact, estimate) in net.exact_beliefs().iter().zip(&result.marginals) {
+        let (exact, estimate) = (exact.as_probabilities(), estimate.as_probabilities());
+        for v in 0..exact.len() {
+            assert!((exact[v] - estimate[v]).abs() < 0.01);
+        }
+    }
+    let frequency = result.samples.iter().filter(|s| s[a] == 1).count() as f32 / 20000.0;
+    assert!((frequency - result.marginals[a].as_probabilities()[1]).abs() < 0.02);
+}
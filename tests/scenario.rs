@@ -0,0 +1,61 @@
+use loopybayesnet::{BayesNet, LogProbVector};
+use ndarray::{Array1, Array2};
+
+fn chain(length: usize) -> BayesNet {
+    let mut net = BayesNet::new();
+    let mut previous = net.add_node_from_probabilities(&[], Array1::from(vec![0.5, 0.5]));
+    for i in 1..length {
+        let p = 0.6 + 0.3 * (i as f32 / length as f32);
+        previous = net.add_node_from_probabilities(
+            &[previous],
+            Array2::from(vec![[p, 1.0 - p], [1.0 - p, p]]),
+        );
+    }
+    net
+}
+
+#[test]
+fn scenarios_share_unmodified_tables() {
+    let mut base = chain(20);
+    base.set_evidence(&[(0, 0)]);
+    for _ in 0..20 {
+        base.step();
+    }
+
+    let scenario = base.with_probabilities(10, Array2::from(vec![[0.5, 0.5], [0.5, 0.5]]));
+    for node in 0..20 {
+        assert_eq!(scenario.shares_table(&base, node), node != 10);
+    }
+
+    // the base model is unchanged, and the scenario is cut after the modified node
+    let before = base.beliefs();
+    let after = beliefs_after(scenario, 20);
+    assert!(before[12].as_probabilities()[0] > 0.5);
+    assert!((after[12].as_probabilities()[0] - 0.5).abs() < 1e-5);
+    assert!((after[8].as_probabilities()[0] - before[8].as_probabilities()[0]).abs() < 1e-6);
+}
+
+fn beliefs_after(mut net: BayesNet, iterations: usize) -> Vec<LogProbVector> {
+    for _ in 0..iterations {
+        net.step();
+    }
+    net.beliefs()
+}
+
+#[test]
+fn set_probabilities_matches_a_new_network() {
+    let mut net = chain(3);
+    net.set_probabilities(1, Array2::from(vec![[0.2, 0.6], [0.8, 0.4]]));
+    net.set_probabilities(0, Array1::from(vec![3.0, 1.0]));
+
+    let mut expected = BayesNet::new();
+    let a = expected.add_node_from_probabilities(&[], Array1::from(vec![0.75, 0.25]));
+    let b = expected.add_node_from_probabilities(&[a], Array2::from(vec![[0.2, 0.6], [0.8, 0.4]]));
+    let p = 0.6 + 0.3 * (2.0 / 3.0);
+    expected.add_node_from_probabilities(&[b], Array2::from(vec![[p, 1.0 - p], [1.0 - p, p]]));
+    for (x, y) in net.exact_beliefs().iter().zip(expected.exact_beliefs()) {
+        assert!((x.as_probabilities() - y.as_probabilities())
+            .iter()
+            .all(|d| d.abs() < 1e-6));
+    }
+}
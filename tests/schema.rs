@@ -0,0 +1,54 @@
+use loopybayesnet::{BayesNet, SchemaError};
+use ndarray::{Array1, Array2};
+use std::collections::HashMap;
+
+fn alarm_net() -> BayesNet {
+    let mut net = BayesNet::new();
+    let burglary = net.add_node_from_probabilities(&[], Array1::from(vec![0.9, 0.1]));
+    let alarm = net.add_node_from_probabilities(
+        &[burglary],
+        Array2::from(vec![[0.9, 0.1], [0.1, 0.8], [0.0, 0.1]]),
+    );
+    net.set_node_name(burglary, "burglary");
+    net.set_state_names(burglary, &["no", "yes"]);
+    net.set_node_name(alarm, "alarm");
+    net.set_state_names(alarm, &["off", "on", "broken"]);
+    net
+}
+
+fn evidence(entries: &[(&str, &str)]) -> HashMap<String, String> {
+    entries
+        .iter()
+        .map(|&(k, v)| (k.to_owned(), v.to_owned()))
+        .collect()
+}
+
+#[test]
+fn validate_named_evidence() {
+    let net = alarm_net();
+    assert_eq!(net.find_node("alarm"), Some(1));
+    assert_eq!(net.find_state(1, "broken"), Some(2));
+    assert_eq!(
+        net.validate_evidence(&evidence(&[("alarm", "on"), ("burglary", "no")])),
+        Ok(vec![(0, 0), (1, 1)])
+    );
+    assert_eq!(
+        net.validate_evidence(&evidence(&[("earthquake", "yes")])),
+        Err(SchemaError::UnknownNode("earthquake".into()))
+    );
+    assert_eq!(
+        net.validate_evidence(&evidence(&[("alarm", "ringing")])),
+        Err(SchemaError::UnknownState {
+            node: "alarm".into(),
+            state: "ringing".into(),
+            available: vec!["off".into(), "on".into(), "broken".into()],
+        })
+    );
+}
+
+#[test]
+#[should_panic]
+fn duplicate_node_names() {
+    let mut net = alarm_net();
+    net.set_node_name(1, "burglary");
+}
@@ -0,0 +1,136 @@
+use loopybayesnet::semiring::{Boolean, MaxProduct};
+use loopybayesnet::{BayesNet, NumericsPolicy};
+use ndarray::{Array1, Array2, Array3};
+
+// b depends on a, c copies b, and d can only be 1 if a is 1
+fn net() -> BayesNet {
+    let mut net = BayesNet::new();
+    let a = net.add_node_from_probabilities(&[], Array1::from(vec![0.6, 0.4]));
+    let b = net.add_node_from_probabilities(&[a], Array2::from(vec![[0.3, 0.9], [0.7, 0.1]]));
+    net.add_node_from_probabilities(&[b], Array2::from(vec![[1.0, 0.0], [0.0, 1.0]]));
+    net.add_node_from_probabilities(&[a], Array2::from(vec![[1.0, 0.5], [0.0, 0.5]]));
+    net
+}
+
+#[test]
+fn most_probable_explanation() {
+    let mut net = net();
+    // the joint of (a, b) is 0.18, 0.42, 0.36, 0.04: the most probable explanation differs from the
+    // most probable value of b alone
+    assert_eq!(net.most_probable_explanation(4), vec![0, 1, 1, 0]);
+    net.set_evidence(&[(3, 1)]);
+    assert_eq!(net.most_probable_explanation(4), vec![1, 0, 0, 1]);
+
+    // the max-marginals are the probabilities of the best joint assignments for each value,
+    // b = 0 being best explained by a = 1 and d = 0 or 1, or a = 0 and d = 0, all with probability 0.18
+    net.set_evidence(&[]);
+    for _ in 0..4 {
+        net.step_in::<MaxProduct>();
+    }
+    let b = net.beliefs_in::<MaxProduct>()[1]
+        .log_probabilities()
+        .mapv(f32::exp);
+    assert!((b[0] - 0.18 / 0.42).abs() < 1e-5);
+    assert!((b[1] - 1.0).abs() < 1e-5);
+    let decoded = net.decode_max_marginals();
+    assert_eq!(decoded, vec![0, 1, 1, 0]);
+    assert!((net.log_joint_probability(&decoded) - 0.42f32.ln()).abs() < 1e-5);
+    assert_eq!(net.log_joint_probability(&[0, 1, 0, 0]), f32::NEG_INFINITY);
+}
+
+#[test]
+fn boolean_support() {
+    let mut net = net();
+    net.set_evidence(&[(3, 1)]);
+    for _ in 0..4 {
+        net.step_in::<Boolean>();
+    }
+    let support: Vec<Vec<bool>> = net
+        .beliefs_in::<Boolean>()
+        .iter()
+        .map(|b| b.log_probabilities().iter().map(|&v| v == 0.0).collect())
+        .collect();
+    assert_eq!(
+        support,
+        vec![
+            vec![false, true],
+            vec![true, true],
+            vec![true, true],
+            vec![false, true]
+        ]
+    );
+}
+
+#[test]
+fn pruning() {
+    let mut net = net();
+    net.set_evidence(&[(3, 1)]);
+    assert_eq!(net.possible_values()[0], vec![false, true]);
+    net.set_numerics(NumericsPolicy {
+        convergence_tolerance: 1e-4,
+        ..NumericsPolicy::default()
+    });
+
+    let (mut pruned, kept) = net.prune_impossible_values();
+    assert_eq!(kept, vec![vec![1], vec![0, 1], vec![0, 1], vec![1]]);
+    assert_eq!(pruned.num_values(0), 1);
+    // the engine options are kept
+    assert_eq!(pruned.numerics(), net.numerics());
+    assert_eq!(pruned.engine_version(), net.engine_version());
+    for _ in 0..4 {
+        net.step();
+        pruned.step();
+    }
+    let expected = net.beliefs();
+    for (node, belief) in pruned.beliefs().iter().enumerate() {
+        let expected = expected[node].as_probabilities();
+        for (i, &v) in kept[node].iter().enumerate() {
+            assert!((belief.as_probabilities()[i] - expected[v]).abs() < 1e-5);
+        }
+    }
+    net.set_damping(0.3);
+    assert_eq!(net.prune_impossible_values().0.damping(), 0.3);
+}
+
+#[test]
+fn pruned_propagation() {
+    let mut net = net();
+    net.set_evidence(&[(3, 1)]);
+    let beliefs = net.pruned_beliefs(4);
+    assert_eq!(beliefs[0].as_probabilities().to_vec(), vec![0.0, 1.0]);
+    assert!((beliefs[1].as_probabilities()[0] - 0.9).abs() < 1e-5);
+    assert!((beliefs[2].as_probabilities()[1] - 0.1).abs() < 1e-5);
+    // the network itself is left untouched
+    assert_eq!(net.iteration(), 0);
+}
+
+#[test]
+fn implied_evidence_through_gates() {
+    let mut net = net();
+    net.set_evidence(&[(3, 1)]);
+    assert_eq!(net.implied_evidence(), vec![(0, 1)]);
+
+    // a loop of gates: b and c copy a, and d is the AND of b and c
+    let mut net = BayesNet::new();
+    let a = net.add_node_from_probabilities(&[], Array1::from(vec![0.7, 0.3]));
+    let copy = Array2::from(vec![[1.0, 0.0], [0.0, 1.0]]);
+    let b = net.add_node_from_probabilities(&[a], copy.clone());
+    let c = net.add_node_from_probabilities(&[a], copy);
+    let d = net.add_node_from_probabilities(
+        &[b, c],
+        Array3::from(vec![[[1.0, 1.0], [1.0, 0.0]], [[0.0, 0.0], [0.0, 1.0]]]),
+    );
+    let e = net.add_node_from_probabilities(&[c], Array2::from(vec![[0.2, 0.6], [0.8, 0.4]]));
+    let implied = net.set_evidence_with_implications(&[(d, 1)]);
+    assert_eq!(implied, vec![(a, 1), (b, 1), (c, 1)]);
+    for _ in 0..5 {
+        net.step();
+    }
+    let beliefs = net.beliefs();
+    assert_eq!(beliefs[a].as_probabilities()[1], 1.0);
+    assert!((beliefs[e].as_probabilities()[0] - 0.6).abs() < 1e-5);
+
+    // nothing is implied without evidence
+    net.set_evidence(&[]);
+    assert!(net.implied_evidence().is_empty());
+}
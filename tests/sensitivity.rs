@@ -0,0 +1,82 @@
+use loopybayesnet::BayesNet;
+use ndarray::{Array1, Array2};
+
+// a target with a strong and a weak sensor, a third sensor already observed, and an unrelated node
+fn sensors() -> BayesNet {
+    let mut net = BayesNet::new();
+    let target = net.add_node_from_probabilities(&[], Array1::from(vec![0.5, 0.5]));
+    net.add_node_from_probabilities(&[target], Array2::from(vec![[0.9, 0.1], [0.1, 0.9]]));
+    net.add_node_from_probabilities(&[target], Array2::from(vec![[0.6, 0.4], [0.4, 0.6]]));
+    net.add_node_from_probabilities(&[], Array1::from(vec![0.3, 0.7]));
+    net.add_node_from_probabilities(&[target], Array2::from(vec![[0.7, 0.3], [0.3, 0.7]]));
+    net
+}
+
+#[test]
+fn sensitivity_to_findings() {
+    let net = sensors();
+    let report = net.sensitivity_to_findings(0, 3);
+    let order: Vec<usize> = report.iter().map(|s| s.node).collect();
+    assert_eq!(order, vec![1, 4, 2, 3]);
+
+    // observing the strong sensor moves the target to 0.9 or 0.1
+    assert!((report[0].expected_shift - 0.4).abs() < 1e-5);
+    assert!((report[0].max_shift - 0.4).abs() < 1e-5);
+    let entropy = |p: f32| -p * p.ln() - (1.0 - p) * (1.0 - p).ln();
+    assert!((report[0].information_gain - (entropy(0.5) - entropy(0.9))).abs() < 1e-5);
+    assert!(report[3].expected_shift < 1e-6 && report[3].information_gain < 1e-6);
+
+    // observed nodes are not candidates
+    let mut observed = sensors();
+    observed.set_evidence(&[(4, 0)]);
+    let report = observed.sensitivity_to_findings(0, 3);
+    assert_eq!(report.len(), 3);
+    assert!(report.iter().all(|s| s.node != 4));
+}
+
+#[test]
+fn observation_plan() {
+    let net = sensors();
+    let entropy = |p: f32| -p * p.ln() - (1.0 - p) * (1.0 - p).ln();
+    let costs = [1.0, 3.0, 1.0, 1.0, 1.0];
+
+    // the strong sensor is not affordable, the medium one is the most informative
+    let plan = net.plan_observations(&[0], 2.0, &costs, 3);
+    let nodes: Vec<usize> = plan.iter().map(|step| step.node).collect();
+    assert_eq!(nodes, vec![4, 2]);
+    assert!((plan[0].information_gain - (entropy(0.5) - entropy(0.7))).abs() < 1e-5);
+
+    // with more budget, the strong sensor is worth its cost, and makes the medium one less informative
+    let plan = net.plan_observations(&[0], 4.0, &costs, 3);
+    let nodes: Vec<usize> = plan.iter().map(|step| step.node).collect();
+    assert_eq!(nodes, vec![1, 4]);
+    assert!(plan[1].information_gain < entropy(0.5) - entropy(0.7));
+    assert!(plan.iter().map(|step| step.cost).sum::<f32>() <= 4.0);
+}
+
+#[test]
+fn value_of_information() {
+    let net = sensors();
+    let entropy = |p: f32| -p * p.ln() - (1.0 - p) * (1.0 - p).ln();
+    let values = net.value_of_information(0, &[3, 2, 1], None, 3);
+    let order: Vec<usize> = values.iter().map(|v| v.node).collect();
+    assert_eq!(order, vec![1, 2, 3]);
+    assert!((values[0].information_gain - (entropy(0.5) - entropy(0.9))).abs() < 1e-5);
+    assert!(values.iter().all(|v| v.utility_gain.is_none()));
+
+    // guessing the target, or a safe option that the weak sensor alone cannot beat
+    let utilities = Array2::from(vec![[1.0, 0.0], [0.0, 1.0], [0.65, 0.65]]);
+    let values = net.value_of_information(0, &[2, 1], Some(&utilities), 3);
+    assert_eq!(values[0].node, 1);
+    assert!((values[0].utility_gain.unwrap() - 0.25).abs() < 1e-5);
+    assert!(values[1].utility_gain.unwrap() < 1e-6);
+    assert!(values[1].information_gain > 0.0);
+}
+
+#[test]
+#[should_panic]
+fn value_of_observed_node() {
+    let mut net = sensors();
+    net.set_evidence(&[(4, 0)]);
+    net.value_of_information(0, &[4], None, 3);
+}
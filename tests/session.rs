@@ -0,0 +1,74 @@
+use loopybayesnet::{BayesNet, Session, SessionEvent, SessionRecorder};
+use ndarray::{Array1, Array2, Array3};
+
+fn loopy() -> BayesNet {
+    // 0 -> 1 -> 3, 0 -> 2 -> 3
+    let mut net = BayesNet::new();
+    let a = net.add_node_from_probabilities(&[], Array1::from(vec![0.3, 0.7]));
+    let b = net.add_node_from_probabilities(&[a], Array2::from(vec![[0.9, 0.2], [0.1, 0.8]]));
+    let c = net.add_node_from_probabilities(&[a], Array2::from(vec![[0.6, 0.3], [0.4, 0.7]]));
+    net.add_node_from_probabilities(
+        &[b, c],
+        Array3::from(vec![[[0.99, 0.5], [0.4, 0.1]], [[0.01, 0.5], [0.6, 0.9]]]),
+    );
+    net
+}
+
+#[test]
+fn replay_reproduces_the_inference() {
+    let mut net = loopy();
+    net.set_evidence(&[(3, 1)]);
+    let mut recorder = SessionRecorder::new(net);
+    for _ in 0..3 {
+        recorder.step();
+    }
+    recorder.set_evidence(&[(3, 0), (1, 1)]);
+    recorder.step();
+    recorder.reset_state();
+    recorder.step();
+    recorder.step();
+    let (net, session) = recorder.into_parts();
+    assert_eq!(
+        session.events(),
+        &[
+            SessionEvent::SetEvidence(vec![(3, 1)]),
+            SessionEvent::ResetState,
+            SessionEvent::Steps(3),
+            SessionEvent::SetEvidence(vec![(3, 0), (1, 1)]),
+            SessionEvent::Steps(1),
+            SessionEvent::ResetState,
+            SessionEvent::Steps(2),
+        ]
+    );
+
+    let text = session.to_text();
+    assert_eq!(
+        text,
+        "evidence 3=1\nreset\nsteps 3\nevidence 3=0 1=1\nsteps 1\nreset\nsteps 2\n"
+    );
+    let parsed = Session::from_text(&text).unwrap();
+    assert_eq!(parsed, session);
+
+    let mut replayed = loopy();
+    parsed.replay(&mut replayed);
+    assert_eq!(replayed.iteration(), 2);
+    for (a, b) in replayed.beliefs().iter().zip(net.beliefs().iter()) {
+        assert_eq!(a.log_probabilities(), b.log_probabilities());
+    }
+}
+
+#[test]
+fn invalid_text() {
+    let error = Session::from_text("reset\n\nsteps two\n").unwrap_err();
+    assert_eq!(error.line, 3);
+    assert_eq!(
+        error.to_string(),
+        "invalid session event at line 3: \"steps two\""
+    );
+    assert!(Session::from_text("evidence 1-2").is_err());
+    assert!(Session::from_text("reset now").is_err());
+    assert_eq!(
+        Session::from_text("evidence\n").unwrap().events(),
+        &[SessionEvent::SetEvidence(vec![])]
+    );
+}
@@ -0,0 +1,50 @@
+use loopybayesnet::BayesNet;
+use ndarray::{Array1, Array2};
+
+#[test]
+fn snapshot_diff() {
+    let mut net = BayesNet::new();
+    let a = net.add_node_from_probabilities(&[], Array1::from(vec![0.5, 0.5]));
+    let b = net.add_node_from_probabilities(&[a], Array2::from(vec![[0.8, 0.4], [0.2, 0.6]]));
+    let c = net.add_node_from_probabilities(&[], Array1::from(vec![0.1, 0.9]));
+    for _ in 0..2 {
+        net.step();
+    }
+    let before = net.snapshot();
+    assert_eq!(before.iteration(), 2);
+
+    net.set_evidence(&[(b, 0)]);
+    for _ in 0..2 {
+        net.step();
+    }
+    let after = net.snapshot();
+    let diff = before.diff(&after);
+    assert_eq!(diff.len(), 3);
+
+    // P(a = 0 | b = 0) = 0.4 / 0.6
+    let p = 0.4f32 / 0.6;
+    let expected_kl = p * (p / 0.5).ln() + (1.0 - p) * ((1.0 - p) / 0.5).ln();
+    assert_eq!(diff[a].0, a);
+    assert!((diff[a].1 - expected_kl).abs() < 1e-5);
+    assert!((diff[a].2 - (p - 0.5)).abs() < 1e-5);
+    // b is now certain
+    assert!((diff[b].1 - (1.0f32 / 0.6).ln()).abs() < 1e-5);
+    assert!((diff[b].2 - 0.4).abs() < 1e-5);
+    // c is independent
+    assert!(diff[c].1.abs() < 1e-6 && diff[c].2 < 1e-6);
+}
+
+#[test]
+fn beliefs_matrix_is_padded() {
+    let mut net = BayesNet::new();
+    let a = net.add_node_from_probabilities(&[], Array1::from(vec![0.2, 0.3, 0.5]));
+    net.add_node_from_probabilities(&[a], Array2::from(vec![[1.0, 0.0, 0.5], [0.0, 1.0, 0.5]]));
+    net.step();
+    let matrix = net.beliefs_matrix();
+    assert_eq!(matrix.dim(), (2, 3));
+    assert!((matrix[(0, 2)] - 0.5).abs() < 1e-6);
+    // P(b = 0) = 0.2 + 0.25
+    assert!((matrix[(1, 0)] - 0.45).abs() < 1e-6);
+    assert!(matrix[(1, 2)].is_nan());
+    assert_eq!(net.snapshot().matrix().row(0), matrix.row(0));
+}
@@ -0,0 +1,126 @@
+use loopybayesnet::learning::{em, em_with_restarts, fit_parameters, fit_softmax, EmOptions};
+use loopybayesnet::BayesNet;
+use ndarray::{Array1, Array2};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+#[test]
+fn softmax_table() {
+    let mut net = BayesNet::new();
+    let a = net.add_node_from_probabilities(&[], Array1::from(vec![0.5, 0.5]));
+    let b = net.add_node_from_probabilities(&[], Array1::from(vec![0.2, 0.3, 0.5]));
+    // value 1 of c gets a bias of 1, plus 2 when a = 1 and -1 when b = 2
+    let weights = Array2::from(vec![
+        [0.0, 0.0, 0.0, 0.0, 0.0, 0.0],
+        [1.0, 0.0, 2.0, 0.0, 0.0, -1.0],
+    ]);
+    let c = net.add_softmax_node(&[a, b], weights.clone());
+    assert_eq!(net.softmax_weights(c), Some(&weights));
+
+    net.set_evidence(&[(a, 1), (b, 2)]);
+    for _ in 0..3 {
+        net.step();
+    }
+    let logit = 1.0f32 + 2.0 - 1.0;
+    let expected = logit.exp() / (1.0 + logit.exp());
+    assert!((net.beliefs()[c].as_probabilities()[1] - expected).abs() < 1e-5);
+
+    let refitted = fit_parameters(&net, &[vec![0, 0, 1]], 1.0);
+    assert_eq!(refitted.softmax_weights(c), None);
+}
+
+#[test]
+#[should_panic(expected = "Softmax weights have 4 columns instead of 6 for parents [0, 1]")]
+fn softmax_shape_mismatch() {
+    let mut net = BayesNet::new();
+    net.add_node_from_probabilities(&[], Array1::from(vec![0.5, 0.5]));
+    net.add_node_from_probabilities(&[], Array1::from(vec![0.2, 0.3, 0.5]));
+    net.add_softmax_node(&[0, 1], Array2::zeros((2, 4)));
+}
+
+#[test]
+fn softmax_learning_matches_frequencies() {
+    let mut net = BayesNet::new();
+    let a = net.add_node_from_probabilities(&[], Array1::from(vec![0.3, 0.3, 0.4]));
+    net.add_node_from_probabilities(&[a], Array2::from_elem((2, 3), 0.5));
+    // P(b = 1 | a) is 1/4, 1/2 and 3/4
+    let mut data = Vec::new();
+    for (value, ones) in [(0, 1), (1, 2), (2, 3)] {
+        for i in 0..4 {
+            data.push(vec![value, usize::from(i < ones)]);
+        }
+    }
+    let fitted = fit_softmax(&net, 1, &data, 2.0, 0.0, 2000);
+    assert_eq!(fitted.softmax_weights(1).unwrap().dim(), (2, 4));
+    for (value, expected) in [(0, 0.25), (1, 0.5), (2, 0.75)] {
+        let mut fitted = fitted.clone();
+        fitted.set_evidence(&[(a, value)]);
+        for _ in 0..3 {
+            fitted.step();
+        }
+        let p = fitted.beliefs()[1].as_probabilities()[1];
+        assert!((p - expected).abs() < 1e-2, "{} != {}", p, expected);
+    }
+}
+
+#[test]
+fn softmax_learning_with_missing_values() {
+    let mut net = BayesNet::new();
+    let a = net.add_node_from_probabilities(&[], Array1::from(vec![0.3, 0.3, 0.4]));
+    let mut table = net.clone();
+    net.add_softmax_node(&[a], Array2::zeros((2, 4)));
+    table.add_node_from_probabilities(&[a], Array2::from_elem((2, 3), 0.5));
+
+    let mut data = Vec::new();
+    for (value, ones) in [(0, 1), (1, 2), (2, 3)] {
+        for i in 0..4 {
+            data.push(vec![Some(value), Some(usize::from(i < ones))]);
+        }
+    }
+    data.push(vec![None, Some(1)]);
+    data.push(vec![None, Some(0)]);
+    data.push(vec![None, Some(1)]);
+
+    let options = EmOptions {
+        max_iterations: 300,
+        tolerance: 1e-7,
+        pseudo_count: 1e-3,
+        softmax_steps: 20,
+        ..EmOptions::default()
+    };
+    let softmax = em(&net, &data, &options).net;
+    let counted = em(&table, &data, &options).net;
+    assert!(softmax.softmax_weights(1).is_some());
+    for value in 0..3 {
+        let mut softmax = softmax.clone();
+        let mut counted = counted.clone();
+        for net in [&mut softmax, &mut counted] {
+            net.set_evidence(&[(a, value)]);
+            for _ in 0..3 {
+                net.step();
+            }
+        }
+        let p = softmax.beliefs()[1].as_probabilities()[1];
+        let q = counted.beliefs()[1].as_probabilities()[1];
+        assert!((p - q).abs() < 1e-2, "{} != {}", p, q);
+    }
+}
+
+#[test]
+fn softmax_child_of_a_latent_node() {
+    let mut net = BayesNet::new();
+    let h = net.add_node_from_probabilities(&[], Array1::from(vec![0.5, 0.5]));
+    let x = net.add_softmax_node(&[h], Array2::zeros((2, 3)));
+    net.add_node_from_probabilities(&[h], Array2::from_elem((2, 2), 0.5));
+    net.set_latent(h, true);
+    let data: Vec<Vec<Option<usize>>> = (0..40)
+        .map(|i| vec![None, Some(i % 2), Some((i / 2) % 2)])
+        .collect();
+
+    let options = EmOptions {
+        max_iterations: 5,
+        ..EmOptions::default()
+    };
+    let result = em_with_restarts(&mut StdRng::seed_from_u64(3), &net, &data, &[], 2, &options);
+    assert!(result.net.softmax_weights(x).is_some());
+}
@@ -0,0 +1,75 @@
+use loopybayesnet::{BayesNet, Report, SourceReliabilities};
+use ndarray::Array1;
+
+#[test]
+fn learn_and_fuse_sources() {
+    let mut net = BayesNet::new();
+    let fact = net.add_node_from_probabilities(&[], Array1::from(vec![0.5, 0.5]));
+
+    // source 0 was right 19 times out of 20, source 1 only 12 times
+    let mut history = Vec::new();
+    for i in 0..20 {
+        let truth = i % 2;
+        let wrong0 = i == 0;
+        let wrong1 = i < 8;
+        history.push((
+            Report {
+                node: fact,
+                value: if wrong0 { 1 - truth } else { truth },
+                source: 0,
+            },
+            truth,
+        ));
+        history.push((
+            Report {
+                node: fact,
+                value: if wrong1 { 1 - truth } else { truth },
+                source: 1,
+            },
+            truth,
+        ));
+    }
+    let mut reliabilities = SourceReliabilities::new(0.5);
+    reliabilities.learn(&net, &history, 200);
+    // with two values, the maximum likelihood reliability is 2 * accuracy - 1
+    assert!((reliabilities.get(0) - 0.9).abs() < 1e-3);
+    assert!((reliabilities.get(1) - 0.2).abs() < 1e-3);
+    assert_eq!(reliabilities.get(2), 0.5);
+
+    // conflicting reports: the reliable source wins
+    net.set_reports(
+        &[
+            Report {
+                node: fact,
+                value: 1,
+                source: 0,
+            },
+            Report {
+                node: fact,
+                value: 0,
+                source: 1,
+            },
+        ],
+        &reliabilities,
+    );
+    net.step();
+    let belief = net.beliefs()[fact].as_probabilities();
+    // likelihoods: [0.05, 0.95] and [0.6, 0.4]
+    let expected = 0.95 * 0.4 / (0.95 * 0.4 + 0.05 * 0.6);
+    assert!((belief[1] - expected).abs() < 1e-4);
+
+    // a fully reliable source is equivalent to hard evidence
+    let mut certain = SourceReliabilities::new(1.0);
+    certain.set(3, 1.0);
+    net.reset_state();
+    net.set_reports(
+        &[Report {
+            node: fact,
+            value: 0,
+            source: 3,
+        }],
+        &certain,
+    );
+    net.step();
+    assert!((net.beliefs()[fact].as_probabilities()[0] - 1.0).abs() < 1e-6);
+}
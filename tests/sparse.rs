@@ -0,0 +1,99 @@
+use loopybayesnet::BayesNet;
+use ndarray::{Array1, Array2};
+
+// two independent chains: 0 -> 1 -> 2 and 3 -> 4
+fn fragments() -> BayesNet {
+    let mut net = BayesNet::new();
+    let a = net.add_node_from_probabilities(&[], Array1::from(vec![0.2, 0.8]));
+    let b = net.add_node_from_probabilities(&[a], Array2::from(vec![[0.9, 0.3], [0.1, 0.7]]));
+    net.add_node_from_probabilities(&[b], Array2::from(vec![[0.5, 0.1], [0.5, 0.9]]));
+    let d = net.add_node_from_probabilities(&[], Array1::from(vec![0.6, 0.4]));
+    net.add_node_from_probabilities(&[d], Array2::from(vec![[0.7, 0.2], [0.3, 0.8]]));
+    net
+}
+
+#[test]
+fn components() {
+    let net = fragments();
+    assert_eq!(net.components(), vec![vec![0, 1, 2], vec![3, 4]]);
+}
+
+#[test]
+fn sparse_propagation() {
+    let mut dense = fragments();
+    let mut sparse = fragments();
+    sparse.set_sparse_propagation(true);
+    assert!(sparse.sparse_propagation());
+
+    for net in [&mut dense, &mut sparse] {
+        net.set_evidence(&[(2, 1)]);
+        for _ in 0..4 {
+            net.step();
+        }
+    }
+    // both fragments are trees, so the results match the full propagation
+    for (d, s) in dense.beliefs().iter().zip(sparse.beliefs().iter()) {
+        for (x, y) in d.as_probabilities().iter().zip(s.as_probabilities().iter()) {
+            assert!((x - y).abs() < 1e-5);
+        }
+    }
+    // the idle fragment is at its priors from the first step
+    sparse.reset_state();
+    sparse.step();
+    assert!((sparse.beliefs()[4].as_probabilities()[0] - (0.6 * 0.7 + 0.4 * 0.2)).abs() < 1e-5);
+
+    // evidence in the second fragment brings it back into the propagation
+    sparse.set_evidence(&[(4, 0)]);
+    dense.set_evidence(&[(4, 0)]);
+    for net in [&mut dense, &mut sparse] {
+        for _ in 0..4 {
+            net.step();
+        }
+    }
+    let expected = dense.beliefs()[3].as_probabilities()[0];
+    assert!((sparse.beliefs()[3].as_probabilities()[0] - expected).abs() < 1e-5);
+    assert!((expected - 0.42 / 0.5).abs() < 1e-5);
+}
+
+#[test]
+fn component_propagation() {
+    let mut net = fragments();
+    let mut reference = fragments();
+    assert_eq!(net.component_of(4), 1);
+    net.set_evidence(&[(2, 1), (4, 0)]);
+    reference.set_evidence(&[(2, 1), (4, 0)]);
+
+    let statuses = net.propagate_components(50, 1e-6);
+    assert_eq!(statuses.len(), 2);
+    assert_eq!(statuses[1].nodes, vec![3, 4]);
+    assert!(statuses.iter().all(|s| s.converged));
+    // the shorter fragment stabilizes first
+    assert!(statuses[1].iterations < statuses[0].iterations);
+    assert_eq!(net.iteration(), statuses[0].iterations);
+
+    for _ in 0..10 {
+        reference.step();
+    }
+    for (a, b) in net.beliefs().iter().zip(reference.beliefs().iter()) {
+        assert!((a.as_probabilities()[0] - b.as_probabilities()[0]).abs() < 1e-5);
+    }
+    // P(c = 1) = 0.2 * (0.9 * 0.5 + 0.1 * 0.9) + 0.8 * (0.3 * 0.5 + 0.7 * 0.9), P(e = 0) = 0.5
+    let log_evidence = net.component_log_evidence();
+    assert!((log_evidence[0] - 0.732f32.ln()).abs() < 1e-4);
+    assert!((log_evidence[1] - 0.5f32.ln()).abs() < 1e-4);
+    assert!((statuses[0].log_evidence - log_evidence[0]).abs() < 1e-6);
+}
+
+// impossible evidence produces NaN on purpose, which panics with the `nan-checks` feature
+#[cfg(not(feature = "nan-checks"))]
+#[test]
+fn impossible_component_does_not_converge() {
+    let mut net = fragments();
+    let x = net.add_node_from_probabilities(&[], Array1::from(vec![0.5, 0.5]));
+    let y = net.add_node_from_probabilities(&[x], Array2::from(vec![[1.0, 0.0], [0.0, 1.0]]));
+    net.set_evidence(&[(x, 0), (y, 1)]);
+    let statuses = net.propagate_components(20, 1e-6);
+    assert!(statuses[0].converged && statuses[1].converged);
+    assert!(!statuses[2].converged);
+    assert_eq!(statuses[2].iterations, 20);
+}
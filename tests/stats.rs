@@ -0,0 +1,70 @@
+use loopybayesnet::stats::{independence_test, IndependenceTest};
+use loopybayesnet::BayesNet;
+use ndarray::{Array1, Array2};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+fn two_binary_nodes() -> BayesNet {
+    let mut net = BayesNet::new();
+    net.add_node_from_probabilities(&[], Array1::from(vec![0.5, 0.5]));
+    net.add_node_from_probabilities(&[], Array1::from(vec![0.5, 0.5]));
+    net
+}
+
+#[test]
+fn contingency_table() {
+    // counts [[10, 20], [30, 40]]
+    let mut data = Vec::new();
+    for (x, y, count) in [(0, 0, 10), (0, 1, 20), (1, 0, 30), (1, 1, 40)] {
+        data.extend(std::iter::repeat_n(vec![x, y], count));
+    }
+    let net = two_binary_nodes();
+
+    let chi = independence_test(&net, &data, 0, 1, &[], IndependenceTest::ChiSquared);
+    assert!((chi.statistic - 0.793_650_8).abs() < 1e-5);
+    assert_eq!(chi.degrees_of_freedom, 1);
+    assert!((chi.p_value - 0.372_998_5).abs() < 1e-5);
+    assert!(!chi.rejects_independence(0.05));
+
+    let g = independence_test(&net, &data, 0, 1, &[], IndependenceTest::GSquared);
+    assert!((g.statistic - 0.804_349).abs() < 1e-4);
+    assert!((g.mutual_information - g.statistic / 200.0).abs() < 1e-7);
+    assert_eq!(g.mutual_information, chi.mutual_information);
+}
+
+#[test]
+fn independencies_of_a_chain() {
+    let mut net = BayesNet::new();
+    let a = net.add_node_from_probabilities(&[], Array1::from(vec![0.3, 0.7]));
+    let b = net.add_node_from_probabilities(&[a], Array2::from(vec![[0.8, 0.3], [0.2, 0.7]]));
+    let c = net
+        .add_node_from_probabilities(&[b], Array2::from(vec![[0.6, 0.1], [0.3, 0.2], [0.1, 0.7]]));
+    let data = net.sample_forward(&mut StdRng::seed_from_u64(4), 5000);
+
+    for test in [IndependenceTest::ChiSquared, IndependenceTest::GSquared] {
+        let marginal = independence_test(&net, &data, a, c, &[], test);
+        assert_eq!(marginal.degrees_of_freedom, 2);
+        assert!(marginal.rejects_independence(0.01));
+        assert!(marginal.mutual_information > 0.01);
+
+        let conditional = independence_test(&net, &data, c, a, &[b], test);
+        assert_eq!(conditional.degrees_of_freedom, 4);
+        assert!(!conditional.rejects_independence(0.01));
+        assert!(conditional.mutual_information < 0.002);
+    }
+}
+
+#[test]
+fn no_data() {
+    let result = independence_test(
+        &two_binary_nodes(),
+        &[],
+        0,
+        1,
+        &[],
+        IndependenceTest::GSquared,
+    );
+    assert_eq!(result.degrees_of_freedom, 0);
+    assert_eq!(result.p_value, 1.0);
+    assert_eq!(result.mutual_information, 0.0);
+}
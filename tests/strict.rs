@@ -0,0 +1,92 @@
+// the tests of zero columns are disabled with the `nan-checks` feature
+#![cfg_attr(feature = "nan-checks", allow(unused_imports))]
+
+use loopybayesnet::{BayesNet, BuildError, InputWarning};
+use ndarray::{Array1, Array2};
+
+#[test]
+fn valid_tables_are_accepted() {
+    let mut net = BayesNet::new();
+    net.set_strict_inputs(true);
+    let a = net.add_node_from_probabilities(&[], Array1::from(vec![0.3, 0.7]));
+    net.add_node_from_log_probabilities(
+        &[a],
+        Array2::from(vec![[0.0, f32::NEG_INFINITY], [-1.0, 0.0]]),
+    );
+    assert!(net.input_warnings().is_empty());
+}
+
+#[test]
+#[should_panic(
+    expected = "Invalid probability -0.5 at index 1 of the probabilities array of node 0"
+)]
+fn negative_probability_panics() {
+    let mut net = BayesNet::new();
+    net.set_strict_inputs(true);
+    net.add_node_from_probabilities(&[], Array1::from(vec![0.3, -0.5]));
+}
+
+#[test]
+#[should_panic(expected = "Invalid probability NaN")]
+fn nan_probability_panics() {
+    let mut net = BayesNet::new();
+    net.set_strict_inputs(true);
+    net.add_node_from_probabilities(&[], Array1::from(vec![f32::NAN, 1.0]));
+}
+
+#[test]
+#[should_panic(expected = "Invalid log-probability inf at index 0")]
+fn infinite_log_probability_panics() {
+    let mut net = BayesNet::new();
+    net.set_strict_inputs(true);
+    net.add_node_from_log_probabilities(&[], Array1::from(vec![f32::INFINITY, 0.0]));
+}
+
+// zero columns produce NaN on purpose, which panics with the `nan-checks` feature
+#[cfg(not(feature = "nan-checks"))]
+#[test]
+fn zero_columns_are_reported() {
+    let mut net = BayesNet::new();
+    net.set_strict_inputs(true);
+    let a = net.add_node_from_probabilities(&[], Array1::from(vec![0.3, 0.7]));
+    let b = net.add_node_from_probabilities(&[], Array1::from(vec![0.5, 0.5]));
+    net.add_node_from_probabilities(
+        &[a, b],
+        ndarray::Array3::from(vec![[[0.9, 0.0], [0.4, 0.1]], [[0.1, 0.0], [0.6, 0.9]]]),
+    );
+    assert_eq!(
+        net.input_warnings(),
+        &[InputWarning::ZeroColumn {
+            node: 2,
+            parent_values: vec![0, 1],
+        }]
+    );
+    assert_eq!(
+        net.input_warnings()[0].to_string(),
+        "all probabilities of node 2 are zero for parent values [0, 1]"
+    );
+}
+
+// zero columns produce NaN on purpose, which panics with the `nan-checks` feature
+#[cfg(not(feature = "nan-checks"))]
+#[test]
+fn lenient_mode_accepts_anything() {
+    let mut net = BayesNet::new();
+    assert!(!net.strict_inputs());
+    net.add_node_from_probabilities(&[], Array1::from(vec![0.0, 0.0]));
+    assert!(net.input_warnings().is_empty());
+}
+
+#[test]
+fn strict_errors_leave_the_network_unchanged() {
+    let mut net = BayesNet::new();
+    net.set_strict_inputs(true);
+    let error = net
+        .try_add_node_from_log_probabilities(&[], Array1::from(vec![0.0, f32::NAN]))
+        .unwrap_err();
+    assert!(matches!(
+        error,
+        BuildError::InvalidLogProbability { node: 0, index: 1, value } if value.is_nan()
+    ));
+    assert_eq!(net.num_nodes(), 0);
+}
@@ -0,0 +1,62 @@
+use loopybayesnet::{sweep, BayesNet};
+use ndarray::{Array1, Array2, Array3};
+
+#[test]
+fn sweep_a_conditional_probability() {
+    let mut net = BayesNet::new();
+    let a = net.add_node_from_probabilities(&[], Array1::from(vec![0.5, 0.5]));
+    let b = net.add_node_from_probabilities(&[], Array1::from(vec![0.2, 0.3, 0.5]));
+    let c = net.add_node_from_probabilities(
+        &[a, b],
+        Array3::from(vec![
+            [[0.9, 0.5, 0.3], [0.4, 0.1, 1.0]],
+            [[0.1, 0.5, 0.7], [0.6, 0.9, 0.0]],
+        ]),
+    );
+    net.set_evidence(&[(a, 1), (b, 2)]);
+
+    // P(c = 0 | a = 1, b = 2)
+    let values = [0.0, 0.25, 0.5, 1.0];
+    let results = sweep::over_parameter(&net, c, &[0, 1, 2], &values, &[c, a], 3);
+    assert_eq!(results.len(), 4);
+    for (value, beliefs) in values.iter().zip(&results) {
+        assert_eq!(beliefs.len(), 2);
+        assert!((beliefs[0].as_probabilities()[0] - value).abs() < 1e-5);
+        assert!((beliefs[1].as_probabilities()[1] - 1.0).abs() < 1e-6);
+    }
+
+    // the other parameters of the distribution are scaled, and the network is left unchanged
+    net.set_evidence(&[]);
+    let results = sweep::over_parameter(&net, b, &[0], &[0.6], &[b], 2);
+    let swept = results[0][0].as_probabilities();
+    assert!((swept[1] - 0.15).abs() < 1e-5);
+    assert!((swept[2] - 0.25).abs() < 1e-5);
+    assert_eq!(net.iteration(), 0);
+    assert!((net.exact_beliefs()[b].as_probabilities()[0] - 0.2).abs() < 1e-6);
+}
+
+#[test]
+fn sweep_matches_cold_runs() {
+    // a loopy network, where the warm starts must reach the same fixed points
+    let mut net = BayesNet::new();
+    let a = net.add_node_from_probabilities(&[], Array1::from(vec![0.6, 0.4]));
+    let b = net.add_node_from_probabilities(&[a], Array2::from(vec![[0.7, 0.2], [0.3, 0.8]]));
+    let c = net.add_node_from_probabilities(&[a], Array2::from(vec![[0.9, 0.4], [0.1, 0.6]]));
+    let d = net.add_node_from_probabilities(
+        &[b, c],
+        Array3::from(vec![[[0.9, 0.5], [0.4, 0.1]], [[0.1, 0.5], [0.6, 0.9]]]),
+    );
+    net.set_evidence(&[(d, 1)]);
+
+    let values = [0.1, 0.5, 0.9];
+    let results = sweep::over_parameter(&net, b, &[0, 1], &values, &[a], 50);
+    for (&value, beliefs) in values.iter().zip(&results) {
+        let mut cold =
+            net.with_probabilities(b, Array2::from(vec![[0.7, value], [0.3, 1.0 - value]]));
+        for _ in 0..50 {
+            cold.step();
+        }
+        let expected = cold.beliefs()[a].as_probabilities();
+        assert!((beliefs[0].as_probabilities()[0] - expected[0]).abs() < 1e-4);
+    }
+}
@@ -0,0 +1,70 @@
+use loopybayesnet::{BayesNet, Staleness};
+use ndarray::{Array1, Array2};
+
+fn sensor() -> BayesNet {
+    // a fault, and a sensor that detects it 90% of the time with 20% false alarms
+    let mut net = BayesNet::new();
+    let fault = net.add_node_from_probabilities(&[], Array1::from(vec![0.9, 0.1]));
+    net.add_node_from_probabilities(&[fault], Array2::from(vec![[0.8, 0.1], [0.2, 0.9]]));
+    net
+}
+
+fn fault_probability(net: &mut BayesNet) -> f32 {
+    for _ in 0..3 {
+        net.step();
+    }
+    net.beliefs()[0].as_probabilities()[1]
+}
+
+#[test]
+fn persistent_and_expiring_evidence() {
+    let mut net = sensor();
+    net.observe_at(1, 1, 10.0);
+    // P(fault | alarm) = 0.09 / (0.09 + 0.18)
+    assert!((fault_probability(&mut net) - 1.0 / 3.0).abs() < 1e-5);
+    net.advance_time(1000.0);
+    assert_eq!(net.timed_observation(1), Some((1, 10.0)));
+    assert!((fault_probability(&mut net) - 1.0 / 3.0).abs() < 1e-5);
+
+    net.set_staleness(1, Staleness::Expiry { lifetime: 5.0 });
+    net.observe_at(1, 1, 10.0);
+    net.advance_time(15.0);
+    assert_eq!(net.timed_observation(1), Some((1, 10.0)));
+    net.advance_time(15.5);
+    assert_eq!(net.timed_observation(1), None);
+    assert!((fault_probability(&mut net) - 0.1).abs() < 1e-5);
+}
+
+#[test]
+fn decaying_evidence() {
+    let mut net = sensor();
+    net.set_staleness(1, Staleness::Decay { half_life: 2.0 });
+    assert_eq!(net.staleness(1), Staleness::Decay { half_life: 2.0 });
+    net.observe_at(1, 1, 0.0);
+    net.advance_time(0.0);
+    assert!((fault_probability(&mut net) - 1.0 / 3.0).abs() < 1e-5);
+
+    // after one half-life, the likelihood of the absence of alarm is 1/2
+    net.advance_time(2.0);
+    let expected = 0.1 * (0.1 * 0.5 + 0.9) / (0.1 * (0.1 * 0.5 + 0.9) + 0.9 * (0.8 * 0.5 + 0.2));
+    assert!((fault_probability(&mut net) - expected).abs() < 1e-5);
+
+    // old evidence carries almost no information
+    net.advance_time(100.0);
+    assert!((fault_probability(&mut net) - 0.1).abs() < 1e-5);
+
+    net.forget_observation(1);
+    assert_eq!(net.timed_observation(1), None);
+}
+
+#[test]
+#[should_panic(expected = "The half-life of the evidence of node 1 must be positive, got 0")]
+fn zero_half_life() {
+    sensor().set_staleness(1, Staleness::Decay { half_life: 0.0 });
+}
+
+#[test]
+#[should_panic(expected = "The lifetime of the evidence of node 1 must be non-negative, got -1")]
+fn negative_lifetime() {
+    sensor().set_staleness(1, Staleness::Expiry { lifetime: -1.0 });
+}
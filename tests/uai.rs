@@ -0,0 +1,143 @@
+use loopybayesnet::{BayesNet, BifError};
+use ndarray::{Array1, Array2, Array3};
+
+// x2 is declared before its parent x1, as happens in the benchmark files
+const UAI: &str = "BAYES
+3
+2 3 2
+3
+1 0
+2 0 2
+3 0 2 1
+
+2
+ 0.436 0.564
+
+4
+ 0.128 0.872
+ 0.920 0.080
+
+12
+ 0.210 0.333 0.457
+ 0.811 0.000 0.189
+ 0.1 0.1 0.8
+ 0.3 0.3 0.4
+";
+
+#[test]
+fn read_uai() {
+    let mut net = BayesNet::from_uai(UAI).unwrap();
+    assert_eq!(net.num_nodes(), 3);
+    let x0 = net.find_node("x0").unwrap();
+    let x1 = net.find_node("x1").unwrap();
+    let x2 = net.find_node("x2").unwrap();
+    assert_eq!(net.parents(x1), vec![x0, x2]);
+    assert_eq!(net.num_values(x1), 3);
+    assert!(net.state_names(x1).is_none());
+
+    let evidence = net.read_uai_evidence("2 0 1 2 0\n").unwrap();
+    assert_eq!(evidence, vec![(x0, 1), (x2, 0)]);
+    // the older format, with the number of samples first
+    assert_eq!(net.read_uai_evidence("1\n2 0 1 2 0").unwrap(), evidence);
+    assert_eq!(net.read_uai_evidence("0").unwrap(), vec![]);
+
+    // P(x1 | x0 = 1, x2 = 0) is the third row
+    net.set_evidence(&evidence);
+    net.step();
+    net.step();
+    let belief = net.beliefs()[x1].as_probabilities();
+    assert!((belief[2] - 0.8).abs() < 1e-5);
+}
+
+#[test]
+fn uai_round_trip() {
+    let mut net = BayesNet::new();
+    let a = net.add_node_from_probabilities(&[], Array1::from(vec![0.3, 0.7]));
+    let b = net.add_node_from_probabilities(&[], Array1::from(vec![0.2, 0.5, 0.3]));
+    let c = net.add_node_from_probabilities(
+        &[a, b],
+        Array3::from(vec![
+            [[0.9, 0.5, 0.25], [0.375, 0.125, 0.5]],
+            [[0.1, 0.5, 0.75], [0.625, 0.875, 0.5]],
+        ]),
+    );
+    net.add_node_from_probabilities(&[c], Array2::from(vec![[0.5, 0.25], [0.5, 0.75]]));
+
+    let text = net.to_uai();
+    assert!(text.starts_with("BAYES\n4\n2 3 2 2\n4\n1 0\n1 1\n3 0 1 2\n2 2 3\n"));
+    // one row per combination of values of a and b, the last parent varying the fastest
+    let rows: Vec<Vec<f32>> = text
+        .split("\n12\n")
+        .nth(1)
+        .unwrap()
+        .lines()
+        .take(6)
+        .map(|row| row.split_whitespace().map(|v| v.parse().unwrap()).collect())
+        .collect();
+    for (row, expected) in rows.iter().zip(&[0.9, 0.5, 0.25, 0.375, 0.125, 0.5]) {
+        assert_eq!(row.len(), 2);
+        assert!((row[0] - expected).abs() < 1e-6);
+    }
+
+    let copy = BayesNet::from_uai(&text).unwrap();
+    assert_eq!(
+        copy.to_uai().lines().take(8).collect::<Vec<_>>(),
+        text.lines().take(8).collect::<Vec<_>>()
+    );
+    let evidence = copy
+        .read_uai_evidence(&net.to_uai_evidence(&[(1, 2), (3, 0)]))
+        .unwrap();
+    assert_eq!(evidence, vec![(1, 2), (3, 0)]);
+    for (original, copied) in net.exact_beliefs().iter().zip(copy.exact_beliefs()) {
+        for (x, y) in original
+            .as_probabilities()
+            .iter()
+            .zip(copied.as_probabilities().iter())
+        {
+            assert!((x - y).abs() < 1e-6);
+        }
+    }
+}
+
+#[test]
+fn uai_errors() {
+    assert_eq!(
+        BayesNet::from_uai("MARKOV\n1\n2\n1\n1 0\n2\n1 1\n").unwrap_err(),
+        BifError::Syntax {
+            line: 1,
+            message: "only BAYES networks are supported".to_owned()
+        }
+    );
+    assert_eq!(
+        BayesNet::from_uai(&UAI.replace("\n4\n", "\n6\n")).unwrap_err(),
+        BifError::TableSize {
+            line: 12,
+            variable: "x2".to_owned(),
+            expected: 4,
+            found: 6
+        }
+    );
+    assert_eq!(
+        BayesNet::from_uai(&UAI.replace("2 0 2", "2 0 7")).unwrap_err(),
+        BifError::UnknownVariable {
+            line: 6,
+            name: "x7".to_owned()
+        }
+    );
+    assert_eq!(
+        BayesNet::from_uai(&UAI.replace("3 0 2 1", "3 0 1 2")).unwrap_err(),
+        BifError::Syntax {
+            line: 7,
+            message: "variable 2 has two functions".to_owned()
+        }
+    );
+    let net = BayesNet::from_uai(UAI).unwrap();
+    assert_eq!(
+        net.read_uai_evidence("1 1 3").unwrap_err(),
+        BifError::UnknownState {
+            line: 1,
+            variable: "x1".to_owned(),
+            state: "3".to_owned()
+        }
+    );
+}
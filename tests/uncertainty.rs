@@ -0,0 +1,25 @@
+use loopybayesnet::BayesNet;
+use ndarray::{Array1, Array2};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+#[test]
+fn dirichlet_error_bars() {
+    let mut rng = StdRng::seed_from_u64(42);
+
+    // both coins have a mean of 50/50, but one was observed many more times
+    let mut net = BayesNet::new();
+    let known = net.add_node_from_dirichlet(&[], Array1::from(vec![500.0, 500.0]));
+    let unknown = net.add_node_from_dirichlet(&[], Array1::from(vec![1.0, 1.0]));
+    let child =
+        net.add_node_from_probabilities(&[unknown], Array2::from(vec![[0.9, 0.1], [0.1, 0.9]]));
+
+    let stats = net.belief_uncertainty(&mut rng, &[], 2000, 3);
+    assert!((stats[known].mean[0] - 0.5).abs() < 0.01);
+    assert!((stats[unknown].mean[0] - 0.5).abs() < 0.03);
+    // std dev of Beta(500, 500) is ~0.016, of Beta(1, 1) it is ~0.289
+    assert!((stats[known].std_dev[0] - 0.016).abs() < 0.003);
+    assert!((stats[unknown].std_dev[0] - 0.289).abs() < 0.02);
+    // the uncertainty propagates to the child
+    assert!(stats[child].std_dev[0] > 0.15);
+}
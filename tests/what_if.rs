@@ -0,0 +1,41 @@
+use loopybayesnet::BayesNet;
+use ndarray::{Array1, Array2, Array3};
+
+#[test]
+fn what_if_matrix() {
+    // 0 -> 2 <- 1, 2 -> 3
+    let mut net = BayesNet::new();
+    let a = net.add_node_from_probabilities(&[], Array1::from(vec![0.3, 0.7]));
+    let b = net.add_node_from_probabilities(&[], Array1::from(vec![0.6, 0.4]));
+    let c = net.add_node_from_probabilities(
+        &[a, b],
+        Array3::from(vec![[[0.9, 0.5], [0.4, 0.1]], [[0.1, 0.5], [0.6, 0.9]]]),
+    );
+    let d = net.add_node_from_probabilities(&[c], Array2::from(vec![[0.8, 0.3], [0.2, 0.7]]));
+    net.set_evidence(&[(d, 1)]);
+
+    let matrix = net.what_if(b, 6);
+    assert_eq!(matrix.len(), 2);
+    assert_eq!(matrix[0].len(), 4);
+    let selected = net.what_if_targets(b, &[a], 6);
+    assert_eq!(selected[1].len(), 1);
+
+    // the same as cold runs with the value as evidence
+    for value in 0..2 {
+        let mut cold = net.clone();
+        cold.set_evidence(&[(d, 1), (b, value)]);
+        for _ in 0..6 {
+            cold.step();
+        }
+        for (warm, cold) in matrix[value].iter().zip(cold.beliefs().iter()) {
+            assert!((warm.as_probabilities()[0] - cold.as_probabilities()[0]).abs() < 1e-5);
+        }
+        assert!(
+            (selected[value][0].as_probabilities()[0] - matrix[value][a].as_probabilities()[0])
+                .abs()
+                < 1e-6
+        );
+    }
+    // the network is untouched
+    assert_eq!(net.iteration(), 0);
+}
@@ -0,0 +1,98 @@
+use loopybayesnet::{BayesNet, BifError, NodeLayout};
+
+const DOG_PROBLEM: &str = r#"<?xml version="1.0" encoding="US-ASCII"?>
+<!DOCTYPE BIF [
+	<!ELEMENT BIF ( NETWORK )*>
+	<!ATTLIST BIF VERSION CDATA #REQUIRED>
+	<!ELEMENT NETWORK ( NAME, ( PROPERTY | VARIABLE | DEFINITION )* )>
+]>
+<BIF VERSION="0.3">
+<NETWORK>
+<NAME>Dog-Problem</NAME>
+<!-- the parents are declared after their child -->
+<VARIABLE TYPE="nature">
+	<NAME>dog-out</NAME>
+	<OUTCOME>true</OUTCOME>
+	<OUTCOME>false</OUTCOME>
+	<PROPERTY>position = (99, 99)</PROPERTY>
+</VARIABLE>
+<VARIABLE TYPE="nature">
+	<NAME>family-out</NAME>
+	<OUTCOME>true</OUTCOME>
+	<OUTCOME>false</OUTCOME>
+</VARIABLE>
+<VARIABLE TYPE="nature">
+	<NAME>bowel-problem</NAME>
+	<OUTCOME>true</OUTCOME>
+	<OUTCOME>false</OUTCOME>
+</VARIABLE>
+<DEFINITION>
+	<FOR>dog-out</FOR>
+	<GIVEN>bowel-problem</GIVEN>
+	<GIVEN>family-out</GIVEN>
+	<TABLE>0.99 0.01 0.97 0.03 0.9 0.1 0.3 0.7 </TABLE>
+</DEFINITION>
+<DEFINITION>
+	<FOR>family-out</FOR>
+	<TABLE>0.15 0.85 </TABLE>
+</DEFINITION>
+<DEFINITION>
+	<FOR>bowel-problem</FOR>
+	<TABLE>0.01 0.99 </TABLE>
+</DEFINITION>
+</NETWORK>
+</BIF>
+"#;
+
+#[test]
+fn read_xmlbif() {
+    let mut net = BayesNet::from_xmlbif(DOG_PROBLEM).unwrap();
+    assert_eq!(net.num_nodes(), 3);
+    let dog = net.find_node("dog-out").unwrap();
+    let bowel = net.find_node("bowel-problem").unwrap();
+    let family = net.find_node("family-out").unwrap();
+    assert_eq!(dog, 2);
+    assert_eq!(net.parents(dog), vec![bowel, family]);
+    assert_eq!(net.state_names(dog).unwrap(), ["true", "false"]);
+    assert_eq!(
+        net.layout(dog),
+        Some(&NodeLayout {
+            x: 99.0,
+            y: 99.0,
+            color: None
+        })
+    );
+
+    // P(dog-out | bowel-problem = false, family-out = true) = 0.9
+    net.set_evidence(&[(bowel, 1), (family, 0)]);
+    net.step();
+    net.step();
+    assert!((net.beliefs()[dog].as_probabilities()[0] - 0.9).abs() < 1e-5);
+}
+
+#[test]
+fn xmlbif_errors() {
+    let table = DOG_PROBLEM.replace("0.15 0.85", "0.15 0.85 0.0");
+    assert_eq!(
+        BayesNet::from_xmlbif(&table).unwrap_err(),
+        BifError::TableSize {
+            line: 35,
+            variable: "family-out".to_owned(),
+            expected: 2,
+            found: 3
+        }
+    );
+    let unknown = DOG_PROBLEM.replace("<GIVEN>family-out", "<GIVEN>cat-out");
+    assert_eq!(
+        BayesNet::from_xmlbif(&unknown).unwrap_err(),
+        BifError::UnknownVariable {
+            line: 30,
+            name: "cat-out".to_owned()
+        }
+    );
+    let unclosed = DOG_PROBLEM.replace("</NETWORK>", "");
+    assert_eq!(
+        BayesNet::from_xmlbif(&unclosed).unwrap_err().to_string(),
+        "line 42: <NETWORK> closed by </BIF>"
+    );
+}